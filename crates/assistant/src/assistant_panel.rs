@@ -20,9 +20,9 @@ use crate::{
     QuoteSelection, RemoteContextMetadata, RequestType, SavedContextMetadata, Split, ToggleFocus,
     ToggleModelSelector,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection};
-use assistant_tool::ToolWorkingSet;
+use assistant_tool::{ToolPermission, ToolPermissionSettings, ToolWorkingSet};
 use client::{proto, zed_urls, Client, Status};
 use collections::{hash_map, BTreeSet, HashMap, HashSet};
 use editor::{
@@ -2063,13 +2063,49 @@ impl ContextEditor {
                     .collect::<Vec<_>>();
 
                 for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+                    let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+                        continue;
+                    };
+                    let tool_use_id = tool_use.id.clone();
+                    let permission = ToolPermissionSettings::get_global(cx)
+                        .permission_for(&tool_use.name, &tool.capabilities());
+                    let task = match permission {
+                        ToolPermission::Allow => {
+                            tool.run(tool_use.input, self.workspace.clone(), cx)
+                        }
+                        ToolPermission::Ask if !tool.needs_confirmation(&tool_use.input, cx) => {
+                            tool.run(tool_use.input, self.workspace.clone(), cx)
+                        }
+                        ToolPermission::Ask => {
+                            let input =
+                                serde_json::to_string_pretty(&tool_use.input).unwrap_or_default();
+                            let confirmation = cx.prompt(
+                                gpui::PromptLevel::Warning,
+                                &format!("The assistant wants to use the {} tool", tool_use.name),
+                                Some(&input),
+                                &["Run", "Cancel"],
+                            );
+                            let workspace = self.workspace.clone();
+                            cx.spawn(|_, mut cx| async move {
+                                if confirmation.await? != 0 {
+                                    anyhow::bail!(
+                                        "the user declined to run the {} tool",
+                                        tool_use.name
+                                    );
+                                }
+                                cx.update(|cx| tool.run(tool_use.input, workspace, cx))?
+                                    .await
+                            })
+                        }
+                        ToolPermission::Deny => Task::ready(Err(anyhow!(
+                            "the {} tool is disabled in the user's settings",
+                            tool_use.name
+                        ))),
+                    };
 
-                        self.context.update(cx, |context, cx| {
-                            context.insert_tool_output(tool_use.id.clone(), task, cx);
-                        });
-                    }
+                    self.context.update(cx, |context, cx| {
+                        context.insert_tool_output(tool_use_id, task, cx);
+                    });
                 }
             }
             ContextEvent::ToolFinished {
@@ -1,20 +1,21 @@
 use std::sync::Arc;
 
-use assistant_tool::ToolWorkingSet;
+use assistant_tool::{ToolPermission, ToolPermissionSettings, ToolWorkingSet};
 use collections::HashMap;
 use gpui::{
     list, AnyElement, AppContext, Empty, ListAlignment, ListState, Model, StyleRefinement,
     Subscription, TextStyleRefinement, View, WeakView,
 };
 use language::LanguageRegistry;
-use language_model::Role;
+use language_model::{LanguageModelToolUseId, Role};
 use markdown::{Markdown, MarkdownStyle};
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::prelude::*;
 use workspace::Workspace;
 
-use crate::thread::{MessageId, Thread, ThreadError, ThreadEvent};
+use crate::thread::{MessageId, PendingToolUse, Thread, ThreadError, ThreadEvent};
+use crate::tool_log::ToolApproval;
 use crate::ui::ContextPill;
 
 pub struct ActiveThread {
@@ -175,24 +176,150 @@ impl ActiveThread {
                     .collect::<Vec<_>>();
 
                 for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
-
-                        self.thread.update(cx, |thread, cx| {
-                            thread.insert_tool_output(
+                    let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+                        continue;
+                    };
+                    let permission = ToolPermissionSettings::get_global(cx)
+                        .permission_for(&tool_use.name, &tool.capabilities());
+                    match permission {
+                        ToolPermission::Allow => {
+                            self.run_tool_use(tool_use, ToolApproval::Allowed, cx)
+                        }
+                        ToolPermission::Ask if !tool.needs_confirmation(&tool_use.input, cx) => {
+                            self.run_tool_use(tool_use, ToolApproval::Allowed, cx)
+                        }
+                        ToolPermission::Ask => self.thread.update(cx, |thread, cx| {
+                            thread.request_tool_confirmation(&tool_use.id, cx)
+                        }),
+                        ToolPermission::Deny => self.thread.update(cx, |thread, cx| {
+                            thread.deny_tool_use(
                                 tool_use.assistant_message_id,
-                                tool_use.id.clone(),
-                                task,
+                                tool_use.id,
+                                ToolApproval::DeniedBySettings,
                                 cx,
-                            );
-                        });
+                            )
+                        }),
                     }
                 }
             }
             ThreadEvent::ToolFinished { .. } => {}
+            ThreadEvent::ToolLogChanged => {}
+        }
+    }
+
+    fn run_tool_use(
+        &mut self,
+        tool_use: PendingToolUse,
+        approval: ToolApproval,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+            return;
+        };
+        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+        self.thread.update(cx, |thread, cx| {
+            thread.insert_tool_output(
+                tool_use.assistant_message_id,
+                tool_use.id.clone(),
+                approval,
+                task,
+                cx,
+            );
+        });
+    }
+
+    fn confirm_tool_use(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        allow: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(tool_use) = self
+            .thread
+            .read(cx)
+            .pending_tool_uses()
+            .into_iter()
+            .find(|tool_use| &tool_use.id == tool_use_id)
+            .cloned()
+        else {
+            return;
+        };
+
+        if allow {
+            self.run_tool_use(tool_use, ToolApproval::Confirmed, cx);
+        } else {
+            self.thread.update(cx, |thread, cx| {
+                thread.deny_tool_use(
+                    tool_use.assistant_message_id,
+                    tool_use.id,
+                    ToolApproval::DeniedByUser,
+                    cx,
+                )
+            });
         }
     }
 
+    fn render_tool_confirmation(
+        &self,
+        tool_use: &PendingToolUse,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let input = serde_json::to_string_pretty(&tool_use.input).unwrap_or_default();
+
+        v_flex()
+            .gap_1()
+            .p_1p5()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Icon::new(IconName::Warning)
+                            .size(IconSize::Small)
+                            .color(Color::Warning),
+                    )
+                    .child(
+                        Label::new(format!("Allow the assistant to use `{}`?", tool_use.name))
+                            .size(LabelSize::Small),
+                    ),
+            )
+            .child(
+                div()
+                    .p_1()
+                    .rounded_md()
+                    .bg(cx.theme().colors().editor_background)
+                    .font_buffer(cx)
+                    .child(Label::new(input).size(LabelSize::Small)),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .justify_end()
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("deny-tool-use-{}", tool_use.id)),
+                            "Deny",
+                        )
+                        .on_click(cx.listener({
+                            let tool_use_id = tool_use.id.clone();
+                            move |this, _, cx| this.confirm_tool_use(&tool_use_id, false, cx)
+                        })),
+                    )
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("allow-tool-use-{}", tool_use.id)),
+                            "Allow",
+                        )
+                        .style(ButtonStyle::Filled)
+                        .on_click(cx.listener({
+                            let tool_use_id = tool_use.id.clone();
+                            move |this, _, cx| this.confirm_tool_use(&tool_use_id, true, cx)
+                        })),
+                    ),
+            )
+    }
+
     fn render_message(&self, ix: usize, cx: &mut ViewContext<Self>) -> AnyElement {
         let message_id = self.messages[ix];
         let Some(message) = self.thread.read(cx).message(message_id) else {
@@ -204,6 +331,16 @@ impl ActiveThread {
         };
 
         let context = self.thread.read(cx).context_for_message(message_id);
+        let tool_confirmations = self
+            .thread
+            .read(cx)
+            .tool_uses_needing_confirmation(message_id)
+            .into_iter()
+            .map(|tool_use| {
+                self.render_tool_confirmation(tool_use, cx)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
 
         let (role_icon, role_name) = match message.role {
             Role::User => (IconName::Person, "You"),
@@ -241,7 +378,8 @@ impl ActiveThread {
                                     .map(|context| ContextPill::new(context.clone())),
                             ),
                         )
-                    }),
+                    })
+                    .children(tool_confirmations),
             )
             .into_any()
     }
@@ -252,3 +390,154 @@ impl Render for ActiveThread {
         list(self.list_state.clone()).flex_1()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use assistant_tool::{Tool, ToolCapability};
+    use gpui::{Task, TestAppContext};
+    use language_model::{
+        fake_provider::FakeLanguageModel, LanguageModelCompletionEvent, LanguageModelRegistry,
+        LanguageModelToolUse, StopReason,
+    };
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use crate::thread::RequestKind;
+
+    struct WriteFileTool;
+
+    impl Tool for WriteFileTool {
+        fn name(&self) -> String {
+            "write-file".into()
+        }
+
+        fn description(&self) -> String {
+            "Writes a file".into()
+        }
+
+        fn capabilities(&self) -> Vec<ToolCapability> {
+            vec![ToolCapability::WritesFiles]
+        }
+
+        fn run(
+            self: Arc<Self>,
+            input: serde_json::Value,
+            _workspace: WeakView<Workspace>,
+            _cx: &mut WindowContext,
+        ) -> Task<Result<String>> {
+            Task::ready(Ok(format!("wrote {}", input["path"])))
+        }
+    }
+
+    #[gpui::test]
+    async fn test_confirm_and_deny_tool_use(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            assistant_tool::init(cx);
+            LanguageModelRegistry::test(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        let tools = Arc::new(ToolWorkingSet::default());
+        tools.insert(Arc::new(WriteFileTool));
+        let thread = cx.new_model(|cx| Thread::new(tools.clone(), cx));
+        let active_thread = cx.new_view(|cx| {
+            ActiveThread::new(
+                thread.clone(),
+                workspace.downgrade(),
+                language_registry,
+                tools,
+                cx,
+            )
+        });
+
+        let model = Arc::new(FakeLanguageModel::default());
+        thread.update(cx, |thread, cx| {
+            thread.insert_user_message("Write a.txt and b.txt", Vec::new(), cx);
+            let request = thread.to_completion_request(RequestKind::Chat, cx);
+            thread.stream_completion(request, model.clone(), cx);
+        });
+        cx.run_until_parked();
+        model.send_last_completion_event(LanguageModelCompletionEvent::StartMessage {
+            message_id: "1".into(),
+        });
+        for (id, path) in [("tool-1", "a.txt"), ("tool-2", "b.txt")] {
+            model.send_last_completion_event(LanguageModelCompletionEvent::ToolUse(
+                LanguageModelToolUse {
+                    id: id.into(),
+                    name: "write-file".into(),
+                    input: json!({ "path": path }),
+                },
+            ));
+        }
+        model.send_last_completion_event(LanguageModelCompletionEvent::Stop(StopReason::ToolUse));
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+
+        // Tools that write files ask for confirmation by default.
+        let assistant_message_id = thread.read_with(cx, |thread, _| {
+            let assistant_message = thread.messages().last().unwrap();
+            assert_eq!(assistant_message.role, Role::Assistant);
+            assert!(thread.tool_log().is_empty());
+            assistant_message.id
+        });
+        thread.read_with(cx, |thread, _| {
+            let mut needing_confirmation = thread
+                .tool_uses_needing_confirmation(assistant_message_id)
+                .into_iter()
+                .map(|tool_use| tool_use.id.to_string())
+                .collect::<Vec<_>>();
+            needing_confirmation.sort();
+            assert_eq!(needing_confirmation, ["tool-1", "tool-2"]);
+        });
+
+        active_thread.update(cx, |active_thread, cx| {
+            active_thread.confirm_tool_use(&"tool-1".into(), true, cx);
+            active_thread.confirm_tool_use(&"tool-2".into(), false, cx);
+        });
+        cx.run_until_parked();
+
+        thread.read_with(cx, |thread, _| {
+            assert!(thread
+                .tool_uses_needing_confirmation(assistant_message_id)
+                .is_empty());
+            assert_eq!(
+                thread
+                    .tool_log()
+                    .iter()
+                    .map(|entry| (
+                        entry.tool_use_id.as_str(),
+                        entry.approval,
+                        entry.output.as_deref(),
+                        entry.is_error
+                    ))
+                    .collect::<Vec<_>>(),
+                [
+                    (
+                        "tool-1",
+                        ToolApproval::Confirmed,
+                        Some("wrote \"a.txt\""),
+                        false
+                    ),
+                    (
+                        "tool-2",
+                        ToolApproval::DeniedByUser,
+                        Some("the user declined to run this tool"),
+                        true
+                    ),
+                ]
+            );
+        });
+    }
+}
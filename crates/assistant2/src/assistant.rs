@@ -11,6 +11,7 @@ mod terminal_inline_assistant;
 mod thread;
 mod thread_history;
 mod thread_store;
+mod tool_log;
 mod ui;
 
 use std::sync::Arc;
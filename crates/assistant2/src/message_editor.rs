@@ -1,5 +1,6 @@
 use std::rc::Rc;
 
+use assistant_tool::{ToolPermission, ToolPermissionSettings};
 use editor::{Editor, EditorElement, EditorStyle};
 use gpui::{AppContext, FocusableView, Model, TextStyle, View, WeakModel, WeakView};
 use language_model::{LanguageModelRegistry, LanguageModelRequestTool};
@@ -111,10 +112,15 @@ impl MessageEditor {
             let mut request = thread.to_completion_request(request_kind, cx);
 
             if self.use_tools {
+                let permissions = ToolPermissionSettings::get_global(cx);
                 request.tools = thread
                     .tools()
                     .tools(cx)
                     .into_iter()
+                    .filter(|tool| {
+                        permissions.permission_for(&tool.name(), &tool.capabilities())
+                            != ToolPermission::Deny
+                    })
                     .map(|tool| LanguageModelRequestTool {
                         name: tool.name(),
                         description: tool.description(),
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use assistant_tool::ToolWorkingSet;
 use chrono::{DateTime, Utc};
 use collections::HashMap;
@@ -18,6 +18,7 @@ use util::{post_inc, TryFutureExt as _};
 use uuid::Uuid;
 
 use crate::context::{Context, ContextKind};
+use crate::tool_log::{ToolApproval, ToolLogEntry};
 
 #[derive(Debug, Clone, Copy)]
 pub enum RequestKind {
@@ -71,6 +72,7 @@ pub struct Thread {
     tool_uses_by_message: HashMap<MessageId, Vec<LanguageModelToolUse>>,
     tool_results_by_message: HashMap<MessageId, Vec<LanguageModelToolResult>>,
     pending_tool_uses_by_id: HashMap<LanguageModelToolUseId, PendingToolUse>,
+    tool_log: Vec<ToolLogEntry>,
 }
 
 impl Thread {
@@ -89,6 +91,7 @@ impl Thread {
             tool_uses_by_message: HashMap::default(),
             tool_results_by_message: HashMap::default(),
             pending_tool_uses_by_id: HashMap::default(),
+            tool_log: Vec::new(),
         }
     }
 
@@ -137,6 +140,54 @@ impl Thread {
         self.pending_tool_uses_by_id.values().collect()
     }
 
+    /// Returns the tool uses requested by the given message that are waiting for the user
+    /// to confirm them.
+    pub fn tool_uses_needing_confirmation(&self, message_id: MessageId) -> Vec<&PendingToolUse> {
+        self.tool_uses_by_message
+            .get(&message_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|tool_use| self.pending_tool_uses_by_id.get(&tool_use.id))
+            .filter(|tool_use| matches!(tool_use.status, PendingToolUseStatus::NeedsConfirmation))
+            .collect()
+    }
+
+    /// Returns the record of every tool the model asked to use in this thread.
+    pub fn tool_log(&self) -> &[ToolLogEntry] {
+        &self.tool_log
+    }
+
+    pub fn request_tool_confirmation(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = PendingToolUseStatus::NeedsConfirmation;
+            cx.notify();
+        }
+    }
+
+    pub fn deny_tool_use(
+        &mut self,
+        assistant_message_id: MessageId,
+        tool_use_id: LanguageModelToolUseId,
+        approval: ToolApproval,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let message = match approval {
+            ToolApproval::DeniedBySettings => "this tool is disabled in the user's settings",
+            _ => "the user declined to run this tool",
+        };
+        self.insert_tool_output(
+            assistant_message_id,
+            tool_use_id,
+            approval,
+            Task::ready(Err(anyhow!(message))),
+            cx,
+        );
+    }
+
     pub fn insert_user_message(
         &mut self,
         text: impl Into<String>,
@@ -436,9 +487,29 @@ impl Thread {
         &mut self,
         assistant_message_id: MessageId,
         tool_use_id: LanguageModelToolUseId,
+        approval: ToolApproval,
         output: Task<Result<String>>,
         cx: &mut ModelContext<Self>,
     ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get(&tool_use_id) {
+            self.tool_log.push(ToolLogEntry {
+                tool_use_id: tool_use_id.to_string(),
+                tool_name: tool_use.name.clone(),
+                capabilities: self
+                    .tools
+                    .tool(&tool_use.name, cx)
+                    .map(|tool| tool.capabilities())
+                    .unwrap_or_default(),
+                input: tool_use.input.clone(),
+                approval,
+                started_at: Utc::now(),
+                finished_at: None,
+                output: None,
+                is_error: false,
+            });
+            cx.emit(ThreadEvent::ToolLogChanged);
+        }
+
         let insert_output_task = cx.spawn(|thread, mut cx| {
             let tool_use_id = tool_use_id.clone();
             async move {
@@ -450,6 +521,22 @@ impl Thread {
                         // user message.
                         let next_user_message = MessageId(assistant_message_id.0 + 1);
 
+                        if let Some(entry) = thread
+                            .tool_log
+                            .iter_mut()
+                            .rfind(|entry| entry.tool_use_id == tool_use_id.to_string())
+                        {
+                            entry.finished_at = Some(Utc::now());
+                            match &output {
+                                Ok(output) => entry.output = Some(output.clone()),
+                                Err(err) => {
+                                    entry.output = Some(err.to_string());
+                                    entry.is_error = true;
+                                }
+                            }
+                            cx.emit(ThreadEvent::ToolLogChanged);
+                        }
+
                         let tool_results = thread
                             .tool_results_by_message
                             .entry(next_user_message)
@@ -511,6 +598,7 @@ pub enum ThreadEvent {
         #[allow(unused)]
         tool_use_id: LanguageModelToolUseId,
     },
+    ToolLogChanged,
 }
 
 impl EventEmitter<ThreadEvent> for Thread {}
@@ -533,6 +621,7 @@ pub struct PendingToolUse {
 #[derive(Debug, Clone)]
 pub enum PendingToolUseStatus {
     Idle,
+    NeedsConfirmation,
     Running { _task: Shared<Task<()>> },
     Error(#[allow(unused)] String),
 }
@@ -10,10 +10,10 @@ use project::Project;
 use unindent::Unindent;
 use util::ResultExt as _;
 
-use crate::thread::{Thread, ThreadId};
+use crate::thread::{Thread, ThreadEvent, ThreadId};
+use crate::tool_log::save_tool_log;
 
 pub struct ThreadStore {
-    #[allow(unused)]
     project: Model<Project>,
     tools: Arc<ToolWorkingSet>,
     context_server_manager: Model<ContextServerManager>,
@@ -69,6 +69,7 @@ impl ThreadStore {
 
     pub fn create_thread(&mut self, cx: &mut ModelContext<Self>) -> Model<Thread> {
         let thread = cx.new_model(|cx| Thread::new(self.tools.clone(), cx));
+        cx.subscribe(&thread, Self::handle_thread_event).detach();
         self.threads.push(thread.clone());
         thread
     }
@@ -84,6 +85,22 @@ impl ThreadStore {
         self.threads.retain(|thread| thread.read(cx).id() != id);
     }
 
+    fn handle_thread_event(
+        &mut self,
+        thread: Model<Thread>,
+        event: &ThreadEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let ThreadEvent::ToolLogChanged = event {
+            let fs = self.project.read(cx).fs().clone();
+            let thread = thread.read(cx);
+            let save = save_tool_log(fs, thread.id().clone(), thread.tool_log().to_vec());
+            cx.background_executor()
+                .spawn(async move { save.await.log_err() })
+                .detach();
+        }
+    }
+
     fn register_context_server_handlers(&self, cx: &mut ModelContext<Self>) {
         cx.subscribe(
             &self.context_server_manager.clone(),
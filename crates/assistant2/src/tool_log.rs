@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use assistant_tool::ToolCapability;
+use chrono::{DateTime, Utc};
+use fs::Fs;
+use serde::{Deserialize, Serialize};
+
+use crate::thread::ThreadId;
+
+/// How a tool use came to run, or not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolApproval {
+    /// The settings allowed the tool to run without asking.
+    Allowed,
+    /// The user confirmed the tool use.
+    Confirmed,
+    /// The settings don't allow the tool to run.
+    DeniedBySettings,
+    /// The user rejected the tool use.
+    DeniedByUser,
+}
+
+/// A record of a tool the model asked to use in a thread.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolLogEntry {
+    pub tool_use_id: String,
+    pub tool_name: String,
+    pub capabilities: Vec<ToolCapability>,
+    pub input: serde_json::Value,
+    pub approval: ToolApproval,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// The output of the tool, or its error message. `None` while the tool is running.
+    pub output: Option<String>,
+    pub is_error: bool,
+}
+
+#[derive(Serialize)]
+struct SavedToolLog<'a> {
+    thread_id: &'a ThreadId,
+    entries: &'a [ToolLogEntry],
+}
+
+/// Writes the tool log of a thread to the assistant tool logs directory.
+pub async fn save_tool_log(
+    fs: Arc<dyn Fs>,
+    thread_id: ThreadId,
+    entries: Vec<ToolLogEntry>,
+) -> Result<()> {
+    let dir = paths::assistant_tool_logs_dir();
+    fs.create_dir(dir).await?;
+    let text = serde_json::to_string_pretty(&SavedToolLog {
+        thread_id: &thread_id,
+        entries: &entries,
+    })?;
+    fs.atomic_write(dir.join(format!("{thread_id}.json")), text)
+        .await
+}
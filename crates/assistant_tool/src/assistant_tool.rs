@@ -1,3 +1,4 @@
+mod tool_permission_settings;
 mod tool_registry;
 mod tool_working_set;
 
@@ -5,13 +6,32 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use gpui::{AppContext, Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
 use workspace::Workspace;
 
+pub use crate::tool_permission_settings::*;
 pub use crate::tool_registry::*;
 pub use crate::tool_working_set::*;
 
 pub fn init(cx: &mut AppContext) {
     ToolRegistry::default_global(cx);
+    ToolPermissionSettings::register(cx);
+}
+
+/// Something a [`Tool`] can do when it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCapability {
+    /// Reads the project or the editor's state without changing anything.
+    ReadOnly,
+    /// Creates, modifies or deletes files.
+    WritesFiles,
+    /// Spawns processes on the user's machine.
+    RunsProcesses,
+    /// Makes requests over the network.
+    Network,
 }
 
 /// A tool that can be used by a language model.
@@ -27,6 +47,19 @@ pub trait Tool: 'static + Send + Sync {
         serde_json::Value::Object(serde_json::Map::default())
     }
 
+    /// Returns what the tool can do when it runs.
+    ///
+    /// Tools that don't declare their capabilities are treated as able to do anything.
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        Vec::new()
+    }
+
+    /// Returns whether running the tool with the provided input needs the user's
+    /// confirmation when the permission policy for the tool is [`ToolPermission::Ask`].
+    fn needs_confirmation(&self, _input: &serde_json::Value, _cx: &AppContext) -> bool {
+        true
+    }
+
     /// Runs the tool with the provided input.
     fn run(
         self: Arc<Self>,
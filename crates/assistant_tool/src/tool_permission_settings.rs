@@ -0,0 +1,113 @@
+use anyhow::Result;
+use collections::HashMap;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+use crate::ToolCapability;
+
+/// Whether a tool may run when the model calls it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPermission {
+    /// Run the tool without asking.
+    Allow,
+    /// Ask the user to confirm every call.
+    #[default]
+    Ask,
+    /// Never run the tool.
+    Deny,
+}
+
+/// Settings controlling which tools the assistant may run.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct ToolPermissionSettings {
+    /// The permission for tools that only read the project.
+    #[serde(default = "default_read_only_permission")]
+    pub read_only: ToolPermission,
+    /// The permission for all other tools.
+    #[serde(default)]
+    pub default: ToolPermission,
+    /// Permissions for individual tools, by name, overriding `read_only` and `default`.
+    #[serde(default)]
+    pub tools: HashMap<String, ToolPermission>,
+}
+
+fn default_read_only_permission() -> ToolPermission {
+    ToolPermission::Allow
+}
+
+impl Default for ToolPermissionSettings {
+    fn default() -> Self {
+        Self {
+            read_only: default_read_only_permission(),
+            default: ToolPermission::default(),
+            tools: HashMap::default(),
+        }
+    }
+}
+
+impl ToolPermissionSettings {
+    /// Returns the permission for the tool with the given name and capabilities.
+    pub fn permission_for(&self, name: &str, capabilities: &[ToolCapability]) -> ToolPermission {
+        if let Some(permission) = self.tools.get(name) {
+            return *permission;
+        }
+        let is_read_only = !capabilities.is_empty()
+            && capabilities
+                .iter()
+                .all(|capability| *capability == ToolCapability::ReadOnly);
+        if is_read_only {
+            self.read_only
+        } else {
+            self.default
+        }
+    }
+}
+
+impl Settings for ToolPermissionSettings {
+    const KEY: Option<&'static str> = Some("tool_permissions");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_for() {
+        let mut settings = ToolPermissionSettings::default();
+        settings
+            .tools
+            .insert("terminal".to_string(), ToolPermission::Deny);
+
+        assert_eq!(
+            settings.permission_for("read-file", &[ToolCapability::ReadOnly]),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            settings.permission_for("propose-edits", &[ToolCapability::WritesFiles]),
+            ToolPermission::Ask
+        );
+        assert_eq!(
+            settings.permission_for("terminal", &[ToolCapability::RunsProcesses]),
+            ToolPermission::Deny
+        );
+        // Tools that don't declare what they do aren't trusted to be read-only.
+        assert_eq!(
+            settings.permission_for("context-server-tool", &[]),
+            ToolPermission::Ask
+        );
+    }
+}
@@ -1,13 +1,38 @@
+mod diagnostics_tool;
+mod list_directory_tool;
 mod now_tool;
+mod propose_edits_tool;
+mod read_file_tool;
+mod regex_search_tool;
+mod terminal_tool;
+mod terminal_tool_settings;
+
+#[cfg(test)]
+mod test;
 
 use assistant_tool::ToolRegistry;
 use gpui::AppContext;
+use settings::Settings;
 
+use crate::diagnostics_tool::DiagnosticsTool;
+use crate::list_directory_tool::ListDirectoryTool;
 use crate::now_tool::NowTool;
+use crate::propose_edits_tool::ProposeEditsTool;
+use crate::read_file_tool::ReadFileTool;
+use crate::regex_search_tool::RegexSearchTool;
+use crate::terminal_tool::TerminalTool;
+use crate::terminal_tool_settings::TerminalToolSettings;
 
 pub fn init(cx: &mut AppContext) {
     assistant_tool::init(cx);
+    TerminalToolSettings::register(cx);
 
     let registry = ToolRegistry::global(cx);
     registry.register_tool(NowTool);
+    registry.register_tool(ReadFileTool);
+    registry.register_tool(ListDirectoryTool);
+    registry.register_tool(RegexSearchTool);
+    registry.register_tool(DiagnosticsTool);
+    registry.register_tool(ProposeEditsTool);
+    registry.register_tool(TerminalTool);
 }
@@ -0,0 +1,198 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::{Tool, ToolCapability};
+use gpui::{Task, WeakView, WindowContext};
+use language::{DiagnosticSeverity, Point};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiagnosticsToolInput {
+    /// The path of a file to list the diagnostics of, starting with the name of the project's
+    /// root directory.
+    ///
+    /// Leave empty to get a summary of the errors and warnings in every file of the project.
+    path: Option<String>,
+}
+
+pub struct DiagnosticsTool;
+
+impl Tool for DiagnosticsTool {
+    fn name(&self) -> String {
+        "diagnostics".into()
+    }
+
+    fn description(&self) -> String {
+        "Returns the errors and warnings reported by language servers, either for a single file or as a per-file summary of the whole project.".into()
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadOnly]
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(DiagnosticsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: DiagnosticsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        let Some(path) = input.path.filter(|path| !path.is_empty()) else {
+            let mut output = String::new();
+            let project = project.read(cx);
+            for (project_path, _, summary) in project.diagnostic_summaries(false, cx) {
+                let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+                    continue;
+                };
+                let path = Path::new(worktree.read(cx).root_name()).join(&project_path.path);
+                writeln!(
+                    output,
+                    "{}: {} error(s), {} warning(s)",
+                    path.display(),
+                    summary.error_count,
+                    summary.warning_count
+                )
+                .ok();
+            }
+            if output.is_empty() {
+                output = "No errors or warnings in the project.".into();
+            }
+            return Task::ready(Ok(output));
+        };
+
+        let Some(project_path) = project.read(cx).find_project_path(Path::new(&path), cx) else {
+            return Task::ready(Err(anyhow!("no such file: {path}")));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer
+                .await
+                .with_context(|| format!("failed to open {path}"))?;
+            let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
+
+            let mut output = String::new();
+            for entry in snapshot.diagnostics_in_range::<_, Point>(0..snapshot.len(), false) {
+                if !entry.diagnostic.is_primary {
+                    continue;
+                }
+                let severity = match entry.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => "error",
+                    DiagnosticSeverity::WARNING => "warning",
+                    DiagnosticSeverity::INFORMATION => "info",
+                    DiagnosticSeverity::HINT => "hint",
+                    _ => continue,
+                };
+                writeln!(
+                    output,
+                    "{path}:{}:{}: {severity}: {}",
+                    entry.range.start.row + 1,
+                    entry.range.start.column + 1,
+                    entry.diagnostic.message
+                )?;
+            }
+            if output.is_empty() {
+                output = format!("No diagnostics in {path}.");
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, VisualTestContext};
+    use lsp::LanguageServerId;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, test_workspace};
+
+    #[gpui::test]
+    async fn test_diagnostics(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) =
+            test_workspace(json!({ "a.rs": "let a = b;\n", "b.rs": "" }), cx).await;
+
+        let diagnostics = |input: serde_json::Value, cx: &mut VisualTestContext| {
+            cx.update(|cx| Arc::new(DiagnosticsTool).run(input, workspace.downgrade(), cx))
+        };
+
+        assert_eq!(
+            diagnostics(json!({}), cx).await.unwrap(),
+            "No errors or warnings in the project."
+        );
+
+        let project = workspace.update(cx, |workspace, _| workspace.project().clone());
+        let lsp_store = project.update(cx, |project, _| project.lsp_store());
+        lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_diagnostics(
+                    LanguageServerId(0),
+                    lsp::PublishDiagnosticsParams {
+                        uri: lsp::Url::from_file_path("/root/a.rs").unwrap(),
+                        version: None,
+                        diagnostics: vec![
+                            lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 8),
+                                    lsp::Position::new(0, 9),
+                                ),
+                                severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                message: "unknown variable 'b'".into(),
+                                ..Default::default()
+                            },
+                            lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 4),
+                                    lsp::Position::new(0, 5),
+                                ),
+                                severity: Some(lsp::DiagnosticSeverity::WARNING),
+                                message: "unused variable 'a'".into(),
+                                ..Default::default()
+                            },
+                        ],
+                    },
+                    &[],
+                    cx,
+                )
+                .unwrap();
+        });
+
+        assert_eq!(
+            diagnostics(json!({}), cx).await.unwrap(),
+            "root/a.rs: 1 error(s), 1 warning(s)\n"
+        );
+        assert_eq!(
+            diagnostics(json!({ "path": "root/a.rs" }), cx)
+                .await
+                .unwrap(),
+            "root/a.rs:1:5: warning: unused variable 'a'\n\
+             root/a.rs:1:9: error: unknown variable 'b'\n"
+        );
+        assert_eq!(
+            diagnostics(json!({ "path": "root/b.rs" }), cx)
+                .await
+                .unwrap(),
+            "No diagnostics in root/b.rs."
+        );
+    }
+}
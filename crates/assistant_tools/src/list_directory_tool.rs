@@ -0,0 +1,146 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{Tool, ToolCapability};
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListDirectoryToolInput {
+    /// The path of the directory to list, starting with the name of the project's root directory.
+    ///
+    /// Leave empty to list the root directories of the project.
+    #[serde(default)]
+    path: String,
+}
+
+pub struct ListDirectoryTool;
+
+impl Tool for ListDirectoryTool {
+    fn name(&self) -> String {
+        "list-directory".into()
+    }
+
+    fn description(&self) -> String {
+        "Lists the files and directories directly inside a directory of the project. Directories are listed with a trailing slash.".into()
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadOnly]
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ListDirectoryToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ListDirectoryToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = project.read(cx);
+
+        let path = input.path.trim_end_matches('/');
+        if path.is_empty() {
+            let mut output = String::new();
+            for worktree in project.visible_worktrees(cx) {
+                output.push_str(worktree.read(cx).root_name());
+                output.push_str("/\n");
+            }
+            return Task::ready(Ok(output));
+        }
+
+        let Some(project_path) = project.find_project_path(Path::new(path), cx) else {
+            return Task::ready(Err(anyhow!("no such directory: {path}")));
+        };
+        let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no such directory: {path}")));
+        };
+        let worktree = worktree.read(cx);
+        match worktree.entry_for_path(&project_path.path) {
+            Some(entry) if entry.is_dir() => {}
+            Some(_) => return Task::ready(Err(anyhow!("{path} is not a directory"))),
+            None => return Task::ready(Err(anyhow!("no such directory: {path}"))),
+        }
+
+        let mut output = String::new();
+        for entry in worktree.child_entries(&project_path.path) {
+            let Some(name) = entry.path.file_name() else {
+                continue;
+            };
+            output.push_str(&name.to_string_lossy());
+            if entry.is_dir() {
+                output.push('/');
+            }
+            output.push('\n');
+        }
+        if output.is_empty() {
+            output = format!("{path} is empty.");
+        }
+        Task::ready(Ok(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, test_workspace};
+
+    #[gpui::test]
+    async fn test_list_directory(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "README.md": "",
+                "src": { "main.rs": "" },
+                "empty": {},
+            }),
+            cx,
+        )
+        .await;
+
+        let list = |path: &str, cx: &mut VisualTestContext| {
+            let input = json!({ "path": path });
+            cx.update(|cx| Arc::new(ListDirectoryTool).run(input, workspace.downgrade(), cx))
+        };
+
+        assert_eq!(list("", cx).await.unwrap(), "root/\n");
+
+        let output = list("root/", cx).await.unwrap();
+        let mut entries = output.lines().collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, ["README.md", "empty/", "src/"]);
+
+        assert_eq!(list("root/src", cx).await.unwrap(), "main.rs\n");
+        assert_eq!(
+            list("root/empty", cx).await.unwrap(),
+            "root/empty is empty."
+        );
+        assert_eq!(
+            list("root/README.md", cx).await.unwrap_err().to_string(),
+            "root/README.md is not a directory"
+        );
+        assert_eq!(
+            list("root/missing", cx).await.unwrap_err().to_string(),
+            "no such directory: root/missing"
+        );
+    }
+}
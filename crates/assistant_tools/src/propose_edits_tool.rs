@@ -0,0 +1,249 @@
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::{Tool, ToolCapability};
+use collections::HashMap;
+use editor::{ProposedChangeLocation, ProposedChangesEditor};
+use gpui::{Model, Task, WeakView, WindowContext};
+use language::Buffer;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProposeEditsToolInput {
+    /// A short title describing the changes, shown on the tab where they are reviewed.
+    title: String,
+    /// The edits to propose.
+    edits: Vec<ProposedEdit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProposedEdit {
+    /// The path of the file to edit, starting with the name of the project's root directory.
+    path: String,
+    /// The text to replace. It must appear exactly once in the file.
+    old_text: String,
+    /// The text to replace it with.
+    new_text: String,
+}
+
+pub struct ProposeEditsTool;
+
+impl Tool for ProposeEditsTool {
+    fn name(&self) -> String {
+        "propose-edits".into()
+    }
+
+    fn description(&self) -> String {
+        "Proposes edits to files in the project. The edits are not applied directly; they are opened in a review tab where the user can accept or discard each of them.".into()
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::WritesFiles]
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ProposeEditsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ProposeEditsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        if input.edits.is_empty() {
+            return Task::ready(Err(anyhow!("no edits were given")));
+        }
+
+        let mut open_buffers = Vec::new();
+        for edit in &input.edits {
+            let Some(project_path) = project
+                .read(cx)
+                .find_project_path(Path::new(&edit.path), cx)
+            else {
+                return Task::ready(Err(anyhow!("no such file: {}", edit.path)));
+            };
+            open_buffers
+                .push(project.update(cx, |project, cx| project.open_buffer(project_path, cx)));
+        }
+
+        cx.spawn(|mut cx| async move {
+            let mut edits_by_buffer: HashMap<Model<Buffer>, Vec<(Range<usize>, String)>> =
+                HashMap::default();
+            let mut buffers = Vec::new();
+            for (edit, open_buffer) in input.edits.into_iter().zip(open_buffers) {
+                let buffer = open_buffer
+                    .await
+                    .with_context(|| format!("failed to open {}", edit.path))?;
+                let text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+                let mut matches = text.match_indices(&edit.old_text);
+                let start = match (matches.next(), matches.next()) {
+                    (Some((start, _)), None) => start,
+                    (None, _) => anyhow::bail!("old text was not found in {}", edit.path),
+                    (Some(_), Some(_)) => anyhow::bail!(
+                        "old text appears more than once in {}; include more context",
+                        edit.path
+                    ),
+                };
+                if !edits_by_buffer.contains_key(&buffer) {
+                    buffers.push((buffer.clone(), edit.path));
+                }
+                edits_by_buffer
+                    .entry(buffer)
+                    .or_default()
+                    .push((start..start + edit.old_text.len(), edit.new_text));
+            }
+
+            for (buffer, path) in &buffers {
+                if let Some(edits) = edits_by_buffer.get_mut(buffer) {
+                    sort_edits(path, edits)?;
+                }
+            }
+
+            let edit_count = edits_by_buffer.values().map(Vec::len).sum::<usize>();
+            let file_count = buffers.len();
+            workspace.update(&mut cx, |workspace, cx| {
+                let editor = cx.new_view(|cx| {
+                    let locations = buffers
+                        .iter()
+                        .map(|(buffer, _)| ProposedChangeLocation {
+                            buffer: buffer.clone(),
+                            ranges: edits_by_buffer[buffer]
+                                .iter()
+                                .map(|(range, _)| range.clone())
+                                .collect(),
+                        })
+                        .collect();
+                    let editor =
+                        ProposedChangesEditor::new(input.title, locations, Some(project), cx);
+                    for (buffer, _) in &buffers {
+                        if let Some(branch) = editor.branch_buffer_for_base(buffer) {
+                            let edits = edits_by_buffer[buffer].clone();
+                            branch.update(cx, |branch, cx| branch.edit(edits, None, cx));
+                        }
+                    }
+                    editor.recalculate_all_buffer_diffs();
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, false, cx);
+            })?;
+
+            Ok(format!(
+                "Proposed {edit_count} edit(s) across {file_count} file(s). They are waiting for the user to review them."
+            ))
+        })
+    }
+}
+
+/// Sorts the edits of a single file by position. Overlapping edits are rejected, because
+/// the buffer would merge them into one edit and produce garbled text.
+fn sort_edits(path: &str, edits: &mut [(Range<usize>, String)]) -> Result<()> {
+    edits.sort_by_key(|(range, _)| range.start);
+    if edits.windows(2).any(|pair| pair[0].0.end > pair[1].0.start) {
+        anyhow::bail!("some edits to {path} overlap; combine them into a single edit");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, test_workspace};
+
+    #[test]
+    fn test_sort_edits() {
+        let mut edits = vec![(6..9, "c".to_string()), (0..2, "a".to_string())];
+        sort_edits("a.txt", &mut edits).unwrap();
+        assert_eq!(edits, [(0..2, "a".to_string()), (6..9, "c".to_string())]);
+
+        // Adjacent edits don't overlap.
+        let mut edits = vec![(2..4, "b".to_string()), (0..2, "a".to_string())];
+        sort_edits("a.txt", &mut edits).unwrap();
+        assert_eq!(edits, [(0..2, "a".to_string()), (2..4, "b".to_string())]);
+
+        let mut edits = vec![(3..6, "b".to_string()), (0..4, "a".to_string())];
+        assert_eq!(
+            sort_edits("a.txt", &mut edits).unwrap_err().to_string(),
+            "some edits to a.txt overlap; combine them into a single edit"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_propose_edits(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(json!({ "a.txt": "one two three four" }), cx).await;
+
+        let propose = |edits: serde_json::Value, cx: &mut VisualTestContext| {
+            let input = json!({ "title": "Edits", "edits": edits });
+            cx.update(|cx| Arc::new(ProposeEditsTool).run(input, workspace.downgrade(), cx))
+        };
+
+        // Edits given out of order are applied at the right positions.
+        let output = propose(
+            json!([
+                { "path": "root/a.txt", "old_text": "four", "new_text": "4" },
+                { "path": "root/a.txt", "old_text": "one", "new_text": "1" },
+                { "path": "root/a.txt", "old_text": "three", "new_text": "3" },
+            ]),
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            output,
+            "Proposed 3 edit(s) across 1 file(s). They are waiting for the user to review them."
+        );
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_item_as::<ProposedChangesEditor>(cx)
+                .unwrap()
+        });
+        let project = workspace.update(cx, |workspace, _| workspace.project().clone());
+        let base = project
+            .update(cx, |project, cx| {
+                let project_path = project
+                    .find_project_path(Path::new("root/a.txt"), cx)
+                    .unwrap();
+                project.open_buffer(project_path, cx)
+            })
+            .await
+            .unwrap();
+        let branch = editor.update(cx, |editor, _| {
+            editor.branch_buffer_for_base(&base).unwrap()
+        });
+        branch.update(cx, |branch, _| assert_eq!(branch.text(), "1 two 3 4"));
+
+        // Overlapping edits are rejected instead of being merged.
+        let error = propose(
+            json!([
+                { "path": "root/a.txt", "old_text": "two three", "new_text": "2 3" },
+                { "path": "root/a.txt", "old_text": "one two", "new_text": "1 2" },
+            ]),
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "some edits to root/a.txt overlap; combine them into a single edit"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::{Tool, ToolCapability};
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadFileToolInput {
+    /// The path of the file to read, starting with the name of the project's root directory.
+    path: String,
+    /// The first line to read, starting at 1. Defaults to the start of the file.
+    start_line: Option<u32>,
+    /// The last line to read, inclusive. Defaults to the end of the file.
+    end_line: Option<u32>,
+}
+
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> String {
+        "read-file".into()
+    }
+
+    fn description(&self) -> String {
+        "Reads the contents of a file in the project, optionally limited to a range of lines. Each line is prefixed with its line number.".into()
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadOnly]
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ReadFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ReadFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let Some(project_path) = project
+            .read(cx)
+            .find_project_path(Path::new(&input.path), cx)
+        else {
+            return Task::ready(Err(anyhow!("no such file: {}", input.path)));
+        };
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer
+                .await
+                .with_context(|| format!("failed to open {}", input.path))?;
+            let text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+            let start_line = input.start_line.unwrap_or(1).max(1) as usize;
+            let end_line = input.end_line.map_or(usize::MAX, |line| line as usize);
+            anyhow::ensure!(
+                start_line <= end_line,
+                "start line {start_line} is after end line {end_line}"
+            );
+
+            let mut output = String::new();
+            for (ix, line) in text
+                .lines()
+                .enumerate()
+                .skip(start_line - 1)
+                .take(end_line - start_line + 1)
+            {
+                output.push_str(&format!("{:>6}\t{line}\n", ix + 1));
+            }
+            if output.is_empty() {
+                output = format!("{} has no lines in the requested range.", input.path);
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, test_workspace};
+
+    #[gpui::test]
+    async fn test_read_file(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(json!({ "a.txt": "one\ntwo\nthree\n" }), cx).await;
+
+        let read = |input: serde_json::Value, cx: &mut VisualTestContext| {
+            cx.update(|cx| Arc::new(ReadFileTool).run(input, workspace.downgrade(), cx))
+        };
+
+        let output = read(json!({ "path": "root/a.txt" }), cx).await.unwrap();
+        assert_eq!(output, "     1\tone\n     2\ttwo\n     3\tthree\n");
+
+        let output = read(
+            json!({ "path": "root/a.txt", "start_line": 2, "end_line": 2 }),
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "     2\ttwo\n");
+
+        let error = read(
+            json!({ "path": "root/a.txt", "start_line": 3, "end_line": 2 }),
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.to_string(), "start line 3 is after end line 2");
+
+        let error = read(json!({ "path": "root/b.txt" }), cx).await.unwrap_err();
+        assert_eq!(error.to_string(), "no such file: root/b.txt");
+    }
+}
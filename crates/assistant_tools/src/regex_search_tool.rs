@@ -0,0 +1,187 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{Tool, ToolCapability};
+use gpui::{Task, WeakView, WindowContext};
+use language::{Point, ToPoint};
+use project::search::{SearchQuery, SearchResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+/// The most matching lines returned for a single search, to keep the output readable.
+const MAX_RESULTS: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RegexSearchToolInput {
+    /// A regular expression to search for across the whole project.
+    regex: String,
+    /// Whether the search should be case-sensitive.
+    #[serde(default)]
+    case_sensitive: bool,
+    /// Glob patterns restricting which files are searched, e.g. `src/**/*.rs`.
+    #[serde(default)]
+    include: Vec<String>,
+    /// Glob patterns of files to leave out of the search.
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+pub struct RegexSearchTool;
+
+impl Tool for RegexSearchTool {
+    fn name(&self) -> String {
+        "regex-search".into()
+    }
+
+    fn description(&self) -> String {
+        "Searches the contents of all files in the project for a regular expression. Each match is returned as `path:line: text`.".into()
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadOnly]
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(RegexSearchToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: RegexSearchToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let query = PathMatcher::new(&input.include)
+            .and_then(|include| Ok((include, PathMatcher::new(&input.exclude)?)))
+            .map_err(|err| anyhow!(err))
+            .and_then(|(include, exclude)| {
+                SearchQuery::regex(
+                    &input.regex,
+                    false,
+                    input.case_sensitive,
+                    false,
+                    include,
+                    exclude,
+                    None,
+                )
+            });
+        let query = match query {
+            Ok(query) => query,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let results = project.update(cx, |project, cx| project.search(query, cx));
+        cx.spawn(|cx| async move {
+            let mut output = String::new();
+            let mut match_count = 0;
+            let mut limit_reached = false;
+            'results: while let Ok(result) = results.recv().await {
+                let SearchResult::Buffer { buffer, ranges } = result else {
+                    limit_reached = true;
+                    break;
+                };
+                let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
+                let Some(path) = buffer.read_with(&cx, |buffer, cx| {
+                    buffer.file().map(|file| file.full_path(cx))
+                })?
+                else {
+                    continue;
+                };
+
+                let mut last_row = None;
+                for range in ranges {
+                    let row = range.start.to_point(&snapshot).row;
+                    if last_row == Some(row) {
+                        continue;
+                    }
+                    last_row = Some(row);
+                    if match_count == MAX_RESULTS {
+                        limit_reached = true;
+                        break 'results;
+                    }
+                    match_count += 1;
+
+                    let line = snapshot
+                        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+                        .collect::<String>();
+                    writeln!(
+                        output,
+                        "{}:{}: {}",
+                        path.display(),
+                        row + 1,
+                        line.trim_end()
+                    )?;
+                }
+            }
+
+            if output.is_empty() {
+                return Ok(format!("No matches found for `{}`.", input.regex));
+            }
+            if limit_reached {
+                writeln!(
+                    output,
+                    "Stopped after {match_count} matches. Narrow the search to see the rest."
+                )?;
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, test_workspace};
+
+    #[gpui::test]
+    async fn test_regex_search(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "a.rs": "fn one() {}\nfn two() {}\nlet one = One;\n",
+                "b.txt": "One\n",
+            }),
+            cx,
+        )
+        .await;
+
+        let search = |input: serde_json::Value, cx: &mut VisualTestContext| {
+            cx.update(|cx| Arc::new(RegexSearchTool).run(input, workspace.downgrade(), cx))
+        };
+
+        let output = search(json!({ "regex": "o.e", "case_sensitive": true }), cx)
+            .await
+            .unwrap();
+        let mut lines = output.lines().collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(
+            lines,
+            ["root/a.rs:1: fn one() {}", "root/a.rs:3: let one = One;"]
+        );
+
+        let output = search(json!({ "regex": "o.e", "include": ["**/*.txt"] }), cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "root/b.txt:1: One\n");
+
+        let output = search(json!({ "regex": "three" }), cx).await.unwrap();
+        assert_eq!(output, "No matches found for `three`.");
+    }
+}
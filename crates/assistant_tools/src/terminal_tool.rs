@@ -0,0 +1,169 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{Tool, ToolCapability};
+use collections::HashMap;
+use futures::FutureExt as _;
+use gpui::{AppContext, Task, WeakView, WindowContext};
+use project::terminals::TerminalKind;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use task::{HideStrategy, RevealStrategy, RevealTarget, Shell, SpawnInTerminal, TaskId};
+use terminal::TaskStatus;
+use workspace::Workspace;
+
+use crate::terminal_tool_settings::TerminalToolSettings;
+
+/// The most lines of output returned to the model; earlier lines are dropped.
+const MAX_OUTPUT_LINES: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TerminalToolInput {
+    /// The shell command to run.
+    command: String,
+    /// The directory to run the command in, starting with the name of the project's root
+    /// directory. Defaults to the root of the first project directory.
+    cwd: Option<String>,
+}
+
+pub struct TerminalTool;
+
+impl Tool for TerminalTool {
+    fn name(&self) -> String {
+        "terminal".into()
+    }
+
+    fn description(&self) -> String {
+        "Runs a shell command in a terminal and returns its output and whether it succeeded. The user is asked to approve the command unless it is allowed in their settings. Use it to build the project or run its tests.".into()
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::RunsProcesses]
+    }
+
+    fn needs_confirmation(&self, input: &serde_json::Value, cx: &AppContext) -> bool {
+        let Ok(input) = serde_json::from_value::<TerminalToolInput>(input.clone()) else {
+            return true;
+        };
+        !TerminalToolSettings::get_global(cx).is_allowed(&input.command)
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(TerminalToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: TerminalToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        let cwd = match input.cwd.as_deref().filter(|cwd| !cwd.is_empty()) {
+            Some(cwd) => {
+                let project = project.read(cx);
+                let Some(abs_path) = project
+                    .find_project_path(Path::new(cwd), cx)
+                    .and_then(|project_path| project.absolute_path(&project_path, cx))
+                else {
+                    return Task::ready(Err(anyhow!("no such directory: {cwd}")));
+                };
+                Some(abs_path)
+            }
+            None => project.read(cx).first_project_directory(cx),
+        };
+
+        let timeout = Duration::from_secs(TerminalToolSettings::get_global(cx).timeout_seconds);
+        let window = cx.window_handle();
+
+        cx.spawn(|mut cx| async move {
+            let (program, args) = shell_command(&input.command);
+            let spawn_task = SpawnInTerminal {
+                id: TaskId(format!("assistant-terminal-tool-{}", input.command)),
+                full_label: input.command.clone(),
+                label: input.command.clone(),
+                command: program,
+                args,
+                command_label: input.command.clone(),
+                cwd,
+                env: HashMap::default(),
+                use_new_terminal: true,
+                allow_concurrent_runs: true,
+                reveal: RevealStrategy::Never,
+                reveal_target: RevealTarget::default(),
+                hide: HideStrategy::Never,
+                shell: Shell::System,
+                show_summary: false,
+                show_command: false,
+                depends_on: Vec::new(),
+                problem_matchers: Vec::new(),
+            };
+            let terminal = project
+                .update(&mut cx, |project, cx| {
+                    project.create_terminal(TerminalKind::Task(spawn_task), window, cx)
+                })?
+                .await?;
+
+            let completed =
+                terminal.update(&mut cx, |terminal, cx| terminal.wait_for_completed_task(cx))?;
+            let mut timer = cx.background_executor().timer(timeout).fuse();
+            let timed_out = futures::select_biased! {
+                _ = completed.fuse() => false,
+                _ = timer => true,
+            };
+
+            let (lines, status) = terminal.read_with(&cx, |terminal, _| {
+                (
+                    terminal.last_n_non_empty_lines(MAX_OUTPUT_LINES),
+                    terminal.task().map(|task| task.status),
+                )
+            })?;
+            // Dropping the terminal shuts down its process if it is still running.
+            drop(terminal);
+
+            let output = lines.join("\n");
+            if timed_out {
+                anyhow::bail!(
+                    "`{}` was stopped after running for {} seconds. Output so far:\n{output}",
+                    input.command,
+                    timeout.as_secs()
+                );
+            }
+            let outcome = match status {
+                Some(TaskStatus::Completed { success: true }) => "succeeded",
+                Some(TaskStatus::Completed { success: false }) => "failed",
+                _ => "exited with an unknown status",
+            };
+            Ok(format!("`{}` {outcome}. Output:\n{output}", input.command))
+        })
+    }
+}
+
+/// Returns the program and arguments that run the command through the system shell.
+fn shell_command(command: &str) -> (String, Vec<String>) {
+    if cfg!(target_os = "windows") {
+        (
+            "cmd".to_string(),
+            vec!["/C".to_string(), command.to_string()],
+        )
+    } else {
+        (
+            std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string()),
+            vec!["-c".to_string(), command.to_string()],
+        )
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// Settings for the assistant's terminal tool.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct TerminalToolSettings {
+    /// Commands the assistant may run without asking for confirmation first.
+    ///
+    /// A command is allowed when it equals an entry or starts with an entry followed by a
+    /// space, e.g. `cargo test` allows `cargo test -p editor`. Commands that chain or redirect
+    /// through the shell always ask.
+    #[serde(default)]
+    pub allowed_commands: Vec<String>,
+    /// How long a command may run before it is stopped, in seconds.
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_timeout_seconds() -> u64 {
+    300
+}
+
+impl Default for TerminalToolSettings {
+    fn default() -> Self {
+        Self {
+            allowed_commands: Vec::new(),
+            timeout_seconds: default_timeout_seconds(),
+        }
+    }
+}
+
+impl TerminalToolSettings {
+    /// Returns whether the command may run without the user confirming it.
+    pub fn is_allowed(&self, command: &str) -> bool {
+        let command = command.trim();
+        if command.contains(['\n', ';', '&', '|', '<', '>', '`', '$', '(', ')']) {
+            return false;
+        }
+        self.allowed_commands.iter().any(|allowed| {
+            let allowed = allowed.trim();
+            !allowed.is_empty()
+                && command
+                    .strip_prefix(allowed)
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with(' '))
+        })
+    }
+}
+
+impl Settings for TerminalToolSettings {
+    const KEY: Option<&'static str> = Some("terminal_tool");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_commands() {
+        let settings = TerminalToolSettings {
+            allowed_commands: vec!["cargo test".into(), "ls".into()],
+            ..Default::default()
+        };
+        assert!(settings.is_allowed("cargo test"));
+        assert!(settings.is_allowed("cargo test -p editor"));
+        assert!(settings.is_allowed("ls"));
+        assert!(!settings.is_allowed("lsof"));
+        assert!(!settings.is_allowed("cargo build"));
+        assert!(!settings.is_allowed("cargo test && rm -rf target"));
+        assert!(!settings.is_allowed("ls $(pwd)"));
+    }
+}
@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use fs::FakeFs;
+use gpui::{TestAppContext, View, VisualTestContext};
+use project::Project;
+use settings::SettingsStore;
+use workspace::Workspace;
+
+pub(crate) fn init_test(cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        theme::init(theme::LoadThemes::JustBase, cx);
+        language::init(cx);
+        Project::init_settings(cx);
+        workspace::init_settings(cx);
+        editor::init(cx);
+    });
+}
+
+/// Opens a workspace for a project whose only worktree, `root`, contains the given tree.
+pub(crate) async fn test_workspace(
+    tree: serde_json::Value,
+    cx: &mut TestAppContext,
+) -> (View<Workspace>, &mut VisualTestContext) {
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/root", tree).await;
+    let project = Project::test(fs, [Path::new("/root")], cx).await;
+    cx.add_window_view(|cx| Workspace::test_new(project, cx))
+}
@@ -2771,6 +2771,111 @@ async fn test_git_diff_base_change(
     });
 }
 
+#[gpui::test]
+async fn test_git_diff_base_revision_staged_text(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    let text = "one\ntwo\nthree\n";
+    let index_text = "one\nthree\n";
+    let revision_text = "one\ntwo\n";
+    client_a
+        .fs()
+        .insert_tree("/dir", json!({ ".git": {}, "a.txt": text }))
+        .await;
+    client_a.fs().set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), index_text.to_string())],
+    );
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), true, |state| {
+            state.revision_contents.insert(
+                "main".into(),
+                [(PathBuf::from("a.txt"), revision_text.to_string())]
+                    .into_iter()
+                    .collect(),
+            );
+        });
+
+    let (project_local, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    project_local
+        .update(cx_a, |project, cx| {
+            project.set_default_diff_base(git::repository::DiffBase::Revision("main".into()), cx)
+        })
+        .unwrap();
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(project_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let project_remote = client_b.join_remote_project(project_id, cx_b).await;
+
+    let buffer_local = project_local
+        .update(cx_a, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let change_set_local = project_local
+        .update(cx_a, |p, cx| {
+            p.open_unstaged_changes(buffer_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    change_set_local.read_with(cx_a, |change_set, cx| {
+        assert_eq!(
+            change_set.base_text_string(cx).as_deref(),
+            Some(revision_text)
+        );
+    });
+
+    // The guest diffs against the index, even though the host diffs against a revision.
+    let buffer_remote = project_remote
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let change_set_remote = project_remote
+        .update(cx_b, |p, cx| {
+            p.open_unstaged_changes(buffer_remote.clone(), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    change_set_remote.read_with(cx_b, |change_set, cx| {
+        assert_eq!(change_set.base_text_string(cx).as_deref(), Some(index_text));
+    });
+
+    // Later changes to the index are sent to the guest too.
+    let new_index_text = "one\ntwo\nthree\n";
+    client_a.fs().set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), new_index_text.to_string())],
+    );
+    executor.run_until_parked();
+    change_set_local.read_with(cx_a, |change_set, cx| {
+        assert_eq!(
+            change_set.base_text_string(cx).as_deref(),
+            Some(revision_text)
+        );
+    });
+    change_set_remote.read_with(cx_b, |change_set, cx| {
+        assert_eq!(
+            change_set.base_text_string(cx).as_deref(),
+            Some(new_index_text)
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_git_branch_name(
     executor: BackgroundExecutor,
@@ -4726,6 +4831,308 @@ async fn test_definition(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_call_and_type_hierarchy(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    let mut fake_language_servers = client_a.language_registry().register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    client_a.language_registry().add(rust_lang());
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root",
+            json!({
+                "a.rs": "fn a() { b() }\nstruct A;",
+                "b.rs": "fn b() {}\nfn c() { b() }\ntrait B {}",
+            }),
+        )
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project("/root", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+
+    // Open the file on client B.
+    let (buffer_b, _handle) = project_b
+        .update(cx_b, |p, cx| {
+            p.open_buffer_with_lsp((worktree_id, "a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> lsp::Range {
+        lsp::Range::new(
+            lsp::Position::new(start.0, start.1),
+            lsp::Position::new(end.0, end.1),
+        )
+    }
+    // The items carry data that the server expects back verbatim when they are expanded.
+    fn call_item(
+        name: &str,
+        path: &str,
+        range: lsp::Range,
+        selection_range: lsp::Range,
+    ) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path(path).unwrap(),
+            range,
+            selection_range,
+            data: Some(json!({ "name": name })),
+        }
+    }
+    fn type_item(
+        name: &str,
+        kind: lsp::SymbolKind,
+        path: &str,
+        range: lsp::Range,
+        selection_range: lsp::Range,
+    ) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path(path).unwrap(),
+            range,
+            selection_range,
+            data: Some(json!({ "name": name })),
+        }
+    }
+    let item_a = || {
+        call_item(
+            "a",
+            "/root/a.rs",
+            range((0, 0), (0, 14)),
+            range((0, 3), (0, 4)),
+        )
+    };
+    let item_b = || {
+        call_item(
+            "b",
+            "/root/b.rs",
+            range((0, 0), (0, 9)),
+            range((0, 3), (0, 4)),
+        )
+    };
+    let item_c = || {
+        call_item(
+            "c",
+            "/root/b.rs",
+            range((1, 0), (1, 14)),
+            range((1, 3), (1, 4)),
+        )
+    };
+    let type_a = || {
+        type_item(
+            "A",
+            lsp::SymbolKind::STRUCT,
+            "/root/a.rs",
+            range((1, 0), (1, 9)),
+            range((1, 7), (1, 8)),
+        )
+    };
+    let type_b = || {
+        type_item(
+            "B",
+            lsp::SymbolKind::INTERFACE,
+            "/root/b.rs",
+            range((2, 0), (2, 10)),
+            range((2, 6), (2, 7)),
+        )
+    };
+
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    fake_language_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(
+        move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 9)
+            );
+            Ok(Some(vec![item_b()]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item, item_b());
+            Ok(Some(vec![
+                lsp::CallHierarchyIncomingCall {
+                    from: item_a(),
+                    from_ranges: vec![range((0, 9), (0, 10))],
+                },
+                lsp::CallHierarchyIncomingCall {
+                    from: item_c(),
+                    from_ranges: vec![range((1, 9), (1, 10))],
+                },
+            ]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item, item_c());
+            Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                to: item_b(),
+                from_ranges: vec![range((1, 9), (1, 10))],
+            }]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(
+        move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 7)
+            );
+            Ok(Some(vec![type_a()]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item, type_a());
+            Ok(Some(vec![type_b()]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item, type_b());
+            Ok(Some(vec![type_a()]))
+        },
+    );
+
+    // Request the callers and callees of a function as the guest.
+    let mut items = project_b
+        .update(cx_b, |p, cx| p.prepare_call_hierarchy(&buffer_b, 9, cx))
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items.pop().unwrap();
+    cx_b.read(|cx| {
+        let item_buffer = item.location.buffer.read(cx);
+        assert_eq!(item.name, "b");
+        assert_eq!(item.kind, lsp::SymbolKind::FUNCTION);
+        assert_eq!(item_buffer.text(), "fn b() {}\nfn c() { b() }\ntrait B {}");
+        assert_eq!(
+            item.location.range.to_point(item_buffer),
+            Point::new(0, 0)..Point::new(0, 9)
+        );
+        assert_eq!(
+            item.selection_range.to_point(item_buffer),
+            Point::new(0, 3)..Point::new(0, 4)
+        );
+    });
+
+    let incoming_calls = project_b
+        .update(cx_b, |p, cx| p.incoming_calls(&item, cx))
+        .await
+        .unwrap();
+    cx_b.read(|cx| {
+        assert_eq!(incoming_calls.len(), 2);
+        let caller_a = &incoming_calls[0];
+        assert_eq!(caller_a.item.name, "a");
+        assert_eq!(caller_a.item.location.buffer, buffer_b);
+        assert_eq!(caller_a.call_sites.len(), 1);
+        assert_eq!(
+            caller_a.call_sites[0]
+                .range
+                .to_point(caller_a.call_sites[0].buffer.read(cx)),
+            Point::new(0, 9)..Point::new(0, 10)
+        );
+        let caller_c = &incoming_calls[1];
+        assert_eq!(caller_c.item.name, "c");
+        assert_eq!(caller_c.item.location.buffer, item.location.buffer);
+        assert_eq!(
+            caller_c.call_sites[0]
+                .range
+                .to_point(caller_c.call_sites[0].buffer.read(cx)),
+            Point::new(1, 9)..Point::new(1, 10)
+        );
+    });
+
+    let outgoing_calls = project_b
+        .update(cx_b, |p, cx| p.outgoing_calls(&incoming_calls[1].item, cx))
+        .await
+        .unwrap();
+    cx_b.read(|cx| {
+        assert_eq!(outgoing_calls.len(), 1);
+        let callee = &outgoing_calls[0];
+        assert_eq!(callee.item.name, "b");
+        assert_eq!(callee.item.location.buffer, item.location.buffer);
+        assert_eq!(callee.call_sites[0].buffer, item.location.buffer);
+        assert_eq!(
+            callee.call_sites[0]
+                .range
+                .to_point(callee.call_sites[0].buffer.read(cx)),
+            Point::new(1, 9)..Point::new(1, 10)
+        );
+    });
+
+    // Request the supertypes and subtypes of a type as the guest.
+    let mut items = project_b
+        .update(cx_b, |p, cx| {
+            p.prepare_type_hierarchy(&buffer_b, Point::new(1, 7), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items.pop().unwrap();
+    assert_eq!(item.name, "A");
+    assert_eq!(item.kind, lsp::SymbolKind::STRUCT);
+
+    let supertypes = project_b
+        .update(cx_b, |p, cx| p.supertypes(&item, cx))
+        .await
+        .unwrap();
+    cx_b.read(|cx| {
+        assert_eq!(supertypes.len(), 1);
+        let supertype = &supertypes[0];
+        let supertype_buffer = supertype.location.buffer.read(cx);
+        assert_eq!(supertype.name, "B");
+        assert_eq!(supertype.kind, lsp::SymbolKind::INTERFACE);
+        assert_eq!(
+            supertype.selection_range.to_point(supertype_buffer),
+            Point::new(2, 6)..Point::new(2, 7)
+        );
+    });
+
+    let subtypes = project_b
+        .update(cx_b, |p, cx| p.subtypes(&supertypes[0], cx))
+        .await
+        .unwrap();
+    cx_b.read(|cx| {
+        assert_eq!(subtypes.len(), 1);
+        assert_eq!(subtypes[0].name, "A");
+        assert_eq!(subtypes[0].location.buffer, buffer_b);
+        assert_eq!(
+            subtypes[0].selection_range.to_point(buffer_b.read(cx)),
+            Point::new(1, 7)..Point::new(1, 8)
+        );
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_references(
     executor: BackgroundExecutor,
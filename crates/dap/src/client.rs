@@ -0,0 +1,722 @@
+use std::{
+    fmt,
+    future::Future,
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        atomic::{AtomicU64, Ordering::SeqCst},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use futures::{
+    channel::oneshot, io::BufWriter, select, AsyncBufReadExt as _, AsyncRead, AsyncWrite,
+    FutureExt as _,
+};
+use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use parking_lot::{Mutex, RwLock};
+use serde_json::Value;
+use smol::{channel, io::BufReader, process::Child};
+use util::ResultExt as _;
+
+use crate::{
+    events::Event,
+    requests::{Disconnect, Initialize, Request},
+    transport,
+    types::{
+        Capabilities, DisconnectArguments, InitializeRequestArguments, Message, RequestMessage,
+        ResponseMessage,
+    },
+};
+
+const DAP_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const ADAPTER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+type EventHandler = Box<dyn Send + FnMut(Value, AsyncAppContext)>;
+type RequestHandler = Box<dyn Send + FnMut(Value, AsyncAppContext) -> Result<Value>>;
+type ResponseHandler = Box<dyn Send + FnOnce(Result<Value>)>;
+
+/// A unique identifier of a debug adapter client within the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DebugAdapterClientId(pub usize);
+
+impl fmt::Display for DebugAdapterClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// How to start a debug adapter process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugAdapterBinary {
+    pub command: String,
+    pub arguments: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub cwd: Option<PathBuf>,
+}
+
+/// A running debug adapter process, speaking the Debug Adapter Protocol over its stdio.
+///
+/// [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification)
+pub struct DebugAdapterClient {
+    id: DebugAdapterClientId,
+    name: Arc<str>,
+    next_seq: Arc<AtomicU64>,
+    outbound_tx: channel::Sender<String>,
+    event_handlers: Arc<Mutex<HashMap<&'static str, EventHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
+    response_handlers: Arc<Mutex<Option<HashMap<u64, ResponseHandler>>>>,
+    capabilities: RwLock<Capabilities>,
+    executor: BackgroundExecutor,
+    #[allow(clippy::type_complexity)]
+    io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
+    process: Arc<Mutex<Option<Child>>>,
+}
+
+/// A handle to an event or request handler of a debug adapter client.
+/// The handler is removed when the handle is dropped, unless it is detached.
+#[must_use]
+pub enum Subscription {
+    Event {
+        event: &'static str,
+        event_handlers: Option<Arc<Mutex<HashMap<&'static str, EventHandler>>>>,
+    },
+    Request {
+        command: &'static str,
+        request_handlers: Option<Arc<Mutex<HashMap<&'static str, RequestHandler>>>>,
+    },
+}
+
+impl DebugAdapterClient {
+    /// Starts a debug adapter process.
+    pub fn new(
+        id: DebugAdapterClientId,
+        name: &str,
+        binary: DebugAdapterBinary,
+        cx: AsyncAppContext,
+    ) -> Result<Self> {
+        log::info!(
+            "starting debug adapter process. command: {:?}, working directory: {:?}, args: {:?}",
+            binary.command,
+            binary.cwd,
+            binary.arguments
+        );
+
+        let mut command = util::command::new_smol_command(&binary.command);
+        if let Some(cwd) = &binary.cwd {
+            command.current_dir(cwd);
+        }
+        let mut process = command
+            .args(&binary.arguments)
+            .envs(binary.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| {
+                format!(
+                    "failed to spawn debug adapter. command: {:?}, working directory: {:?}, args: {:?}",
+                    binary.command, binary.cwd, binary.arguments
+                )
+            })?;
+
+        let stdin = process.stdin.take().unwrap();
+        let stdout = process.stdout.take().unwrap();
+        let stderr = process.stderr.take().unwrap();
+        Ok(Self::new_internal(
+            id,
+            name,
+            stdin,
+            stdout,
+            Some(stderr),
+            Some(process),
+            cx,
+        ))
+    }
+
+    fn new_internal<Stdin, Stdout, Stderr>(
+        id: DebugAdapterClientId,
+        name: &str,
+        stdin: Stdin,
+        stdout: Stdout,
+        stderr: Option<Stderr>,
+        process: Option<Child>,
+        cx: AsyncAppContext,
+    ) -> Self
+    where
+        Stdin: AsyncWrite + Unpin + Send + 'static,
+        Stdout: AsyncRead + Unpin + Send + 'static,
+        Stderr: AsyncRead + Unpin + Send + 'static,
+    {
+        let name: Arc<str> = name.into();
+        let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
+        let next_seq = Arc::new(AtomicU64::new(1));
+        let event_handlers = Arc::new(Mutex::new(HashMap::<_, EventHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
+        let response_handlers =
+            Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
+
+        let stdout_input_task = cx.spawn({
+            let event_handlers = event_handlers.clone();
+            let request_handlers = request_handlers.clone();
+            let response_handlers = response_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
+            let next_seq = next_seq.clone();
+            move |cx| {
+                Self::handle_input(
+                    stdout,
+                    event_handlers,
+                    request_handlers,
+                    response_handlers,
+                    outbound_tx,
+                    next_seq,
+                    cx,
+                )
+                .log_err()
+            }
+        });
+        let stderr_input_task = stderr
+            .map(|stderr| {
+                let name = name.clone();
+                cx.background_executor()
+                    .spawn(Self::handle_stderr(stderr, name).log_err())
+            })
+            .unwrap_or_else(|| Task::ready(None));
+        let input_task = cx.spawn(|_| async move {
+            let (stdout, stderr) = futures::join!(stdout_input_task, stderr_input_task);
+            stdout.or(stderr)
+        });
+        let output_task = cx
+            .background_executor()
+            .spawn(Self::handle_output(stdin, outbound_rx, response_handlers.clone()).log_err());
+
+        Self {
+            id,
+            name,
+            next_seq,
+            outbound_tx,
+            event_handlers,
+            request_handlers,
+            response_handlers,
+            capabilities: Default::default(),
+            executor: cx.background_executor().clone(),
+            io_tasks: Mutex::new(Some((input_task, output_task))),
+            process: Arc::new(Mutex::new(process)),
+        }
+    }
+
+    async fn handle_input<Stdout>(
+        stdout: Stdout,
+        event_handlers: Arc<Mutex<HashMap<&'static str, EventHandler>>>,
+        request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
+        response_handlers: Arc<Mutex<Option<HashMap<u64, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
+        next_seq: Arc<AtomicU64>,
+        cx: AsyncAppContext,
+    ) -> Result<()>
+    where
+        Stdout: AsyncRead + Unpin + Send + 'static,
+    {
+        let mut stdout = BufReader::new(stdout);
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+
+        let mut buffer = Vec::new();
+        loop {
+            transport::read_message(&mut stdout, &mut buffer).await?;
+            let message = match serde_json::from_slice::<Message>(&buffer) {
+                Ok(message) => message,
+                Err(error) => {
+                    log::warn!(
+                        "failed to deserialize debug adapter message: {error}\n{}",
+                        String::from_utf8_lossy(&buffer)
+                    );
+                    continue;
+                }
+            };
+
+            match message {
+                Message::Response(response) => {
+                    let handler = response_handlers
+                        .lock()
+                        .as_mut()
+                        .and_then(|handlers| handlers.remove(&response.request_seq));
+                    if let Some(handler) = handler {
+                        if response.success {
+                            handler(Ok(response.body.unwrap_or(Value::Null)));
+                        } else {
+                            handler(Err(anyhow!(response.message.unwrap_or_else(|| {
+                                format!("{} request failed", response.command)
+                            }))));
+                        }
+                    }
+                }
+                Message::Event(event) => {
+                    let mut event_handlers = event_handlers.lock();
+                    if let Some(handler) = event_handlers.get_mut(event.event.as_str()) {
+                        handler(event.body.unwrap_or(Value::Null), cx.clone());
+                    } else {
+                        log::debug!("unhandled debug adapter event {}", event.event);
+                    }
+                }
+                Message::Request(request) => {
+                    let result = match request_handlers.lock().get_mut(request.command.as_str()) {
+                        Some(handler) => {
+                            handler(request.arguments.unwrap_or(Value::Null), cx.clone())
+                        }
+                        None => Err(anyhow!("unsupported request {}", request.command)),
+                    };
+                    let (success, message, body) = match result {
+                        Ok(body) => (true, None, Some(body)),
+                        Err(error) => (false, Some(error.to_string()), None),
+                    };
+                    let response = serde_json::to_string(&Message::Response(ResponseMessage {
+                        seq: next_seq.fetch_add(1, SeqCst),
+                        request_seq: request.seq,
+                        success,
+                        command: request.command,
+                        message,
+                        body,
+                    }))?;
+                    outbound_tx.try_send(response)?;
+                }
+            }
+
+            // Don't starve the main thread when receiving lots of messages at once.
+            smol::future::yield_now().await;
+        }
+    }
+
+    async fn handle_stderr<Stderr>(stderr: Stderr, name: Arc<str>) -> Result<()>
+    where
+        Stderr: AsyncRead + Unpin + Send + 'static,
+    {
+        let mut stderr = BufReader::new(stderr);
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            if stderr.read_line(&mut buffer).await? == 0 {
+                return Ok(());
+            }
+            log::debug!("debug adapter {name} stderr: {}", buffer.trim_end());
+        }
+    }
+
+    async fn handle_output<Stdin>(
+        stdin: Stdin,
+        outbound_rx: channel::Receiver<String>,
+        response_handlers: Arc<Mutex<Option<HashMap<u64, ResponseHandler>>>>,
+    ) -> Result<()>
+    where
+        Stdin: AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut stdin = BufWriter::new(stdin);
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        while let Ok(message) = outbound_rx.recv().await {
+            log::trace!("outgoing debug adapter message: {message}");
+            transport::write_message(&mut stdin, &message).await?;
+        }
+        Ok(())
+    }
+
+    pub fn id(&self) -> DebugAdapterClientId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The capabilities the adapter reported in its response to `initialize`.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.read().clone()
+    }
+
+    /// Sends the `initialize` request, which must be the first request sent to the adapter,
+    /// and stores the capabilities the adapter responds with.
+    pub async fn initialize(&self, adapter_id: &str) -> Result<Capabilities> {
+        let capabilities = self
+            .request::<Initialize>(InitializeRequestArguments {
+                client_id: "zed".into(),
+                client_name: "Zed".into(),
+                adapter_id: adapter_id.into(),
+                locale: None,
+                lines_start_at1: true,
+                columns_start_at1: true,
+                path_format: "path".into(),
+                supports_variable_type: true,
+                supports_run_in_terminal_request: false,
+            })
+            .await?;
+        *self.capabilities.write() = capabilities.clone();
+        Ok(capabilities)
+    }
+
+    /// Sends a request to the debug adapter and waits for its response.
+    pub fn request<R: Request>(
+        &self,
+        arguments: R::Arguments,
+    ) -> impl 'static + Send + Future<Output = Result<R::Response>> {
+        let seq = self.next_seq.fetch_add(1, SeqCst);
+        let (tx, rx) = oneshot::channel();
+        let send = serde_json::to_value(arguments)
+            .and_then(|arguments| {
+                serde_json::to_string(&Message::Request(RequestMessage {
+                    seq,
+                    command: R::COMMAND.into(),
+                    arguments: Some(arguments),
+                }))
+            })
+            .context("failed to serialize debug adapter request")
+            .and_then(|message| {
+                self.response_handlers
+                    .lock()
+                    .as_mut()
+                    .ok_or_else(|| anyhow!("debug adapter shut down"))?
+                    .insert(
+                        seq,
+                        Box::new(move |result| {
+                            tx.send(result).ok();
+                        }),
+                    );
+                self.outbound_tx
+                    .try_send(message)
+                    .context("failed to write to the debug adapter's stdin")
+            });
+
+        let mut timeout = self.executor.timer(DAP_REQUEST_TIMEOUT).fuse();
+        async move {
+            send?;
+            let command = R::COMMAND;
+            let body = select! {
+                response = rx.fuse() => response.map_err(|_| anyhow!("debug adapter shut down"))??,
+                _ = timeout => {
+                    log::error!("cancelled {command:?} debug adapter request which took over {DAP_REQUEST_TIMEOUT:?}");
+                    anyhow::bail!("debug adapter request timeout");
+                }
+            };
+            serde_json::from_value(body)
+                .with_context(|| format!("failed to deserialize the response to {command:?}"))
+        }
+    }
+
+    /// Registers a handler for events of the given kind sent by the debug adapter.
+    pub fn on_event<E, F>(&self, mut f: F) -> Subscription
+    where
+        E: Event,
+        F: 'static + Send + FnMut(E::Body, AsyncAppContext),
+    {
+        let prev_handler = self.event_handlers.lock().insert(
+            E::EVENT,
+            Box::new(move |body, cx| match serde_json::from_value(body) {
+                Ok(body) => f(body, cx),
+                Err(error) => log::error!("failed to deserialize {:?} event: {error}", E::EVENT),
+            }),
+        );
+        assert!(
+            prev_handler.is_none(),
+            "registered multiple handlers for the same debug adapter event"
+        );
+        Subscription::Event {
+            event: E::EVENT,
+            event_handlers: Some(self.event_handlers.clone()),
+        }
+    }
+
+    /// Registers a handler for requests of the given kind sent by the debug adapter to the client,
+    /// such as `runInTerminal`. Requests without a handler are answered with an error.
+    pub fn on_request<R, F>(&self, f: F) -> Subscription
+    where
+        R: Request,
+        F: 'static + Send + FnMut(R::Arguments, AsyncAppContext) -> Result<R::Response>,
+    {
+        let prev_handler = self.set_request_handler::<R, F>(f);
+        assert!(
+            prev_handler.is_none(),
+            "registered multiple handlers for the same debug adapter request"
+        );
+        Subscription::Request {
+            command: R::COMMAND,
+            request_handlers: Some(self.request_handlers.clone()),
+        }
+    }
+
+    fn set_request_handler<R, F>(&self, mut f: F) -> Option<RequestHandler>
+    where
+        R: Request,
+        F: 'static + Send + FnMut(R::Arguments, AsyncAppContext) -> Result<R::Response>,
+    {
+        self.request_handlers.lock().insert(
+            R::COMMAND,
+            Box::new(move |arguments, cx| {
+                let arguments = serde_json::from_value(arguments)
+                    .with_context(|| format!("invalid arguments for {:?}", R::COMMAND))?;
+                Ok(serde_json::to_value(f(arguments, cx)?)?)
+            }),
+        )
+    }
+
+    /// Disconnects from the debug adapter, terminating the debuggee, and stops the adapter process.
+    pub fn shutdown(&self) -> Option<impl 'static + Send + Future<Output = Option<()>>> {
+        let tasks = self.io_tasks.lock().take()?;
+        let disconnect = self.request::<Disconnect>(DisconnectArguments {
+            restart: None,
+            terminate_debuggee: Some(true),
+        });
+        let outbound_tx = self.outbound_tx.clone();
+        let response_handlers = self.response_handlers.clone();
+        let process = self.process.clone();
+        let name = self.name.clone();
+        let mut timer = self.executor.timer(ADAPTER_SHUTDOWN_TIMEOUT).fuse();
+        Some(
+            async move {
+                log::debug!("debug adapter {name} shutdown started");
+                select! {
+                    result = disconnect.fuse() => {
+                        // The adapter may have exited on its own already.
+                        result.log_err();
+                    }
+                    _ = timer => {
+                        log::info!("timeout waiting for debug adapter {name} to disconnect");
+                    }
+                }
+                outbound_tx.close();
+                response_handlers.lock().take();
+                if let Some(mut process) = process.lock().take() {
+                    process.kill().log_err();
+                }
+                drop(tasks);
+                log::debug!("debug adapter {name} shutdown finished");
+                anyhow::Ok(())
+            }
+            .log_err(),
+        )
+    }
+}
+
+impl Drop for DebugAdapterClient {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown() {
+            self.executor.spawn(shutdown).detach();
+        }
+    }
+}
+
+impl fmt::Debug for DebugAdapterClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugAdapterClient")
+            .field("id", &self.id.0)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Subscription {
+    /// Detaching a subscription handle prevents it from unsubscribing on drop.
+    pub fn detach(&mut self) {
+        match self {
+            Subscription::Event { event_handlers, .. } => *event_handlers = None,
+            Subscription::Request {
+                request_handlers, ..
+            } => *request_handlers = None,
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        match self {
+            Subscription::Event {
+                event,
+                event_handlers,
+            } => {
+                if let Some(handlers) = event_handlers {
+                    handlers.lock().remove(event);
+                }
+            }
+            Subscription::Request {
+                command,
+                request_handlers,
+            } => {
+                if let Some(handlers) = request_handlers {
+                    handlers.lock().remove(command);
+                }
+            }
+        }
+    }
+}
+
+/// Mock debug adapter for use in tests, answering the requests of a [`DebugAdapterClient`]
+/// with the handlers registered on it.
+#[cfg(any(test, feature = "test-support"))]
+pub struct FakeDebugAdapter {
+    adapter: DebugAdapterClient,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl FakeDebugAdapter {
+    /// Constructs a client connected to a fake debug adapter.
+    pub fn new(
+        id: DebugAdapterClientId,
+        name: &str,
+        cx: AsyncAppContext,
+    ) -> (DebugAdapterClient, FakeDebugAdapter) {
+        let (stdin_writer, stdin_reader) = async_pipe::pipe();
+        let (stdout_writer, stdout_reader) = async_pipe::pipe();
+
+        let client = DebugAdapterClient::new_internal(
+            id,
+            name,
+            stdin_writer,
+            stdout_reader,
+            None::<async_pipe::PipeReader>,
+            None,
+            cx.clone(),
+        );
+        let fake = FakeDebugAdapter {
+            adapter: DebugAdapterClient::new_internal(
+                id,
+                name,
+                stdout_writer,
+                stdin_reader,
+                None::<async_pipe::PipeReader>,
+                None,
+                cx,
+            ),
+        };
+        fake.handle_request::<Initialize, _>(|_, _| Ok(Capabilities::default()));
+        fake.handle_request::<Disconnect, _>(|_, _| Ok(()));
+        (client, fake)
+    }
+
+    /// Sets the handler for requests of the given kind, replacing any previous one.
+    pub fn handle_request<R, F>(&self, f: F)
+    where
+        R: Request,
+        F: 'static + Send + FnMut(R::Arguments, AsyncAppContext) -> Result<R::Response>,
+    {
+        self.adapter.set_request_handler::<R, F>(f);
+    }
+
+    /// Sends an event to the client.
+    pub fn send_event<E: Event>(&self, body: E::Body) -> Result<()> {
+        let message = serde_json::to_string(&Message::Event(crate::types::EventMessage {
+            seq: self.adapter.next_seq.fetch_add(1, SeqCst),
+            event: E::EVENT.into(),
+            body: Some(serde_json::to_value(body)?),
+        }))?;
+        self.adapter.outbound_tx.try_send(message)?;
+        Ok(())
+    }
+
+    /// Sends a request to the client and waits for its response.
+    pub fn request<R: Request>(
+        &self,
+        arguments: R::Arguments,
+    ) -> impl 'static + Send + Future<Output = Result<R::Response>> {
+        self.adapter.request::<R>(arguments)
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl Drop for FakeDebugAdapter {
+    fn drop(&mut self) {
+        // The fake adapter has no process to stop, and must not disconnect from its client.
+        self.adapter.io_tasks.lock().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{Output, Stopped},
+        requests::{Continue, Threads},
+        types::*,
+    };
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_fake_debug_adapter(cx: &mut TestAppContext) {
+        let (client, fake) = FakeDebugAdapter::new(DebugAdapterClientId(0), "fake", cx.to_async());
+
+        fake.handle_request::<Initialize, _>(|arguments, _| {
+            assert_eq!(arguments.adapter_id, "fake");
+            assert!(arguments.lines_start_at1);
+            Ok(Capabilities {
+                supports_configuration_done_request: Some(true),
+                ..Default::default()
+            })
+        });
+        client.initialize("fake").await.unwrap();
+        assert_eq!(
+            client.capabilities().supports_configuration_done_request,
+            Some(true)
+        );
+
+        fake.handle_request::<Threads, _>(|_, _| {
+            Ok(ThreadsResponse {
+                threads: vec![Thread {
+                    id: 1,
+                    name: "main".into(),
+                }],
+            })
+        });
+        let threads = client.request::<Threads>(None).await.unwrap();
+        assert_eq!(threads.threads[0].name, "main");
+
+        fake.handle_request::<Continue, _>(|arguments, _| {
+            Err(anyhow!("thread {} is not stopped", arguments.thread_id))
+        });
+        let error = client
+            .request::<Continue>(ThreadArguments { thread_id: 7 })
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "thread 7 is not stopped");
+
+        let (stopped_tx, stopped_rx) = futures::channel::mpsc::unbounded();
+        let _stopped = client.on_event::<Stopped, _>(move |body, _| {
+            stopped_tx.unbounded_send(body).ok();
+        });
+        let (output_tx, output_rx) = futures::channel::mpsc::unbounded();
+        let output = client.on_event::<Output, _>(move |body, _| {
+            output_tx.unbounded_send(body.output).ok();
+        });
+        fake.send_event::<Output>(OutputEventBody {
+            category: Some("stdout".into()),
+            output: "hello\n".into(),
+        })
+        .unwrap();
+        fake.send_event::<Stopped>(StoppedEventBody {
+            reason: "breakpoint".into(),
+            description: None,
+            thread_id: Some(1),
+            all_threads_stopped: Some(true),
+            text: None,
+        })
+        .unwrap();
+        cx.run_until_parked();
+        drop(output);
+        assert_eq!(output_rx.collect::<Vec<_>>().await, vec!["hello\n"]);
+        let stopped = stopped_rx.take(1).collect::<Vec<_>>().await;
+        assert_eq!(stopped[0].thread_id, Some(1));
+
+        // Requests the client has no handler for are answered with an error.
+        let error = fake.request::<Threads>(None).await.unwrap_err();
+        assert_eq!(error.to_string(), "unsupported request threads");
+    }
+}
@@ -0,0 +1,7 @@
+mod client;
+pub mod events;
+pub mod requests;
+mod transport;
+pub mod types;
+
+pub use client::*;
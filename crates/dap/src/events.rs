@@ -0,0 +1,29 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::types::*;
+
+/// An event that a debug adapter sends to its client.
+pub trait Event {
+    type Body: DeserializeOwned + Serialize + Send + 'static;
+    const EVENT: &'static str;
+}
+
+macro_rules! event {
+    ($name:ident, $event:literal, $body:ty) => {
+        #[derive(Debug)]
+        pub enum $name {}
+
+        impl Event for $name {
+            type Body = $body;
+            const EVENT: &'static str = $event;
+        }
+    };
+}
+
+event!(Initialized, "initialized", Option<serde_json::Value>);
+event!(Stopped, "stopped", StoppedEventBody);
+event!(Continued, "continued", ContinuedEventBody);
+event!(Exited, "exited", ExitedEventBody);
+event!(Terminated, "terminated", Option<TerminatedEventBody>);
+event!(Output, "output", OutputEventBody);
+event!(Thread, "thread", ThreadEventBody);
@@ -0,0 +1,63 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::types::*;
+
+/// A request that a debug adapter client sends to a debug adapter, or that an adapter sends back
+/// to its client.
+pub trait Request {
+    type Arguments: DeserializeOwned + Serialize + Send + 'static;
+    type Response: DeserializeOwned + Serialize + Send + 'static;
+    const COMMAND: &'static str;
+}
+
+macro_rules! request {
+    ($name:ident, $command:literal, $arguments:ty, $response:ty) => {
+        #[derive(Debug)]
+        pub enum $name {}
+
+        impl Request for $name {
+            type Arguments = $arguments;
+            type Response = $response;
+            const COMMAND: &'static str = $command;
+        }
+    };
+}
+
+request!(
+    Initialize,
+    "initialize",
+    InitializeRequestArguments,
+    Capabilities
+);
+// The arguments of `launch` and `attach` are specific to each adapter.
+request!(Launch, "launch", Value, ());
+request!(Attach, "attach", Value, ());
+request!(ConfigurationDone, "configurationDone", Option<Value>, ());
+request!(
+    SetBreakpoints,
+    "setBreakpoints",
+    SetBreakpointsArguments,
+    SetBreakpointsResponse
+);
+request!(Threads, "threads", Option<Value>, ThreadsResponse);
+request!(
+    StackTrace,
+    "stackTrace",
+    StackTraceArguments,
+    StackTraceResponse
+);
+request!(Scopes, "scopes", ScopesArguments, ScopesResponse);
+request!(
+    Variables,
+    "variables",
+    VariablesArguments,
+    VariablesResponse
+);
+request!(Continue, "continue", ThreadArguments, ContinueResponse);
+request!(Next, "next", ThreadArguments, ());
+request!(StepIn, "stepIn", ThreadArguments, ());
+request!(StepOut, "stepOut", ThreadArguments, ());
+request!(Pause, "pause", ThreadArguments, ());
+request!(Disconnect, "disconnect", DisconnectArguments, ());
+request!(Evaluate, "evaluate", EvaluateArguments, EvaluateResponse);
@@ -0,0 +1,92 @@
+use std::str;
+
+use anyhow::{anyhow, Result};
+use futures::{AsyncBufReadExt as _, AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _};
+use smol::io::BufReader;
+
+const CONTENT_LEN_HEADER: &str = "Content-Length: ";
+const HEADER_DELIMITER: &[u8; 4] = b"\r\n\r\n";
+
+async fn read_headers<Input>(reader: &mut BufReader<Input>, buffer: &mut Vec<u8>) -> Result<()>
+where
+    Input: AsyncRead + Unpin + Send + 'static,
+{
+    loop {
+        if buffer.len() >= HEADER_DELIMITER.len()
+            && buffer[(buffer.len() - HEADER_DELIMITER.len())..] == HEADER_DELIMITER[..]
+        {
+            return Ok(());
+        }
+
+        if reader.read_until(b'\n', buffer).await? == 0 {
+            return Err(anyhow!("cannot read debug adapter message headers"));
+        }
+    }
+}
+
+/// Reads the content of the next message into `buffer`, dropping its headers.
+pub(crate) async fn read_message<Input>(
+    reader: &mut BufReader<Input>,
+    buffer: &mut Vec<u8>,
+) -> Result<()>
+where
+    Input: AsyncRead + Unpin + Send + 'static,
+{
+    buffer.clear();
+    read_headers(reader, buffer).await?;
+
+    let headers = str::from_utf8(buffer)?;
+    let message_len = headers
+        .split('\n')
+        .find_map(|line| line.strip_prefix(CONTENT_LEN_HEADER))
+        .ok_or_else(|| anyhow!("invalid debug adapter message header {headers:?}"))?
+        .trim_end()
+        .parse()?;
+
+    buffer.resize(message_len, 0);
+    reader.read_exact(buffer).await?;
+    Ok(())
+}
+
+pub(crate) async fn write_message<Output>(writer: &mut Output, content: &str) -> Result<()>
+where
+    Output: AsyncWrite + Unpin,
+{
+    writer.write_all(CONTENT_LEN_HEADER.as_bytes()).await?;
+    writer
+        .write_all(content.len().to_string().as_bytes())
+        .await?;
+    writer.write_all(HEADER_DELIMITER).await?;
+    writer.write_all(content.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_read_and_write_messages() {
+        let mut output = Vec::new();
+        write_message(&mut output, r#"{"seq":1}"#).await.unwrap();
+        write_message(&mut output, r#"{"seq":2,"body":"é"}"#)
+            .await
+            .unwrap();
+        assert!(output.starts_with(b"Content-Length: 9\r\n\r\n{\"seq\":1}Content-Length: 21\r\n"));
+
+        let mut reader = BufReader::new(smol::io::Cursor::new(output));
+        let mut buffer = Vec::new();
+        read_message(&mut reader, &mut buffer).await.unwrap();
+        assert_eq!(str::from_utf8(&buffer).unwrap(), r#"{"seq":1}"#);
+        read_message(&mut reader, &mut buffer).await.unwrap();
+        assert_eq!(str::from_utf8(&buffer).unwrap(), r#"{"seq":2,"body":"é"}"#);
+        assert!(read_message(&mut reader, &mut buffer).await.is_err());
+
+        let mut reader = BufReader::new(smol::io::Cursor::new(
+            b"Content-Type: application/json\r\nContent-Length: 2\r\n\r\n{}".to_vec(),
+        ));
+        read_message(&mut reader, &mut buffer).await.unwrap();
+        assert_eq!(buffer, b"{}");
+    }
+}
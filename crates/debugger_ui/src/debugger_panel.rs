@@ -0,0 +1,824 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use dap::{DebugAdapterBinary, DebugAdapterClient, DebugAdapterClientId};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    Action, AnyElement, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView,
+    Model, Pixels, Render, Subscription, Task, View, ViewContext, WeakView, WindowContext,
+};
+use language::Point;
+use project::{Fs, Project};
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use task::DebugTemplate;
+use ui::{prelude::*, ListItem, Tooltip};
+use util::{post_inc, ResultExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    item::ItemHandle as _,
+    notifications::NotifyTaskExt,
+    Workspace, WorkspaceId,
+};
+
+use crate::{
+    persistence::DB,
+    session::{DebugSession, DebugSessionEvent, DebugSessionStatus},
+    settings::DebuggerPanelSettings,
+    Continue, Pause, Start, StepInto, StepOut, StepOver, Stop, ToggleFocus,
+};
+
+const DEBUG_PANEL_KEY: &str = "DebugPanel";
+const BREAKPOINTS_SERIALIZATION_DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Deserialize)]
+struct SerializedDebugPanel {
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+}
+
+/// Highlights the row that the selected stack frame of a stopped debuggee is at.
+enum DebugStoppedRow {}
+
+/// A debug configuration of `.zed/debug.json`, along with the root of the worktree defining it.
+#[derive(Clone)]
+struct DebugConfiguration {
+    worktree_root: Arc<Path>,
+    template: DebugTemplate,
+}
+
+pub struct DebugPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    database_id: Option<WorkspaceId>,
+    configurations: Vec<DebugConfiguration>,
+    session: Option<Model<DebugSession>>,
+    stopped_editor: Option<WeakView<Editor>>,
+    console_input: View<Editor>,
+    next_client_id: usize,
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+    pending_serialization: Task<Option<()>>,
+    pending_breakpoints_serialization: Task<Option<()>>,
+    _session_subscriptions: Vec<Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DebugPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(DEBUG_PANEL_KEY) })
+            .await
+            .map_err(|e| anyhow!("Failed to load debug panel: {}", e))
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedDebugPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        let panel = workspace.update(&mut cx, |workspace, cx| {
+            let panel = cx.new_view(|cx| DebugPanel::new(workspace, cx));
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.height = serialized_panel.height.map(|px| px.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })?;
+
+        let database_id = panel.update(&mut cx, |panel, _| panel.database_id)?;
+        if let Some(database_id) = database_id {
+            let breakpoints = cx
+                .background_executor()
+                .spawn(async move { DB.get_breakpoints(database_id) })
+                .await
+                .context("loading breakpoints")
+                .log_err()
+                .unwrap_or_default();
+            panel.update(&mut cx, |panel, cx| {
+                panel.restore_breakpoints(breakpoints, cx)
+            })?;
+        }
+        Ok(panel)
+    }
+
+    fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
+        let project = workspace.project().clone();
+        let breakpoint_store = project.read(cx).breakpoint_store().clone();
+        let console_input = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Evaluate an expression", cx);
+            editor
+        });
+        let subscriptions = vec![
+            cx.subscribe(&breakpoint_store, |this, _, _, cx| {
+                this.serialize_breakpoints(cx)
+            }),
+            cx.subscribe(&project, |this, _, event, cx| match event {
+                project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                    this.reload_configurations(cx).detach();
+                }
+                _ => {}
+            }),
+        ];
+
+        let mut this = Self {
+            workspace: workspace.weak_handle(),
+            project,
+            fs: workspace.app_state().fs.clone(),
+            focus_handle: cx.focus_handle(),
+            database_id: workspace.database_id(),
+            configurations: Vec::new(),
+            session: None,
+            stopped_editor: None,
+            console_input,
+            next_client_id: 0,
+            width: None,
+            height: None,
+            pending_serialization: Task::ready(None),
+            pending_breakpoints_serialization: Task::ready(None),
+            _session_subscriptions: Vec::new(),
+            _subscriptions: subscriptions,
+        };
+        this.reload_configurations(cx).detach();
+        this
+    }
+
+    /// The current debug session, which may have exited already.
+    pub fn session(&self) -> Option<&Model<DebugSession>> {
+        self.session.as_ref()
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        let height = self.height;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        DEBUG_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedDebugPanel { width, height })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Adds the saved breakpoints to the ones toggled while they were loading.
+    fn restore_breakpoints(
+        &mut self,
+        breakpoints: Vec<(PathBuf, u32)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut rows_by_path = BTreeMap::<PathBuf, Vec<u32>>::new();
+        for (abs_path, row) in breakpoints {
+            rows_by_path.entry(abs_path).or_default().push(row);
+        }
+        let breakpoint_store = self.project.read(cx).breakpoint_store().clone();
+        breakpoint_store.update(cx, |store, cx| {
+            for (abs_path, mut rows) in rows_by_path {
+                rows.extend_from_slice(store.breakpoints_for_path(&abs_path));
+                store.set_breakpoints(abs_path.into(), rows, cx);
+            }
+        });
+    }
+
+    fn serialize_breakpoints(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(database_id) = self.database_id else {
+            return;
+        };
+        let breakpoint_store = self.project.read(cx).breakpoint_store().clone();
+        self.pending_breakpoints_serialization = cx.spawn(|_, cx| {
+            async move {
+                cx.background_executor()
+                    .timer(BREAKPOINTS_SERIALIZATION_DEBOUNCE)
+                    .await;
+                let breakpoints = breakpoint_store.read_with(&cx, |store, _| {
+                    store
+                        .breakpoints()
+                        .flat_map(|(abs_path, rows)| {
+                            rows.iter().map(|row| (abs_path.to_path_buf(), *row))
+                        })
+                        .collect::<Vec<_>>()
+                })?;
+                DB.save_breakpoints(database_id, breakpoints).await
+            }
+            .log_err()
+        });
+    }
+
+    fn reload_configurations(&mut self, cx: &mut ViewContext<Self>) -> Task<()> {
+        let fs = self.fs.clone();
+        let worktree_roots = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let mut configurations = Vec::new();
+            for worktree_root in worktree_roots {
+                let path = worktree_root.join(paths::local_debug_file_relative_path());
+                let Ok(contents) = fs.load(&path).await else {
+                    continue;
+                };
+                match serde_json_lenient::from_str::<task::DebugTemplates>(&contents) {
+                    Ok(templates) => {
+                        configurations.extend(templates.0.into_iter().map(|template| {
+                            DebugConfiguration {
+                                worktree_root: worktree_root.clone(),
+                                template,
+                            }
+                        }))
+                    }
+                    Err(error) => log::error!("failed to parse {path:?}: {error}"),
+                }
+            }
+            this.update(&mut cx, |this, cx| {
+                this.configurations = configurations;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    /// Continues the stopped debuggee, or starts a new session: right away if there is only one
+    /// debug configuration, by choosing one in the panel otherwise.
+    pub fn start(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(session) = &self.session {
+            match session.read(cx).status() {
+                DebugSessionStatus::Stopped { .. } => {
+                    session
+                        .update(cx, |session, cx| session.continue_thread(cx))
+                        .detach_and_notify_err(cx);
+                    return;
+                }
+                DebugSessionStatus::Starting | DebugSessionStatus::Running => return,
+                DebugSessionStatus::Exited => {}
+            }
+        }
+
+        let reload = self.reload_configurations(cx);
+        cx.spawn(|this, mut cx| async move {
+            reload.await;
+            this.update(&mut cx, |this, cx| {
+                if this.configurations.len() == 1 {
+                    this.start_configuration(0, cx);
+                } else {
+                    this.clear_session(cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn start_configuration(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(configuration) = self.configurations.get(ix).cloned() else {
+            return;
+        };
+        let Ok(task_context) = self.workspace.update(cx, |workspace, cx| {
+            editor::tasks::task_context(workspace, cx)
+        }) else {
+            return;
+        };
+        if let Some(session) = self.session.take() {
+            session.update(cx, |session, cx| session.stop(cx)).detach();
+        }
+        let breakpoint_store = self.project.read(cx).breakpoint_store().clone();
+        let client_id = DebugAdapterClientId(post_inc(&mut self.next_client_id));
+
+        cx.spawn(|this, mut cx| async move {
+            let task_context = task_context.await;
+            let template = configuration.template;
+            let resolved = template.resolve(&task_context).with_context(|| {
+                format!("failed to resolve debug configuration {:?}", template.label)
+            })?;
+            let binary = DebugAdapterBinary {
+                command: resolved.command,
+                arguments: resolved.args,
+                env: resolved.env,
+                cwd: Some(
+                    resolved
+                        .cwd
+                        .unwrap_or_else(|| configuration.worktree_root.to_path_buf()),
+                ),
+            };
+            let client =
+                DebugAdapterClient::new(client_id, &resolved.adapter, binary, (*cx).clone())?;
+
+            let start = this.update(&mut cx, |this, cx| {
+                let session = cx.new_model(|cx| {
+                    DebugSession::new(resolved.label, Arc::new(client), breakpoint_store, cx)
+                });
+                this.set_session(session.clone(), cx);
+                session.update(cx, |session, cx| {
+                    session.start(
+                        resolved.adapter,
+                        resolved.request,
+                        resolved.configuration,
+                        cx,
+                    )
+                })
+            })?;
+            start.await
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    fn set_session(&mut self, session: Model<DebugSession>, cx: &mut ViewContext<Self>) {
+        self.clear_stopped_row(cx);
+        self._session_subscriptions = vec![
+            cx.observe(&session, |_, _, cx| cx.notify()),
+            cx.subscribe(&session, |this, _, event, cx| match event {
+                DebugSessionEvent::Stopped => this.show_stopped_frame(cx),
+                DebugSessionEvent::Continued | DebugSessionEvent::Exited => {
+                    this.clear_stopped_row(cx)
+                }
+            }),
+        ];
+        self.session = Some(session);
+        cx.notify();
+    }
+
+    fn clear_session(&mut self, cx: &mut ViewContext<Self>) {
+        self.clear_stopped_row(cx);
+        self._session_subscriptions.clear();
+        self.session = None;
+        cx.notify();
+    }
+
+    fn show_stopped_frame(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        let Some(frame) = session.read(cx).selected_frame() else {
+            return;
+        };
+        let Some(abs_path) = frame.source.as_ref().and_then(|source| source.path.clone()) else {
+            return;
+        };
+        let row = frame.line.saturating_sub(1) as u32;
+        let Ok(open_path) = self.workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(PathBuf::from(abs_path), true, cx)
+        }) else {
+            return;
+        };
+
+        cx.spawn(|this, mut cx| async move {
+            let item = open_path.await?;
+            this.update(&mut cx, |this, cx| {
+                let Some(editor) = item.act_as::<Editor>(cx) else {
+                    return;
+                };
+                this.clear_stopped_row(cx);
+                editor.update(cx, |editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let point = Point::new(row.min(snapshot.max_point().row), 0);
+                    let anchor = snapshot.anchor_before(point);
+                    editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                        selections.select_ranges([point..point])
+                    });
+                    editor.highlight_rows::<DebugStoppedRow>(
+                        anchor..anchor,
+                        cx.theme().colors().editor_highlighted_line_background,
+                        true,
+                        cx,
+                    );
+                });
+                this.stopped_editor = Some(editor.downgrade());
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn clear_stopped_row(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(editor) = self
+            .stopped_editor
+            .take()
+            .and_then(|editor| editor.upgrade())
+        {
+            editor.update(cx, |editor, cx| {
+                editor.clear_row_highlights::<DebugStoppedRow>();
+                cx.notify();
+            });
+        }
+    }
+
+    fn confirm_console_input(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.session.clone() else {
+            return;
+        };
+        let expression = self.console_input.read(cx).text(cx);
+        if expression.trim().is_empty() {
+            return;
+        }
+        self.console_input
+            .update(cx, |editor, cx| editor.set_text("", cx));
+        session.update(cx, |session, cx| session.evaluate(expression, cx));
+    }
+
+    fn render_control_button(
+        &self,
+        id: &'static str,
+        icon: IconName,
+        tooltip: &'static str,
+        action: Box<dyn Action>,
+        enabled: bool,
+    ) -> impl IntoElement {
+        let tooltip_action = action.boxed_clone();
+        IconButton::new(id, icon)
+            .icon_size(IconSize::Small)
+            .disabled(!enabled)
+            .tooltip(move |cx| Tooltip::for_action(tooltip, &*tooltip_action, cx))
+            .on_click(move |_, cx| cx.dispatch_action(action.boxed_clone()))
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let session = self.session.as_ref().map(|session| session.read(cx));
+        let status = session.map(|session| session.status());
+        let is_stopped = matches!(status, Some(DebugSessionStatus::Stopped { .. }));
+        let is_running = status == Some(DebugSessionStatus::Running);
+        let is_live = status.is_some() && status != Some(DebugSessionStatus::Exited);
+        let title = match (session, status) {
+            (Some(session), Some(DebugSessionStatus::Starting)) => {
+                format!("{} (starting)", session.label())
+            }
+            (Some(session), Some(DebugSessionStatus::Running)) => {
+                format!("{} (running)", session.label())
+            }
+            (Some(session), Some(DebugSessionStatus::Stopped { .. })) => {
+                format!("{} (paused)", session.label())
+            }
+            (Some(session), _) => format!("{} (exited)", session.label()),
+            (None, _) => "No Debug Session".to_string(),
+        };
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(title).size(LabelSize::Small))
+            .child(
+                h_flex()
+                    .gap_0p5()
+                    .child(self.render_control_button(
+                        "debug-start",
+                        IconName::Play,
+                        if is_stopped {
+                            "Continue"
+                        } else {
+                            "Start Debugging"
+                        },
+                        if is_stopped {
+                            Box::new(Continue)
+                        } else {
+                            Box::new(Start)
+                        },
+                        is_stopped || !is_live,
+                    ))
+                    .child(self.render_control_button(
+                        "debug-pause",
+                        IconName::DebugPause,
+                        "Pause",
+                        Box::new(Pause),
+                        is_running,
+                    ))
+                    .child(self.render_control_button(
+                        "debug-step-over",
+                        IconName::DebugStepOver,
+                        "Step Over",
+                        Box::new(StepOver),
+                        is_stopped,
+                    ))
+                    .child(self.render_control_button(
+                        "debug-step-into",
+                        IconName::DebugStepInto,
+                        "Step Into",
+                        Box::new(StepInto),
+                        is_stopped,
+                    ))
+                    .child(self.render_control_button(
+                        "debug-step-out",
+                        IconName::DebugStepOut,
+                        "Step Out",
+                        Box::new(StepOut),
+                        is_stopped,
+                    ))
+                    .child(self.render_control_button(
+                        "debug-stop",
+                        IconName::Stop,
+                        "Stop",
+                        Box::new(Stop),
+                        is_live,
+                    )),
+            )
+    }
+
+    fn render_configurations(&self, cx: &mut ViewContext<Self>) -> AnyElement {
+        if self.configurations.is_empty() {
+            return v_flex()
+                .size_full()
+                .justify_center()
+                .items_center()
+                .gap_1()
+                .child(Label::new("No debug configurations").color(Color::Muted))
+                .child(
+                    Label::new(format!(
+                        "Add them to {}",
+                        paths::local_debug_file_relative_path().display()
+                    ))
+                    .size(LabelSize::Small)
+                    .color(Color::Placeholder),
+                )
+                .into_any_element();
+        }
+
+        v_flex()
+            .id("debug-configurations")
+            .size_full()
+            .p_1()
+            .overflow_y_scroll()
+            .children(
+                self.configurations
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, configuration)| {
+                        ListItem::new(("debug-configuration", ix))
+                            .start_slot(Icon::new(IconName::Debug).size(IconSize::Small))
+                            .child(Label::new(configuration.template.label.clone()))
+                            .end_slot(
+                                Label::new(configuration.template.adapter.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .on_click(
+                                cx.listener(move |this, _, cx| this.start_configuration(ix, cx)),
+                            )
+                    }),
+            )
+            .into_any_element()
+    }
+
+    fn render_stack_frames(
+        &self,
+        session: &Model<DebugSession>,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let session = session.read(cx);
+        let selected_frame_id = session.selected_frame().map(|frame| frame.id);
+        v_flex()
+            .id("debug-stack-frames")
+            .flex_1()
+            .h_full()
+            .overflow_y_scroll()
+            .child(section_header("Call Stack"))
+            .children(session.stack_frames().iter().map(|frame| {
+                let frame_id = frame.id;
+                let location = frame
+                    .source
+                    .as_ref()
+                    .and_then(|source| {
+                        source.name.clone().or_else(|| {
+                            source.path.as_ref().and_then(|path| {
+                                Path::new(path)
+                                    .file_name()
+                                    .map(|name| name.to_string_lossy().into_owned())
+                            })
+                        })
+                    })
+                    .map(|name| format!("{name}:{}", frame.line))
+                    .unwrap_or_default();
+                ListItem::new(("debug-stack-frame", frame_id as usize))
+                    .toggle_state(selected_frame_id == Some(frame_id))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(frame.name.clone()).size(LabelSize::Small))
+                            .child(
+                                Label::new(location)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .on_click(cx.listener(move |this, _, cx| {
+                        if let Some(session) = this.session.clone() {
+                            session.update(cx, |session, cx| session.select_frame(frame_id, cx));
+                        }
+                    }))
+            }))
+    }
+
+    fn render_variables(
+        &self,
+        session: &Model<DebugSession>,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let session = session.read(cx);
+        v_flex()
+            .id("debug-variables")
+            .flex_1()
+            .h_full()
+            .overflow_y_scroll()
+            .border_l_1()
+            .border_color(cx.theme().colors().border)
+            .child(section_header("Variables"))
+            .children(
+                session
+                    .scopes()
+                    .iter()
+                    .enumerate()
+                    .map(|(scope_ix, scope)| {
+                        v_flex()
+                            .child(
+                                div().px_2().child(
+                                    Label::new(scope.scope.name.clone())
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                            )
+                            .children(scope.variables.iter().enumerate().map(
+                                |(variable_ix, variable)| {
+                                    ListItem::new((
+                                        "debug-variable",
+                                        scope_ix * 10_000 + variable_ix,
+                                    ))
+                                    .indent_level(1)
+                                    .child(
+                                        h_flex()
+                                            .gap_1()
+                                            .child(
+                                                Label::new(format!("{}:", variable.name))
+                                                    .size(LabelSize::Small),
+                                            )
+                                            .child(
+                                                Label::new(variable.value.clone())
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Accent),
+                                            )
+                                            .children(variable.type_.clone().map(|type_| {
+                                                Label::new(type_)
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Muted)
+                                            })),
+                                    )
+                                },
+                            ))
+                    }),
+            )
+    }
+
+    fn render_console(
+        &self,
+        session: &Model<DebugSession>,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let session = session.read(cx);
+        v_flex()
+            .flex_1()
+            .h_full()
+            .border_l_1()
+            .border_color(cx.theme().colors().border)
+            .child(section_header("Console"))
+            .child(
+                v_flex()
+                    .id("debug-console")
+                    .flex_1()
+                    .px_2()
+                    .overflow_y_scroll()
+                    .children(session.console().iter().map(|entry| {
+                        let color = match entry.category.as_deref() {
+                            Some("stderr") => Color::Error,
+                            Some("input") | Some("console") => Color::Muted,
+                            _ => Color::Default,
+                        };
+                        Label::new(entry.text.trim_end().to_string())
+                            .size(LabelSize::Small)
+                            .color(color)
+                    })),
+            )
+            .child(
+                div()
+                    .key_context("DebugConsole")
+                    .on_action(cx.listener(Self::confirm_console_input))
+                    .px_2()
+                    .py_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.console_input.clone()),
+            )
+    }
+}
+
+fn section_header(title: &'static str) -> impl IntoElement {
+    div()
+        .px_2()
+        .py_1()
+        .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+}
+
+impl Render for DebugPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("DebugPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(self.render_toolbar(cx))
+            .child(match self.session.clone() {
+                Some(session) => h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .child(self.render_stack_frames(&session, cx))
+                    .child(self.render_variables(&session, cx))
+                    .child(self.render_console(&session, cx))
+                    .into_any_element(),
+                None => self.render_configurations(cx),
+            })
+    }
+}
+
+impl FocusableView for DebugPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for DebugPanel {}
+
+impl Panel for DebugPanel {
+    fn persistent_name() -> &'static str {
+        "DebugPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        DebuggerPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<DebuggerPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        let settings = DebuggerPanelSettings::get_global(cx);
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => {
+                self.width.unwrap_or(settings.default_width)
+            }
+            DockPosition::Bottom => self.height.unwrap_or(settings.default_height),
+        }
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => self.width = size,
+            DockPosition::Bottom => self.height = size,
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        Some(IconName::Debug).filter(|_| DebuggerPanelSettings::get_global(cx).button)
+    }
+
+    fn icon_tooltip(&self, _cx: &WindowContext) -> Option<&'static str> {
+        Some("Debugger Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
+        if active && self.session.is_none() {
+            self.reload_configurations(cx).detach();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use ::settings::Settings;
+use gpui::{actions, AppContext, ViewContext};
+use workspace::{notifications::NotifyTaskExt, Workspace};
+
+use debugger_panel::DebugPanel;
+use session::DebugSession;
+use settings::DebuggerPanelSettings;
+
+pub mod debugger_panel;
+mod persistence;
+pub mod session;
+mod settings;
+
+actions!(
+    debugger,
+    [
+        Start,
+        Continue,
+        Pause,
+        StepOver,
+        StepInto,
+        StepOut,
+        Stop,
+        ClearBreakpoints,
+        ToggleFocus
+    ]
+);
+
+pub fn init(cx: &mut AppContext) {
+    DebuggerPanelSettings::register(cx);
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace
+                .register_action(|workspace, _: &ToggleFocus, cx| {
+                    workspace.toggle_panel_focus::<DebugPanel>(cx);
+                })
+                .register_action(|workspace, _: &Start, cx| {
+                    if let Some(panel) = workspace.focus_panel::<DebugPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.start(cx));
+                    }
+                })
+                .register_action(|workspace, _: &Continue, cx| {
+                    update_session(workspace, cx, DebugSession::continue_thread);
+                })
+                .register_action(|workspace, _: &Pause, cx| {
+                    update_session(workspace, cx, DebugSession::pause);
+                })
+                .register_action(|workspace, _: &StepOver, cx| {
+                    update_session(workspace, cx, DebugSession::step_over);
+                })
+                .register_action(|workspace, _: &StepInto, cx| {
+                    update_session(workspace, cx, DebugSession::step_into);
+                })
+                .register_action(|workspace, _: &StepOut, cx| {
+                    update_session(workspace, cx, DebugSession::step_out);
+                })
+                .register_action(|workspace, _: &Stop, cx| {
+                    if let Some(session) = active_session(workspace, cx) {
+                        session.update(cx, |session, cx| session.stop(cx)).detach();
+                    }
+                })
+                .register_action(|workspace, _: &ClearBreakpoints, cx| {
+                    let breakpoint_store = workspace.project().read(cx).breakpoint_store().clone();
+                    breakpoint_store.update(cx, |store, cx| store.clear_breakpoints(cx));
+                });
+        },
+    )
+    .detach();
+}
+
+fn active_session(
+    workspace: &Workspace,
+    cx: &ViewContext<Workspace>,
+) -> Option<gpui::Model<DebugSession>> {
+    workspace
+        .panel::<DebugPanel>(cx)?
+        .read(cx)
+        .session()
+        .cloned()
+}
+
+fn update_session(
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+    update: impl FnOnce(
+        &mut DebugSession,
+        &mut gpui::ModelContext<DebugSession>,
+    ) -> gpui::Task<anyhow::Result<()>>,
+) {
+    if let Some(session) = active_session(workspace, cx) {
+        session.update(cx, update).detach_and_notify_err(cx);
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // breakpoints(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   row: u32,
+    // )
+    pub static ref DB: DebuggerDb<WorkspaceDb> =
+        &[sql! (
+            CREATE TABLE breakpoints(
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, path, row),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+);
+
+impl DebuggerDb {
+    // Returns the zero-based breakpoint rows of the workspace, per absolute path
+    query! {
+        pub fn get_breakpoints(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32)>> {
+            SELECT path, row
+            FROM breakpoints
+            WHERE workspace_id = ?
+            ORDER BY path, row
+        }
+    }
+
+    /// Replaces all breakpoints of the workspace.
+    pub async fn save_breakpoints(
+        &self,
+        workspace_id: WorkspaceId,
+        breakpoints: Vec<(PathBuf, u32)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_breakpoints", || {
+                conn.exec_bound(sql!(DELETE FROM breakpoints WHERE workspace_id = ?))?(
+                    workspace_id,
+                )?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO breakpoints(workspace_id, path, row) VALUES (?, ?, ?)
+                ))?;
+                for (path, row) in breakpoints {
+                    insert((workspace_id, path, row))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_and_get_breakpoints() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        assert_eq!(DB.get_breakpoints(workspace_id).unwrap(), Vec::new());
+
+        DB.save_breakpoints(
+            workspace_id,
+            vec![
+                (PathBuf::from("/project/b.rs"), 3),
+                (PathBuf::from("/project/a.rs"), 10),
+                (PathBuf::from("/project/a.rs"), 2),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            DB.get_breakpoints(workspace_id).unwrap(),
+            vec![
+                (PathBuf::from("/project/a.rs"), 2),
+                (PathBuf::from("/project/a.rs"), 10),
+                (PathBuf::from("/project/b.rs"), 3),
+            ]
+        );
+
+        DB.save_breakpoints(workspace_id, vec![(PathBuf::from("/project/c.rs"), 0)])
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_breakpoints(workspace_id).unwrap(),
+            vec![(PathBuf::from("/project/c.rs"), 0)]
+        );
+    }
+}
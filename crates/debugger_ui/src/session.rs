@@ -0,0 +1,725 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Result;
+use dap::{
+    events,
+    requests::{self, Request},
+    types::*,
+    DebugAdapterClient,
+};
+use gpui::{EventEmitter, Model, ModelContext, Subscription, Task};
+use project::{BreakpointStore, BreakpointStoreEvent};
+use task::DebugRequestKind;
+use util::ResultExt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugSessionStatus {
+    /// The adapter is being initialized and configured.
+    Starting,
+    Running,
+    /// The given thread of the debuggee is stopped, e.g. at a breakpoint.
+    Stopped {
+        thread_id: u64,
+    },
+    Exited,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DebugSessionEvent {
+    /// The debuggee stopped, and the stack of the stopped thread was fetched.
+    Stopped,
+    Continued,
+    Exited,
+}
+
+/// A line of the debug console: output of the debuggee, or an evaluated expression and its result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleEntry {
+    pub category: Option<String>,
+    pub text: String,
+}
+
+/// The variables of a scope of the selected stack frame.
+#[derive(Clone, Debug)]
+pub struct ScopeVariables {
+    pub scope: Scope,
+    pub variables: Vec<Variable>,
+}
+
+/// A running debug adapter, along with the state of the debuggee that it last reported.
+pub struct DebugSession {
+    label: String,
+    client: Arc<DebugAdapterClient>,
+    breakpoint_store: Model<BreakpointStore>,
+    status: DebugSessionStatus,
+    threads: Vec<Thread>,
+    stack_frames: Vec<StackFrame>,
+    selected_frame_id: Option<u64>,
+    scopes: Vec<ScopeVariables>,
+    console: Vec<ConsoleEntry>,
+    fetch_task: Task<()>,
+    _dap_subscriptions: Vec<dap::Subscription>,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<DebugSessionEvent> for DebugSession {}
+
+impl DebugSession {
+    pub fn new(
+        label: String,
+        client: Arc<DebugAdapterClient>,
+        breakpoint_store: Model<BreakpointStore>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let this = cx.weak_model();
+        let dap_subscriptions = vec![
+            client.on_event::<events::Initialized, _>({
+                let this = this.clone();
+                move |_, mut cx| {
+                    this.update(&mut cx, |this, cx| this.configure(cx)).ok();
+                }
+            }),
+            client.on_event::<events::Stopped, _>({
+                let this = this.clone();
+                move |body, mut cx| {
+                    this.update(&mut cx, |this, cx| this.stopped(body, cx)).ok();
+                }
+            }),
+            client.on_event::<events::Continued, _>({
+                let this = this.clone();
+                move |_, mut cx| {
+                    this.update(&mut cx, |this, cx| this.resumed(cx)).ok();
+                }
+            }),
+            client.on_event::<events::Output, _>({
+                let this = this.clone();
+                move |body, mut cx| {
+                    this.update(&mut cx, |this, cx| {
+                        this.push_console_entry(body.category, body.output, cx)
+                    })
+                    .ok();
+                }
+            }),
+            client.on_event::<events::Exited, _>({
+                let this = this.clone();
+                move |body, mut cx| {
+                    this.update(&mut cx, |this, cx| {
+                        this.push_console_entry(
+                            Some("console".into()),
+                            format!("Process exited with code {}", body.exit_code),
+                            cx,
+                        )
+                    })
+                    .ok();
+                }
+            }),
+            client.on_event::<events::Terminated, _>(move |_, mut cx| {
+                this.update(&mut cx, |this, cx| this.exited(cx)).ok();
+            }),
+        ];
+
+        Self {
+            label,
+            client,
+            _subscription: cx.subscribe(&breakpoint_store, Self::on_breakpoint_store_event),
+            breakpoint_store,
+            status: DebugSessionStatus::Starting,
+            threads: Vec::new(),
+            stack_frames: Vec::new(),
+            selected_frame_id: None,
+            scopes: Vec::new(),
+            console: Vec::new(),
+            fetch_task: Task::ready(()),
+            _dap_subscriptions: dap_subscriptions,
+        }
+    }
+
+    /// Initializes the debug adapter and sends it the `launch` or `attach` request.
+    /// Breakpoints are sent once the adapter reports that it is initialized.
+    pub fn start(
+        &mut self,
+        adapter_id: String,
+        request: DebugRequestKind,
+        configuration: serde_json::Value,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let client = self.client.clone();
+        cx.spawn(|this, mut cx| async move {
+            let result = async {
+                client.initialize(&adapter_id).await?;
+                match request {
+                    DebugRequestKind::Launch => {
+                        client.request::<requests::Launch>(configuration).await?
+                    }
+                    DebugRequestKind::Attach => {
+                        client.request::<requests::Attach>(configuration).await?
+                    }
+                }
+                anyhow::Ok(())
+            }
+            .await;
+            if let Err(error) = &result {
+                this.update(&mut cx, |this, cx| {
+                    this.push_console_entry(Some("stderr".into()), error.to_string(), cx);
+                    this.exited(cx);
+                })
+                .ok();
+            }
+            result
+        })
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn client(&self) -> &Arc<DebugAdapterClient> {
+        &self.client
+    }
+
+    pub fn status(&self) -> DebugSessionStatus {
+        self.status
+    }
+
+    pub fn threads(&self) -> &[Thread] {
+        &self.threads
+    }
+
+    /// The stack of the stopped thread, innermost frame first.
+    pub fn stack_frames(&self) -> &[StackFrame] {
+        &self.stack_frames
+    }
+
+    pub fn selected_frame(&self) -> Option<&StackFrame> {
+        let frame_id = self.selected_frame_id?;
+        self.stack_frames.iter().find(|frame| frame.id == frame_id)
+    }
+
+    /// The scopes of the selected stack frame, with the variables of the inexpensive ones.
+    pub fn scopes(&self) -> &[ScopeVariables] {
+        &self.scopes
+    }
+
+    pub fn console(&self) -> &[ConsoleEntry] {
+        &self.console
+    }
+
+    fn configure(&mut self, cx: &mut ModelContext<Self>) {
+        let set_breakpoints = self
+            .breakpoint_store
+            .read(cx)
+            .breakpoints()
+            .map(|(abs_path, rows)| {
+                self.client
+                    .request::<requests::SetBreakpoints>(set_breakpoints_arguments(abs_path, rows))
+            })
+            .collect::<Vec<_>>();
+        let configuration_done = self
+            .client
+            .capabilities()
+            .supports_configuration_done_request
+            .unwrap_or(false)
+            .then(|| self.client.request::<requests::ConfigurationDone>(None));
+
+        cx.spawn(|this, mut cx| async move {
+            for response in futures::future::join_all(set_breakpoints).await {
+                response.log_err();
+            }
+            if let Some(configuration_done) = configuration_done {
+                configuration_done.await?;
+            }
+            this.update(&mut cx, |this, cx| {
+                if this.status == DebugSessionStatus::Starting {
+                    this.status = DebugSessionStatus::Running;
+                    cx.notify();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn on_breakpoint_store_event(
+        &mut self,
+        breakpoint_store: Model<BreakpointStore>,
+        event: &BreakpointStoreEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if matches!(
+            self.status,
+            DebugSessionStatus::Starting | DebugSessionStatus::Exited
+        ) {
+            return;
+        }
+        let BreakpointStoreEvent::BreakpointsChanged(abs_path) = event;
+        let rows = breakpoint_store.read(cx).breakpoints_for_path(abs_path);
+        let request = self
+            .client
+            .request::<requests::SetBreakpoints>(set_breakpoints_arguments(abs_path, rows));
+        cx.background_executor()
+            .spawn(async move { request.await.log_err() })
+            .detach();
+    }
+
+    fn stopped(&mut self, body: StoppedEventBody, cx: &mut ModelContext<Self>) {
+        let thread_id = body
+            .thread_id
+            .or_else(|| match self.status {
+                DebugSessionStatus::Stopped { thread_id } => Some(thread_id),
+                _ => None,
+            })
+            .or_else(|| self.threads.first().map(|thread| thread.id));
+        let description = body.description.unwrap_or(body.reason);
+        self.push_console_entry(
+            Some("console".into()),
+            format!("Paused on {description}"),
+            cx,
+        );
+
+        let client = self.client.clone();
+        self.fetch_task = cx.spawn(|this, mut cx| async move {
+            let result = async {
+                let threads = client.request::<requests::Threads>(None).await?.threads;
+                let thread_id = thread_id
+                    .or_else(|| threads.first().map(|thread| thread.id))
+                    .ok_or_else(|| anyhow::anyhow!("no thread stopped"))?;
+                let stack_frames = client
+                    .request::<requests::StackTrace>(StackTraceArguments {
+                        thread_id,
+                        start_frame: None,
+                        levels: None,
+                    })
+                    .await?
+                    .stack_frames;
+                let selected_frame_id = stack_frames.first().map(|frame| frame.id);
+                let scopes = match selected_frame_id {
+                    Some(frame_id) => fetch_scopes(&client, frame_id).await?,
+                    None => Vec::new(),
+                };
+                this.update(&mut cx, |this, cx| {
+                    this.status = DebugSessionStatus::Stopped { thread_id };
+                    this.threads = threads;
+                    this.stack_frames = stack_frames;
+                    this.selected_frame_id = selected_frame_id;
+                    this.scopes = scopes;
+                    cx.emit(DebugSessionEvent::Stopped);
+                    cx.notify();
+                })
+            }
+            .await;
+            result.log_err();
+        });
+    }
+
+    /// Shows the scopes and variables of the given frame of the stopped thread.
+    pub fn select_frame(&mut self, frame_id: u64, cx: &mut ModelContext<Self>) {
+        if self.selected_frame_id == Some(frame_id) {
+            return;
+        }
+        let client = self.client.clone();
+        self.fetch_task = cx.spawn(|this, mut cx| async move {
+            let Some(scopes) = fetch_scopes(&client, frame_id).await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.selected_frame_id = Some(frame_id);
+                this.scopes = scopes;
+                cx.emit(DebugSessionEvent::Stopped);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    pub fn continue_thread(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        self.thread_request::<requests::Continue>(cx)
+    }
+
+    pub fn step_over(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        self.thread_request::<requests::Next>(cx)
+    }
+
+    pub fn step_into(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        self.thread_request::<requests::StepIn>(cx)
+    }
+
+    pub fn step_out(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        self.thread_request::<requests::StepOut>(cx)
+    }
+
+    fn thread_request<R>(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>>
+    where
+        R: Request<Arguments = ThreadArguments>,
+    {
+        let DebugSessionStatus::Stopped { thread_id } = self.status else {
+            return Task::ready(Ok(()));
+        };
+        let request = self.client.request::<R>(ThreadArguments { thread_id });
+        self.resumed(cx);
+        cx.background_executor().spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    /// Pauses the first thread of the running debuggee.
+    pub fn pause(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        if self.status != DebugSessionStatus::Running {
+            return Task::ready(Ok(()));
+        }
+        let client = self.client.clone();
+        cx.background_executor().spawn(async move {
+            let threads = client.request::<requests::Threads>(None).await?.threads;
+            if let Some(thread) = threads.first() {
+                client
+                    .request::<requests::Pause>(ThreadArguments {
+                        thread_id: thread.id,
+                    })
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Disconnects from the debug adapter, terminating the debuggee.
+    pub fn stop(&mut self, cx: &mut ModelContext<Self>) -> Task<()> {
+        let shutdown = self.client.shutdown();
+        self.exited(cx);
+        cx.background_executor().spawn(async move {
+            if let Some(shutdown) = shutdown {
+                shutdown.await;
+            }
+        })
+    }
+
+    /// Evaluates an expression in the context of the selected stack frame, and prints its
+    /// result to the console.
+    pub fn evaluate(&mut self, expression: String, cx: &mut ModelContext<Self>) {
+        if self.status == DebugSessionStatus::Exited {
+            return;
+        }
+        self.push_console_entry(Some("input".into()), format!("> {expression}"), cx);
+        let request = self
+            .client
+            .request::<requests::Evaluate>(EvaluateArguments {
+                expression,
+                frame_id: self.selected_frame_id,
+                context: Some("repl".into()),
+            });
+        cx.spawn(|this, mut cx| async move {
+            let (category, text) = match request.await {
+                Ok(response) => (None, response.result),
+                Err(error) => (Some("stderr".into()), error.to_string()),
+            };
+            this.update(&mut cx, |this, cx| {
+                this.push_console_entry(category, text, cx)
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn push_console_entry(
+        &mut self,
+        category: Option<String>,
+        text: String,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if category.as_deref() == Some("telemetry") {
+            return;
+        }
+        self.console.push(ConsoleEntry { category, text });
+        cx.notify();
+    }
+
+    fn resumed(&mut self, cx: &mut ModelContext<Self>) {
+        if self.status == DebugSessionStatus::Exited {
+            return;
+        }
+        self.status = DebugSessionStatus::Running;
+        self.stack_frames.clear();
+        self.selected_frame_id = None;
+        self.scopes.clear();
+        self.fetch_task = Task::ready(());
+        cx.emit(DebugSessionEvent::Continued);
+        cx.notify();
+    }
+
+    fn exited(&mut self, cx: &mut ModelContext<Self>) {
+        if self.status == DebugSessionStatus::Exited {
+            return;
+        }
+        self.status = DebugSessionStatus::Exited;
+        self.threads.clear();
+        self.stack_frames.clear();
+        self.selected_frame_id = None;
+        self.scopes.clear();
+        self.fetch_task = Task::ready(());
+        cx.emit(DebugSessionEvent::Exited);
+        cx.notify();
+    }
+}
+
+/// Debug adapters expect one-based lines, while breakpoints are stored as zero-based rows.
+fn set_breakpoints_arguments(abs_path: &Path, rows: &[u32]) -> SetBreakpointsArguments {
+    SetBreakpointsArguments {
+        source: Source {
+            name: abs_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            path: Some(abs_path.to_string_lossy().into_owned()),
+            source_reference: None,
+        },
+        breakpoints: rows
+            .iter()
+            .map(|row| SourceBreakpoint {
+                line: *row as u64 + 1,
+                column: None,
+                condition: None,
+            })
+            .collect(),
+        source_modified: Some(false),
+    }
+}
+
+async fn fetch_scopes(client: &DebugAdapterClient, frame_id: u64) -> Result<Vec<ScopeVariables>> {
+    let scopes = client
+        .request::<requests::Scopes>(ScopesArguments { frame_id })
+        .await?
+        .scopes;
+    let mut result = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        let variables = if scope.expensive {
+            Vec::new()
+        } else {
+            client
+                .request::<requests::Variables>(VariablesArguments {
+                    variables_reference: scope.variables_reference,
+                })
+                .await?
+                .variables
+        };
+        result.push(ScopeVariables { scope, variables });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use dap::{DebugAdapterClientId, FakeDebugAdapter};
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use project::Project;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_debug_session(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({ "main.rs": "fn main() {\n    one();\n    two();\n}" }),
+        )
+        .await;
+        let project = Project::test(fs, ["/project".as_ref()], cx).await;
+        let breakpoint_store =
+            project.read_with(cx, |project, _| project.breakpoint_store().clone());
+        breakpoint_store.update(cx, |store, cx| {
+            store.set_breakpoints(Path::new("/project/main.rs").into(), vec![1], cx)
+        });
+
+        let (client, fake) = FakeDebugAdapter::new(DebugAdapterClientId(0), "fake", cx.to_async());
+        let sent_breakpoints = Arc::new(Mutex::new(Vec::new()));
+        fake.handle_request::<requests::Initialize, _>(|_, _| {
+            Ok(Capabilities {
+                supports_configuration_done_request: Some(true),
+                ..Capabilities::default()
+            })
+        });
+        fake.handle_request::<requests::Launch, _>(|configuration, _| {
+            assert_eq!(configuration, json!({ "program": "main" }));
+            Ok(())
+        });
+        fake.handle_request::<requests::SetBreakpoints, _>({
+            let sent_breakpoints = sent_breakpoints.clone();
+            move |arguments, _| {
+                sent_breakpoints.lock().unwrap().push((
+                    arguments.source.path.unwrap(),
+                    arguments
+                        .breakpoints
+                        .iter()
+                        .map(|breakpoint| breakpoint.line)
+                        .collect::<Vec<_>>(),
+                ));
+                Ok(SetBreakpointsResponse {
+                    breakpoints: Vec::new(),
+                })
+            }
+        });
+        fake.handle_request::<requests::ConfigurationDone, _>(|_, _| Ok(()));
+        fake.handle_request::<requests::Threads, _>(|_, _| {
+            Ok(ThreadsResponse {
+                threads: vec![Thread {
+                    id: 1,
+                    name: "main".into(),
+                }],
+            })
+        });
+        fake.handle_request::<requests::StackTrace, _>(|arguments, _| {
+            assert_eq!(arguments.thread_id, 1);
+            Ok(StackTraceResponse {
+                stack_frames: vec![StackFrame {
+                    id: 7,
+                    name: "main".into(),
+                    source: Some(Source {
+                        name: None,
+                        path: Some("/project/main.rs".into()),
+                        source_reference: None,
+                    }),
+                    line: 2,
+                    column: 5,
+                }],
+                total_frames: Some(1),
+            })
+        });
+        fake.handle_request::<requests::Scopes, _>(|arguments, _| {
+            assert_eq!(arguments.frame_id, 7);
+            Ok(ScopesResponse {
+                scopes: vec![Scope {
+                    name: "Locals".into(),
+                    variables_reference: 3,
+                    expensive: false,
+                }],
+            })
+        });
+        fake.handle_request::<requests::Variables, _>(|_, _| {
+            Ok(VariablesResponse {
+                variables: vec![Variable {
+                    name: "x".into(),
+                    value: "42".into(),
+                    type_: Some("i32".into()),
+                    variables_reference: 0,
+                }],
+            })
+        });
+        fake.handle_request::<requests::Next, _>(|arguments, _| {
+            assert_eq!(arguments.thread_id, 1);
+            Ok(())
+        });
+
+        let session = cx.new_model(|cx| {
+            DebugSession::new(
+                "Debug main".into(),
+                Arc::new(client),
+                breakpoint_store.clone(),
+                cx,
+            )
+        });
+        let events = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            let events = events.clone();
+            cx.subscribe(&session, move |_, event, _| {
+                events.lock().unwrap().push(event.clone())
+            })
+            .detach();
+        });
+
+        session
+            .update(cx, |session, cx| {
+                session.start(
+                    "fake".into(),
+                    DebugRequestKind::Launch,
+                    json!({ "program": "main" }),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        fake.send_event::<events::Initialized>(None).unwrap();
+        cx.run_until_parked();
+        session.read_with(cx, |session, _| {
+            assert_eq!(session.status(), DebugSessionStatus::Running)
+        });
+        assert_eq!(
+            sent_breakpoints
+                .lock()
+                .unwrap()
+                .drain(..)
+                .collect::<Vec<_>>(),
+            vec![("/project/main.rs".to_string(), vec![2])]
+        );
+
+        fake.send_event::<events::Stopped>(StoppedEventBody {
+            reason: "breakpoint".into(),
+            description: None,
+            thread_id: Some(1),
+            all_threads_stopped: Some(true),
+            text: None,
+        })
+        .unwrap();
+        cx.run_until_parked();
+        session.read_with(cx, |session, _| {
+            assert_eq!(
+                session.status(),
+                DebugSessionStatus::Stopped { thread_id: 1 }
+            );
+            assert_eq!(session.selected_frame().unwrap().line, 2);
+            assert_eq!(session.scopes().len(), 1);
+            assert_eq!(session.scopes()[0].variables[0].value, "42");
+            assert_eq!(
+                session.console().last().unwrap().text,
+                "Paused on breakpoint"
+            );
+        });
+
+        // Breakpoints changed while the session runs are sent to the adapter.
+        breakpoint_store.update(cx, |store, cx| {
+            store.set_breakpoints(Path::new("/project/main.rs").into(), vec![1, 2], cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            sent_breakpoints
+                .lock()
+                .unwrap()
+                .drain(..)
+                .collect::<Vec<_>>(),
+            vec![("/project/main.rs".to_string(), vec![2, 3])]
+        );
+
+        session
+            .update(cx, |session, cx| session.step_over(cx))
+            .await
+            .unwrap();
+        session.read_with(cx, |session, _| {
+            assert_eq!(session.status(), DebugSessionStatus::Running);
+            assert!(session.stack_frames().is_empty());
+        });
+
+        fake.send_event::<events::Terminated>(None).unwrap();
+        cx.run_until_parked();
+        session.read_with(cx, |session, _| {
+            assert_eq!(session.status(), DebugSessionStatus::Exited)
+        });
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                DebugSessionEvent::Stopped,
+                DebugSessionEvent::Continued,
+                DebugSessionEvent::Exited
+            ]
+        );
+    }
+}
@@ -211,9 +211,12 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptBothConflictSides,
         AcceptInlineCompletion,
+        AcceptOursConflictSide,
         AcceptPartialCopilotSuggestion,
         AcceptPartialInlineCompletion,
+        AcceptTheirsConflictSide,
         AddSelectionAbove,
         AddSelectionBelow,
         ApplyAllDiffHunks,
@@ -362,10 +365,13 @@ gpui::actions!(
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageSelectedHunks,
+        StageSelectedLines,
         SwitchSourceHeader,
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBreakpoint,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleHunkDiff,
@@ -385,6 +391,7 @@ gpui::actions!(
         UnfoldRecursive,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
+        UnstageSelectedHunks,
     ]
 );
 
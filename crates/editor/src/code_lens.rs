@@ -0,0 +1,326 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use collections::{HashMap, HashSet};
+use futures::future;
+use gpui::{Model, WeakView};
+use language::{language_settings::language_settings, Buffer, Point};
+use multi_buffer::ToPoint as _;
+use project::CodeLens;
+use text::{Bias, ToPoint};
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::notifications::NotifyTaskExt;
+
+use crate::{
+    display_map::CustomBlockId,
+    lsp_refresh::{debounced_buffer_request, RefreshScope},
+    BlockContext, BlockPlacement, BlockProperties, BlockStyle, Editor, EditorMode, RenderBlock,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The code lenses shown above one row of a buffer, in a block per excerpt containing it.
+pub(super) struct CodeLensRow {
+    lenses: Vec<CodeLens>,
+    block_ids: Vec<CustomBlockId>,
+    resolve_requested: bool,
+}
+
+/// Requests code lenses for the buffers in `scope` that have them enabled,
+/// and shows them as blocks above the lines they belong to.
+pub(super) fn refresh_code_lens(
+    editor: &mut Editor,
+    scope: RefreshScope,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.clone()?;
+
+    let buffers = scope
+        .buffers(editor, cx)
+        .into_iter()
+        .filter(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx).show_code_lens
+        })
+        .collect::<Vec<_>>();
+    let enabled_buffers = buffers
+        .iter()
+        .map(|buffer| buffer.read(cx).remote_id())
+        .collect::<HashSet<_>>();
+    editor
+        .code_lens_tasks
+        .retain(|buffer_id, _| !scope.covers(*buffer_id) || enabled_buffers.contains(buffer_id));
+    let stale_blocks = editor
+        .code_lens_rows
+        .iter()
+        .filter(|(buffer_id, _)| scope.covers(**buffer_id) && !enabled_buffers.contains(buffer_id))
+        .flat_map(|(_, rows)| rows.values())
+        .flat_map(|row| row.block_ids.iter().copied())
+        .collect::<HashSet<_>>();
+    if !stale_blocks.is_empty() {
+        editor.code_lens_rows.retain(|buffer_id, _| {
+            !scope.covers(*buffer_id) || enabled_buffers.contains(buffer_id)
+        });
+        editor.remove_blocks(stale_blocks, None, cx);
+    }
+
+    for buffer in buffers {
+        let buffer_id = buffer.read(cx).remote_id();
+        let task = debounced_buffer_request(
+            project.clone(),
+            buffer,
+            UPDATE_DEBOUNCE,
+            |project, buffer, cx| project.code_lens(buffer, cx),
+            update_code_lens_blocks,
+            cx,
+        );
+        editor.code_lens_tasks.insert(buffer_id, task);
+    }
+
+    Some(())
+}
+
+/// Resolves the code lenses of the rows that are currently visible, for servers that
+/// report lenses without their commands. Resolving happens once per fetched row.
+pub(super) fn resolve_visible_code_lens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.code_lens_rows.is_empty() {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let visible_start = editor.scroll_manager.anchor().anchor.to_point(&snapshot);
+    let visible_end = snapshot.clip_point(
+        visible_start + Point::new(editor.visible_line_count().unwrap_or(0.).ceil() as u32, 0),
+        Bias::Left,
+    );
+    let visible_rows = multi_buffer
+        .range_to_buffer_ranges(visible_start..visible_end, cx)
+        .into_iter()
+        .map(|(buffer, range, _)| {
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            let rows = buffer_snapshot.offset_to_point(range.start).row
+                ..=buffer_snapshot.offset_to_point(range.end).row;
+            (buffer, rows)
+        })
+        .collect::<Vec<_>>();
+
+    for (buffer, rows) in visible_rows {
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(code_lens_rows) = editor.code_lens_rows.get_mut(&buffer_id) else {
+            continue;
+        };
+        for (row, code_lens_row) in code_lens_rows.range_mut(rows) {
+            if code_lens_row.resolve_requested
+                || code_lens_row.lenses.iter().all(CodeLens::is_resolved)
+            {
+                continue;
+            }
+            code_lens_row.resolve_requested = true;
+
+            let resolve_tasks = project.update(cx, |project, cx| {
+                code_lens_row
+                    .lenses
+                    .iter()
+                    .map(|lens| project.resolve_code_lens(buffer.clone(), lens.clone(), cx))
+                    .collect::<Vec<_>>()
+            });
+            let row = *row;
+            let block_ids = code_lens_row.block_ids.clone();
+            let buffer = buffer.clone();
+            cx.spawn(|editor, mut cx| async move {
+                let lenses = future::join_all(resolve_tasks)
+                    .await
+                    .into_iter()
+                    .filter_map(|lens| lens.log_err())
+                    .collect::<Vec<_>>();
+                editor
+                    .update(&mut cx, |editor, cx| {
+                        // The lenses were fetched again while these were resolving.
+                        let Some(code_lens_row) = editor
+                            .code_lens_rows
+                            .get_mut(&buffer_id)
+                            .and_then(|rows| rows.get_mut(&row))
+                            .filter(|code_lens_row| code_lens_row.block_ids == block_ids)
+                        else {
+                            return;
+                        };
+                        code_lens_row.lenses = lenses;
+                        let weak_editor = cx.view().downgrade();
+                        let renderers = block_ids
+                            .iter()
+                            .map(|block_id| {
+                                let render = render_code_lens_block(
+                                    weak_editor.clone(),
+                                    buffer.clone(),
+                                    code_lens_row.lenses.clone(),
+                                );
+                                (*block_id, render)
+                            })
+                            .collect::<HashMap<_, _>>();
+                        editor.replace_blocks(renderers, None, cx);
+                    })
+                    .ok();
+            })
+            .detach();
+        }
+    }
+}
+
+/// Replaces the blocks of a buffer's code lenses. Every row with lenses gets a block right
+/// away, so that resolving the lenses once they're scrolled into view doesn't move the text.
+fn update_code_lens_blocks(
+    editor: &mut Editor,
+    buffer: Model<Buffer>,
+    lenses: Vec<CodeLens>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let buffer_id = buffer.read(cx).remote_id();
+    if let Some(rows) = editor.code_lens_rows.remove(&buffer_id) {
+        let block_ids = rows
+            .into_values()
+            .flat_map(|row| row.block_ids)
+            .collect::<HashSet<_>>();
+        editor.remove_blocks(block_ids, None, cx);
+    }
+
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let mut rows = BTreeMap::<u32, CodeLensRow>::new();
+    for lens in lenses {
+        let row = lens.range.start.to_point(&buffer_snapshot).row;
+        rows.entry(row)
+            .or_insert_with(|| CodeLensRow {
+                lenses: Vec::new(),
+                block_ids: Vec::new(),
+                resolve_requested: false,
+            })
+            .lenses
+            .push(lens);
+    }
+
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let weak_editor = cx.view().downgrade();
+    let mut blocks = Vec::new();
+    let mut block_rows = Vec::new();
+    for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+        let context = excerpt_range.context.start.to_point(&buffer_snapshot).row
+            ..=excerpt_range.context.end.to_point(&buffer_snapshot).row;
+        for (row, code_lens_row) in rows.range(context) {
+            let indent = buffer_snapshot.indent_size_for_line(*row).len;
+            let position = buffer_snapshot.anchor_before(Point::new(*row, indent));
+            let Some(position) = snapshot.anchor_in_excerpt(excerpt_id, position) else {
+                continue;
+            };
+            blocks.push(BlockProperties {
+                placement: BlockPlacement::Above(position),
+                height: 1,
+                style: BlockStyle::Flex,
+                render: render_code_lens_block(
+                    weak_editor.clone(),
+                    buffer.clone(),
+                    code_lens_row.lenses.clone(),
+                ),
+                priority: 0,
+            });
+            block_rows.push(*row);
+        }
+    }
+
+    let block_ids = editor.insert_blocks(blocks, None, cx);
+    for (row, block_id) in block_rows.into_iter().zip(block_ids) {
+        if let Some(code_lens_row) = rows.get_mut(&row) {
+            code_lens_row.block_ids.push(block_id);
+        }
+    }
+    rows.retain(|_, code_lens_row| !code_lens_row.block_ids.is_empty());
+    if !rows.is_empty() {
+        editor.code_lens_rows.insert(buffer_id, rows);
+        resolve_visible_code_lens(editor, cx);
+    }
+}
+
+fn render_code_lens_block(
+    editor: WeakView<Editor>,
+    buffer: Model<Buffer>,
+    lenses: Vec<CodeLens>,
+) -> RenderBlock {
+    Arc::new(move |cx: &mut BlockContext| {
+        let mut children = Vec::with_capacity(lenses.len() * 2);
+        for (ix, lens) in lenses.iter().enumerate() {
+            // Lenses that aren't resolved yet, or failed to resolve, have nothing to show.
+            let Some(title) = lens.title() else {
+                continue;
+            };
+            if !children.is_empty() {
+                children.push(
+                    Label::new("|")
+                        .size(LabelSize::Small)
+                        .color(Color::Disabled)
+                        .into_any_element(),
+                );
+            }
+            children.push(
+                Button::new(("code-lens", ix), title.to_string())
+                    .label_size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .style(ButtonStyle::Transparent)
+                    .on_click({
+                        let editor = editor.clone();
+                        let buffer = buffer.clone();
+                        let lens = lens.clone();
+                        move |_, cx| {
+                            editor
+                                .update(cx, |editor, cx| {
+                                    apply_code_lens(editor, buffer.clone(), lens.clone(), cx)
+                                })
+                                .ok();
+                        }
+                    })
+                    .into_any_element(),
+            );
+        }
+
+        h_flex()
+            .id(cx.block_id)
+            .block_mouse_down()
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(children)
+            .into_any_element()
+    })
+}
+
+/// Runs the command of a code lens, showing the resulting edits. Commands that the language
+/// server doesn't provide, like the client-side commands some servers attach to their lenses,
+/// fail with an error shown to the user.
+fn apply_code_lens(
+    editor: &mut Editor,
+    buffer: Model<Buffer>,
+    lens: CodeLens,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let Some(workspace) = editor.workspace() else {
+        return;
+    };
+    let title = lens.title().unwrap_or_default().to_string();
+    let apply_code_lens = project.update(cx, |project, cx| {
+        project.apply_code_lens(buffer, lens, true, cx)
+    });
+    let workspace = workspace.downgrade();
+    cx.spawn(|editor, cx| async move {
+        let project_transaction = apply_code_lens.await?;
+        Editor::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+    })
+    .detach_and_notify_err(cx);
+}
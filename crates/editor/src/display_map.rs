@@ -65,7 +65,7 @@ use std::{
 };
 use sum_tree::{Bias, TreeMap};
 use tab_map::{TabMap, TabSnapshot};
-use text::LineIndent;
+use text::{BufferId, LineIndent};
 use ui::{px, SharedString, WindowContext};
 use unicode_segmentation::UnicodeSegmentation;
 use wrap_map::{WrapMap, WrapSnapshot};
@@ -84,6 +84,10 @@ pub trait ToDisplayPoint {
 
 type TextHighlights = TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
 type InlayHighlights = TreeMap<TypeId, TreeMap<InlayId, (HighlightStyle, InlayHighlight)>>;
+/// Highlights for individual ranges of each buffer, sorted by range and non-overlapping.
+type SemanticHighlights = TreeMap<BufferId, Arc<Vec<(Range<Anchor>, HighlightStyle)>>>;
+/// Foldable ranges reported by a language server for each buffer, sorted by start.
+type FoldingRanges = TreeMap<BufferId, Arc<Vec<Range<Anchor>>>>;
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
 /// folding, hard tabs, soft wrapping, custom blocks (like diagnostics), and highlighting.
@@ -107,8 +111,12 @@ pub struct DisplayMap {
     text_highlights: TextHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
+    /// Regions of text highlighted according to language server semantic tokens.
+    semantic_highlights: SemanticHighlights,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
+    /// Foldable ranges from language servers, which replace indentation based fold range suggestions.
+    folding_ranges: FoldingRanges,
     pub(crate) fold_placeholder: FoldPlaceholder,
     pub clip_at_line_ends: bool,
     pub(crate) masked: bool,
@@ -156,9 +164,11 @@ impl DisplayMap {
             wrap_map,
             block_map,
             crease_map,
+            folding_ranges: Default::default(),
             fold_placeholder,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
+            semantic_highlights: Default::default(),
             clip_at_line_ends: false,
             masked: false,
         }
@@ -184,8 +194,10 @@ impl DisplayMap {
             wrap_snapshot,
             block_snapshot,
             crease_snapshot: self.crease_map.snapshot(),
+            folding_ranges: self.folding_ranges.clone(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
+            semantic_highlights: self.semantic_highlights.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
             masked: self.masked,
             fold_placeholder: self.fold_placeholder.clone(),
@@ -488,6 +500,33 @@ impl DisplayMap {
         }
     }
 
+    pub(crate) fn set_semantic_highlights(
+        &mut self,
+        buffer_id: BufferId,
+        highlights: Vec<(Range<Anchor>, HighlightStyle)>,
+    ) {
+        if highlights.is_empty() {
+            self.semantic_highlights.remove(&buffer_id);
+        } else {
+            self.semantic_highlights
+                .insert(buffer_id, Arc::new(highlights));
+        }
+    }
+
+    pub(crate) fn clear_semantic_highlights(&mut self) -> bool {
+        let cleared = !self.semantic_highlights.is_empty();
+        self.semantic_highlights = SemanticHighlights::default();
+        cleared
+    }
+
+    pub(crate) fn set_folding_ranges(&mut self, buffer_id: BufferId, ranges: Vec<Range<Anchor>>) {
+        self.folding_ranges.insert(buffer_id, Arc::new(ranges));
+    }
+
+    pub(crate) fn remove_folding_ranges(&mut self, buffer_id: BufferId) -> bool {
+        self.folding_ranges.remove(&buffer_id).is_some()
+    }
+
     pub fn text_highlights(&self, type_id: TypeId) -> Option<(HighlightStyle, &[Range<Anchor>])> {
         let highlights = self.text_highlights.get(&type_id)?;
         Some((highlights.0, &highlights.1))
@@ -564,9 +603,20 @@ impl DisplayMap {
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
+    pub semantic_highlights: Option<&'a SemanticHighlights>,
     pub styles: HighlightStyles,
 }
 
+impl<'a> Highlights<'a> {
+    fn has_text_highlights(&self) -> bool {
+        self.text_highlights
+            .map_or(false, |highlights| !highlights.is_empty())
+            || self
+                .semantic_highlights
+                .map_or(false, |highlights| !highlights.is_empty())
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct InlineCompletionStyles {
     pub insertion: HighlightStyle,
@@ -692,12 +742,14 @@ pub struct DisplaySnapshot {
     pub buffer_snapshot: MultiBufferSnapshot,
     pub fold_snapshot: FoldSnapshot,
     pub crease_snapshot: CreaseSnapshot,
+    folding_ranges: FoldingRanges,
     inlay_snapshot: InlaySnapshot,
     tab_snapshot: TabSnapshot,
     wrap_snapshot: WrapSnapshot,
     block_snapshot: BlockSnapshot,
     text_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
+    semantic_highlights: SemanticHighlights,
     clip_at_line_ends: bool,
     masked: bool,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -885,6 +937,7 @@ impl DisplaySnapshot {
             Highlights {
                 text_highlights: Some(&self.text_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
+                semantic_highlights: Some(&self.semantic_highlights),
                 styles: highlight_styles,
             },
         )
@@ -1194,6 +1247,41 @@ impl DisplaySnapshot {
             .unwrap_or(false)
     }
 
+    /// Whether a fold suggestion starts at the given row, either from a language server
+    /// folding range or, when the buffer has none, from indentation.
+    pub fn starts_fold(&self, buffer_row: MultiBufferRow) -> bool {
+        match self.folding_ranges_for_buffer_row(buffer_row) {
+            Some(folding_ranges) => self.folding_range_end(folding_ranges, buffer_row).is_some(),
+            None => self.starts_indent(buffer_row),
+        }
+    }
+
+    fn folding_ranges_for_buffer_row(
+        &self,
+        buffer_row: MultiBufferRow,
+    ) -> Option<&[Range<Anchor>]> {
+        let (buffer, _) = self.buffer_snapshot.buffer_line_for_row(buffer_row)?;
+        let folding_ranges = self.folding_ranges.get(&buffer.remote_id())?;
+        Some(folding_ranges.as_slice())
+    }
+
+    /// Returns the end of the outermost folding range starting at the given row.
+    fn folding_range_end(
+        &self,
+        folding_ranges: &[Range<Anchor>],
+        buffer_row: MultiBufferRow,
+    ) -> Option<Point> {
+        let start_ix = folding_ranges.partition_point(|range| {
+            range.start.to_point(&self.buffer_snapshot).row < buffer_row.0
+        });
+        folding_ranges[start_ix..]
+            .iter()
+            .take_while(|range| range.start.to_point(&self.buffer_snapshot).row == buffer_row.0)
+            .map(|range| range.end.to_point(&self.buffer_snapshot))
+            .filter(|end| end.row > buffer_row.0)
+            .max()
+    }
+
     pub fn crease_for_buffer_row(&self, buffer_row: MultiBufferRow) -> Option<Crease<Point>> {
         let start = MultiBufferPoint::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
         if let Some(crease) = self
@@ -1230,6 +1318,18 @@ impl DisplaySnapshot {
                     render_toggle: render_toggle.clone(),
                 }),
             }
+        } else if let Some(folding_ranges) = self.folding_ranges_for_buffer_row(buffer_row) {
+            let end = self.folding_range_end(folding_ranges, buffer_row)?;
+            if self.is_line_folded(buffer_row) {
+                return None;
+            }
+            Some(Crease::Inline {
+                range: start..end,
+                placeholder: self.fold_placeholder.clone(),
+                render_toggle: None,
+                render_trailer: None,
+                metadata: None,
+            })
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
@@ -10,11 +10,10 @@ use std::{
     cmp,
     iter::Peekable,
     ops::{Add, AddAssign, Range, Sub, SubAssign},
-    sync::Arc,
     vec,
 };
-use sum_tree::{Bias, Cursor, SumTree, TreeMap};
-use text::{Patch, Rope};
+use sum_tree::{Bias, Cursor, SumTree};
+use text::{BufferId, Patch, Rope};
 
 use super::Highlights;
 
@@ -207,11 +206,19 @@ pub struct InlayBufferRows<'a> {
     max_buffer_row: MultiBufferRow,
 }
 
+/// Identifies an active highlight. Semantic token highlights sort before other text
+/// highlights, so that the latter are applied on top of them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum HighlightKey {
+    SemanticToken(BufferId, usize),
+    Text(TypeId),
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct HighlightEndpoint {
     offset: InlayOffset,
     is_start: bool,
-    tag: TypeId,
+    tag: HighlightKey,
     style: HighlightStyle,
 }
 
@@ -239,7 +246,7 @@ pub struct InlayChunks<'a> {
     max_output_offset: InlayOffset,
     highlight_styles: HighlightStyles,
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
-    active_highlights: BTreeMap<TypeId, HighlightStyle>,
+    active_highlights: BTreeMap<HighlightKey, HighlightStyle>,
     highlights: Highlights<'a>,
     snapshot: &'a InlaySnapshot,
 }
@@ -257,17 +264,15 @@ impl<'a> InlayChunks<'a> {
         self.max_output_offset = new_range.end;
 
         let mut highlight_endpoints = Vec::new();
-        if let Some(text_highlights) = self.highlights.text_highlights {
-            if !text_highlights.is_empty() {
-                self.snapshot.apply_text_highlights(
-                    &mut self.transforms,
-                    &new_range,
-                    text_highlights,
-                    &mut highlight_endpoints,
-                );
-                self.transforms.seek(&new_range.start, Bias::Right, &());
-                highlight_endpoints.sort();
-            }
+        if self.highlights.has_text_highlights() {
+            self.snapshot.apply_text_highlights(
+                &mut self.transforms,
+                &new_range,
+                &self.highlights,
+                &mut highlight_endpoints,
+            );
+            self.transforms.seek(&new_range.start, Bias::Right, &());
+            highlight_endpoints.sort();
         }
         self.highlight_endpoints = highlight_endpoints.into_iter().peekable();
         self.active_highlights.clear();
@@ -1069,16 +1074,9 @@ impl InlaySnapshot {
         cursor.seek(&range.start, Bias::Right, &());
 
         let mut highlight_endpoints = Vec::new();
-        if let Some(text_highlights) = highlights.text_highlights {
-            if !text_highlights.is_empty() {
-                self.apply_text_highlights(
-                    &mut cursor,
-                    &range,
-                    text_highlights,
-                    &mut highlight_endpoints,
-                );
-                cursor.seek(&range.start, Bias::Right, &());
-            }
+        if highlights.has_text_highlights() {
+            self.apply_text_highlights(&mut cursor, &range, &highlights, &mut highlight_endpoints);
+            cursor.seek(&range.start, Bias::Right, &());
         }
         highlight_endpoints.sort();
         let buffer_range = self.to_buffer_offset(range.start)..self.to_buffer_offset(range.end);
@@ -1104,9 +1102,22 @@ impl InlaySnapshot {
         &self,
         cursor: &mut Cursor<'_, Transform, (InlayOffset, usize)>,
         range: &Range<InlayOffset>,
-        text_highlights: &TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>,
+        highlights: &Highlights,
         highlight_endpoints: &mut Vec<HighlightEndpoint>,
     ) {
+        let text_highlights = highlights
+            .text_highlights
+            .into_iter()
+            .flat_map(|highlights| {
+                highlights
+                    .iter()
+                    .map(|(tag, highlights)| (HighlightKey::Text(*tag), highlights))
+            });
+        let semantic_highlights = highlights
+            .semantic_highlights
+            .into_iter()
+            .flat_map(|h| h.iter());
+
         while cursor.start().0 < range.end {
             let transform_start = self
                 .buffer
@@ -1120,37 +1131,34 @@ impl InlaySnapshot {
                     )))
                 };
 
-            for (&tag, text_highlights) in text_highlights.iter() {
+            for (tag, text_highlights) in text_highlights.clone() {
                 let style = text_highlights.0;
                 let ranges = &text_highlights.1;
 
-                let start_ix = match ranges.binary_search_by(|probe| {
-                    let cmp = probe.end.cmp(&transform_start, &self.buffer);
-                    if cmp.is_gt() {
-                        cmp::Ordering::Greater
-                    } else {
-                        cmp::Ordering::Less
-                    }
-                }) {
-                    Ok(i) | Err(i) => i,
-                };
+                let start_ix =
+                    self.first_highlight_ending_after(ranges, &transform_start, |range| range);
                 for range in &ranges[start_ix..] {
                     if range.start.cmp(&transform_end, &self.buffer).is_ge() {
                         break;
                     }
 
-                    highlight_endpoints.push(HighlightEndpoint {
-                        offset: self.to_inlay_offset(range.start.to_offset(&self.buffer)),
-                        is_start: true,
-                        tag,
-                        style,
-                    });
-                    highlight_endpoints.push(HighlightEndpoint {
-                        offset: self.to_inlay_offset(range.end.to_offset(&self.buffer)),
-                        is_start: false,
-                        tag,
-                        style,
-                    });
+                    self.push_highlight_endpoints(range, tag, style, highlight_endpoints);
+                }
+            }
+
+            for (buffer_id, highlights) in semantic_highlights.clone() {
+                let start_ix = self.first_highlight_ending_after(
+                    highlights,
+                    &transform_start,
+                    |(range, _)| range,
+                );
+                for (ix, (range, style)) in highlights.iter().enumerate().skip(start_ix) {
+                    if range.start.cmp(&transform_end, &self.buffer).is_ge() {
+                        break;
+                    }
+
+                    let tag = HighlightKey::SemanticToken(*buffer_id, ix);
+                    self.push_highlight_endpoints(range, tag, *style, highlight_endpoints);
                 }
             }
 
@@ -1158,6 +1166,45 @@ impl InlaySnapshot {
         }
     }
 
+    fn first_highlight_ending_after<T>(
+        &self,
+        highlights: &[T],
+        position: &Anchor,
+        range: impl Fn(&T) -> &Range<Anchor>,
+    ) -> usize {
+        match highlights.binary_search_by(|probe| {
+            let cmp = range(probe).end.cmp(position, &self.buffer);
+            if cmp.is_gt() {
+                cmp::Ordering::Greater
+            } else {
+                cmp::Ordering::Less
+            }
+        }) {
+            Ok(i) | Err(i) => i,
+        }
+    }
+
+    fn push_highlight_endpoints(
+        &self,
+        range: &Range<Anchor>,
+        tag: HighlightKey,
+        style: HighlightStyle,
+        highlight_endpoints: &mut Vec<HighlightEndpoint>,
+    ) {
+        highlight_endpoints.push(HighlightEndpoint {
+            offset: self.to_inlay_offset(range.start.to_offset(&self.buffer)),
+            is_start: true,
+            tag,
+            style,
+        });
+        highlight_endpoints.push(HighlightEndpoint {
+            offset: self.to_inlay_offset(range.end.to_offset(&self.buffer)),
+            is_start: false,
+            tag,
+            style,
+        });
+    }
+
     #[cfg(test)]
     pub fn text(&self) -> String {
         self.chunks(Default::default()..self.len(), false, Highlights::default())
@@ -1218,6 +1265,7 @@ mod tests {
     use rand::prelude::*;
     use settings::SettingsStore;
     use std::{cmp::Reverse, env, sync::Arc};
+    use sum_tree::TreeMap;
     use text::Patch;
     use util::post_inc;
 
@@ -17,10 +17,12 @@ mod blame_entry_tooltip;
 mod blink_manager;
 mod clangd_ext;
 mod code_context_menus;
+mod code_lens;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -31,13 +33,16 @@ mod inlay_hint_cache;
 pub mod items;
 mod linked_editing_ranges;
 mod lsp_ext;
+mod lsp_refresh;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
 mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
+mod selection_ranges;
 mod selections_collection;
+mod semantic_tokens;
 pub mod tasks;
 
 #[cfg(test)]
@@ -75,7 +80,10 @@ use code_context_menus::{
     AvailableCodeAction, CodeActionContents, CodeActionsItem, CodeActionsMenu, CodeContextMenu,
     CompletionsMenu, ContextMenuOrigin,
 };
-use git::blame::GitBlame;
+use code_lens::refresh_code_lens;
+use folding_ranges::refresh_folding_ranges;
+pub use git::project_diff;
+use git::{blame::GitBlame, conflicts::ConflictState};
 use gpui::{
     div, impl_actions, point, prelude::*, px, relative, size, Action, AnyElement, AppContext,
     AsyncWindowContext, AvailableSpace, Bounds, ClipboardEntry, ClipboardItem, Context,
@@ -104,10 +112,12 @@ use language::{
 };
 use language::{point_to_lsp, BufferRow, CharClassifier, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
+use lsp_refresh::RefreshScope;
 use mouse_context_menu::MouseContextMenu;
 pub use proposed_changes_editor::{
     ProposedChangeLocation, ProposedChangesEditor, ProposedChangesEditorToolbar,
 };
+use semantic_tokens::refresh_semantic_tokens;
 use similar::{ChangeTag, TextDiff};
 use std::iter::Peekable;
 use task::{ResolvedTask, TaskTemplate, TaskVariables};
@@ -575,6 +585,8 @@ pub struct Editor {
     autoclose_regions: Vec<AutocloseRegion>,
     snippet_stack: InvalidationStack<SnippetState>,
     select_larger_syntax_node_stack: Vec<Box<[Selection<usize>]>>,
+    /// The number of selection expansions waiting for a language server's selection ranges.
+    pending_lsp_range_expansions: usize,
     ime_transaction: Option<TransactionId>,
     active_diagnostics: Option<ActiveDiagnosticGroup>,
     soft_wrap_mode_override: Option<language_settings::SoftWrap>,
@@ -618,6 +630,11 @@ pub struct Editor {
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
+    semantic_tokens_tasks: HashMap<BufferId, Task<()>>,
+    semantic_tokens: HashMap<BufferId, project::SemanticTokens>,
+    code_lens_tasks: HashMap<BufferId, Task<()>>,
+    code_lens_rows: HashMap<BufferId, BTreeMap<u32, code_lens::CodeLensRow>>,
+    folding_ranges_tasks: HashMap<BufferId, Task<()>>,
     pending_rename: Option<RenameState>,
     searchable: bool,
     cursor_shape: CursorShape,
@@ -661,6 +678,7 @@ pub struct Editor {
     show_selection_menu: Option<bool>,
     blame: Option<Model<GitBlame>>,
     blame_subscription: Option<Subscription>,
+    conflict_state: ConflictState,
     custom_context_menu: Option<
         Box<
             dyn 'static
@@ -930,14 +948,22 @@ pub struct ClipboardSelection {
     pub first_line_indent: u32,
 }
 
+/// The state of an editor stored in the navigation history.
 #[derive(Debug)]
-pub(crate) struct NavigationData {
+pub struct NavigationData {
     cursor_anchor: Anchor,
     cursor_position: Point,
     scroll_anchor: ScrollAnchor,
     scroll_top_row: u32,
 }
 
+impl NavigationData {
+    /// The position of the cursor when the entry was pushed.
+    pub fn cursor_position(&self) -> Point {
+        self.cursor_position
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GotoDefinitionKind {
     Symbol,
@@ -1140,6 +1166,12 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::RefreshSemanticTokens = event {
+                        refresh_semantic_tokens(editor, RefreshScope::All, cx);
+                    } else if let project::Event::RefreshCodeLens = event {
+                        refresh_code_lens(editor, RefreshScope::All, cx);
+                    } else if let project::Event::LanguageServerAdded(..) = event {
+                        refresh_folding_ranges(editor, RefreshScope::All, cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1156,6 +1188,8 @@ impl Editor {
                         }
                     }
                 }));
+                let breakpoint_store = project.read(cx).breakpoint_store().clone();
+                project_subscriptions.push(cx.observe(&breakpoint_store, |_, _, cx| cx.notify()));
                 if let Some(task_inventory) = project
                     .read(cx)
                     .task_store()
@@ -1210,6 +1244,7 @@ impl Editor {
             autoclose_regions: Default::default(),
             snippet_stack: Default::default(),
             select_larger_syntax_node_stack: Vec::new(),
+            pending_lsp_range_expansions: 0,
             ime_transaction: Default::default(),
             active_diagnostics: None,
             soft_wrap_mode_override,
@@ -1297,6 +1332,7 @@ impl Editor {
                 .restore_unsaved_buffers,
             blame: None,
             blame_subscription: None,
+            conflict_state: ConflictState::default(),
             tasks: Default::default(),
             _subscriptions: vec![
                 cx.observe(&buffer, Self::on_buffer_changed),
@@ -1318,6 +1354,11 @@ impl Editor {
             ],
             tasks_update_task: None,
             linked_edit_ranges: Default::default(),
+            semantic_tokens_tasks: HashMap::default(),
+            semantic_tokens: HashMap::default(),
+            code_lens_tasks: HashMap::default(),
+            code_lens_rows: HashMap::default(),
+            folding_ranges_tasks: HashMap::default(),
             previous_search_ranges: None,
             breadcrumb_header: None,
             focused_block: None,
@@ -1329,6 +1370,7 @@ impl Editor {
             text_style_refinement: None,
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
+        this.refresh_conflicts(cx);
         this._subscriptions.extend(project_subscriptions);
 
         this.end_selection(cx);
@@ -1353,6 +1395,9 @@ impl Editor {
                         .insert(buffer.read(cx).remote_id(), handle);
                 }
             }
+            refresh_semantic_tokens(&mut this, RefreshScope::All, cx);
+            refresh_code_lens(&mut this, RefreshScope::All, cx);
+            refresh_folding_ranges(&mut this, RefreshScope::All, cx);
         }
 
         this.report_editor_event("open", None, cx);
@@ -4999,6 +5044,115 @@ impl Editor {
             }))
     }
 
+    fn render_breakpoint_indicator(
+        &self,
+        row: DisplayRow,
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        IconButton::new(
+            ("breakpoint_indicator", row.0 as usize),
+            ui::IconName::DebugBreakpoint,
+        )
+        .shape(ui::IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(Color::Error)
+        .tooltip(|cx| Tooltip::for_action("Remove Breakpoint", &ToggleBreakpoint, cx))
+        .on_click(cx.listener(move |editor, _e, cx| {
+            editor.focus(cx);
+            editor.toggle_breakpoint_at_display_row(row, cx);
+        }))
+    }
+
+    /// Returns the display rows in the given range that have a breakpoint.
+    pub(crate) fn breakpoint_display_rows(
+        &self,
+        range: Range<DisplayRow>,
+        snapshot: &EditorSnapshot,
+        cx: &AppContext,
+    ) -> Vec<DisplayRow> {
+        let Some(project) = self.project.as_ref() else {
+            return Vec::new();
+        };
+        let breakpoint_store = project.read(cx).breakpoint_store().read(cx);
+        let start = snapshot
+            .display_point_to_anchor(DisplayPoint::new(range.start, 0), Bias::Left)
+            .to_offset(&snapshot.buffer_snapshot);
+        let end = snapshot
+            .display_point_to_anchor(DisplayPoint::new(range.end, 0), Bias::Right)
+            .to_offset(&snapshot.buffer_snapshot);
+
+        let mut display_rows = Vec::new();
+        for excerpt in snapshot.buffer_snapshot.excerpts_for_range(start..end) {
+            let buffer = excerpt.buffer();
+            let excerpt_range = excerpt.buffer_range();
+            for row in breakpoint_store.breakpoints_for_buffer(buffer, cx) {
+                if *row > buffer.max_point().row {
+                    break;
+                }
+                let anchor = buffer.anchor_before(Point::new(*row, 0));
+                if anchor.cmp(&excerpt_range.start, buffer).is_lt()
+                    || anchor.cmp(&excerpt_range.end, buffer).is_gt()
+                {
+                    continue;
+                }
+                let Some(anchor) = snapshot
+                    .buffer_snapshot
+                    .anchor_in_excerpt(excerpt.id(), anchor)
+                else {
+                    continue;
+                };
+                let display_row = anchor.to_display_point(snapshot).row();
+                if range.contains(&display_row) {
+                    display_rows.push(display_row);
+                }
+            }
+        }
+        // Breakpoints inside of a fold share its display row.
+        display_rows.sort_unstable();
+        display_rows.dedup();
+        display_rows
+    }
+
+    pub fn toggle_breakpoint(&mut self, _: &ToggleBreakpoint, cx: &mut ViewContext<Self>) {
+        let snapshot = self.snapshot(cx);
+        let mut rows = self
+            .selections
+            .all_display(cx)
+            .into_iter()
+            .map(|selection| selection.head().row())
+            .collect::<Vec<_>>();
+        rows.dedup();
+        for row in rows {
+            self.toggle_breakpoint_at_display_row_in(row, &snapshot, cx);
+        }
+    }
+
+    fn toggle_breakpoint_at_display_row(&mut self, row: DisplayRow, cx: &mut ViewContext<Self>) {
+        let snapshot = self.snapshot(cx);
+        self.toggle_breakpoint_at_display_row_in(row, &snapshot, cx);
+    }
+
+    fn toggle_breakpoint_at_display_row_in(
+        &mut self,
+        row: DisplayRow,
+        snapshot: &EditorSnapshot,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let point = DisplayPoint::new(row, 0).to_point(snapshot);
+        let Some((buffer, buffer_point, _)) = self.buffer.read(cx).point_to_buffer_point(point, cx)
+        else {
+            return;
+        };
+        project.update(cx, |project, cx| {
+            project.breakpoint_store().update(cx, |store, cx| {
+                store.toggle_breakpoint(&buffer, buffer_point.row, cx)
+            })
+        });
+    }
+
     pub fn context_menu_visible(&self) -> bool {
         self.context_menu
             .borrow()
@@ -8635,6 +8789,13 @@ impl Editor {
         _: &SelectLargerSyntaxNode,
         cx: &mut ViewContext<Self>,
     ) {
+        if selection_ranges::select_larger_lsp_range(self, cx) {
+            return;
+        }
+        self.select_larger_syntax_ancestor(cx);
+    }
+
+    fn select_larger_syntax_ancestor(&mut self, cx: &mut ViewContext<Self>) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = self.buffer.read(cx).snapshot(cx);
         let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
@@ -12098,6 +12259,7 @@ impl Editor {
                         }
                     }
                 }
+                self.refresh_conflicts(cx);
                 cx.emit(EditorEvent::BufferEdited);
                 cx.emit(SearchEvent::MatchesInvalidated);
                 if *singleton_buffer_edited {
@@ -12141,6 +12303,12 @@ impl Editor {
                     (telemetry, is_via_ssh)
                 };
                 refresh_linked_ranges(self, cx);
+                if let Some(buffer) = buffer_edited {
+                    let scope = RefreshScope::Buffer(buffer.read(cx).remote_id());
+                    refresh_semantic_tokens(self, scope, cx);
+                    refresh_code_lens(self, scope, cx);
+                    refresh_folding_ranges(self, scope, cx);
+                }
                 telemetry.log_edit_event("editor", is_via_ssh);
             }
             multi_buffer::Event::ExcerptsAdded {
@@ -12149,6 +12317,7 @@ impl Editor {
                 excerpts,
             } => {
                 self.tasks_update_task = Some(self.refresh_runnables(cx));
+                self.refresh_conflicts(cx);
                 let buffer_id = buffer.read(cx).remote_id();
                 if !self.diff_map.diff_bases.contains_key(&buffer_id) {
                     if let Some(project) = &self.project {
@@ -12161,6 +12330,10 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                let scope = RefreshScope::Buffer(buffer_id);
+                refresh_semantic_tokens(self, scope, cx);
+                refresh_code_lens(self, scope, cx);
+                refresh_folding_ranges(self, scope, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
@@ -12182,6 +12355,10 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                let scope = RefreshScope::Buffer(*buffer_id);
+                refresh_semantic_tokens(self, scope, cx);
+                refresh_code_lens(self, scope, cx);
+                refresh_folding_ranges(self, scope, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -12220,6 +12397,9 @@ impl Editor {
             )),
             cx,
         );
+        refresh_semantic_tokens(self, RefreshScope::All, cx);
+        refresh_code_lens(self, RefreshScope::All, cx);
+        refresh_folding_ranges(self, RefreshScope::All, cx);
 
         let old_cursor_shape = self.cursor_shape;
 
@@ -13893,7 +14073,7 @@ impl EditorSnapshot {
             }
         }
 
-        is_foldable |= self.starts_indent(buffer_row);
+        is_foldable |= self.starts_fold(buffer_row);
 
         if folded || (is_foldable && (row_contains_cursor || self.gutter_hovered)) {
             Some(
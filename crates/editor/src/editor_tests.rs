@@ -3915,6 +3915,55 @@ fn test_duplicate_line(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_resolve_conflicts(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let conflicted_text = indoc! {"
+        one
+        <<<<<<< HEAD
+        ouˇrs
+        ||||||| base
+        base
+        =======
+        theirs
+        >>>>>>> branch
+        two
+    "};
+
+    cx.set_state(conflicted_text);
+    cx.update_editor(|e, cx| e.accept_ours_conflict_side(&AcceptOursConflictSide, cx));
+    assert_eq!(cx.buffer_text(), "one\nours\ntwo\n");
+
+    cx.set_state(conflicted_text);
+    cx.update_editor(|e, cx| e.accept_theirs_conflict_side(&AcceptTheirsConflictSide, cx));
+    assert_eq!(cx.buffer_text(), "one\ntheirs\ntwo\n");
+
+    cx.set_state(conflicted_text);
+    cx.update_editor(|e, cx| e.accept_both_conflict_sides(&AcceptBothConflictSides, cx));
+    assert_eq!(cx.buffer_text(), "one\nours\ntheirs\ntwo\n");
+
+    // Selections outside of a conflict leave the buffer unchanged.
+    cx.set_state(indoc! {"
+        ˇone
+        <<<<<<< HEAD
+        ours
+        =======
+        theirs
+        >>>>>>> branch
+    "});
+    cx.update_editor(|e, cx| e.accept_ours_conflict_side(&AcceptOursConflictSide, cx));
+    cx.assert_editor_state(indoc! {"
+        ˇone
+        <<<<<<< HEAD
+        ours
+        =======
+        theirs
+        >>>>>>> branch
+    "});
+}
+
 #[gpui::test]
 fn test_move_line_up_down(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -5478,6 +5527,61 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_select_larger_lsp_range_applies_queued_expansions(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.set_state(indoc! {"
+        fn main() {
+            let ˇfoo = bar(1);
+        }
+    "});
+    cx.lsp
+        .handle_request::<lsp::request::SelectionRangeRequest, _, _>(|_, _| async move {
+            Ok(Some(vec![lsp::SelectionRange {
+                range: lsp::Range::new(lsp::Position::new(1, 8), lsp::Position::new(1, 11)),
+                parent: Some(Box::new(lsp::SelectionRange {
+                    range: lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 21)),
+                    parent: Some(Box::new(lsp::SelectionRange {
+                        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(2, 1)),
+                        parent: None,
+                    })),
+                })),
+            }]))
+        });
+
+    // Both expansions take effect, even though the second was requested before the
+    // language server responded to the first.
+    cx.update_editor(|editor, cx| {
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx);
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx);
+    });
+    cx.executor().run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            «let foo = bar(1);ˇ»
+        }
+    "});
+
+    cx.update_editor(|editor, cx| {
+        editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            let «fooˇ» = bar(1);
+        }
+    "});
+}
+
 #[gpui::test]
 async fn test_autoindent(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -13051,6 +13155,84 @@ async fn test_edit_after_expanded_modification_hunk(
     );
 }
 
+#[gpui::test]
+async fn test_stage_and_unstage_from_editor(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    // Staged text keeps the line endings of the index, which differ from the file's ones
+    // when git converts them, e.g. with `core.autocrlf`.
+    for (file_line_ending, index_line_ending) in [("\n", "\n"), ("\r\n", "\r\n"), ("\r\n", "\n")] {
+        let with_line_ending = |text: &str| text.replace('\n', index_line_ending);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                "file.txt": "ONE\ntwo\nTHREE\nFOUR\nfive\n".replace('\n', file_line_ending),
+            }),
+        )
+        .await;
+        let committed_text = with_line_ending("one\ntwo\nthree\nfour\nfive\n");
+        let dot_git = std::path::Path::new("/root/.git");
+        let file_path = std::path::Path::new("file.txt");
+        fs.set_head_for_repo(dot_git, &[(file_path, committed_text.clone())]);
+        fs.set_index_for_repo(dot_git, &[(file_path, committed_text.clone())]);
+        let index_text = || {
+            let mut index_text = None;
+            fs.with_git_state(dot_git, false, |state| {
+                index_text = state.index_contents.get(file_path).cloned();
+            });
+            index_text.unwrap()
+        };
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/file.txt", cx)
+            })
+            .await
+            .unwrap();
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let (editor, cx) =
+            cx.add_window_view(|cx| build_editor_with_project(project.clone(), buffer, cx));
+        cx.run_until_parked();
+        let select_row = |row: u32, cx: &mut VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(None, cx, |s| {
+                    s.select_ranges([Point::new(row, 0)..Point::new(row, 0)])
+                });
+            });
+        };
+
+        select_row(0, cx);
+        editor.update(cx, |editor, cx| {
+            editor.stage_selected_hunks(&StageSelectedHunks, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            index_text(),
+            with_line_ending("ONE\ntwo\nthree\nfour\nfive\n")
+        );
+
+        editor.update(cx, |editor, cx| {
+            editor.unstage_selected_hunks(&UnstageSelectedHunks, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(index_text(), committed_text);
+
+        // Only the selected line of the two-line hunk is staged.
+        select_row(3, cx);
+        editor.update(cx, |editor, cx| {
+            editor.stage_selected_lines(&StageSelectedLines, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            index_text(),
+            with_line_ending("one\ntwo\nthree\nFOUR\nfive\n")
+        );
+    }
+}
+
 async fn setup_indent_guides_editor(
     text: &str,
     cx: &mut gpui::TestAppContext,
@@ -360,6 +360,7 @@ impl EditorElement {
         register_action(view, cx, Editor::fold_selected_ranges);
         register_action(view, cx, Editor::show_completions);
         register_action(view, cx, Editor::toggle_code_actions);
+        register_action(view, cx, Editor::toggle_breakpoint);
         register_action(view, cx, Editor::open_excerpts);
         register_action(view, cx, Editor::open_excerpts_in_split);
         register_action(view, cx, Editor::open_proposed_changes_editor);
@@ -456,6 +457,12 @@ impl EditorElement {
         register_action(view, cx, Editor::accept_inline_completion);
         register_action(view, cx, Editor::revert_file);
         register_action(view, cx, Editor::revert_selected_hunks);
+        register_action(view, cx, Editor::stage_selected_hunks);
+        register_action(view, cx, Editor::unstage_selected_hunks);
+        register_action(view, cx, Editor::stage_selected_lines);
+        register_action(view, cx, Editor::accept_ours_conflict_side);
+        register_action(view, cx, Editor::accept_theirs_conflict_side);
+        register_action(view, cx, Editor::accept_both_conflict_sides);
         register_action(view, cx, Editor::apply_all_diff_hunks);
         register_action(view, cx, Editor::apply_selected_diff_hunks);
         register_action(view, cx, Editor::open_active_item_in_terminal);
@@ -1414,6 +1421,33 @@ impl EditorElement {
         })
     }
 
+    /// Lays out the hunks that are staged in the index. These are displayed as hollow
+    /// strips, under any unstaged changes to the same lines.
+    fn layout_gutter_staged_hunks(
+        &self,
+        display_rows: Range<DisplayRow>,
+        snapshot: &EditorSnapshot,
+        cx: &mut WindowContext,
+    ) -> Vec<DisplayDiffHunk> {
+        let git_gutter_setting = ProjectSettings::get_global(cx)
+            .git
+            .git_gutter
+            .unwrap_or_default();
+        if let GitGutterSetting::Hide = git_gutter_setting {
+            return Vec::new();
+        }
+
+        let buffer_start = DisplayPoint::new(display_rows.start, 0).to_point(snapshot);
+        let buffer_end = DisplayPoint::new(display_rows.end, 0).to_point(snapshot);
+        snapshot
+            .diff_map
+            .staged_diff_hunks_in_range(buffer_start..buffer_end, &snapshot.buffer_snapshot)
+            .map(|hunk| diff_hunk_to_display(&hunk, snapshot))
+            .filter(|hunk| matches!(hunk, DisplayDiffHunk::Unfolded { .. }))
+            .dedup()
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_inline_blame(
         &self,
@@ -1677,6 +1711,7 @@ impl EditorElement {
         gutter_dimensions: &GutterDimensions,
         gutter_hitbox: &Hitbox,
         rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        breakpoint_rows: &[DisplayRow],
         snapshot: &EditorSnapshot,
         cx: &mut WindowContext,
     ) -> Vec<AnyElement> {
@@ -1736,6 +1771,10 @@ impl EditorElement {
                         }
                     }
                     let display_row = multibuffer_point.to_display_point(snapshot).row();
+                    // Breakpoints take the place of run indicators on their rows.
+                    if breakpoint_rows.contains(&display_row) {
+                        return None;
+                    }
                     let button = editor.render_run_indicator(
                         &self.style,
                         Some(display_row) == active_task_indicator_row,
@@ -1759,6 +1798,37 @@ impl EditorElement {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_breakpoint_indicators(
+        &self,
+        line_height: Pixels,
+        breakpoint_rows: &[DisplayRow],
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            breakpoint_rows
+                .iter()
+                .map(|row| {
+                    let button = editor.render_breakpoint_indicator(*row, cx);
+                    prepaint_gutter_button(
+                        button,
+                        *row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        cx,
+                    )
+                })
+                .collect_vec()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_code_actions_indicator(
         &self,
@@ -3482,12 +3552,54 @@ impl EditorElement {
     }
 
     fn paint_diff_hunks(layout: &mut EditorLayout, cx: &mut WindowContext) {
-        if layout.display_hunks.is_empty() {
+        if layout.display_hunks.is_empty() && layout.display_staged_hunks.is_empty() {
             return;
         }
 
         let line_height = layout.position_map.line_height;
         cx.paint_layer(layout.gutter_hitbox.bounds, |cx| {
+            for hunk in &layout.display_staged_hunks {
+                let DisplayDiffHunk::Unfolded { status, .. } = hunk else {
+                    continue;
+                };
+                let hunk_bounds = Self::diff_hunk_bounds(
+                    &layout.position_map.snapshot,
+                    line_height,
+                    layout.gutter_hitbox.bounds,
+                    hunk,
+                );
+                let (hunk_bounds, border_color, corner_radii) = match status {
+                    DiffHunkStatus::Added => (
+                        hunk_bounds,
+                        cx.theme().status().created,
+                        Corners::all(px(0.)),
+                    ),
+                    DiffHunkStatus::Modified => (
+                        hunk_bounds,
+                        cx.theme().status().modified,
+                        Corners::all(px(0.)),
+                    ),
+                    DiffHunkStatus::Removed => (
+                        Bounds::new(
+                            point(
+                                hunk_bounds.origin.x - hunk_bounds.size.width,
+                                hunk_bounds.origin.y,
+                            ),
+                            size(hunk_bounds.size.width * px(2.), hunk_bounds.size.height),
+                        ),
+                        cx.theme().status().deleted,
+                        Corners::all(1. * line_height),
+                    ),
+                };
+                cx.paint_quad(quad(
+                    hunk_bounds,
+                    corner_radii,
+                    transparent_black(),
+                    Edges::all(px(1.)),
+                    border_color,
+                ));
+            }
+
             for (hunk, hitbox) in &layout.display_hunks {
                 let hunk_to_paint = match hunk {
                     DisplayDiffHunk::Folded { .. } => {
@@ -3629,6 +3741,10 @@ impl EditorElement {
                 test_indicator.paint(cx);
             }
 
+            for breakpoint_indicator in layout.breakpoint_indicators.iter_mut() {
+                breakpoint_indicator.paint(cx);
+            }
+
             if let Some(indicator) = layout.code_actions_indicator.as_mut() {
                 indicator.paint(cx);
             }
@@ -5608,6 +5724,8 @@ impl Element for EditorElement {
                         &snapshot,
                         cx,
                     );
+                    let display_staged_hunks =
+                        self.layout_gutter_staged_hunks(start_row..end_row, &snapshot, cx);
 
                     let mut max_visible_line_width = Pixels::ZERO;
                     let mut line_layouts = Self::layout_lines(
@@ -5934,6 +6052,20 @@ impl Element for EditorElement {
                         }
                     }
 
+                    let breakpoint_rows = self.editor.read(cx).breakpoint_display_rows(
+                        start_row..end_row,
+                        &snapshot,
+                        cx,
+                    );
+                    let breakpoint_indicators = self.layout_breakpoint_indicators(
+                        line_height,
+                        &breakpoint_rows,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        cx,
+                    );
                     let test_indicators = if gutter_settings.runnables {
                         self.layout_run_indicators(
                             line_height,
@@ -5942,6 +6074,7 @@ impl Element for EditorElement {
                             &gutter_dimensions,
                             &gutter_hitbox,
                             &rows_with_hunk_bounds,
+                            &breakpoint_rows,
                             &snapshot,
                             cx,
                         )
@@ -6061,6 +6194,7 @@ impl Element for EditorElement {
                         gutter_hitbox,
                         gutter_dimensions,
                         display_hunks,
+                        display_staged_hunks,
                         content_origin,
                         scrollbar_layout,
                         active_rows,
@@ -6079,6 +6213,7 @@ impl Element for EditorElement {
                         inline_completion_popover,
                         mouse_context_menu,
                         test_indicators,
+                        breakpoint_indicators,
                         code_actions_indicator,
                         crease_toggles,
                         crease_trailers,
@@ -6209,6 +6344,7 @@ pub struct EditorLayout {
     line_elements: SmallVec<[AnyElement; 1]>,
     line_numbers: Vec<Option<ShapedLine>>,
     display_hunks: Vec<(DisplayDiffHunk, Option<Hitbox>)>,
+    display_staged_hunks: Vec<DisplayDiffHunk>,
     blamed_display_rows: Option<Vec<AnyElement>>,
     inline_blame: Option<AnyElement>,
     blocks: Vec<BlockLayout>,
@@ -6220,6 +6356,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    breakpoint_indicators: Vec<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
     inline_completion_popover: Option<AnyElement>,
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use gpui::{Model, ViewContext};
+use language::{
+    language_settings::{language_settings, RangeSource},
+    Buffer,
+};
+use project::FoldingRange;
+use text::{OffsetRangeExt, ToOffset};
+
+use crate::{
+    lsp_refresh::{debounced_buffer_request, RefreshScope},
+    Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Requests folding ranges for the buffers in `scope` that may use them, replacing the
+/// indentation based fold suggestions of buffers whose language server reports any.
+pub(super) fn refresh_folding_ranges(
+    editor: &mut Editor,
+    scope: RefreshScope,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.clone()?;
+
+    let mut buffers = Vec::new();
+    for buffer in scope.buffers(editor, cx) {
+        let source = {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx).folding_ranges
+        };
+        if source == RangeSource::Syntax {
+            let buffer_id = buffer.read(cx).remote_id();
+            editor.folding_ranges_tasks.remove(&buffer_id);
+            editor.display_map.update(cx, |display_map, _| {
+                display_map.remove_folding_ranges(buffer_id)
+            });
+        } else {
+            buffers.push((buffer, source));
+        }
+    }
+    if scope == RefreshScope::All {
+        let multi_buffer = editor.buffer.read(cx);
+        editor
+            .folding_ranges_tasks
+            .retain(|buffer_id, _| multi_buffer.buffer(*buffer_id).is_some());
+    }
+
+    for (buffer, source) in buffers {
+        let buffer_id = buffer.read(cx).remote_id();
+        let task = debounced_buffer_request(
+            project.clone(),
+            buffer,
+            UPDATE_DEBOUNCE,
+            |project, buffer, cx| project.folding_ranges(buffer, cx),
+            move |editor, buffer, folding_ranges, cx| {
+                update_folding_ranges(editor, buffer, source, folding_ranges, cx)
+            },
+            cx,
+        );
+        editor.folding_ranges_tasks.insert(buffer_id, task);
+    }
+
+    Some(())
+}
+
+fn update_folding_ranges(
+    editor: &mut Editor,
+    buffer: Model<Buffer>,
+    source: RangeSource,
+    folding_ranges: Vec<FoldingRange>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let buffer_id = buffer.read(cx).remote_id();
+    if folding_ranges.is_empty() && source == RangeSource::Auto {
+        editor.display_map.update(cx, |display_map, _| {
+            display_map.remove_folding_ranges(buffer_id)
+        });
+        cx.notify();
+        return;
+    }
+
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let mut ranges = Vec::new();
+    for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+        let context = excerpt_range.context.to_offset(&buffer_snapshot);
+        for folding_range in &folding_ranges {
+            let start = folding_range.range.start.to_offset(&buffer_snapshot);
+            if !context.contains(&start) {
+                continue;
+            }
+            let start = snapshot.anchor_in_excerpt(excerpt_id, folding_range.range.start);
+            let end = snapshot.anchor_in_excerpt(excerpt_id, folding_range.range.end);
+            if let Some((start, end)) = start.zip(end) {
+                ranges.push(start..end);
+            }
+        }
+    }
+    ranges.sort_by(|a, b| {
+        a.start
+            .cmp(&b.start, &snapshot)
+            .then_with(|| b.end.cmp(&a.end, &snapshot))
+    });
+
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.set_folding_ranges(buffer_id, ranges)
+    });
+    cx.notify();
+}
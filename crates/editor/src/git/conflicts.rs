@@ -0,0 +1,334 @@
+use std::{ops::Range, sync::Arc, time::Duration};
+
+use collections::HashMap;
+use gpui::{Action, Hsla, Task};
+use language::ConflictRegion;
+use multi_buffer::{Anchor, AnchorRangeExt, MultiBufferSnapshot, ToOffset};
+use text::OffsetRangeExt;
+use ui::{prelude::*, Tooltip};
+
+use crate::{
+    AcceptBothConflictSides, AcceptOursConflictSide, AcceptTheirsConflictSide, BlockPlacement,
+    BlockProperties, BlockStyle, CustomBlockId, Editor, EditorMode,
+};
+
+const CONFLICT_REFRESH_DEBOUNCE: Duration = Duration::from_millis(50);
+
+enum ConflictMarkerHighlight {}
+enum ConflictOursHighlight {}
+enum ConflictBaseHighlight {}
+enum ConflictTheirsHighlight {}
+
+/// Which sides of a merge conflict to keep when resolving it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+/// A merge conflict, positioned in the editor's multibuffer.
+#[derive(Clone, Debug)]
+pub struct EditorConflict {
+    pub range: Range<Anchor>,
+    pub ours: Range<Anchor>,
+    pub base: Option<Range<Anchor>>,
+    pub theirs: Range<Anchor>,
+}
+
+#[derive(Default)]
+pub(crate) struct ConflictState {
+    conflicts: Vec<EditorConflict>,
+    blocks: Vec<CustomBlockId>,
+    refresh_task: Option<Task<()>>,
+}
+
+impl Editor {
+    /// Returns the merge conflicts that were found in the editor's buffers when they
+    /// last changed.
+    pub fn conflicts(&self) -> &[EditorConflict] {
+        &self.conflict_state.conflicts
+    }
+
+    pub fn accept_ours_conflict_side(
+        &mut self,
+        _: &AcceptOursConflictSide,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.resolve_selected_conflicts(ConflictResolution::Ours, cx);
+    }
+
+    pub fn accept_theirs_conflict_side(
+        &mut self,
+        _: &AcceptTheirsConflictSide,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.resolve_selected_conflicts(ConflictResolution::Theirs, cx);
+    }
+
+    pub fn accept_both_conflict_sides(
+        &mut self,
+        _: &AcceptBothConflictSides,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.resolve_selected_conflicts(ConflictResolution::Both, cx);
+    }
+
+    pub(crate) fn refresh_conflicts(&mut self, cx: &mut ViewContext<Self>) {
+        if self.mode != EditorMode::Full {
+            return;
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        self.conflict_state.refresh_task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(CONFLICT_REFRESH_DEBOUNCE)
+                .await;
+            let conflicts = cx
+                .background_executor()
+                .spawn(async move { conflicts_in_snapshot(&snapshot) })
+                .await;
+            this.update(&mut cx, |this, cx| this.set_conflicts(conflicts, cx))
+                .ok();
+        }));
+    }
+
+    fn set_conflicts(&mut self, conflicts: Vec<EditorConflict>, cx: &mut ViewContext<Self>) {
+        self.clear_row_highlights::<ConflictMarkerHighlight>();
+        self.clear_row_highlights::<ConflictOursHighlight>();
+        self.clear_row_highlights::<ConflictBaseHighlight>();
+        self.clear_row_highlights::<ConflictTheirsHighlight>();
+
+        let status = cx.theme().status();
+        let marker_color = status.conflict_background;
+        let ours_color = status.created_background;
+        let base_color = status.hidden_background;
+        let theirs_color = status.info_background;
+        for conflict in &conflicts {
+            let separator_start = conflict
+                .base
+                .as_ref()
+                .map_or(conflict.theirs.start, |base| base.start);
+            let mut marker_ranges = vec![
+                conflict.range.start..conflict.ours.start,
+                conflict.ours.end..separator_start,
+                conflict.theirs.end..conflict.range.end,
+            ];
+            if let Some(base) = &conflict.base {
+                marker_ranges.push(base.end..conflict.theirs.start);
+                self.highlight_conflict_rows::<ConflictBaseHighlight>(base.clone(), base_color, cx);
+            }
+            for range in marker_ranges {
+                self.highlight_conflict_rows::<ConflictMarkerHighlight>(range, marker_color, cx);
+            }
+            self.highlight_conflict_rows::<ConflictOursHighlight>(
+                conflict.ours.clone(),
+                ours_color,
+                cx,
+            );
+            self.highlight_conflict_rows::<ConflictTheirsHighlight>(
+                conflict.theirs.clone(),
+                theirs_color,
+                cx,
+            );
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let conflicts_moved = self.conflict_state.conflicts.len() != conflicts.len()
+            || self
+                .conflict_state
+                .conflicts
+                .iter()
+                .zip(&conflicts)
+                .any(|(old, new)| old.range.to_offset(&snapshot) != new.range.to_offset(&snapshot));
+        if conflicts_moved {
+            let old_blocks = self.conflict_state.blocks.drain(..).collect();
+            self.remove_blocks(old_blocks, None, cx);
+            let blocks = conflicts
+                .iter()
+                .map(|conflict| self.conflict_header_block(conflict, cx))
+                .collect::<Vec<_>>();
+            self.conflict_state.blocks = self.insert_blocks(blocks, None, cx);
+        }
+        self.conflict_state.conflicts = conflicts;
+        cx.notify();
+    }
+
+    fn highlight_conflict_rows<T: 'static>(
+        &mut self,
+        range: Range<Anchor>,
+        color: Hsla,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if range.start.cmp(&range.end, &snapshot).is_lt() {
+            self.highlight_rows::<T>(range, color, false, cx);
+        }
+    }
+
+    fn conflict_header_block(
+        &self,
+        conflict: &EditorConflict,
+        cx: &mut ViewContext<Self>,
+    ) -> BlockProperties<Anchor> {
+        let editor = cx.view().clone();
+        let position = conflict.range.start;
+        BlockProperties {
+            placement: BlockPlacement::Above(position),
+            height: 1,
+            style: BlockStyle::Sticky,
+            priority: 0,
+            render: Arc::new(move |cx| {
+                let focus_handle = editor.focus_handle(cx);
+                let resolve_button =
+                    |id: &'static str,
+                     label: &'static str,
+                     action: Box<dyn Action>,
+                     resolution: ConflictResolution| {
+                        let editor = editor.clone();
+                        let focus_handle = focus_handle.clone();
+                        Button::new(id, label)
+                            .style(ButtonStyle::Subtle)
+                            .label_size(LabelSize::Small)
+                            .tooltip(move |cx| {
+                                Tooltip::for_action_in(label, action.as_ref(), &focus_handle, cx)
+                            })
+                            .on_click(move |_, cx| {
+                                editor.update(cx, |editor, cx| {
+                                    editor.resolve_conflicts_at(vec![position], resolution, cx);
+                                });
+                            })
+                    };
+
+                h_flex()
+                    .id(cx.block_id)
+                    .block_mouse_down()
+                    .h(cx.line_height)
+                    .w_full()
+                    .pl(cx.gutter_dimensions.full_width())
+                    .gap_1()
+                    .child(
+                        Label::new("Merge conflict")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(resolve_button(
+                        "accept-ours",
+                        "Accept Ours",
+                        Box::new(AcceptOursConflictSide),
+                        ConflictResolution::Ours,
+                    ))
+                    .child(resolve_button(
+                        "accept-theirs",
+                        "Accept Theirs",
+                        Box::new(AcceptTheirsConflictSide),
+                        ConflictResolution::Theirs,
+                    ))
+                    .child(resolve_button(
+                        "accept-both",
+                        "Accept Both",
+                        Box::new(AcceptBothConflictSides),
+                        ConflictResolution::Both,
+                    ))
+                    .into_any_element()
+            }),
+        }
+    }
+
+    fn resolve_selected_conflicts(
+        &mut self,
+        resolution: ConflictResolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let positions = self
+            .selections
+            .disjoint_anchors()
+            .iter()
+            .flat_map(|selection| [selection.start, selection.end])
+            .collect();
+        self.resolve_conflicts_at(positions, resolution, cx);
+    }
+
+    /// Replaces each conflict containing one of the given positions with the chosen
+    /// sides, removing its markers.
+    pub fn resolve_conflicts_at(
+        &mut self,
+        positions: Vec<Anchor>,
+        resolution: ConflictResolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let positions = positions
+            .iter()
+            .map(|position| position.to_offset(&snapshot))
+            .collect::<Vec<_>>();
+        let mut edits = Vec::new();
+        for conflict in conflicts_in_snapshot(&snapshot) {
+            let range = conflict.range.to_offset(&snapshot);
+            if !positions
+                .iter()
+                .any(|position| range.start <= *position && *position < range.end)
+            {
+                continue;
+            }
+
+            let ours = snapshot
+                .text_for_range(conflict.ours.clone())
+                .collect::<String>();
+            let theirs = snapshot
+                .text_for_range(conflict.theirs.clone())
+                .collect::<String>();
+            let text = match resolution {
+                ConflictResolution::Ours => ours,
+                ConflictResolution::Theirs => theirs,
+                ConflictResolution::Both => ours + &theirs,
+            };
+            edits.push((range, text));
+        }
+
+        if edits.is_empty() {
+            return;
+        }
+        self.transact(cx, |editor, cx| editor.edit(edits, cx));
+    }
+}
+
+/// Finds the conflicts that lie entirely within one of the snapshot's excerpts.
+fn conflicts_in_snapshot(snapshot: &MultiBufferSnapshot) -> Vec<EditorConflict> {
+    let mut conflicts_by_buffer = HashMap::<_, Vec<ConflictRegion>>::default();
+    let mut conflicts = Vec::new();
+    for (excerpt_id, buffer, excerpt_range) in snapshot.excerpts() {
+        let buffer_conflicts = conflicts_by_buffer
+            .entry(buffer.remote_id())
+            .or_insert_with(|| buffer.conflicts());
+        let context = excerpt_range.context.to_offset(buffer);
+        for conflict in buffer_conflicts.iter() {
+            let range = conflict.range.to_offset(buffer);
+            if range.start < context.start || range.end > context.end {
+                continue;
+            }
+
+            let anchor_range = |range: &Range<text::Anchor>| {
+                Some(
+                    snapshot.anchor_in_excerpt(excerpt_id, range.start)?
+                        ..snapshot.anchor_in_excerpt(excerpt_id, range.end)?,
+                )
+            };
+            let (Some(range), Some(ours), Some(theirs)) = (
+                anchor_range(&conflict.range),
+                anchor_range(&conflict.ours),
+                anchor_range(&conflict.theirs),
+            ) else {
+                continue;
+            };
+            let base = conflict.base.as_ref().and_then(anchor_range);
+            conflicts.push(EditorConflict {
+                range,
+                ours,
+                base,
+                theirs,
+            });
+        }
+    }
+    conflicts
+}
@@ -8,10 +8,10 @@ use std::{
 
 use anyhow::{anyhow, Context as _};
 use collections::{BTreeMap, HashMap};
-use feature_flags::FeatureFlagAppExt;
+use feature_flags::{FeatureFlagAppExt, GitUiFeatureFlag};
 use git::{
     diff::{BufferDiff, DiffHunk},
-    repository::GitFileStatus,
+    repository::{DiffBase, GitFileStatus},
 };
 use gpui::{
     actions, AnyElement, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView,
@@ -31,19 +31,23 @@ use workspace::{
 
 use crate::{Editor, EditorEvent, DEFAULT_MULTIBUFFER_CONTEXT};
 
-actions!(project_diff, [Deploy]);
+actions!(project_diff, [Deploy, ExpandContext, CollapseContext]);
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(ProjectDiffEditor::register).detach();
 }
 
 const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+/// The number of lines of context that [`ExpandContext`] adds around each hunk.
+const CONTEXT_EXPANSION_STEP: u32 = 5;
 
 struct ProjectDiffEditor {
     buffer_changes: BTreeMap<WorktreeId, HashMap<ProjectEntryId, Changes>>,
     entry_order: HashMap<WorktreeId, Vec<(ProjectPath, ProjectEntryId)>>,
     excerpts: Model<MultiBuffer>,
     editor: View<Editor>,
+    /// The number of lines shown around each hunk.
+    context_lines: u32,
 
     project: Model<Project>,
     workspace: WeakView<Workspace>,
@@ -65,7 +69,7 @@ impl ProjectDiffEditor {
     }
 
     fn deploy(workspace: &mut Workspace, _: &Deploy, cx: &mut ViewContext<Workspace>) {
-        if !cx.is_staff() {
+        if !cx.has_flag::<GitUiFeatureFlag>() {
             return;
         }
 
@@ -137,6 +141,9 @@ impl ProjectDiffEditor {
                     project::Event::Closed => {
                         project_diff_editor.buffer_changes.clear();
                     }
+                    project::Event::DiffBaseChanged => {
+                        project_diff_editor.schedule_rescan_all(cx);
+                    }
                     _ => {}
                 }
 
@@ -163,12 +170,48 @@ impl ProjectDiffEditor {
             focus_handle,
             editor,
             excerpts,
+            context_lines: DEFAULT_MULTIBUFFER_CONTEXT,
             _subscriptions: vec![changed_entries_subscription],
         };
         new_self.schedule_rescan_all(cx);
         new_self
     }
 
+    fn expand_context(&mut self, _: &ExpandContext, cx: &mut ViewContext<Self>) {
+        self.context_lines += CONTEXT_EXPANSION_STEP;
+        self.excerpts.update(cx, |multi_buffer, cx| {
+            let excerpt_ids = multi_buffer.excerpt_ids();
+            multi_buffer.expand_excerpts(
+                excerpt_ids,
+                CONTEXT_EXPANSION_STEP,
+                ExpandExcerptDirection::UpAndDown,
+                cx,
+            );
+        });
+    }
+
+    /// Shrinks the context around each hunk back to its default size, which requires
+    /// rebuilding the excerpts as they can't be shrunk in place.
+    fn collapse_context(&mut self, _: &CollapseContext, cx: &mut ViewContext<Self>) {
+        if self.context_lines == DEFAULT_MULTIBUFFER_CONTEXT {
+            return;
+        }
+        self.context_lines = DEFAULT_MULTIBUFFER_CONTEXT;
+        self.excerpts
+            .update(cx, |multi_buffer, cx| multi_buffer.clear(cx));
+        self.buffer_changes.clear();
+        self.entry_order.clear();
+        self.schedule_rescan_all(cx);
+    }
+
+    fn changed_file_count(&self) -> usize {
+        self.buffer_changes
+            .values()
+            .flat_map(|changes| changes.values())
+            .filter(|changes| !changes.hunks.is_empty())
+            .count()
+    }
+
     fn schedule_rescan_all(&mut self, cx: &mut ViewContext<Self>) {
         let mut current_worktrees = HashSet::<WorktreeId>::default();
         for worktree in self.project.read(cx).worktrees(cx).collect::<Vec<_>>() {
@@ -191,6 +234,27 @@ impl ProjectDiffEditor {
             id,
             cx.spawn(|project_diff_editor, mut cx| async move {
                 cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+                // When diffing against a revision, files that were committed since then
+                // have no git status but still need to be shown.
+                let load_changed_paths = project
+                    .update(&mut cx, |project, cx| match project.default_diff_base(cx) {
+                        DiffBase::Index => None,
+                        DiffBase::Revision(revision) => {
+                            Some(project.paths_changed_since(id, revision.clone(), cx))
+                        }
+                    })
+                    .ok()
+                    .flatten();
+                let changed_paths = match load_changed_paths {
+                    Some(load) => Some(
+                        load.await
+                            .log_err()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .collect::<HashSet<_>>(),
+                    ),
+                    None => None,
+                };
                 let open_tasks = project
                     .update(&mut cx, |project, cx| {
                         let worktree = project.worktree_for_id(id, cx)?;
@@ -199,7 +263,17 @@ impl ProjectDiffEditor {
                             .entries(false, 0)
                             .filter(|entry| !entry.is_external)
                             .filter(|entry| entry.is_file())
-                            .filter_map(|entry| Some((entry.git_status?, entry)))
+                            .filter_map(|entry| {
+                                let git_status = match &changed_paths {
+                                    Some(changed_paths) => {
+                                        changed_paths.contains(&entry.path).then(|| {
+                                            entry.git_status.unwrap_or(GitFileStatus::Modified)
+                                        })?
+                                    }
+                                    None => entry.git_status?,
+                                };
+                                Some((git_status, entry))
+                            })
                             .filter_map(|(git_status, entry)| {
                                 Some((git_status, entry.id, project.path_for_entry(entry.id, cx)?))
                             })
@@ -228,6 +302,7 @@ impl ProjectDiffEditor {
                                 text::BufferSnapshot,
                                 Model<Buffer>,
                                 BufferDiff,
+                                BufferDiff,
                             ),
                         >::default();
                         let mut change_sets = Vec::new();
@@ -258,13 +333,15 @@ impl ProjectDiffEditor {
                             };
 
                             cx.update(|cx| {
+                                let change_set = change_set.read(cx);
                                 buffers.insert(
                                     entry_id,
                                     (
                                         status,
                                         buffer.read(cx).text_snapshot(),
                                         buffer,
-                                        change_set.read(cx).diff_to_buffer.clone(),
+                                        change_set.diff_to_buffer.clone(),
+                                        change_set.staged_diff.clone(),
                                     ),
                                 );
                             })?;
@@ -284,15 +361,31 @@ impl ProjectDiffEditor {
                     .background_executor()
                     .spawn(async move {
                         let mut new_changes = HashMap::<ProjectEntryId, Changes>::default();
-                        for (entry_id, (status, buffer_snapshot, buffer, buffer_diff)) in buffers {
+                        for (
+                            entry_id,
+                            (status, buffer_snapshot, buffer, buffer_diff, staged_diff),
+                        ) in buffers
+                        {
+                            // Staged hunks are part of the uncommitted changes too, even
+                            // though the buffer no longer differs from the index there.
+                            let mut hunks = buffer_diff
+                                .hunks_in_row_range(0..BufferRow::MAX, &buffer_snapshot)
+                                .chain(
+                                    staged_diff
+                                        .hunks_in_row_range(0..BufferRow::MAX, &buffer_snapshot),
+                                )
+                                .collect::<Vec<_>>();
+                            hunks.sort_by(|a, b| {
+                                a.buffer_range
+                                    .start
+                                    .cmp(&b.buffer_range.start, &buffer_snapshot)
+                            });
                             new_changes.insert(
                                 entry_id,
                                 Changes {
                                     _status: status,
                                     buffer,
-                                    hunks: buffer_diff
-                                        .hunks_in_row_range(0..BufferRow::MAX, &buffer_snapshot)
-                                        .collect::<Vec<_>>(),
+                                    hunks,
                                 },
                             );
                         }
@@ -328,6 +421,7 @@ impl ProjectDiffEditor {
         new_entry_order: Vec<(ProjectPath, ProjectEntryId)>,
         cx: &mut ViewContext<ProjectDiffEditor>,
     ) {
+        let context_lines = self.context_lines;
         if let Some(current_order) = self.entry_order.get(&worktree_id) {
             let current_entries = self.buffer_changes.entry(worktree_id).or_default();
             let mut new_order_entries = new_entry_order.iter().fuse().peekable();
@@ -588,7 +682,7 @@ impl ProjectDiffEditor {
                                                                             )
                                                                             .row,
                                                                     );
-                                                                    excerpt_to_expand.entry((expand_up.max(expand_down).max(DEFAULT_MULTIBUFFER_CONTEXT), ExpandExcerptDirection::UpAndDown)).or_default().push(*current_excerpt_id);
+                                                                    excerpt_to_expand.entry((expand_up.max(expand_down).max(context_lines), ExpandExcerptDirection::UpAndDown)).or_default().push(*current_excerpt_id);
                                                                     excerpts_with_new_changes
                                                                         .insert(
                                                                             *current_excerpt_id,
@@ -623,7 +717,7 @@ impl ProjectDiffEditor {
                                                                                 )
                                                                                 .row,
                                                                         );
-                                                                        excerpt_to_expand.entry((expand_up.max(DEFAULT_MULTIBUFFER_CONTEXT), ExpandExcerptDirection::Up)).or_default().push(*current_excerpt_id);
+                                                                        excerpt_to_expand.entry((expand_up.max(context_lines), ExpandExcerptDirection::Up)).or_default().push(*current_excerpt_id);
                                                                         excerpts_with_new_changes
                                                                             .insert(
                                                                                 *current_excerpt_id,
@@ -702,7 +796,7 @@ impl ProjectDiffEditor {
                                                                             )
                                                                             .row,
                                                                     );
-                                                                        excerpt_to_expand.entry((expand_down.max(DEFAULT_MULTIBUFFER_CONTEXT), ExpandExcerptDirection::Down)).or_default().push(*current_excerpt_id);
+                                                                        excerpt_to_expand.entry((expand_down.max(context_lines), ExpandExcerptDirection::Down)).or_default().push(*current_excerpt_id);
                                                                         excerpts_with_new_changes
                                                                             .insert(
                                                                                 *current_excerpt_id,
@@ -844,12 +938,10 @@ impl ProjectDiffEditor {
                             buffer,
                             hunk_ranges.into_iter().map(|range| {
                                 let mut extended_point_range = range.to_point(&buffer_snapshot);
-                                extended_point_range.start.row = extended_point_range
-                                    .start
-                                    .row
-                                    .saturating_sub(DEFAULT_MULTIBUFFER_CONTEXT);
+                                extended_point_range.start.row =
+                                    extended_point_range.start.row.saturating_sub(context_lines);
                                 extended_point_range.end.row = (extended_point_range.end.row
-                                    + DEFAULT_MULTIBUFFER_CONTEXT)
+                                    + context_lines)
                                     .min(max_point.row);
                                 ExcerptRange {
                                     context: extended_point_range,
@@ -879,7 +971,7 @@ impl ProjectDiffEditor {
                             .iter()
                             .map(|hunk| hunk.buffer_range.clone())
                             .collect(),
-                        DEFAULT_MULTIBUFFER_CONTEXT,
+                        context_lines,
                         cx,
                     );
                 }
@@ -924,55 +1016,32 @@ impl Item for ProjectDiffEditor {
     }
 
     fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
-        Some("Project Diff".into())
+        Some("Uncommitted Changes".into())
     }
 
     fn tab_content(&self, params: TabContentParams, _: &WindowContext) -> AnyElement {
-        if self.buffer_changes.is_empty() {
+        let label_color = if params.selected {
+            Color::Default
+        } else {
+            Color::Muted
+        };
+        let file_count = self.changed_file_count();
+        if file_count == 0 {
             Label::new("No changes")
-                .color(if params.selected {
-                    Color::Default
-                } else {
-                    Color::Muted
-                })
+                .color(label_color)
                 .into_any_element()
         } else {
             h_flex()
                 .gap_1()
-                .when(true, |then| {
-                    then.child(
-                        h_flex()
-                            .gap_1()
-                            .child(Icon::new(IconName::XCircle).color(Color::Error))
-                            .child(Label::new(self.buffer_changes.len().to_string()).color(
-                                if params.selected {
-                                    Color::Default
-                                } else {
-                                    Color::Muted
-                                },
-                            )),
-                    )
-                })
-                .when(true, |then| {
-                    then.child(
-                        h_flex()
-                            .gap_1()
-                            .child(Icon::new(IconName::Indicator).color(Color::Warning))
-                            .child(Label::new(self.buffer_changes.len().to_string()).color(
-                                if params.selected {
-                                    Color::Default
-                                } else {
-                                    Color::Muted
-                                },
-                            )),
-                    )
-                })
+                .child(Icon::new(IconName::FileGit).color(label_color))
+                .child(Label::new("Uncommitted Changes").color(label_color))
+                .child(Label::new(file_count.to_string()).color(Color::Muted))
                 .into_any_element()
         }
     }
 
     fn telemetry_event_text(&self) -> Option<&'static str> {
-        Some("project diagnostics")
+        Some("project diff")
     }
 
     fn for_each_project_item(
@@ -1075,7 +1144,7 @@ impl Item for ProjectDiffEditor {
 
 impl Render for ProjectDiffEditor {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let child = if self.buffer_changes.is_empty() {
+        let child = if self.changed_file_count() == 0 {
             div()
                 .bg(cx.theme().colors().editor_background)
                 .flex()
@@ -1089,6 +1158,9 @@ impl Render for ProjectDiffEditor {
 
         div()
             .track_focus(&self.focus_handle)
+            .key_context("ProjectDiff")
+            .on_action(cx.listener(Self::expand_context))
+            .on_action(cx.listener(Self::collapse_context))
             .size_full()
             .child(child)
     }
@@ -1096,6 +1168,7 @@ impl Render for ProjectDiffEditor {
 
 #[cfg(test)]
 mod tests {
+    use feature_flags::FeatureFlag as _;
     use gpui::{SemanticVersion, TestAppContext, VisualTestContext};
     use project::buffer_store::BufferChangeSet;
     use serde_json::json;
@@ -1229,6 +1302,61 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn staged_changes_test(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        init_test(cx);
+
+        let fs = fs::FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                "file_a": "one\ntwo\nthree\n",
+            }),
+        )
+        .await;
+        fs.set_head_for_repo(
+            Path::new("/root/.git"),
+            &[(Path::new("file_a"), "one\nTWO\nthree\n".to_string())],
+        );
+        fs.set_index_for_repo(
+            Path::new("/root/.git"),
+            &[(Path::new("file_a"), "one\ntwo\nthree\n".to_string())],
+        );
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/root/.git"),
+            &[(Path::new("file_a"), GitFileStatus::Modified)],
+        );
+
+        let project = Project::test(fs.clone(), [Path::new("/root")], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+        let project_diff_editor = workspace
+            .update(cx, |workspace, cx| {
+                ProjectDiffEditor::deploy(workspace, &Deploy, cx);
+                workspace
+                    .active_pane()
+                    .read(cx)
+                    .items()
+                    .find_map(|item| item.downcast::<ProjectDiffEditor>())
+            })
+            .unwrap()
+            .expect("did not find a ProjectDiffEditor");
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(100));
+        cx.run_until_parked();
+
+        project_diff_editor.update(cx, |project_diff_editor, cx| {
+            assert_eq!(project_diff_editor.changed_file_count(), 1);
+            assert_eq!(
+                project_diff_editor.editor.read(cx).text(cx),
+                "one\ntwo\nthree\n",
+                "Should show files whose changes are all staged"
+            );
+        });
+    }
+
     fn init_test(cx: &mut gpui::TestAppContext) {
         if std::env::var("RUST_LOG").is_ok() {
             env_logger::try_init().ok();
@@ -1245,7 +1373,7 @@ mod tests {
             Project::init_settings(cx);
             workspace::init_settings(cx);
             crate::init(cx);
-            cx.set_staff(true);
+            cx.update_flags(false, vec![GitUiFeatureFlag::NAME.to_string()]);
         });
     }
 }
@@ -18,13 +18,14 @@ use ui::{
     ParentElement, PopoverMenu, Styled, Tooltip, ViewContext, VisualContext,
 };
 use util::RangeExt;
-use workspace::Item;
+use workspace::{notifications::NotifyTaskExt, Item};
 
 use crate::{
     editor_settings::CurrentLineHighlight, hunk_status, hunks_for_selections, ApplyAllDiffHunks,
     ApplyDiffHunk, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, DiffRowHighlight,
     DisplayRow, DisplaySnapshot, Editor, EditorElement, ExpandAllHunkDiffs, GoToHunk, GoToPrevHunk,
-    RevertFile, RevertSelectedHunks, ToDisplayPoint, ToggleHunkDiff,
+    RevertFile, RevertSelectedHunks, StageSelectedHunks, StageSelectedLines, ToDisplayPoint,
+    ToggleHunkDiff, UnstageSelectedHunks,
 };
 
 #[derive(Debug, Clone)]
@@ -53,7 +54,11 @@ pub(super) struct ExpandedHunk {
 }
 
 #[derive(Clone, Debug, Default)]
-pub(crate) struct DiffMapSnapshot(TreeMap<BufferId, git::diff::BufferDiff>);
+pub(crate) struct DiffMapSnapshot {
+    diffs: TreeMap<BufferId, git::diff::BufferDiff>,
+    /// Changes between the HEAD commit and the index, positioned in the buffer.
+    staged_diffs: TreeMap<BufferId, git::diff::BufferDiff>,
+}
 
 pub(crate) struct DiffBaseState {
     pub(crate) change_set: Model<BufferChangeSet>,
@@ -86,9 +91,7 @@ impl DiffMap {
         cx: &mut ViewContext<Editor>,
     ) {
         let buffer_id = change_set.read(cx).buffer_id;
-        self.snapshot
-            .0
-            .insert(buffer_id, change_set.read(cx).diff_to_buffer.clone());
+        self.snapshot.update(buffer_id, change_set.read(cx));
         self.diff_bases.insert(
             buffer_id,
             DiffBaseState {
@@ -97,8 +100,7 @@ impl DiffMap {
                     editor
                         .diff_map
                         .snapshot
-                        .0
-                        .insert(buffer_id, change_set.read(cx).diff_to_buffer.clone());
+                        .update(buffer_id, change_set.read(cx));
                     Editor::sync_expanded_diff_hunks(&mut editor.diff_map, buffer_id, cx);
                 }),
                 change_set,
@@ -115,8 +117,15 @@ impl DiffMap {
 }
 
 impl DiffMapSnapshot {
+    fn update(&mut self, buffer_id: BufferId, change_set: &BufferChangeSet) {
+        self.diffs
+            .insert(buffer_id, change_set.diff_to_buffer.clone());
+        self.staged_diffs
+            .insert(buffer_id, change_set.staged_diff.clone());
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.0.values().all(|diff| diff.is_empty())
+        self.diffs.values().all(|diff| diff.is_empty())
     }
 
     pub fn diff_hunks<'a>(
@@ -130,6 +139,23 @@ impl DiffMapSnapshot {
         &'a self,
         range: Range<T>,
         buffer_snapshot: &'a MultiBufferSnapshot,
+    ) -> impl Iterator<Item = MultiBufferDiffHunk> + 'a {
+        Self::hunks_in_range(&self.diffs, range, buffer_snapshot)
+    }
+
+    /// Returns the hunks that are staged in the index, relative to the HEAD commit.
+    pub fn staged_diff_hunks_in_range<'a, T: ToOffset>(
+        &'a self,
+        range: Range<T>,
+        buffer_snapshot: &'a MultiBufferSnapshot,
+    ) -> impl Iterator<Item = MultiBufferDiffHunk> + 'a {
+        Self::hunks_in_range(&self.staged_diffs, range, buffer_snapshot)
+    }
+
+    fn hunks_in_range<'a, T: ToOffset>(
+        diffs: &'a TreeMap<BufferId, git::diff::BufferDiff>,
+        range: Range<T>,
+        buffer_snapshot: &'a MultiBufferSnapshot,
     ) -> impl Iterator<Item = MultiBufferDiffHunk> + 'a {
         let range = range.start.to_offset(buffer_snapshot)..range.end.to_offset(buffer_snapshot);
         buffer_snapshot
@@ -137,7 +163,7 @@ impl DiffMapSnapshot {
             .filter_map(move |excerpt| {
                 let buffer = excerpt.buffer();
                 let buffer_id = buffer.remote_id();
-                let diff = self.0.get(&buffer_id)?;
+                let diff = diffs.get(&buffer_id)?;
                 let buffer_range = excerpt.map_range_to_buffer(range.clone());
                 let buffer_range =
                     buffer.anchor_before(buffer_range.start)..buffer.anchor_after(buffer_range.end);
@@ -171,7 +197,7 @@ impl DiffMapSnapshot {
             .filter_map(move |excerpt| {
                 let buffer = excerpt.buffer();
                 let buffer_id = buffer.remote_id();
-                let diff = self.0.get(&buffer_id)?;
+                let diff = self.diffs.get(&buffer_id)?;
                 let buffer_range = excerpt.map_range_to_buffer(range.clone());
                 let buffer_range =
                     buffer.anchor_before(buffer_range.start)..buffer.anchor_after(buffer_range.end);
@@ -202,6 +228,18 @@ impl Editor {
         self.diff_map.expand_all = true;
     }
 
+    /// Displays the diff of the given change set in its buffer's excerpts.
+    ///
+    /// This is meant for buffers that aren't backed by project files, whose diff base
+    /// isn't loaded by the editor itself.
+    pub fn add_change_set(
+        &mut self,
+        change_set: Model<BufferChangeSet>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.diff_map.add_change_set(change_set, cx);
+    }
+
     pub(super) fn toggle_hovered_hunk(
         &mut self,
         hovered_hunk: &HoveredHunk,
@@ -522,6 +560,150 @@ impl Editor {
         }
     }
 
+    pub(crate) fn stage_selected_hunks(
+        &mut self,
+        _: &StageSelectedHunks,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.snapshot(cx);
+        let hunks = hunks_for_selections(&snapshot, &self.selections.all(cx));
+        let rows_by_buffer = self.buffer_rows_for_hunks(hunks, cx);
+        self.update_index_for_rows(rows_by_buffer, true, cx);
+    }
+
+    pub(crate) fn unstage_selected_hunks(
+        &mut self,
+        _: &UnstageSelectedHunks,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.snapshot(cx);
+        let mut hunks = Vec::new();
+        for selection in self.selections.all::<Point>(cx) {
+            let query_rows =
+                MultiBufferRow(selection.start.row)..MultiBufferRow(selection.end.row + 1);
+            hunks.extend(
+                snapshot
+                    .diff_map
+                    .staged_diff_hunks_in_range(
+                        Point::new(query_rows.start.0, 0)..Point::new(query_rows.end.0, 0),
+                        &snapshot.buffer_snapshot,
+                    )
+                    .filter(|hunk| {
+                        hunk.row_range.overlaps(&query_rows)
+                            || (hunk_status(hunk) == DiffHunkStatus::Removed
+                                && (hunk.row_range.start == query_rows.end
+                                    || hunk.row_range.end == query_rows.start))
+                    }),
+            );
+        }
+        let rows_by_buffer = self.buffer_rows_for_hunks(hunks, cx);
+        self.update_index_for_rows(rows_by_buffer, false, cx);
+    }
+
+    pub(crate) fn stage_selected_lines(
+        &mut self,
+        _: &StageSelectedLines,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let selections = self.selections.all::<Point>(cx);
+        let multi_buffer = self.buffer.read(cx);
+        let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+        let mut rows_by_buffer = HashMap::default();
+        for selection in selections {
+            let end_row = MultiBufferRow(selection.end.row);
+            let range = Point::new(selection.start.row, 0)
+                ..Point::new(end_row.0, multi_buffer_snapshot.line_len(end_row));
+            for (buffer, range, _) in multi_buffer.range_to_buffer_ranges(range, cx) {
+                let range = range.to_point(buffer.read(cx));
+                rows_by_buffer
+                    .entry(buffer)
+                    .or_insert_with(Vec::new)
+                    .push(range.start.row..range.end.row + 1);
+            }
+        }
+        self.update_index_for_rows(rows_by_buffer, true, cx);
+    }
+
+    fn stage_hunk(&mut self, hunk: &HoveredHunk, cx: &mut ViewContext<Editor>) {
+        let snapshot = self.buffer.read(cx).read(cx);
+        if let Some(hunk) = to_diff_hunk(hunk, &snapshot) {
+            drop(snapshot);
+            let rows_by_buffer = self.buffer_rows_for_hunks(vec![hunk], cx);
+            self.update_index_for_rows(rows_by_buffer, true, cx);
+        }
+    }
+
+    fn buffer_rows_for_hunks(
+        &self,
+        hunks: Vec<MultiBufferDiffHunk>,
+        cx: &AppContext,
+    ) -> HashMap<Model<Buffer>, Vec<Range<u32>>> {
+        let mut rows_by_buffer = HashMap::default();
+        for hunk in hunks {
+            if let Some(buffer) = self.buffer.read(cx).buffer(hunk.buffer_id) {
+                let range = hunk.buffer_range.to_point(buffer.read(cx));
+                rows_by_buffer
+                    .entry(buffer)
+                    .or_insert_with(Vec::new)
+                    .push(range.start.row..range.end.row);
+            }
+        }
+        rows_by_buffer
+    }
+
+    /// Stages or unstages the changes in the given buffer rows, by writing new contents
+    /// for each buffer to the index.
+    fn update_index_for_rows(
+        &mut self,
+        rows_by_buffer: HashMap<Model<Buffer>, Vec<Range<u32>>>,
+        stage: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+
+        for (buffer, rows) in rows_by_buffer {
+            let buffer_id = buffer.read(cx).remote_id();
+            let Some(diff_base) = self.diff_map.diff_bases.get(&buffer_id) else {
+                continue;
+            };
+            let change_set = diff_base.change_set.read(cx);
+            // Hunks against another revision don't correspond to changes in the index.
+            if !change_set.diff_base.is_index() {
+                continue;
+            }
+            let Some(index_text) = change_set
+                .base_text
+                .as_ref()
+                .map(|base_text| base_text.read(cx).as_rope().clone())
+            else {
+                continue;
+            };
+            let buffer_snapshot = buffer.read(cx).text_snapshot();
+            let new_index_text = if stage {
+                change_set
+                    .diff_to_buffer
+                    .stage_rows(&index_text, &buffer_snapshot, &rows)
+            } else {
+                let Some(head_text) = change_set.head_text.as_ref() else {
+                    continue;
+                };
+                change_set.diff_to_buffer.unstage_rows(
+                    head_text,
+                    &index_text,
+                    &buffer_snapshot,
+                    &rows,
+                )
+            };
+
+            project
+                .read(cx)
+                .write_staged_text(&buffer, Some(new_index_text), cx)
+                .detach_and_notify_err(cx);
+        }
+    }
+
     fn has_multiple_hunks(&self, cx: &AppContext) -> bool {
         let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut hunks = self.diff_map.snapshot.diff_hunks(&snapshot);
@@ -681,6 +863,33 @@ impl Editor {
                                                     }
                                                 }),
                                         )
+                                        .when(!is_branch_buffer, |row| {
+                                            row.child(
+                                                IconButton::new("stage", IconName::Plus)
+                                                    .shape(IconButtonShape::Square)
+                                                    .icon_size(IconSize::Small)
+                                                    .tooltip({
+                                                        let focus_handle = editor.focus_handle(cx);
+                                                        move |cx| {
+                                                            Tooltip::for_action_in(
+                                                                "Stage Hunk",
+                                                                &StageSelectedHunks,
+                                                                &focus_handle,
+                                                                cx,
+                                                            )
+                                                        }
+                                                    })
+                                                    .on_click({
+                                                        let editor = editor.clone();
+                                                        let hunk = hunk.clone();
+                                                        move |_event, cx| {
+                                                            editor.update(cx, |editor, cx| {
+                                                                editor.stage_hunk(&hunk, cx);
+                                                            });
+                                                        }
+                                                    }),
+                                            )
+                                        })
                                         .map(|this| {
                                             if is_branch_buffer {
                                                 this.child(
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use gpui::{AppContext, Model, ModelContext, Task, ViewContext};
+use language::Buffer;
+use project::Project;
+use text::BufferId;
+use util::ResultExt;
+
+use crate::Editor;
+
+/// The buffers of an editor whose language server data, such as semantic tokens,
+/// code lenses or folding ranges, is requested again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RefreshScope {
+    /// Every buffer, e.g. after settings changed or a language server asked for a refresh.
+    All,
+    /// A single buffer that was edited, got new excerpts or changed its language.
+    Buffer(BufferId),
+}
+
+impl RefreshScope {
+    /// The buffers of the editor that this refresh covers and that are still part of it.
+    pub fn buffers(self, editor: &Editor, cx: &AppContext) -> Vec<Model<Buffer>> {
+        let multi_buffer = editor.buffer.read(cx);
+        match self {
+            Self::All => multi_buffer.all_buffers().into_iter().collect(),
+            Self::Buffer(buffer_id) => multi_buffer.buffer(buffer_id).into_iter().collect(),
+        }
+    }
+
+    /// Whether the data kept for `buffer_id` is replaced by this refresh, and so has to be
+    /// dropped when the refresh no longer requests it.
+    pub fn covers(self, buffer_id: BufferId) -> bool {
+        match self {
+            Self::All => true,
+            Self::Buffer(refreshed_id) => refreshed_id == buffer_id,
+        }
+    }
+}
+
+/// Requests data for `buffer` once `debounce` passes without another refresh replacing the
+/// returned task, and hands the data to `apply`. Failed requests are logged.
+pub(super) fn debounced_buffer_request<T: 'static>(
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    debounce: Duration,
+    request: impl FnOnce(&mut Project, &Model<Buffer>, &mut ModelContext<Project>) -> Task<Result<T>>
+        + 'static,
+    apply: impl FnOnce(&mut Editor, Model<Buffer>, T, &mut ViewContext<Editor>) + 'static,
+    cx: &mut ViewContext<Editor>,
+) -> Task<()> {
+    cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(debounce).await;
+        let Some(request) = project
+            .update(&mut cx, |project, cx| request(project, &buffer, cx))
+            .ok()
+        else {
+            return;
+        };
+        let Some(data) = request.await.log_err() else {
+            return;
+        };
+        editor
+            .update(&mut cx, |editor, cx| apply(editor, buffer, data, cx))
+            .ok();
+    })
+}
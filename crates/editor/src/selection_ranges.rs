@@ -0,0 +1,162 @@
+use std::{mem, ops::Range};
+
+use language::language_settings::{language_settings, RangeSource};
+use multi_buffer::MultiBufferSnapshot;
+use text::{OffsetRangeExt, Selection, SelectionGoal};
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::{scroll::Autoscroll, Editor};
+
+/// Expands every selection to the smallest enclosing language server selection range,
+/// when the buffer of the selections is configured to use them. Expansions requested while
+/// a response is pending are applied together once it arrives.
+///
+/// Returns `false` if the syntax tree should be used instead.
+pub(super) fn select_larger_lsp_range(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
+    if editor.pending_lsp_range_expansions > 0 {
+        editor.pending_lsp_range_expansions += 1;
+        return true;
+    }
+    let Some(project) = editor.project.clone() else {
+        return false;
+    };
+    let old_selections = editor.selections.all::<usize>(cx);
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+
+    let mut buffer_id = None;
+    let mut positions = Vec::with_capacity(old_selections.len());
+    for selection in &old_selections {
+        let Some(excerpt) = snapshot.excerpt_containing(selection.range()) else {
+            return false;
+        };
+        let buffer = excerpt.buffer();
+        if *buffer_id.get_or_insert(buffer.remote_id()) != buffer.remote_id() {
+            return false;
+        }
+        positions.push(buffer.anchor_before(excerpt.map_offset_to_buffer(selection.start)));
+    }
+    let Some(buffer) = buffer_id.and_then(|buffer_id| editor.buffer.read(cx).buffer(buffer_id))
+    else {
+        return false;
+    };
+
+    let source = {
+        let buffer = buffer.read(cx);
+        language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx).selection_ranges
+    };
+    let use_language_server = match source {
+        RangeSource::Syntax => false,
+        RangeSource::LanguageServer => true,
+        // The servers of remote projects aren't known here. Their host answers with no
+        // ranges when the server lacks the capability, falling back to the syntax below.
+        RangeSource::Auto if !project.read(cx).is_local() => true,
+        RangeSource::Auto => project
+            .read(cx)
+            .language_servers_for_local_buffer(buffer.read(cx), cx)
+            .any(|(_, server)| {
+                !matches!(
+                    server.capabilities().selection_range_provider,
+                    None | Some(lsp::SelectionRangeProviderCapability::Simple(false))
+                )
+            }),
+    };
+    if !use_language_server {
+        return false;
+    }
+
+    let selection_ranges = project.update(cx, |project, cx| {
+        project.selection_ranges(&buffer, positions, cx)
+    });
+    editor.pending_lsp_range_expansions = 1;
+    cx.spawn(|editor, mut cx| async move {
+        let selection_ranges = selection_ranges.await.log_err().unwrap_or_default();
+        editor.update(&mut cx, |editor, cx| {
+            let expansions = mem::take(&mut editor.pending_lsp_range_expansions);
+
+            // Ignore the response if the selections moved while it was in flight.
+            let current_selections = editor.selections.all::<usize>(cx);
+            if current_selections
+                .iter()
+                .map(Selection::range)
+                .ne(old_selections.iter().map(Selection::range))
+            {
+                return;
+            }
+
+            let snapshot = editor.buffer.read(cx).snapshot(cx);
+            let mut stack = mem::take(&mut editor.select_larger_syntax_node_stack);
+            let mut selections = old_selections;
+            let mut expanded = 0;
+            while expanded < expansions {
+                let Some(larger_selections) =
+                    expand_selections(&selections, &selection_ranges, &snapshot)
+                else {
+                    break;
+                };
+                stack.push(mem::replace(&mut selections, larger_selections).into_boxed_slice());
+                expanded += 1;
+            }
+            if expanded > 0 {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select(selections);
+                });
+            }
+            editor.select_larger_syntax_node_stack = stack;
+
+            if source == RangeSource::Auto {
+                for _ in expanded..expansions {
+                    editor.select_larger_syntax_ancestor(cx);
+                }
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+
+    true
+}
+
+/// Expands each selection to the smallest of its selection ranges that is larger than it.
+///
+/// Returns `None` if none of the selections could be expanded.
+fn expand_selections(
+    selections: &[Selection<usize>],
+    selection_ranges: &[Vec<Range<text::Anchor>>],
+    snapshot: &MultiBufferSnapshot,
+) -> Option<Vec<Selection<usize>>> {
+    let mut selected_larger_range = false;
+    let larger_selections = selections
+        .iter()
+        .enumerate()
+        .map(|(ix, selection)| {
+            let old_range = selection.range();
+            let ranges = selection_ranges.get(ix).map_or(&[][..], Vec::as_slice);
+            let new_range = snapshot
+                .excerpt_containing(old_range.clone())
+                .and_then(|excerpt| {
+                    let buffer_range = excerpt.map_range_to_buffer(old_range.clone());
+                    ranges
+                        .iter()
+                        .map(|range| range.to_offset(excerpt.buffer()))
+                        .filter(|range| excerpt.contains_buffer_range(range.clone()))
+                        .find(|range| {
+                            range.start <= buffer_range.start
+                                && range.end >= buffer_range.end
+                                && *range != buffer_range
+                        })
+                        .map(|range| excerpt.map_range_from_buffer(range))
+                })
+                .unwrap_or(old_range);
+
+            selected_larger_range |= new_range != selection.range();
+            Selection {
+                id: selection.id,
+                start: new_range.start,
+                end: new_range.end,
+                goal: SelectionGoal::None,
+                reversed: selection.reversed,
+            }
+        })
+        .collect::<Vec<_>>();
+    selected_larger_range.then_some(larger_selections)
+}
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use collections::HashSet;
+use gpui::{HighlightStyle, ViewContext};
+use language::language_settings::{language_settings, SemanticTokensSettings};
+use project::{SemanticToken, SemanticTokens};
+use theme::{ActiveTheme, SyntaxTheme};
+
+use crate::{
+    lsp_refresh::{debounced_buffer_request, RefreshScope},
+    Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Requests semantic tokens for the buffers in `scope` that have them enabled,
+/// and layers the resulting styles over the tree-sitter highlights.
+pub(super) fn refresh_semantic_tokens(
+    editor: &mut Editor,
+    scope: RefreshScope,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.clone()?;
+
+    let buffers = scope
+        .buffers(editor, cx)
+        .into_iter()
+        .filter(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx)
+                .semantic_tokens
+                .enabled
+        })
+        .collect::<Vec<_>>();
+    let enabled_buffers = buffers
+        .iter()
+        .map(|buffer| buffer.read(cx).remote_id())
+        .collect::<HashSet<_>>();
+    editor
+        .semantic_tokens_tasks
+        .retain(|buffer_id, _| !scope.covers(*buffer_id) || enabled_buffers.contains(buffer_id));
+    let tokens_before = editor.semantic_tokens.len();
+    editor
+        .semantic_tokens
+        .retain(|buffer_id, _| !scope.covers(*buffer_id) || enabled_buffers.contains(buffer_id));
+    if editor.semantic_tokens.len() != tokens_before {
+        update_semantic_highlights(editor, cx);
+    }
+
+    for buffer in buffers {
+        let buffer_id = buffer.read(cx).remote_id();
+        let task = debounced_buffer_request(
+            project.clone(),
+            buffer,
+            UPDATE_DEBOUNCE,
+            |project, buffer, cx| project.semantic_tokens(buffer, cx),
+            move |editor, _, tokens, cx| {
+                editor.semantic_tokens.insert(buffer_id, tokens);
+                update_semantic_highlights(editor, cx);
+            },
+            cx,
+        );
+        editor.semantic_tokens_tasks.insert(buffer_id, task);
+    }
+
+    Some(())
+}
+
+fn update_semantic_highlights(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let syntax = cx.theme().syntax().clone();
+
+    let mut highlights_by_buffer = Vec::with_capacity(editor.semantic_tokens.len());
+    for (buffer_id, tokens) in &editor.semantic_tokens {
+        let Some(buffer) = multi_buffer.buffer(*buffer_id) else {
+            continue;
+        };
+        let buffer_snapshot = buffer.read(cx).text_snapshot();
+        let settings = language_settings(
+            buffer.read(cx).language().map(|l| l.name()),
+            buffer.read(cx).file(),
+            cx,
+        );
+
+        let mut highlights = Vec::new();
+        for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+            let context = excerpt_range.context;
+            let start_ix = tokens.tokens.partition_point(|token| {
+                token
+                    .range
+                    .end
+                    .cmp(&context.start, &buffer_snapshot)
+                    .is_le()
+            });
+            for token in &tokens.tokens[start_ix..] {
+                if token
+                    .range
+                    .start
+                    .cmp(&context.end, &buffer_snapshot)
+                    .is_ge()
+                {
+                    break;
+                }
+                let Some(style) = token_style(tokens, token, &settings.semantic_tokens, &syntax)
+                else {
+                    continue;
+                };
+                let start = snapshot.anchor_in_excerpt(excerpt_id, token.range.start);
+                let end = snapshot.anchor_in_excerpt(excerpt_id, token.range.end);
+                if let Some((start, end)) = start.zip(end) {
+                    highlights.push((start..end, style));
+                }
+            }
+        }
+        highlights_by_buffer.push((*buffer_id, highlights));
+    }
+
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.clear_semantic_highlights();
+        for (buffer_id, highlights) in highlights_by_buffer {
+            display_map.set_semantic_highlights(buffer_id, highlights);
+        }
+    });
+    cx.notify();
+}
+
+/// Looks up the theme style for a token, falling back from `function.method` to
+/// `function` when the theme lacks the more specific name.
+fn token_style(
+    tokens: &SemanticTokens,
+    token: &SemanticToken,
+    settings: &SemanticTokensSettings,
+    syntax: &SyntaxTheme,
+) -> Option<HighlightStyle> {
+    let mut name =
+        settings.highlight_name(tokens.token_type(token)?, tokens.token_modifiers(token))?;
+    loop {
+        if syntax.highlight_id(name).is_some() {
+            return Some(syntax.get(name));
+        }
+        name = &name[..name.rfind('.')?];
+    }
+}
@@ -1,7 +1,10 @@
-use crate::Oid;
+use crate::repository::RepoPath;
+use crate::{parse_git_remote_url, BuildCommitPermalinkParams, GitHostingProviderRegistry, Oid};
 use anyhow::{anyhow, Result};
 use collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
+use url::Url;
 
 pub fn get_messages(working_directory: &Path, shas: &[Oid]) -> Result<HashMap<Oid, String>> {
     if shas.is_empty() {
@@ -36,3 +39,65 @@ pub fn get_messages(working_directory: &Path, shas: &[Oid]) -> Result<HashMap<Oi
         )
         .collect::<HashMap<Oid, String>>())
 }
+
+/// A commit, as listed in the repository's history.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub sha: Oid,
+    pub author_name: String,
+    pub author_email: String,
+    /// Time at which the commit was authored, normalized to Unix Epoch format.
+    pub author_time: i64,
+    /// Offset of the author's timezone from UTC, in minutes.
+    pub author_offset: i32,
+    pub message: String,
+}
+
+impl CommitSummary {
+    pub fn author_offset_date_time(&self) -> Result<time::OffsetDateTime> {
+        let offset = time::UtcOffset::from_whole_seconds(self.author_offset * 60)?;
+        let date_time_utc = time::OffsetDateTime::from_unix_timestamp(self.author_time)?;
+        Ok(date_time_utc.to_offset(offset))
+    }
+
+    /// Returns the first line of the commit message.
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// A page of a repository's history, along with the remote used to build links to it.
+#[derive(Clone, Debug, Default)]
+pub struct CommitLog {
+    pub commits: Vec<CommitSummary>,
+    pub remote_url: Option<String>,
+}
+
+impl CommitLog {
+    /// Returns a link to the given commit on the repository's hosting provider, if it has one.
+    pub fn permalink(
+        &self,
+        sha: &Oid,
+        provider_registry: Arc<GitHostingProviderRegistry>,
+    ) -> Option<Url> {
+        let (provider, remote) =
+            parse_git_remote_url(provider_registry, self.remote_url.as_deref()?)?;
+        Some(provider.build_commit_permalink(
+            &remote,
+            BuildCommitPermalinkParams {
+                sha: sha.to_string().as_str(),
+            },
+        ))
+    }
+}
+
+/// A file changed by a commit, with its contents before and after the commit.
+///
+/// The old text is `None` for files added by the commit, and the new text is `None`
+/// for files it deleted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitFileChange {
+    pub path: RepoPath,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+}
@@ -4,7 +4,7 @@ use sum_tree::SumTree;
 use text::{Anchor, BufferSnapshot, OffsetRangeExt, Point};
 
 pub use git2 as libgit;
-use libgit::{DiffLineType as GitDiffLineType, DiffOptions as GitOptions, Patch as GitPatch};
+use libgit::{DiffOptions as GitOptions, Patch as GitPatch};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DiffHunkStatus {
@@ -81,9 +81,9 @@ impl BufferDiff {
         let patch = Self::diff(diff_base, &buffer_text);
 
         if let Some(patch) = patch {
-            let mut divergence = 0;
+            let diff_base = Rope::from(diff_base);
             for hunk_index in 0..patch.num_hunks() {
-                let hunk = Self::process_patch_hunk(&patch, hunk_index, buffer, &mut divergence);
+                let hunk = Self::process_patch_hunk(&patch, hunk_index, &diff_base, buffer);
                 tree.push(hunk, buffer);
             }
         }
@@ -91,6 +91,47 @@ impl BufferDiff {
         Self { tree }
     }
 
+    /// Builds a diff of the changes staged in the index relative to `head_text`.
+    ///
+    /// Each hunk is positioned in the buffer by mapping its rows in the index through
+    /// `unstaged_diff`, the diff between the index and the buffer. The resulting hunks'
+    /// diff base ranges refer to `head_text`.
+    pub async fn build_staged(
+        head_text: &str,
+        index_text: &Rope,
+        unstaged_diff: &BufferDiff,
+        buffer: &text::BufferSnapshot,
+    ) -> Self {
+        let mut tree = SumTree::new(buffer);
+
+        let index_string = index_text.to_string();
+        let patch = Self::diff(head_text, &index_string);
+
+        if let Some(patch) = patch {
+            let head_text = Rope::from(head_text);
+            let unstaged_rows = unstaged_diff
+                .row_changes(index_text, buffer)
+                .map(|(buffer_rows, index_rows)| (index_rows, buffer_rows))
+                .collect::<Vec<_>>();
+            for hunk_index in 0..patch.num_hunks() {
+                let (index_rows, _, diff_base_byte_range) =
+                    Self::patch_hunk_ranges(&patch, hunk_index, &head_text);
+                let start = map_row(index_rows.start, &unstaged_rows, false);
+                let end = map_row(index_rows.end, &unstaged_rows, true).max(start);
+                tree.push(
+                    InternalDiffHunk {
+                        buffer_range: buffer.anchor_before(Point::new(start, 0))
+                            ..buffer.anchor_before(Point::new(end, 0)),
+                        diff_base_byte_range,
+                    },
+                    buffer,
+                );
+            }
+        }
+
+        Self { tree }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.tree.is_empty()
     }
@@ -181,6 +222,127 @@ impl BufferDiff {
         })
     }
 
+    /// Returns the diff base with the buffer's changes in the given rows applied to it.
+    ///
+    /// This is the index text that results from staging those rows. Hunks that only
+    /// partially intersect `rows` are split line by line when they replace the same
+    /// number of lines. Otherwise, their removed lines are replaced by the selected
+    /// added lines.
+    pub fn stage_rows(
+        &self,
+        diff_base: &Rope,
+        buffer: &text::BufferSnapshot,
+        rows: &[Range<u32>],
+    ) -> String {
+        let rows = normalize_rows(rows.iter().cloned());
+        let buffer_text = |rows: Range<u32>| {
+            buffer
+                .as_rope()
+                .slice(rows_to_offsets(buffer.as_rope(), rows))
+                .to_string()
+        };
+        let edits = self
+            .row_changes(diff_base, buffer)
+            .flat_map(|(buffer_rows, base_rows)| {
+                let pieces = intersect_change(&buffer_rows, &base_rows, &rows);
+                if pieces.is_empty() {
+                    Vec::new()
+                } else if buffer_rows.len() == base_rows.len() {
+                    pieces
+                        .into_iter()
+                        .map(|(buffer_rows, base_rows)| {
+                            (
+                                rows_to_offsets(diff_base, base_rows),
+                                buffer_text(buffer_rows),
+                            )
+                        })
+                        .collect()
+                } else {
+                    let new_text = pieces
+                        .into_iter()
+                        .map(|(buffer_rows, _)| buffer_text(buffer_rows))
+                        .collect();
+                    vec![(rows_to_offsets(diff_base, base_rows), new_text)]
+                }
+            })
+            .collect::<Vec<_>>();
+        splice(diff_base, edits)
+    }
+
+    /// Returns `index_text` with the staged changes that touch the given buffer rows
+    /// reverted to their contents in `head_text`.
+    ///
+    /// `self` must be the diff between `index_text` and the buffer; it is used to locate
+    /// the buffer rows in the index.
+    pub fn unstage_rows(
+        &self,
+        head_text: &Rope,
+        index_text: &Rope,
+        buffer: &text::BufferSnapshot,
+        rows: &[Range<u32>],
+    ) -> String {
+        let unstaged_rows = self.row_changes(index_text, buffer).collect::<Vec<_>>();
+        let rows = normalize_rows(rows.iter().map(|rows| {
+            let start = map_row(rows.start, &unstaged_rows, false);
+            let end = map_row(rows.end, &unstaged_rows, true).max(start);
+            start..end
+        }));
+
+        let head_string = head_text.to_string();
+        let index_string = index_text.to_string();
+        let Some(patch) = Self::diff(&head_string, &index_string) else {
+            return index_string;
+        };
+        let head_text_for_rows = |rows: Range<u32>| {
+            head_text
+                .slice(rows_to_offsets(head_text, rows))
+                .to_string()
+        };
+        let edits = (0..patch.num_hunks())
+            .flat_map(|hunk_index| {
+                let (index_rows, head_rows, _) =
+                    Self::patch_hunk_ranges(&patch, hunk_index, head_text);
+                let pieces = intersect_change(&index_rows, &head_rows, &rows);
+                if pieces.is_empty() {
+                    Vec::new()
+                } else if index_rows.len() == head_rows.len() {
+                    pieces
+                        .into_iter()
+                        .map(|(index_rows, head_rows)| {
+                            (
+                                rows_to_offsets(index_text, index_rows),
+                                head_text_for_rows(head_rows),
+                            )
+                        })
+                        .collect()
+                } else {
+                    vec![(
+                        rows_to_offsets(index_text, index_rows),
+                        head_text_for_rows(head_rows),
+                    )]
+                }
+            })
+            .collect::<Vec<_>>();
+        splice(index_text, edits)
+    }
+
+    /// Returns the buffer rows and diff base rows spanned by each hunk.
+    fn row_changes<'a>(
+        &'a self,
+        diff_base: &'a Rope,
+        buffer: &'a text::BufferSnapshot,
+    ) -> impl 'a + Iterator<Item = (Range<u32>, Range<u32>)> {
+        let start = buffer.anchor_before(Point::new(0, 0));
+        let end = buffer.anchor_after(Point::new(u32::MAX, u32::MAX));
+        self.hunks_intersecting_range(start..end, buffer)
+            .map(move |hunk| {
+                let start = diff_base.offset_to_point(hunk.diff_base_byte_range.start);
+                let end = diff_base.offset_to_point(hunk.diff_base_byte_range.end);
+                let end_row = if end.column > 0 { end.row + 1 } else { end.row };
+                (hunk.row_range, start.row..end_row)
+            })
+    }
+
     #[cfg(test)]
     fn clear(&mut self, buffer: &text::BufferSnapshot) {
         self.tree = SumTree::new(buffer);
@@ -222,59 +384,11 @@ impl BufferDiff {
     fn process_patch_hunk(
         patch: &GitPatch<'_>,
         hunk_index: usize,
+        diff_base: &Rope,
         buffer: &text::BufferSnapshot,
-        buffer_row_divergence: &mut i64,
     ) -> InternalDiffHunk {
-        let line_item_count = patch.num_lines_in_hunk(hunk_index).unwrap();
-        assert!(line_item_count > 0);
-
-        let mut first_deletion_buffer_row: Option<u32> = None;
-        let mut buffer_row_range: Option<Range<u32>> = None;
-        let mut diff_base_byte_range: Option<Range<usize>> = None;
-
-        for line_index in 0..line_item_count {
-            let line = patch.line_in_hunk(hunk_index, line_index).unwrap();
-            let kind = line.origin_value();
-            let content_offset = line.content_offset() as isize;
-            let content_len = line.content().len() as isize;
-
-            if kind == GitDiffLineType::Addition {
-                *buffer_row_divergence += 1;
-                let row = line.new_lineno().unwrap().saturating_sub(1);
-
-                match &mut buffer_row_range {
-                    Some(buffer_row_range) => buffer_row_range.end = row + 1,
-                    None => buffer_row_range = Some(row..row + 1),
-                }
-            }
-
-            if kind == GitDiffLineType::Deletion {
-                let end = content_offset + content_len;
-
-                match &mut diff_base_byte_range {
-                    Some(head_byte_range) => head_byte_range.end = end as usize,
-                    None => diff_base_byte_range = Some(content_offset as usize..end as usize),
-                }
-
-                if first_deletion_buffer_row.is_none() {
-                    let old_row = line.old_lineno().unwrap().saturating_sub(1);
-                    let row = old_row as i64 + *buffer_row_divergence;
-                    first_deletion_buffer_row = Some(row as u32);
-                }
-
-                *buffer_row_divergence -= 1;
-            }
-        }
-
-        //unwrap_or deletion without addition
-        let buffer_row_range = buffer_row_range.unwrap_or_else(|| {
-            //we cannot have an addition-less hunk without deletion(s) or else there would be no hunk
-            let row = first_deletion_buffer_row.unwrap();
-            row..row
-        });
-
-        //unwrap_or addition without deletion
-        let diff_base_byte_range = diff_base_byte_range.unwrap_or(0..0);
+        let (buffer_row_range, _, diff_base_byte_range) =
+            Self::patch_hunk_ranges(patch, hunk_index, diff_base);
 
         let start = Point::new(buffer_row_range.start, 0);
         let end = Point::new(buffer_row_range.end, 0);
@@ -284,6 +398,121 @@ impl BufferDiff {
             diff_base_byte_range,
         }
     }
+
+    /// Returns the new rows, old rows and old byte range spanned by a patch hunk.
+    ///
+    /// Hunks that only add lines have an empty old range positioned where the lines were
+    /// inserted, and hunks that only remove lines have an empty new range positioned where
+    /// the lines were removed.
+    fn patch_hunk_ranges(
+        patch: &GitPatch<'_>,
+        hunk_index: usize,
+        old_text: &Rope,
+    ) -> (Range<u32>, Range<u32>, Range<usize>) {
+        let (hunk, _) = patch.hunk(hunk_index).unwrap();
+
+        // Line numbers are 1-based, except that an empty side refers to the line
+        // *preceding* the change (or 0 when the change is at the start of the file).
+        let rows = |start: u32, lines: u32| {
+            if lines == 0 {
+                start..start
+            } else {
+                start - 1..start - 1 + lines
+            }
+        };
+        let new_rows = rows(hunk.new_start(), hunk.new_lines());
+        let old_rows = rows(hunk.old_start(), hunk.old_lines());
+        let old_byte_range = rows_to_offsets(old_text, old_rows.clone());
+        (new_rows, old_rows, old_byte_range)
+    }
+}
+
+fn rows_to_offsets(text: &Rope, rows: Range<u32>) -> Range<usize> {
+    text.point_to_offset(Point::new(rows.start, 0))..text.point_to_offset(Point::new(rows.end, 0))
+}
+
+/// Maps a row boundary through a list of sorted, non-overlapping changes, each given as
+/// the rows it spans before and after the change.
+///
+/// Rows inside a change map to the start of its new rows, or to their end if `bias_end`
+/// is set. The same applies to a row at which lines were only inserted.
+fn map_row(row: u32, changes: &[(Range<u32>, Range<u32>)], bias_end: bool) -> u32 {
+    let mut delta = 0i64;
+    for (old_rows, new_rows) in changes {
+        if row < old_rows.start || (row == old_rows.start && !(old_rows.is_empty() && bias_end)) {
+            break;
+        } else if row < old_rows.end {
+            return if bias_end {
+                new_rows.end
+            } else {
+                new_rows.start
+            };
+        }
+        delta = new_rows.end as i64 - old_rows.end as i64;
+    }
+    (row as i64 + delta) as u32
+}
+
+/// Sorts the given row ranges and merges the ones that overlap or touch.
+fn normalize_rows(rows: impl IntoIterator<Item = Range<u32>>) -> Vec<Range<u32>> {
+    let mut rows = rows.into_iter().collect::<Vec<_>>();
+    rows.sort_unstable_by_key(|rows| rows.start);
+    let mut result: Vec<Range<u32>> = Vec::with_capacity(rows.len());
+    for rows in rows {
+        match result.last_mut() {
+            Some(last) if rows.start <= last.end => last.end = last.end.max(rows.end),
+            _ => result.push(rows),
+        }
+    }
+    result
+}
+
+/// Restricts a change to the given sorted, non-overlapping rows on its new side.
+///
+/// Changes that remove lines without adding any are included when they touch `rows`.
+/// When both sides of a change span the same number of lines, the old side is narrowed
+/// line by line along with the new side. Otherwise, each piece keeps the whole old side.
+fn intersect_change(
+    new_rows: &Range<u32>,
+    old_rows: &Range<u32>,
+    rows: &[Range<u32>],
+) -> Vec<(Range<u32>, Range<u32>)> {
+    if new_rows.is_empty() {
+        return rows
+            .iter()
+            .find(|rows| rows.start <= new_rows.start && new_rows.start <= rows.end)
+            .map(|_| (new_rows.clone(), old_rows.clone()))
+            .into_iter()
+            .collect();
+    }
+
+    rows.iter()
+        .filter_map(|rows| {
+            let start = new_rows.start.max(rows.start);
+            let end = new_rows.end.min(rows.end);
+            if start >= end {
+                None
+            } else if new_rows.len() == old_rows.len() {
+                let old_start = old_rows.start + (start - new_rows.start);
+                Some((start..end, old_start..old_start + (end - start)))
+            } else {
+                Some((start..end, old_rows.clone()))
+            }
+        })
+        .collect()
+}
+
+/// Replaces the given sorted, non-overlapping byte ranges of `text`.
+fn splice(text: &Rope, edits: impl IntoIterator<Item = (Range<usize>, String)>) -> String {
+    let mut result = String::new();
+    let mut last_end = 0;
+    for (range, new_text) in edits {
+        result.extend(text.chunks_in_range(last_end..range.start));
+        result.push_str(&new_text);
+        last_end = range.end;
+    }
+    result.extend(text.chunks_in_range(last_end..text.len()));
+    result
 }
 
 /// Range (crossing new lines), old, new
@@ -421,4 +650,77 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_stage_rows() {
+        let diff_base = Rope::from("one\ntwo\nthree\nfour\n");
+        let buffer = Buffer::new(
+            0,
+            BufferId::new(1).unwrap(),
+            "one\nTWO\nthree\nFOUR\nfive\n".to_string(),
+        );
+        let mut diff = BufferDiff::new(&buffer);
+        smol::block_on(diff.update(&diff_base, &buffer));
+
+        assert_eq!(
+            diff.stage_rows(&diff_base, &buffer, &[1..2]),
+            "one\nTWO\nthree\nfour\n"
+        );
+        // The second hunk replaces one line with two, so it can't be split by line.
+        assert_eq!(
+            diff.stage_rows(&diff_base, &buffer, &[3..4]),
+            "one\ntwo\nthree\nFOUR\n"
+        );
+        assert_eq!(diff.stage_rows(&diff_base, &buffer, &[0..5]), buffer.text());
+        assert_eq!(
+            diff.stage_rows(&diff_base, &buffer, &[4..5, 1..2]),
+            "one\nTWO\nthree\nfive\n"
+        );
+
+        let diff_base = Rope::from("a\nb\nc\n");
+        let buffer = Buffer::new(0, BufferId::new(2).unwrap(), "A\nB\nc\n".to_string());
+        let mut diff = BufferDiff::new(&buffer);
+        smol::block_on(diff.update(&diff_base, &buffer));
+        assert_eq!(diff.stage_rows(&diff_base, &buffer, &[1..2]), "a\nB\nc\n");
+    }
+
+    #[test]
+    fn test_staged_diff_and_unstage_rows() {
+        let head_text = Rope::from("one\ntwo\nthree\n");
+        let index_text = Rope::from("one\nTWO\nthree\nfour\n");
+        let buffer = Buffer::new(
+            0,
+            BufferId::new(1).unwrap(),
+            "zero\none\nTWO\nthree\nfour\n".to_string(),
+        );
+        let mut unstaged_diff = BufferDiff::new(&buffer);
+        smol::block_on(unstaged_diff.update(&index_text, &buffer));
+
+        let staged_diff = smol::block_on(BufferDiff::build_staged(
+            &head_text.to_string(),
+            &index_text,
+            &unstaged_diff,
+            &buffer,
+        ));
+        assert_hunks(
+            staged_diff.hunks(&buffer),
+            &buffer,
+            &head_text.to_string(),
+            &[(2..3, "two\n", "TWO\n"), (4..5, "", "four\n")],
+        );
+
+        assert_eq!(
+            unstaged_diff.unstage_rows(&head_text, &index_text, &buffer, &[2..3]),
+            "one\ntwo\nthree\nfour\n"
+        );
+        assert_eq!(
+            unstaged_diff.unstage_rows(&head_text, &index_text, &buffer, &[4..5]),
+            "one\nTWO\nthree\n"
+        );
+        // The unstaged line isn't in the index, so there is nothing to unstage.
+        assert_eq!(
+            unstaged_diff.unstage_rows(&head_text, &index_text, &buffer, &[0..1]),
+            index_text.to_string()
+        );
+    }
 }
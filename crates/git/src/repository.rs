@@ -1,5 +1,6 @@
-use crate::GitHostingProviderRegistry;
+use crate::commit::{CommitFileChange, CommitSummary};
 use crate::{blame::Blame, status::GitStatus};
+use crate::{GitHostingProviderRegistry, Oid};
 use anyhow::{Context, Result};
 use collections::{HashMap, HashSet};
 use git2::BranchType;
@@ -10,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     path::{Component, Path, PathBuf},
+    process::Stdio,
     sync::Arc,
 };
 use sum_tree::MapSeekTarget;
@@ -23,6 +25,47 @@ pub struct Branch {
     pub unix_timestamp: Option<i64>,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The position of the entry in the stash, where 0 is the most recently stashed.
+    pub index: usize,
+    pub message: String,
+}
+
+/// How a pull integrates the commits of the upstream branch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PullStrategy {
+    /// Only update the branch if it can be fast-forwarded to its upstream.
+    FastForwardOnly,
+    /// Replay local commits on top of the upstream branch.
+    Rebase,
+}
+
+/// The text that a buffer's changes are computed against.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub enum DiffBase {
+    /// The file's contents in the index, so that the diff shows the unstaged changes.
+    #[default]
+    Index,
+    /// The file's contents as of a revision, such as `HEAD`, a branch, a tag or a commit SHA.
+    Revision(SharedString),
+}
+
+impl DiffBase {
+    pub fn is_index(&self) -> bool {
+        matches!(self, Self::Index)
+    }
+}
+
+impl std::fmt::Display for DiffBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index => write!(f, "Index"),
+            Self::Revision(revision) => write!(f, "{revision}"),
+        }
+    }
+}
+
 pub trait GitRepository: Send + Sync {
     fn reload_index(&self);
 
@@ -30,6 +73,27 @@ pub trait GitRepository: Send + Sync {
     /// Note that for symlink entries, this will return the contents of the symlink, not the target.
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads the contents of a file as of the HEAD commit.
+    /// Note that for symlink entries, this will return `None`.
+    fn load_committed_text(&self, relative_file_path: &Path) -> Option<String>;
+
+    /// Loads the contents of a file as of the given revision, which may be any expression
+    /// understood by `git rev-parse`, such as a branch, a tag or a commit SHA.
+    ///
+    /// Returns `None` if the file doesn't exist in that revision, or is a symlink.
+    fn load_revision_text(
+        &self,
+        revision: &str,
+        relative_file_path: &Path,
+    ) -> Result<Option<String>>;
+
+    /// Returns the paths whose contents in the worktree differ from the given revision,
+    /// including untracked files.
+    fn paths_changed_since(&self, revision: &str) -> Result<Vec<RepoPath>>;
+
+    /// Replaces the contents of a path in the index, removing it from the index if `content` is `None`.
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
@@ -48,6 +112,55 @@ pub trait GitRepository: Send + Sync {
 
     /// Returns the path to the repository, typically the `.git` folder.
     fn dot_git_dir(&self) -> PathBuf;
+
+    /// Returns the paths whose index entries differ from HEAD.
+    fn staged_paths(&self) -> Result<HashSet<RepoPath>>;
+
+    /// Updates the index to match the worktree at the given paths.
+    ///
+    /// If any of the paths have been deleted from the worktree, they will be removed from the index if found there.
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Updates the index to match HEAD at the given paths.
+    ///
+    /// If any of the paths were previously staged but do not exist in HEAD, they will be removed from the index.
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Overwrites the given paths in the worktree with their contents in the index,
+    /// discarding any unstaged changes.
+    fn checkout_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Creates a commit on top of HEAD from the current contents of the index.
+    fn commit(&self, message: &str) -> Result<()>;
+
+    /// Returns up to `limit` commits reachable from HEAD, newest first, after skipping the first `skip`.
+    ///
+    /// If a path is given, only the commits that changed it are returned.
+    fn log(&self, path: Option<&RepoPath>, skip: usize, limit: usize)
+        -> Result<Vec<CommitSummary>>;
+
+    /// Returns the files changed by the given commit, relative to its first parent.
+    fn commit_changes(&self, sha: Oid) -> Result<Vec<CommitFileChange>>;
+
+    /// Returns the entries of the stash, most recent first.
+    fn stash_entries(&self) -> Result<Vec<StashEntry>>;
+
+    /// Saves the uncommitted changes, including untracked files, to a new stash entry
+    /// and reverts the worktree to HEAD.
+    fn stash_push(&self, message: Option<&str>) -> Result<()>;
+
+    /// Applies the stash entry at the given index to the worktree and removes it from the stash.
+    fn stash_pop(&self, index: usize) -> Result<()>;
+
+    /// Downloads objects and refs from the remote tracked by the current branch.
+    fn fetch(&self) -> Result<()>;
+
+    /// Fetches the upstream of the current branch and integrates it into the branch.
+    fn pull(&self, strategy: PullStrategy) -> Result<()>;
+
+    /// Pushes the current branch, setting its upstream to the branch of the same name on
+    /// `origin` if it doesn't have one yet.
+    fn push(&self) -> Result<()>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -74,10 +187,34 @@ impl RealGitRepository {
             hosting_provider_registry,
         }
     }
+
+    /// Runs a git command in the working directory, failing instead of prompting
+    /// for credentials.
+    fn run_git_command(&self, args: &[&str]) -> Result<String> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        let output = util::command::new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("failed to start git {}", args.join(" ")))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git {} failed: {}", args.join(" "), stderr.trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
 }
 
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
 const GIT_MODE_SYMLINK: u32 = 0o120000;
+const GIT_MODE_REGULAR: u32 = 0o100644;
 
 impl GitRepository for RealGitRepository {
     fn reload_index(&self) {
@@ -115,6 +252,109 @@ impl GitRepository for RealGitRepository {
         None
     }
 
+    fn load_committed_text(&self, relative_file_path: &Path) -> Option<String> {
+        fn logic(repo: &git2::Repository, relative_file_path: &Path) -> Result<Option<String>> {
+            check_path_to_repo_path_errors(relative_file_path)?;
+
+            let head_tree = match repo.head() {
+                Ok(head) => head.peel_to_tree()?,
+                Err(error) if error.code() == git2::ErrorCode::UnbornBranch => return Ok(None),
+                Err(error) => return Err(error.into()),
+            };
+            let entry = match head_tree.get_path(relative_file_path) {
+                Ok(entry) if entry.filemode() as u32 != GIT_MODE_SYMLINK => entry,
+                Ok(_) => return Ok(None),
+                Err(error) if error.code() == git2::ErrorCode::NotFound => return Ok(None),
+                Err(error) => return Err(error.into()),
+            };
+
+            let content = repo.find_blob(entry.id())?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        }
+
+        match logic(&self.repository.lock(), relative_file_path) {
+            Ok(value) => return value,
+            Err(err) => log::error!("Error loading committed text: {:?}", err),
+        }
+        None
+    }
+
+    fn load_revision_text(
+        &self,
+        revision: &str,
+        relative_file_path: &Path,
+    ) -> Result<Option<String>> {
+        check_path_to_repo_path_errors(relative_file_path)?;
+
+        let repo = self.repository.lock();
+        let tree = repo
+            .revparse_single(revision)
+            .and_then(|object| object.peel_to_tree())
+            .with_context(|| format!("failed to resolve revision {revision:?}"))?;
+        let entry = match tree.get_path(relative_file_path) {
+            Ok(entry) if entry.filemode() as u32 != GIT_MODE_SYMLINK => entry,
+            Ok(_) => return Ok(None),
+            Err(error) if error.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        let content = repo.find_blob(entry.id())?.content().to_owned();
+        Ok(Some(String::from_utf8(content)?))
+    }
+
+    fn paths_changed_since(&self, revision: &str) -> Result<Vec<RepoPath>> {
+        let repo = self.repository.lock();
+        let tree = repo
+            .revparse_single(revision)
+            .and_then(|object| object.peel_to_tree())
+            .with_context(|| format!("failed to resolve revision {revision:?}"))?;
+        let mut options = git2::DiffOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .ignore_submodules(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+            .map(RepoPath::from)
+            .collect())
+    }
+
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        check_path_to_repo_path_errors(path)?;
+        let repo = self.repository.lock();
+        let mut index = repo.index()?;
+        match content {
+            Some(content) => {
+                const STAGE_NORMAL: i32 = 0;
+                let mode = index
+                    .get_path(path, STAGE_NORMAL)
+                    .map_or(GIT_MODE_REGULAR, |entry| entry.mode);
+                // Zeroed stat information makes git re-check the worktree file
+                // against this entry rather than trusting its cached timestamps.
+                let entry = git2::IndexEntry {
+                    ctime: git2::IndexTime::new(0, 0),
+                    mtime: git2::IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode,
+                    uid: 0,
+                    gid: 0,
+                    file_size: content.len() as u32,
+                    id: git2::Oid::zero(),
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path.to_string_lossy().replace('\\', "/").into_bytes(),
+                };
+                index.add_frombuffer(&entry, content.as_bytes())?;
+            }
+            None => index.remove_path(path)?,
+        }
+        index.write()?;
+        Ok(())
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
@@ -225,6 +465,287 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn staged_paths(&self) -> Result<HashSet<RepoPath>> {
+        let repo = self.repository.lock();
+        let mut options = git2::StatusOptions::new();
+        options
+            .show(git2::StatusShow::Index)
+            .include_untracked(false)
+            .include_ignored(false);
+        let statuses = repo.statuses(Some(&mut options))?;
+        Ok(statuses
+            .iter()
+            .filter_map(|entry| Some(RepoPath::new(PathBuf::from(entry.path()?))))
+            .collect())
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        let working_directory = repo
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        let mut index = repo.index()?;
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            if working_directory.join(path).symlink_metadata().is_ok() {
+                index.add_path(path)?;
+            } else {
+                index.remove_path(path)?;
+            }
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+        }
+        match repo.head() {
+            Ok(head) => {
+                let head_commit = head.peel_to_commit()?;
+                repo.reset_default(
+                    Some(head_commit.as_object()),
+                    paths.iter().map(|path| path.as_path()),
+                )?;
+            }
+            // Nothing has been committed yet, so unstaging means dropping the paths from the index.
+            Err(error) if error.code() == git2::ErrorCode::UnbornBranch => {
+                let mut index = repo.index()?;
+                for path in paths {
+                    index.remove_path(path)?;
+                }
+                index.write()?;
+            }
+            Err(error) => return Err(error.into()),
+        }
+        Ok(())
+    }
+
+    fn checkout_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let repo = self.repository.lock();
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.force().disable_pathspec_match(true);
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            checkout.path(path.as_path());
+        }
+        repo.checkout_index(None, Some(&mut checkout))?;
+        Ok(())
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        let repo = self.repository.lock();
+        let signature = repo
+            .signature()
+            .context("failed to determine the commit author, is `user.name` configured?")?;
+        let tree_id = repo.index()?.write_tree()?;
+        let tree = repo.find_tree(tree_id)?;
+        let parent = match repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(error) if error.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(error) => return Err(error.into()),
+        };
+        if parent
+            .as_ref()
+            .map_or(tree.is_empty(), |parent| parent.tree_id() == tree_id)
+        {
+            anyhow::bail!("nothing to commit");
+        }
+        let parents = parent.iter().collect::<Vec<_>>();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?;
+        Ok(())
+    }
+
+    fn log(
+        &self,
+        path: Option<&RepoPath>,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<CommitSummary>> {
+        let repo = self.repository.lock();
+        let mut revwalk = repo.revwalk()?;
+        match revwalk.push_head() {
+            Ok(()) => {}
+            Err(error) if error.code() == git2::ErrorCode::UnbornBranch => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        }
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+        let mut commits = Vec::new();
+        let mut skipped = 0;
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            if let Some(path) = path {
+                if !commit_changed_path(&commit, path)? {
+                    continue;
+                }
+            }
+            if skipped < skip {
+                skipped += 1;
+                continue;
+            }
+
+            let author = commit.author();
+            commits.push(CommitSummary {
+                sha: Oid(commit.id()),
+                author_name: author.name().unwrap_or_default().to_string(),
+                author_email: author.email().unwrap_or_default().to_string(),
+                author_time: author.when().seconds(),
+                author_offset: author.when().offset_minutes(),
+                message: String::from_utf8_lossy(commit.message_bytes()).into_owned(),
+            });
+            if commits.len() >= limit {
+                break;
+            }
+        }
+        Ok(commits)
+    }
+
+    fn commit_changes(&self, sha: Oid) -> Result<Vec<CommitFileChange>> {
+        let repo = self.repository.lock();
+        let commit = repo.find_commit(sha.0)?;
+        let new_tree = commit.tree()?;
+        let old_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
+
+        let load_blob = |id: git2::Oid| -> Result<Option<git2::Blob>> {
+            if id.is_zero() {
+                Ok(None)
+            } else {
+                Ok(Some(repo.find_blob(id)?))
+            }
+        };
+        let blob_text = |blob: git2::Blob| String::from_utf8_lossy(blob.content()).into_owned();
+
+        let mut changes = Vec::new();
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            // Submodules have no contents to display.
+            if delta.old_file().mode() == git2::FileMode::Commit
+                || delta.new_file().mode() == git2::FileMode::Commit
+            {
+                continue;
+            }
+            let old_blob = load_blob(delta.old_file().id())?;
+            let new_blob = load_blob(delta.new_file().id())?;
+            if old_blob
+                .iter()
+                .chain(&new_blob)
+                .any(|blob| blob.is_binary())
+            {
+                continue;
+            }
+            changes.push(CommitFileChange {
+                path: RepoPath::from(path),
+                old_text: old_blob.map(blob_text),
+                new_text: new_blob.map(blob_text),
+            });
+        }
+        Ok(changes)
+    }
+
+    fn stash_entries(&self) -> Result<Vec<StashEntry>> {
+        let mut repo = self.repository.lock();
+        let mut entries = Vec::new();
+        repo.stash_foreach(|index, message, _| {
+            entries.push(StashEntry {
+                index,
+                message: message.to_string(),
+            });
+            true
+        })?;
+        Ok(entries)
+    }
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()> {
+        let mut repo = self.repository.lock();
+        let signature = repo
+            .signature()
+            .context("failed to determine the stash author, is `user.name` configured?")?;
+        repo.stash_save2(
+            &signature,
+            message,
+            Some(git2::StashFlags::INCLUDE_UNTRACKED),
+        )
+        .map_err(|error| match error.code() {
+            git2::ErrorCode::NotFound => anyhow::anyhow!("no local changes to stash"),
+            _ => error.into(),
+        })?;
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize) -> Result<()> {
+        let mut repo = self.repository.lock();
+        repo.stash_pop(index, None)?;
+        Ok(())
+    }
+
+    fn fetch(&self) -> Result<()> {
+        self.run_git_command(&["fetch"])?;
+        Ok(())
+    }
+
+    fn pull(&self, strategy: PullStrategy) -> Result<()> {
+        let strategy_arg = match strategy {
+            PullStrategy::FastForwardOnly => "--ff-only",
+            PullStrategy::Rebase => "--rebase",
+        };
+        self.run_git_command(&["pull", strategy_arg])?;
+        Ok(())
+    }
+
+    fn push(&self) -> Result<()> {
+        let (branch_name, has_upstream) = {
+            let repo = self.repository.lock();
+            let head = repo.head()?;
+            anyhow::ensure!(head.is_branch(), "cannot push a detached HEAD");
+            let branch_name = head
+                .shorthand()
+                .context("current branch name is not valid UTF-8")?
+                .to_string();
+            let has_upstream = repo
+                .find_branch(&branch_name, BranchType::Local)?
+                .upstream()
+                .is_ok();
+            (branch_name, has_upstream)
+        };
+        if has_upstream {
+            self.run_git_command(&["push"])?;
+        } else {
+            self.run_git_command(&["push", "--set-upstream", "origin", &branch_name])?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the given commit changed the file at `path`, compared to its first parent.
+fn commit_changed_path(commit: &git2::Commit, path: &Path) -> Result<bool> {
+    let entry_id = |tree: git2::Tree| tree.get_path(path).ok().map(|entry| entry.id());
+    let new_id = entry_id(commit.tree()?);
+    let old_id = match commit.parents().next() {
+        Some(parent) => entry_id(parent.tree()?),
+        None => None,
+    };
+    Ok(new_id != old_id)
 }
 
 #[derive(Debug, Clone)]
@@ -237,10 +758,21 @@ pub struct FakeGitRepositoryState {
     pub dot_git_dir: PathBuf,
     pub event_emitter: smol::channel::Sender<PathBuf>,
     pub index_contents: HashMap<PathBuf, String>,
+    pub head_contents: HashMap<PathBuf, String>,
+    /// File contents by revision, for revisions other than HEAD.
+    pub revision_contents: HashMap<String, HashMap<PathBuf, String>>,
+    pub staged_paths: HashSet<RepoPath>,
+    pub commit_messages: Vec<String>,
+    /// The repository's history, newest first.
+    pub commits: Vec<(CommitSummary, Vec<CommitFileChange>)>,
     pub blames: HashMap<PathBuf, Blame>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
+    /// Stashed messages and worktree statuses, most recent first.
+    pub stashes: Vec<(String, HashMap<RepoPath, GitFileStatus>)>,
+    /// The remote operations that were performed, as git command lines.
+    pub remote_operations: Vec<String>,
 }
 
 impl FakeGitRepository {
@@ -255,10 +787,27 @@ impl FakeGitRepositoryState {
             dot_git_dir,
             event_emitter,
             index_contents: Default::default(),
+            head_contents: Default::default(),
+            revision_contents: Default::default(),
+            staged_paths: Default::default(),
+            commit_messages: Default::default(),
+            commits: Default::default(),
             blames: Default::default(),
             worktree_statuses: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
+            stashes: Default::default(),
+            remote_operations: Default::default(),
+        }
+    }
+
+    fn contents_at_revision(&self, revision: &str) -> Result<&HashMap<PathBuf, String>> {
+        if revision == "HEAD" {
+            Ok(&self.head_contents)
+        } else {
+            self.revision_contents
+                .get(revision)
+                .with_context(|| format!("failed to resolve revision {revision:?}"))
         }
     }
 }
@@ -271,6 +820,54 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_committed_text(&self, path: &Path) -> Option<String> {
+        let state = self.state.lock();
+        state.head_contents.get(path).cloned()
+    }
+
+    fn load_revision_text(&self, revision: &str, path: &Path) -> Result<Option<String>> {
+        let state = self.state.lock();
+        Ok(state.contents_at_revision(revision)?.get(path).cloned())
+    }
+
+    fn paths_changed_since(&self, revision: &str) -> Result<Vec<RepoPath>> {
+        let state = self.state.lock();
+        let revision_contents = state.contents_at_revision(revision)?;
+        let mut paths = revision_contents
+            .iter()
+            .filter(|(path, text)| state.head_contents.get(*path) != Some(*text))
+            .map(|(path, _)| RepoPath::from(path.as_path()))
+            .chain(
+                state
+                    .head_contents
+                    .keys()
+                    .filter(|path| !revision_contents.contains_key(*path))
+                    .map(|path| RepoPath::from(path.as_path())),
+            )
+            .chain(state.worktree_statuses.keys().cloned())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        let mut state = self.state.lock();
+        match content {
+            Some(content) => {
+                state.index_contents.insert(path.to_path_buf(), content);
+            }
+            None => {
+                state.index_contents.remove(path.as_path());
+            }
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -358,6 +955,180 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn staged_paths(&self) -> Result<HashSet<RepoPath>> {
+        let state = self.state.lock();
+        Ok(state.staged_paths.clone())
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            if !state.worktree_statuses.contains_key(path) {
+                anyhow::bail!("no changes to stage for {:?}", path);
+            }
+            state.staged_paths.insert(path.clone());
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            state.staged_paths.remove(path);
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn checkout_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            if !state.staged_paths.contains(path) {
+                state.worktree_statuses.remove(path);
+            }
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.staged_paths.is_empty() {
+            anyhow::bail!("nothing to commit");
+        }
+        let staged_paths = std::mem::take(&mut state.staged_paths);
+        for path in &staged_paths {
+            state.worktree_statuses.remove(path);
+        }
+        state.commit_messages.push(message.to_owned());
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn log(
+        &self,
+        path: Option<&RepoPath>,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<CommitSummary>> {
+        let state = self.state.lock();
+        Ok(state
+            .commits
+            .iter()
+            .filter(|(_, changes)| {
+                path.map_or(true, |path| {
+                    changes.iter().any(|change| &change.path == path)
+                })
+            })
+            .skip(skip)
+            .take(limit)
+            .map(|(commit, _)| commit.clone())
+            .collect())
+    }
+
+    fn commit_changes(&self, sha: Oid) -> Result<Vec<CommitFileChange>> {
+        let state = self.state.lock();
+        state
+            .commits
+            .iter()
+            .find(|(commit, _)| commit.sha == sha)
+            .map(|(_, changes)| changes.clone())
+            .with_context(|| format!("no commit found for {sha}"))
+    }
+
+    fn stash_entries(&self) -> Result<Vec<StashEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .stashes
+            .iter()
+            .enumerate()
+            .map(|(index, (message, _))| StashEntry {
+                index,
+                message: message.clone(),
+            })
+            .collect())
+    }
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.worktree_statuses.is_empty() {
+            anyhow::bail!("no local changes to stash");
+        }
+        let message = match message {
+            Some(message) => format!(
+                "On {}: {message}",
+                state.current_branch_name.clone().unwrap_or_default()
+            ),
+            None => format!(
+                "WIP on {}",
+                state.current_branch_name.clone().unwrap_or_default()
+            ),
+        };
+        let statuses = std::mem::take(&mut state.worktree_statuses);
+        state.staged_paths.clear();
+        state.stashes.insert(0, (message, statuses));
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize) -> Result<()> {
+        let mut state = self.state.lock();
+        if index >= state.stashes.len() {
+            anyhow::bail!("no stash entry at index {index}");
+        }
+        let (_, statuses) = state.stashes.remove(index);
+        state.worktree_statuses.extend(statuses);
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn fetch(&self) -> Result<()> {
+        let mut state = self.state.lock();
+        state.remote_operations.push("fetch".to_string());
+        Ok(())
+    }
+
+    fn pull(&self, strategy: PullStrategy) -> Result<()> {
+        let mut state = self.state.lock();
+        let operation = match strategy {
+            PullStrategy::FastForwardOnly => "pull --ff-only",
+            PullStrategy::Rebase => "pull --rebase",
+        };
+        state.remote_operations.push(operation.to_string());
+        Ok(())
+    }
+
+    fn push(&self) -> Result<()> {
+        let mut state = self.state.lock();
+        let branch = state
+            .current_branch_name
+            .clone()
+            .context("cannot push a detached HEAD")?;
+        state
+            .remote_operations
+            .push(format!("push origin {branch}"));
+        Ok(())
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -476,3 +1247,193 @@ impl<'a> MapSeekTarget<RepoPath> for RepoPathDescendants<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_repository(path: &Path) -> RealGitRepository {
+        let repo = git2::Repository::init(path).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "test").unwrap();
+        config.set_str("user.email", "test@zed.dev").unwrap();
+        RealGitRepository::new(repo, None, Arc::new(GitHostingProviderRegistry::new()))
+    }
+
+    #[test]
+    fn test_stage_unstage_and_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repository(dir.path());
+        let a = RepoPath::new(PathBuf::from("a.txt"));
+        let b = RepoPath::new(PathBuf::from("b.txt"));
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "b").unwrap();
+
+        repo.stage_paths(&[a.clone(), b.clone()]).unwrap();
+        assert_eq!(
+            repo.staged_paths().unwrap(),
+            HashSet::from_iter([a.clone(), b.clone()])
+        );
+
+        repo.unstage_paths(&[b.clone()]).unwrap();
+        assert_eq!(
+            repo.staged_paths().unwrap(),
+            HashSet::from_iter([a.clone()])
+        );
+
+        repo.commit("Add a").unwrap();
+        assert!(repo.staged_paths().unwrap().is_empty());
+        assert_eq!(repo.load_index_text(&a).as_deref(), Some("a"));
+        assert!(repo.commit("Nothing").is_err());
+
+        std::fs::write(dir.path().join("a.txt"), "changed").unwrap();
+        repo.stage_paths(&[a.clone()]).unwrap();
+        repo.unstage_paths(&[a.clone()]).unwrap();
+        assert_eq!(repo.load_index_text(&a).as_deref(), Some("a"));
+
+        repo.checkout_paths(&[a.clone()]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "a"
+        );
+
+        std::fs::remove_file(dir.path().join("a.txt")).unwrap();
+        repo.stage_paths(&[a.clone()]).unwrap();
+        assert_eq!(
+            repo.staged_paths().unwrap(),
+            HashSet::from_iter([a.clone()])
+        );
+        assert_eq!(repo.load_index_text(&a), None);
+    }
+
+    #[test]
+    fn test_set_index_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repository(dir.path());
+        let a = RepoPath::new(PathBuf::from("a.txt"));
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        assert_eq!(repo.load_committed_text(&a), None);
+
+        repo.stage_paths(&[a.clone()]).unwrap();
+        repo.commit("Add a").unwrap();
+        assert_eq!(repo.load_committed_text(&a).as_deref(), Some("one\ntwo\n"));
+
+        repo.set_index_text(&a, Some("one\nTWO\n".to_string()))
+            .unwrap();
+        assert_eq!(repo.load_index_text(&a).as_deref(), Some("one\nTWO\n"));
+        assert_eq!(
+            repo.staged_paths().unwrap(),
+            HashSet::from_iter([a.clone()])
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+
+        repo.set_index_text(&a, None).unwrap();
+        assert_eq!(repo.load_index_text(&a), None);
+        assert_eq!(repo.load_committed_text(&a).as_deref(), Some("one\ntwo\n"));
+    }
+
+    #[test]
+    fn test_log_and_commit_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repository(dir.path());
+        let a = RepoPath::new(PathBuf::from("a.txt"));
+        let b = RepoPath::new(PathBuf::from("b.txt"));
+        assert_eq!(repo.log(None, 0, 10).unwrap(), Vec::new());
+
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        repo.stage_paths(&[a.clone()]).unwrap();
+        repo.commit("Add a\n\nWith a body").unwrap();
+
+        std::fs::write(dir.path().join("b.txt"), "two\n").unwrap();
+        repo.stage_paths(&[b.clone()]).unwrap();
+        repo.commit("Add b").unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "ONE\n").unwrap();
+        std::fs::remove_file(dir.path().join("b.txt")).unwrap();
+        repo.stage_paths(&[a.clone(), b.clone()]).unwrap();
+        repo.commit("Update a, remove b").unwrap();
+
+        let log = repo.log(None, 0, 10).unwrap();
+        assert_eq!(
+            log.iter()
+                .map(|commit| commit.subject())
+                .collect::<Vec<_>>(),
+            ["Update a, remove b", "Add b", "Add a"]
+        );
+        assert_eq!(log[2].author_name, "test");
+        assert_eq!(
+            repo.log(None, 1, 1)
+                .unwrap()
+                .iter()
+                .map(|commit| commit.subject())
+                .collect::<Vec<_>>(),
+            ["Add b"]
+        );
+        assert_eq!(
+            repo.log(Some(&a), 0, 10)
+                .unwrap()
+                .iter()
+                .map(|commit| commit.subject())
+                .collect::<Vec<_>>(),
+            ["Update a, remove b", "Add a"]
+        );
+
+        assert_eq!(
+            repo.commit_changes(log[0].sha).unwrap(),
+            [
+                CommitFileChange {
+                    path: a.clone(),
+                    old_text: Some("one\n".into()),
+                    new_text: Some("ONE\n".into()),
+                },
+                CommitFileChange {
+                    path: b.clone(),
+                    old_text: Some("two\n".into()),
+                    new_text: None,
+                },
+            ]
+        );
+        assert_eq!(
+            repo.commit_changes(log[2].sha).unwrap(),
+            [CommitFileChange {
+                path: a,
+                old_text: None,
+                new_text: Some("one\n".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_load_revision_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repository(dir.path());
+        let a = RepoPath::new(PathBuf::from("a.txt"));
+        let b = RepoPath::new(PathBuf::from("b.txt"));
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        repo.stage_paths(&[a.clone()]).unwrap();
+        repo.commit("Add a").unwrap();
+        repo.create_branch("base").unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        repo.stage_paths(&[a.clone()]).unwrap();
+        repo.commit("Change a").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "b\n").unwrap();
+
+        assert_eq!(
+            repo.load_revision_text("base", &a).unwrap().as_deref(),
+            Some("one\n")
+        );
+        assert_eq!(
+            repo.load_revision_text("HEAD", &a).unwrap().as_deref(),
+            Some("two\n")
+        );
+        assert_eq!(repo.load_revision_text("base", &b).unwrap(), None);
+        assert!(repo.load_revision_text("no-such-branch", &a).is_err());
+
+        assert_eq!(repo.paths_changed_since("base").unwrap(), [a, b.clone()]);
+        assert_eq!(repo.paths_changed_since("HEAD").unwrap(), [b]);
+    }
+}
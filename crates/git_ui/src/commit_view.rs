@@ -0,0 +1,310 @@
+use std::{
+    any::Any,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use editor::{Editor, EditorEvent, DEFAULT_MULTIBUFFER_CONTEXT};
+use git::commit::{CommitFileChange, CommitSummary};
+use gpui::*;
+use language::{Buffer, Capability, DiskState, File, LanguageRegistry, LocalFile};
+use multi_buffer::MultiBuffer;
+use project::{buffer_store::BufferChangeSet, Project, ProjectPath, WorktreeId};
+use text::ToPoint as _;
+use ui::{prelude::*, Tooltip};
+use url::Url;
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent},
+    Workspace,
+};
+
+use crate::git_log::commit_timestamp;
+
+/// A read-only multibuffer showing every change made by a single commit.
+pub struct CommitView {
+    commit: CommitSummary,
+    permalink: Option<Url>,
+    editor: View<Editor>,
+    multibuffer: Model<MultiBuffer>,
+    _load_changes: Task<Result<()>>,
+}
+
+/// The file of a buffer holding a file's contents as of a commit.
+struct CommitFile {
+    path: Arc<Path>,
+    worktree_id: WorktreeId,
+    is_deleted: bool,
+}
+
+impl File for CommitFile {
+    fn as_local(&self) -> Option<&dyn LocalFile> {
+        None
+    }
+
+    fn disk_state(&self) -> DiskState {
+        if self.is_deleted {
+            DiskState::Deleted
+        } else {
+            DiskState::New
+        }
+    }
+
+    fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &AppContext) -> PathBuf {
+        self.path.to_path_buf()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a AppContext) -> &'a OsStr {
+        self.path
+            .file_name()
+            .unwrap_or_else(|| self.path.as_os_str())
+    }
+
+    fn worktree_id(&self, _: &AppContext) -> WorktreeId {
+        self.worktree_id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_proto(&self, _: &AppContext) -> rpc::proto::File {
+        rpc::proto::File {
+            worktree_id: self.worktree_id.to_proto(),
+            entry_id: None,
+            path: self.path.to_string_lossy().into(),
+            mtime: None,
+            is_deleted: self.is_deleted,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+}
+
+impl CommitView {
+    /// Opens the given commit of the repository containing `project_path`, reusing an
+    /// existing view of it if there is one.
+    pub fn open(
+        commit: CommitSummary,
+        permalink: Option<Url>,
+        project_path: ProjectPath,
+        workspace: &mut Workspace,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        if let Some(existing) = workspace
+            .items_of_type::<Self>(cx)
+            .find(|view| view.read(cx).commit.sha == commit.sha)
+        {
+            workspace.activate_item(&existing, true, true, cx);
+            return;
+        }
+
+        let project = workspace.project().clone();
+        let view = cx.new_view(|cx| Self::new(commit, permalink, project, project_path, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+    }
+
+    fn new(
+        commit: CommitSummary,
+        permalink: Option<Url>,
+        project: Model<Project>,
+        project_path: ProjectPath,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(Capability::ReadOnly));
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer.clone(), None, true, cx);
+            editor.set_read_only(true);
+            editor.set_expand_all_diff_hunks();
+            editor
+        });
+
+        let changes = project
+            .read(cx)
+            .load_commit_changes(&project_path, commit.sha, cx);
+        let languages = project.read(cx).languages().clone();
+        let worktree_id = project_path.worktree_id;
+        let load_changes = cx.spawn(|this, mut cx| async move {
+            for change in changes.await? {
+                let (buffer, change_set) =
+                    Self::load_change(change, worktree_id, &languages, &mut cx).await?;
+                this.update(&mut cx, |this, cx| this.push_change(buffer, change_set, cx))?;
+            }
+            anyhow::Ok(())
+        });
+
+        Self {
+            commit,
+            permalink,
+            editor,
+            multibuffer,
+            _load_changes: load_changes,
+        }
+    }
+
+    async fn load_change(
+        change: CommitFileChange,
+        worktree_id: WorktreeId,
+        languages: &Arc<LanguageRegistry>,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<(Model<Buffer>, Model<BufferChangeSet>)> {
+        let path = Arc::<Path>::from(change.path.0.as_path());
+        let language = languages.language_for_file_path(&path).await.log_err();
+        let file = Arc::new(CommitFile {
+            path,
+            worktree_id,
+            is_deleted: change.new_text.is_none(),
+        });
+        let buffer = cx.new_model(|cx| {
+            let mut buffer = Buffer::local(change.new_text.unwrap_or_default(), cx);
+            buffer.file_updated(file, cx);
+            buffer.set_language_registry(languages.clone());
+            buffer.set_language(language, cx);
+            buffer.set_capability(Capability::ReadOnly, cx);
+            buffer
+        })?;
+
+        let mut diff_updated = None;
+        let change_set = cx.new_model(|cx| {
+            let buffer_snapshot = buffer.read(cx).text_snapshot();
+            let mut change_set = BufferChangeSet::new(&buffer_snapshot);
+            diff_updated = Some(change_set.set_base_text(
+                change.old_text.unwrap_or_default(),
+                buffer_snapshot,
+                cx,
+            ));
+            change_set
+        })?;
+        if let Some(diff_updated) = diff_updated {
+            diff_updated.await.ok();
+        }
+        Ok((buffer, change_set))
+    }
+
+    fn push_change(
+        &mut self,
+        buffer: Model<Buffer>,
+        change_set: Model<BufferChangeSet>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = buffer.read(cx).text_snapshot();
+        let hunk_ranges = change_set
+            .read(cx)
+            .diff_hunks_intersecting_range(
+                snapshot.anchor_before(0)..snapshot.anchor_after(snapshot.len()),
+                &snapshot,
+            )
+            .map(|hunk| {
+                hunk.buffer_range.start.to_point(&snapshot)
+                    ..hunk.buffer_range.end.to_point(&snapshot)
+            })
+            .collect::<Vec<_>>();
+
+        self.multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts_with_context_lines(
+                buffer,
+                hunk_ranges,
+                DEFAULT_MULTIBUFFER_CONTEXT,
+                cx,
+            );
+        });
+        self.editor
+            .update(cx, |editor, cx| editor.add_change_set(change_set, cx));
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let commit = &self.commit;
+        v_flex()
+            .w_full()
+            .p_2()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .child(
+                        div()
+                            .font_buffer(cx)
+                            .child(Label::new(commit.sha.display_short()).color(Color::Muted)),
+                    )
+                    .child(Label::new(commit.author_name.clone()))
+                    .child(Label::new(commit_timestamp(commit)).color(Color::Muted))
+                    .child(div().flex_1())
+                    .when_some(self.permalink.clone(), |this, permalink| {
+                        this.child(
+                            Button::new("open-permalink", "Open Commit")
+                                .icon(IconName::ArrowUpRight)
+                                .icon_position(IconPosition::End)
+                                .icon_size(IconSize::Small)
+                                .tooltip({
+                                    let permalink = permalink.clone();
+                                    move |cx| Tooltip::text(permalink.to_string(), cx)
+                                })
+                                .on_click(move |_, cx| cx.open_url(permalink.as_str())),
+                        )
+                    }),
+            )
+            .child(div().text_ui(cx).child(commit.message.trim().to_string()))
+    }
+}
+
+impl EventEmitter<EditorEvent> for CommitView {}
+
+impl FocusableView for CommitView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for CommitView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::FileGit))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(format!("Commit {}", self.commit.sha.display_short()).into())
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        Some(self.commit.subject().to_string().into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("commit view")
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+}
+
+impl Render for CommitView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(div().flex_1().child(self.editor.clone()))
+    }
+}
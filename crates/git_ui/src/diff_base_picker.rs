@@ -0,0 +1,307 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use editor::Editor;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use git::repository::DiffBase;
+use gpui::*;
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use project::{Project, ProjectPath};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+use crate::{SelectDiffBase, SelectFileDiffBase};
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace.register_action(DiffBasePicker::select_diff_base);
+            workspace.register_action(DiffBasePicker::select_file_diff_base);
+        },
+    )
+    .detach();
+}
+
+/// What the chosen diff base applies to.
+#[derive(Clone)]
+enum DiffBaseScope {
+    Project,
+    Buffer(Model<Buffer>),
+}
+
+/// A modal for choosing what buffers are diffed against: the index, `HEAD`, a branch or
+/// any other revision typed into the query.
+pub struct DiffBasePicker {
+    picker: View<Picker<DiffBasePickerDelegate>>,
+    _subscription: Subscription,
+}
+
+impl DiffBasePicker {
+    fn select_diff_base(
+        workspace: &mut Workspace,
+        _: &SelectDiffBase,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::toggle(workspace, DiffBaseScope::Project, cx);
+    }
+
+    fn select_file_diff_base(
+        workspace: &mut Workspace,
+        _: &SelectFileDiffBase,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(buffer) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+        Self::toggle(workspace, DiffBaseScope::Buffer(buffer), cx);
+    }
+
+    fn toggle(workspace: &mut Workspace, scope: DiffBaseScope, cx: &mut ViewContext<Workspace>) {
+        let project = workspace.project().clone();
+        let load_branches = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| {
+                let repository = ProjectPath::root_path(worktree.read(cx).id());
+                project.read(cx).branches(repository, cx)
+            });
+        cx.spawn(|workspace, mut cx| async move {
+            let load_branches = load_branches.context("No worktrees found")?;
+            let branches = load_branches.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let delegate = DiffBasePickerDelegate::new(project, scope, branches, cx);
+                workspace.toggle_modal(cx, |cx| Self::new(delegate, cx));
+            })
+        })
+        .detach_and_prompt_err("Failed to read branches", cx, |_, _| None);
+    }
+
+    fn new(delegate: DiffBasePickerDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for DiffBasePicker {}
+impl EventEmitter<DismissEvent> for DiffBasePicker {}
+
+impl FocusableView for DiffBasePicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for DiffBasePicker {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+#[derive(Clone)]
+struct DiffBaseOption {
+    /// `None` makes a buffer use the project's diff base again.
+    diff_base: Option<DiffBase>,
+    label: String,
+}
+
+#[derive(Clone)]
+enum DiffBaseMatch {
+    Option(DiffBaseOption, StringMatch),
+    Revision(String),
+}
+
+pub struct DiffBasePickerDelegate {
+    project: Model<Project>,
+    scope: DiffBaseScope,
+    current: Option<DiffBase>,
+    options: Vec<DiffBaseOption>,
+    matches: Vec<DiffBaseMatch>,
+    selected_index: usize,
+}
+
+impl DiffBasePickerDelegate {
+    fn new(
+        project: Model<Project>,
+        scope: DiffBaseScope,
+        branches: Vec<git::repository::Branch>,
+        cx: &AppContext,
+    ) -> Self {
+        let mut options = Vec::new();
+        let current = match &scope {
+            DiffBaseScope::Project => Some(project.read(cx).default_diff_base(cx).clone()),
+            DiffBaseScope::Buffer(buffer) => {
+                options.push(DiffBaseOption {
+                    diff_base: None,
+                    label: format!(
+                        "Project default ({})",
+                        project.read(cx).default_diff_base(cx)
+                    ),
+                });
+                Some(project.read(cx).diff_base_for_buffer(buffer, cx).clone())
+            }
+        };
+        options.push(DiffBaseOption {
+            diff_base: Some(DiffBase::Index),
+            label: "Index (unstaged changes)".to_string(),
+        });
+        options.push(DiffBaseOption {
+            diff_base: Some(DiffBase::Revision("HEAD".into())),
+            label: "HEAD (uncommitted changes)".to_string(),
+        });
+        options.extend(branches.into_iter().map(|branch| DiffBaseOption {
+            label: branch.name.to_string(),
+            diff_base: Some(DiffBase::Revision(branch.name)),
+        }));
+
+        Self {
+            project,
+            scope,
+            current,
+            options,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    fn set_diff_base(&self, diff_base: Option<DiffBase>, cx: &mut AppContext) -> Result<()> {
+        self.project.update(cx, |project, cx| match &self.scope {
+            DiffBaseScope::Project => {
+                project.set_default_diff_base(diff_base.unwrap_or_default(), cx)
+            }
+            DiffBaseScope::Buffer(buffer) => project.set_buffer_diff_base(buffer, diff_base, cx),
+        })
+    }
+}
+
+impl PickerDelegate for DiffBasePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Compare against a branch, tag or commit...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .options
+            .iter()
+            .enumerate()
+            .map(|(ix, option)| StringMatchCandidate::new(ix, &option.label))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string.clone(),
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    let revision = query.trim();
+                    let has_exact_match = matches.iter().any(|m| m.string == revision);
+                    delegate.matches = matches
+                        .into_iter()
+                        .map(|m| DiffBaseMatch::Option(delegate.options[m.candidate_id].clone(), m))
+                        .collect();
+                    if !revision.is_empty() && !has_exact_match {
+                        delegate
+                            .matches
+                            .push(DiffBaseMatch::Revision(revision.to_string()));
+                    }
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let diff_base = match entry {
+            DiffBaseMatch::Option(option, _) => option.diff_base.clone(),
+            DiffBaseMatch::Revision(revision) => Some(DiffBase::Revision(revision.clone().into())),
+        };
+        match self.set_diff_base(diff_base, cx) {
+            Ok(()) => cx.emit(DismissEvent),
+            Err(error) => {
+                cx.prompt(
+                    PromptLevel::Critical,
+                    "Failed to change diff base",
+                    Some(&format!("{error:#}")),
+                    &["Ok"],
+                )
+                .detach();
+            }
+        }
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected);
+        let item = match &self.matches[ix] {
+            DiffBaseMatch::Option(option, string_match) => item
+                .child(HighlightedLabel::new(
+                    option.label.clone(),
+                    string_match.positions.clone(),
+                ))
+                .when(option.diff_base == self.current, |item| {
+                    item.end_slot(Icon::new(IconName::Check).color(Color::Accent))
+                }),
+            DiffBaseMatch::Revision(revision) => {
+                item.child(Label::new(format!("Compare against '{revision}'")))
+            }
+        };
+        Some(item)
+    }
+}
@@ -0,0 +1,344 @@
+use std::{path::Path, sync::Arc};
+
+use git::{
+    commit::{CommitLog, CommitSummary},
+    GitHostingProviderRegistry,
+};
+use gpui::*;
+use project::{Project, ProjectPath};
+use time::UtcOffset;
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{item::Item, Workspace};
+
+use crate::{commit_view::CommitView, ViewFileHistory, ViewHistory};
+
+/// The number of commits loaded at a time, as the log is scrolled.
+const PAGE_SIZE: usize = 200;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace.register_action(GitLogView::view_history);
+            workspace.register_action(GitLogView::view_file_history);
+        },
+    )
+    .detach();
+}
+
+/// Lists the commits of a repository, or the commits that changed one of its files.
+pub struct GitLogView {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    project_path: ProjectPath,
+    only_path: bool,
+    log: CommitLog,
+    selected_index: Option<usize>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    load_task: Option<Task<()>>,
+    is_fully_loaded: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GitLogEvent {}
+
+impl GitLogView {
+    fn view_history(workspace: &mut Workspace, _: &ViewHistory, cx: &mut ViewContext<Workspace>) {
+        let project_path = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+            .or_else(|| {
+                let worktree = workspace.project().read(cx).visible_worktrees(cx).next()?;
+                Some(ProjectPath {
+                    worktree_id: worktree.read(cx).id(),
+                    path: Arc::from(Path::new("")),
+                })
+            });
+        if let Some(project_path) = project_path {
+            Self::deploy(workspace, project_path, false, cx);
+        }
+    }
+
+    fn view_file_history(
+        workspace: &mut Workspace,
+        _: &ViewFileHistory,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        if let Some(project_path) = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+        {
+            Self::deploy(workspace, project_path, true, cx);
+        }
+    }
+
+    fn deploy(
+        workspace: &mut Workspace,
+        project_path: ProjectPath,
+        only_path: bool,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let existing = workspace.items_of_type::<Self>(cx).find(|view| {
+            let view = view.read(cx);
+            if only_path {
+                view.only_path && view.project_path == project_path
+            } else {
+                !view.only_path && view.project_path.worktree_id == project_path.worktree_id
+            }
+        });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, cx);
+            return;
+        }
+
+        let project = workspace.project().clone();
+        let workspace_handle = cx.view().downgrade();
+        let view =
+            cx.new_view(|cx| Self::new(project, workspace_handle, project_path, only_path, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+    }
+
+    fn new(
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        project_path: ProjectPath,
+        only_path: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut this = Self {
+            project,
+            workspace,
+            project_path,
+            only_path,
+            log: CommitLog::default(),
+            selected_index: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            load_task: None,
+            is_fully_loaded: false,
+        };
+        this.load_more(cx);
+        this
+    }
+
+    fn load_more(&mut self, cx: &mut ViewContext<Self>) {
+        if self.load_task.is_some() || self.is_fully_loaded {
+            return;
+        }
+
+        let load = self.project.read(cx).load_commit_log(
+            &self.project_path,
+            self.only_path,
+            self.log.commits.len(),
+            PAGE_SIZE,
+            cx,
+        );
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            let log = load.await;
+            this.update(&mut cx, |this, cx| {
+                this.load_task = None;
+                match log {
+                    Ok(log) => {
+                        this.is_fully_loaded = log.commits.len() < PAGE_SIZE;
+                        this.log.commits.extend(log.commits);
+                        this.log.remote_url = log.remote_url;
+                    }
+                    Err(error) => {
+                        this.is_fully_loaded = true;
+                        this.workspace
+                            .update(cx, |workspace, cx| workspace.show_error(&error, cx))
+                            .ok();
+                    }
+                }
+                cx.notify();
+            })
+            .log_err();
+        }));
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        if self.log.commits.is_empty() {
+            return;
+        }
+        let index = self
+            .selected_index
+            .map_or(0, |index| (index + 1).min(self.log.commits.len() - 1));
+        self.select_index(index, cx);
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.log.commits.is_empty() {
+            return;
+        }
+        let index = self
+            .selected_index
+            .map_or(0, |index| index.saturating_sub(1));
+        self.select_index(index, cx);
+    }
+
+    fn select_index(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        self.selected_index = Some(index);
+        self.scroll_handle
+            .scroll_to_item(index, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(index) = self.selected_index {
+            self.open_commit(index, cx);
+        }
+    }
+
+    fn open_commit(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        let Some(commit) = self.log.commits.get(index).cloned() else {
+            return;
+        };
+        let permalink = self
+            .log
+            .permalink(&commit.sha, GitHostingProviderRegistry::default_global(cx));
+        let project_path = self.project_path.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                CommitView::open(commit, permalink, project_path, workspace, cx)
+            })
+            .log_err();
+    }
+
+    fn title(&self) -> SharedString {
+        if self.only_path {
+            format!("History: {}", self.project_path.path.display()).into()
+        } else {
+            "History".into()
+        }
+    }
+
+    fn render_commit(&self, index: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let commit = &self.log.commits[index];
+        ListItem::new(index)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected_index == Some(index))
+            .tooltip({
+                let message = commit.message.trim().to_string();
+                move |cx| Tooltip::text(message.clone(), cx)
+            })
+            .on_click(cx.listener(move |this, _, cx| {
+                this.selected_index = Some(index);
+                this.open_commit(index, cx);
+            }))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .child(
+                        div().font_buffer(cx).child(
+                            Label::new(commit.sha.display_short())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(Label::new(commit.subject().to_string()).single_line()),
+                    )
+                    .child(
+                        Label::new(commit.author_name.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(commit_timestamp(commit))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+    }
+}
+
+/// Formats the time at which the commit was authored, relative to now.
+pub(crate) fn commit_timestamp(commit: &CommitSummary) -> String {
+    match commit.author_offset_date_time() {
+        Ok(timestamp) => {
+            let local = chrono::Local::now().offset().local_minus_utc();
+            time_format::format_localized_timestamp(
+                timestamp,
+                time::OffsetDateTime::now_utc(),
+                UtcOffset::from_whole_seconds(local).unwrap(),
+                time_format::TimestampFormat::Relative,
+            )
+        }
+        Err(_) => "Error parsing date".to_string(),
+    }
+}
+
+impl EventEmitter<GitLogEvent> for GitLogView {}
+
+impl FocusableView for GitLogView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for GitLogView {
+    type Event = GitLogEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::FileGit))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(self.title())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("git log")
+    }
+
+    fn to_item_events(_event: &Self::Event, _f: impl FnMut(workspace::item::ItemEvent)) {}
+}
+
+impl Render for GitLogView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let item_count = self.log.commits.len();
+        v_flex()
+            .id("git_log")
+            .key_context("GitLog")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .p_1()
+            .bg(cx.theme().colors().editor_background)
+            .map(|this| {
+                if item_count == 0 {
+                    let message = if self.load_task.is_some() {
+                        "Loading history…"
+                    } else {
+                        "No commits"
+                    };
+                    this.items_center()
+                        .justify_center()
+                        .child(Label::new(message).color(Color::Muted))
+                } else {
+                    this.child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "commits",
+                            item_count,
+                            |this, range, cx| {
+                                if range.end >= this.log.commits.len() {
+                                    this.load_more(cx);
+                                }
+                                range.map(|index| this.render_commit(index, cx)).collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
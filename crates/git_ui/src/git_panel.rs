@@ -9,19 +9,21 @@ use std::{
     time::Duration,
 };
 
-use git::repository::GitFileStatus;
+use git::repository::{GitFileStatus, GitRepository, RepoPath};
 
 use util::{ResultExt, TryFutureExt};
 
 use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
 use gpui::*;
-use project::{Entry, EntryKind, Fs, Project, ProjectEntryId, WorktreeId};
+use project::{Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath, WorktreeId};
 use serde::{Deserialize, Serialize};
 use settings::Settings as _;
 use ui::{
     prelude::*, Checkbox, Divider, DividerColor, ElevationIndex, Scrollbar, ScrollbarState, Tooltip,
 };
 use workspace::dock::{DockPosition, Panel, PanelEvent};
+use workspace::notifications::NotifyTaskExt;
 use workspace::Workspace;
 
 use crate::{git_status_icon, settings::GitPanelSettings};
@@ -72,7 +74,8 @@ struct SerializedGitPanel {
 }
 
 pub struct GitPanel {
-    _workspace: WeakView<Workspace>,
+    workspace: WeakView<Workspace>,
+    commit_editor: View<Editor>,
     current_modifiers: Modifiers,
     focus_handle: FocusHandle,
     fs: Arc<dyn Fs>,
@@ -88,6 +91,9 @@ pub struct GitPanel {
     // The entries that are currently shown in the panel, aka
     // not hidden by folding or such
     visible_entries: Vec<(WorktreeId, Vec<Entry>, OnceCell<HashSet<Arc<Path>>>)>,
+    // The visible entries whose changes are staged in the index
+    staged_entries: HashSet<ProjectEntryId>,
+    pending_staged_entries_update: Task<()>,
     width: Option<Pixels>,
 }
 
@@ -122,6 +128,7 @@ impl GitPanel {
                     cx.notify();
                 }
                 project::Event::WorktreeUpdatedEntries(_, _)
+                | project::Event::WorktreeUpdatedGitRepositories(_)
                 | project::Event::WorktreeAdded(_)
                 | project::Event::WorktreeOrderChanged => {
                     this.update_visible_entries(None, cx);
@@ -131,15 +138,24 @@ impl GitPanel {
             })
             .detach();
 
+            let commit_editor = cx.new_view(|cx| {
+                let mut editor = Editor::auto_height(6, cx);
+                editor.set_placeholder_text("Add a message", cx);
+                editor
+            });
+
             let scroll_handle = UniformListScrollHandle::new();
 
             let mut this = Self {
-                _workspace: weak_workspace,
+                workspace: weak_workspace,
+                commit_editor,
                 focus_handle: cx.focus_handle(),
                 fs,
                 pending_serialization: Task::ready(None),
                 project,
                 visible_entries: Vec::new(),
+                staged_entries: HashSet::default(),
+                pending_staged_entries_update: Task::ready(()),
                 current_modifiers: cx.modifiers(),
                 expanded_dir_ids: Default::default(),
 
@@ -254,36 +270,288 @@ impl GitPanel {
 }
 
 impl GitPanel {
-    fn stage_all(&mut self, _: &StageAll, _cx: &mut ViewContext<Self>) {
-        // todo!(): Implement stage all
-        println!("Stage all triggered");
+    fn stage_all(&mut self, _: &StageAll, cx: &mut ViewContext<Self>) {
+        let entries = self.changed_entries().collect::<Vec<_>>();
+        self.stage_entries(entries, cx);
     }
 
-    fn unstage_all(&mut self, _: &UnstageAll, _cx: &mut ViewContext<Self>) {
-        // todo!(): Implement unstage all
-        println!("Unstage all triggered");
+    fn unstage_all(&mut self, _: &UnstageAll, cx: &mut ViewContext<Self>) {
+        let entries = self.changed_entries().collect::<Vec<_>>();
+        self.unstage_entries(entries, cx);
     }
 
-    fn discard_all(&mut self, _: &DiscardAll, _cx: &mut ViewContext<Self>) {
-        // todo!(): Implement discard all
-        println!("Discard all triggered");
+    /// Stages or unstages every change, given the state the header checkbox was toggled to.
+    fn set_all_staged(&mut self, state: &ToggleState, cx: &mut ViewContext<Self>) {
+        match state {
+            ToggleState::Selected => self.stage_all(&StageAll, cx),
+            _ => self.unstage_all(&UnstageAll, cx),
+        }
+    }
+
+    fn discard_all(&mut self, _: &DiscardAll, cx: &mut ViewContext<Self>) {
+        let entries = self.changed_entries().collect::<Vec<_>>();
+        if entries.is_empty() {
+            return;
+        }
+        let repositories = self.repositories_for_entries(entries, cx);
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            "Discard all changes?",
+            Some("Staged and unstaged changes to tracked files will be lost."),
+            &["Discard", "Cancel"],
+        );
+        let task = cx.spawn(|_, cx| async move {
+            if answer.await != Ok(0) {
+                return anyhow::Ok(());
+            }
+            cx.background_executor()
+                .spawn(async move {
+                    for (repo, paths) in repositories {
+                        repo.unstage_paths(&paths)?;
+                        repo.checkout_paths(&paths)?;
+                    }
+                    anyhow::Ok(())
+                })
+                .await
+        });
+        task.detach_and_notify_err(cx);
     }
 
     /// Commit all staged changes
-    fn commit_staged_changes(&mut self, _: &CommitStagedChanges, _cx: &mut ViewContext<Self>) {
-        // todo!(): Implement commit all staged
-        println!("Commit staged changes triggered");
+    fn commit_staged_changes(&mut self, _: &CommitStagedChanges, cx: &mut ViewContext<Self>) {
+        self.commit(false, cx);
     }
 
     /// Commit all changes, regardless of whether they are staged or not
-    fn commit_all_changes(&mut self, _: &CommitAllChanges, _cx: &mut ViewContext<Self>) {
-        // todo!(): Implement commit all changes
-        println!("Commit all changes triggered");
+    fn commit_all_changes(&mut self, _: &CommitAllChanges, cx: &mut ViewContext<Self>) {
+        self.commit(true, cx);
+    }
+
+    fn commit(&mut self, stage_all: bool, cx: &mut ViewContext<Self>) {
+        let message = self.commit_editor.read(cx).text(cx);
+        if message.trim().is_empty() {
+            return;
+        }
+        let entries = self.changed_entries().collect::<Vec<_>>();
+        let repositories = self.repositories_for_entries(entries, cx);
+        let commit = cx.background_executor().spawn(async move {
+            let mut committed = false;
+            for (repo, paths) in repositories {
+                if stage_all {
+                    repo.stage_paths(&paths)?;
+                } else if repo.staged_paths()?.is_empty() {
+                    continue;
+                }
+                repo.commit(&message)?;
+                committed = true;
+            }
+            anyhow::ensure!(committed, "nothing to commit");
+            anyhow::Ok(())
+        });
+        let task = cx.spawn(|this, mut cx| async move {
+            commit.await?;
+            this.update(&mut cx, |this, cx| {
+                this.commit_editor
+                    .update(cx, |editor, cx| editor.set_text("", cx));
+            })
+        });
+        task.detach_and_notify_err(cx);
+    }
+
+    fn toggle_staged_for_entry(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        let Some(entries) = self
+            .visible_entries
+            .iter()
+            .find_map(|(worktree_id, entries, _)| {
+                let entry = entries.iter().find(|entry| entry.id == entry_id)?;
+                Some(vec![(*worktree_id, entry.path.clone())])
+            })
+        else {
+            return;
+        };
+        if self.staged_entries.contains(&entry_id) {
+            self.unstage_entries(entries, cx);
+        } else {
+            self.stage_entries(entries, cx);
+        }
+    }
+
+    fn stage_entries(&mut self, entries: Vec<(WorktreeId, Arc<Path>)>, cx: &mut ViewContext<Self>) {
+        let repositories = self.repositories_for_entries(entries, cx);
+        cx.background_executor()
+            .spawn(async move {
+                for (repo, paths) in repositories {
+                    repo.stage_paths(&paths)?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_notify_err(cx);
+    }
+
+    fn unstage_entries(
+        &mut self,
+        entries: Vec<(WorktreeId, Arc<Path>)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let repositories = self.repositories_for_entries(entries, cx);
+        cx.background_executor()
+            .spawn(async move {
+                for (repo, paths) in repositories {
+                    repo.unstage_paths(&paths)?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_notify_err(cx);
+    }
+
+    /// Returns the files in the panel that have changes.
+    fn changed_entries(&self) -> impl Iterator<Item = (WorktreeId, Arc<Path>)> + '_ {
+        self.visible_entries
+            .iter()
+            .flat_map(|(worktree_id, entries, _)| {
+                entries
+                    .iter()
+                    .filter(|entry| entry.is_file() && entry.git_status.is_some())
+                    .map(|entry| (*worktree_id, entry.path.clone()))
+            })
+    }
+
+    /// Returns the files in the panel that git reports as having unresolved merge conflicts.
+    fn conflicted_entries(&self) -> Vec<(WorktreeId, Arc<Path>)> {
+        self.visible_entries
+            .iter()
+            .flat_map(|(worktree_id, entries, _)| {
+                entries
+                    .iter()
+                    .filter(|entry| {
+                        entry.is_file() && entry.git_status == Some(GitFileStatus::Conflict)
+                    })
+                    .map(|entry| (*worktree_id, entry.path.clone()))
+            })
+            .collect()
+    }
+
+    /// Stages a conflicted file, which tells git that its conflicts have been resolved.
+    fn mark_resolved(
+        &mut self,
+        worktree_id: WorktreeId,
+        path: Arc<Path>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.stage_entries(vec![(worktree_id, path)], cx);
+    }
+
+    fn open_entry(&mut self, worktree_id: WorktreeId, path: Arc<Path>, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_path(ProjectPath { worktree_id, path }, None, true, cx)
+                    .detach_and_log_err(cx);
+            })
+            .log_err();
+    }
+
+    /// Groups the given worktree paths by the local repository that contains them.
+    fn repositories_for_entries(
+        &self,
+        entries: impl IntoIterator<Item = (WorktreeId, Arc<Path>)>,
+        cx: &AppContext,
+    ) -> Vec<(Arc<dyn GitRepository>, Vec<RepoPath>)> {
+        let project = self.project.read(cx);
+        let mut repositories: HashMap<_, (Arc<dyn GitRepository>, Vec<RepoPath>)> =
+            HashMap::default();
+        for (worktree_id, path) in entries {
+            let Some(worktree) = project.worktree_for_id(worktree_id, cx) else {
+                continue;
+            };
+            let Some(worktree) = worktree.read(cx).as_local() else {
+                continue;
+            };
+            let Some((repo_entry, local_repo)) = worktree.repo_for_path(&path) else {
+                continue;
+            };
+            let Some(repo_path) = repo_entry.relativize(worktree, &path).log_err() else {
+                continue;
+            };
+            repositories
+                .entry((worktree_id, repo_entry.work_directory_id()))
+                .or_insert_with(|| (local_repo.repo().clone(), Vec::new()))
+                .1
+                .push(repo_path);
+        }
+        repositories.into_values().collect()
+    }
+
+    fn update_staged_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let mut entries_by_repository = Vec::new();
+        {
+            let project = self.project.read(cx);
+            for (worktree_id, entries, _) in &self.visible_entries {
+                let Some(worktree) = project.worktree_for_id(*worktree_id, cx) else {
+                    continue;
+                };
+                let Some(worktree) = worktree.read(cx).as_local() else {
+                    continue;
+                };
+                for entry in entries.iter().filter(|entry| entry.is_file()) {
+                    if let Some((repo_entry, local_repo)) = worktree.repo_for_path(&entry.path) {
+                        if let Some(repo_path) =
+                            repo_entry.relativize(worktree, &entry.path).log_err()
+                        {
+                            entries_by_repository.push((
+                                local_repo.repo().clone(),
+                                entry.id,
+                                repo_path,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        let staged_entries = cx.background_executor().spawn(async move {
+            let mut staged_paths_by_repository = HashMap::default();
+            let mut staged_entries = HashSet::default();
+            for (repo, entry_id, repo_path) in entries_by_repository {
+                let staged_paths = staged_paths_by_repository
+                    .entry(repo.dot_git_dir())
+                    .or_insert_with(|| repo.staged_paths().log_err().unwrap_or_default());
+                if staged_paths.contains(&repo_path) {
+                    staged_entries.insert(entry_id);
+                }
+            }
+            staged_entries
+        });
+        self.pending_staged_entries_update = cx.spawn(|this, mut cx| async move {
+            let staged_entries = staged_entries.await;
+            this.update(&mut cx, |this, cx| {
+                this.staged_entries = staged_entries;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn staged_state(&self) -> ToggleState {
+        let mut entry_ids = self
+            .visible_entries
+            .iter()
+            .flat_map(|(_, entries, _)| entries.iter())
+            .filter(|entry| entry.is_file() && entry.git_status.is_some())
+            .map(|entry| entry.id)
+            .peekable();
+        if entry_ids.peek().is_none() || self.staged_entries.is_empty() {
+            return ToggleState::Unselected;
+        }
+        if entry_ids.all(|entry_id| self.staged_entries.contains(&entry_id)) {
+            ToggleState::Selected
+        } else {
+            ToggleState::Indeterminate
+        }
     }
 
     fn all_staged(&self) -> bool {
-        // todo!(): Implement all_staged
-        true
+        self.staged_state() == ToggleState::Selected
     }
 
     fn no_entries(&self) -> bool {
@@ -409,6 +677,8 @@ impl GitPanel {
             }
         }
 
+        self.update_staged_entries(cx);
+
         if let Some((worktree_id, entry_id)) = new_selected_entry {
             self.selected_item = self.visible_entries.iter().enumerate().find_map(
                 |(worktree_index, (id, entries, _))| {
@@ -464,13 +734,36 @@ impl GitPanel {
             .child(
                 h_flex()
                     .gap_2()
-                    .child(Checkbox::new("all-changes", true.into()).disabled(true))
+                    .child(
+                        Checkbox::new("all-changes", self.staged_state())
+                            .disabled(self.no_entries())
+                            .on_click(cx.listener(|this, state: &ToggleState, cx| {
+                                this.set_all_staged(state, cx)
+                            })),
+                    )
                     .child(div().text_buffer(cx).text_ui_sm(cx).child(changes_string)),
             )
             .child(div().flex_grow())
             .child(
                 h_flex()
                     .gap_2()
+                    .child({
+                        let focus_handle = focus_handle.clone();
+                        IconButton::new("review-changes", IconName::FileGit)
+                            .tooltip(move |cx| {
+                                Tooltip::for_action_in(
+                                    "Review all changes",
+                                    &editor::project_diff::Deploy,
+                                    &focus_handle,
+                                    cx,
+                                )
+                            })
+                            .icon_size(IconSize::Small)
+                            .disabled(self.no_entries())
+                            .on_click(cx.listener(|_, _, cx| {
+                                cx.dispatch_action(Box::new(editor::project_diff::Deploy))
+                            }))
+                    })
                     .child(
                         IconButton::new("discard-changes", IconName::Undo)
                             .tooltip(move |cx| {
@@ -484,11 +777,12 @@ impl GitPanel {
                                 )
                             })
                             .icon_size(IconSize::Small)
-                            .disabled(true),
+                            .disabled(self.no_entries())
+                            .on_click(cx.listener(|this, _, cx| this.discard_all(&DiscardAll, cx))),
                     )
                     .child(if self.all_staged() {
                         self.panel_button("unstage-all", "Unstage All").on_click(
-                            cx.listener(move |_, _, cx| cx.dispatch_action(Box::new(DiscardAll))),
+                            cx.listener(move |_, _, cx| cx.dispatch_action(Box::new(UnstageAll))),
                         )
                     } else {
                         self.panel_button("stage-all", "Stage All").on_click(
@@ -540,8 +834,7 @@ impl GitPanel {
                 .bg(cx.theme().colors().editor_background)
                 .font_buffer(cx)
                 .text_ui_sm(cx)
-                .text_color(cx.theme().colors().text_muted)
-                .child("Add a message")
+                .child(self.commit_editor.clone())
                 .gap_1()
                 .child(div().flex_grow())
                 .child(h_flex().child(div().gap_1().flex_grow()).child(
@@ -550,9 +843,7 @@ impl GitPanel {
                     } else {
                         commit_staged_button
                     },
-                ))
-                .cursor(CursorStyle::OperationNotAllowed)
-                .opacity(0.5),
+                )),
         )
     }
 
@@ -622,6 +913,72 @@ impl GitPanel {
         )
     }
 
+    fn render_conflicts(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let conflicted_entries = self.conflicted_entries();
+        if conflicted_entries.is_empty() {
+            return None;
+        }
+
+        let is_read_only = self.project.read(cx).is_read_only(cx);
+        Some(
+            v_flex()
+                .w_full()
+                .py_1()
+                .child(
+                    h_flex()
+                        .h(px(28.))
+                        .px_3()
+                        .gap_2()
+                        .child(git_status_icon(GitFileStatus::Conflict))
+                        .child(
+                            div()
+                                .text_ui_sm(cx)
+                                .child(format!("{} conflicts", conflicted_entries.len())),
+                        ),
+                )
+                .children(conflicted_entries.into_iter().enumerate().map(
+                    |(ix, (worktree_id, path))| {
+                        h_flex()
+                            .id(("conflict", ix))
+                            .h(px(28.))
+                            .w_full()
+                            .pl(px(24.))
+                            .pr(px(4.))
+                            .items_center()
+                            .gap_2()
+                            .text_ui_sm(cx)
+                            .cursor_pointer()
+                            .hover(|style| style.bg(cx.theme().colors().ghost_element_hover))
+                            .on_click(cx.listener({
+                                let path = path.clone();
+                                move |this, _, cx| this.open_entry(worktree_id, path.clone(), cx)
+                            }))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .overflow_hidden()
+                                    .child(path.to_string_lossy().into_owned()),
+                            )
+                            .child(
+                                Button::new(("mark-resolved", ix), "Mark Resolved")
+                                    .label_size(LabelSize::Small)
+                                    .disabled(is_read_only)
+                                    .tooltip(|cx| {
+                                        Tooltip::text(
+                                            "Stage the file to mark its conflicts as resolved",
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(cx.listener(move |this, _, cx| {
+                                        this.mark_resolved(worktree_id, path.clone(), cx)
+                                    })),
+                            )
+                    },
+                ))
+                .child(self.render_divider(cx)),
+        )
+    }
+
     fn render_entries(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let item_count = self
             .visible_entries
@@ -656,9 +1013,10 @@ impl GitPanel {
         details: EntryDetails,
         cx: &ViewContext<Self>,
     ) -> impl IntoElement {
+        let entry_id = id;
         let id = id.to_proto() as usize;
         let checkbox_id = ElementId::Name(format!("checkbox_{}", id).into());
-        let is_staged = ToggleState::Selected;
+        let is_staged = ToggleState::from(self.staged_entries.contains(&entry_id));
 
         h_flex()
             .id(id)
@@ -671,7 +1029,9 @@ impl GitPanel {
             .font_buffer(cx)
             .text_ui_sm(cx)
             .when(!details.is_dir(), |this| {
-                this.child(Checkbox::new(checkbox_id, is_staged))
+                this.child(Checkbox::new(checkbox_id, is_staged).on_click(
+                    cx.listener(move |this, _, cx| this.toggle_staged_for_entry(entry_id, cx)),
+                ))
             })
             .when_some(details.status, |this, status| {
                 this.child(git_status_icon(status))
@@ -720,6 +1080,7 @@ impl Render for GitPanel {
             .bg(ElevationIndex::Surface.bg(cx))
             .child(self.render_panel_header(cx))
             .child(self.render_divider(cx))
+            .children(self.render_conflicts(cx))
             .child(if !self.no_entries() {
                 self.render_entries(cx).into_any_element()
             } else {
@@ -784,3 +1145,129 @@ impl Panel for GitPanel {
         Box::new(ToggleFocus)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::settings::SettingsStore;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_stage_and_commit(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                "a.txt": "a",
+                "b.txt": "b",
+                "c.txt": "c",
+            }),
+        )
+        .await;
+        let dot_git = Path::new("/root/.git");
+        fs.set_status_for_repo_via_git_operation(
+            dot_git,
+            &[
+                (Path::new("a.txt"), GitFileStatus::Modified),
+                (Path::new("b.txt"), GitFileStatus::Added),
+            ],
+        );
+
+        let project = Project::test(fs.clone(), [Path::new("/root")], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let panel = workspace.update(cx, |workspace, cx| GitPanel::new(workspace, cx));
+        cx.run_until_parked();
+
+        let staged_paths = || {
+            let mut paths = Vec::new();
+            fs.with_git_state(dot_git, false, |state| {
+                paths = state.staged_paths.iter().cloned().collect::<Vec<_>>();
+            });
+            paths.sort();
+            paths
+        };
+        let repo_path = |path: &str| RepoPath::from(Path::new(path));
+
+        panel.update(cx, |panel, _| {
+            assert_eq!(panel.entry_count(), 2);
+            assert_eq!(panel.staged_state(), ToggleState::Unselected);
+        });
+
+        // Clicking the header checkbox stages every change...
+        panel.update(cx, |panel, cx| {
+            let state = panel.staged_state().inverse();
+            panel.set_all_staged(&state, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(staged_paths(), [repo_path("a.txt"), repo_path("b.txt")]);
+        panel.update(cx, |panel, _| {
+            assert_eq!(panel.staged_state(), ToggleState::Selected);
+        });
+
+        // ...and clicking it again unstages them.
+        panel.update(cx, |panel, cx| {
+            let state = panel.staged_state().inverse();
+            panel.set_all_staged(&state, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(staged_paths(), Vec::<RepoPath>::new());
+
+        let entry_id = project.update(cx, |project, cx| {
+            let worktree_id = project.worktrees(cx).next().unwrap().read(cx).id();
+            project
+                .entry_for_path(
+                    &ProjectPath {
+                        worktree_id,
+                        path: Path::new("a.txt").into(),
+                    },
+                    cx,
+                )
+                .unwrap()
+                .id
+        });
+        panel.update(cx, |panel, cx| panel.toggle_staged_for_entry(entry_id, cx));
+        cx.run_until_parked();
+        assert_eq!(staged_paths(), [repo_path("a.txt")]);
+        panel.update(cx, |panel, _| {
+            assert_eq!(panel.staged_state(), ToggleState::Indeterminate);
+        });
+
+        // Committing the staged changes only commits the staged file.
+        panel.update(cx, |panel, cx| {
+            panel
+                .commit_editor
+                .update(cx, |editor, cx| editor.set_text("Update a", cx));
+            panel.commit_staged_changes(&CommitStagedChanges, cx);
+        });
+        cx.run_until_parked();
+        fs.with_git_state(dot_git, false, |state| {
+            assert_eq!(state.commit_messages, ["Update a"]);
+            assert!(state.staged_paths.is_empty());
+            assert_eq!(
+                state.worktree_statuses.keys().cloned().collect::<Vec<_>>(),
+                [repo_path("b.txt")]
+            );
+        });
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.entry_count(), 1);
+            assert_eq!(panel.commit_editor.read(cx).text(cx), "");
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+}
@@ -4,6 +4,9 @@ use gpui::{actions, AppContext, Hsla};
 use settings::GitPanelSettings;
 use ui::{Color, Icon, IconName, IntoElement};
 
+pub mod commit_view;
+pub mod diff_base_picker;
+pub mod git_log;
 pub mod git_panel;
 mod settings;
 
@@ -14,12 +17,18 @@ actions!(
         UnstageAll,
         DiscardAll,
         CommitStagedChanges,
-        CommitAllChanges
+        CommitAllChanges,
+        ViewHistory,
+        ViewFileHistory,
+        SelectDiffBase,
+        SelectFileDiffBase
     ]
 );
 
 pub fn init(cx: &mut AppContext) {
     GitPanelSettings::register(cx);
+    git_log::init(cx);
+    diff_base_picker::init(cx);
 }
 
 const ADDED_COLOR: Hsla = Hsla {
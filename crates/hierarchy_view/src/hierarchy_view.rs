@@ -0,0 +1,691 @@
+use anyhow::Result;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    actions, uniform_list, AppContext, EventEmitter, FocusHandle, FocusableView, KeyContext, Model,
+    ScrollStrategy, Task, UniformListScrollHandle, View, ViewContext, WeakView,
+};
+use language::OffsetRangeExt;
+use menu::{Confirm, SelectNext, SelectPrev};
+use project::{HierarchyCall, HierarchyItem, Project};
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{
+    item::Item, notifications::NotifyTaskExt, Pane, SplitDirection, Workspace, WorkspaceId,
+};
+
+actions!(
+    hierarchy_view,
+    [
+        ShowIncomingCalls,
+        ShowOutgoingCalls,
+        ShowSupertypes,
+        ShowSubtypes,
+        ExpandSelectedEntry,
+        CollapseSelectedEntry,
+    ]
+);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace
+                .register_action(|workspace, _: &ShowIncomingCalls, cx| {
+                    HierarchyView::deploy(workspace, HierarchyKind::IncomingCalls, cx);
+                })
+                .register_action(|workspace, _: &ShowOutgoingCalls, cx| {
+                    HierarchyView::deploy(workspace, HierarchyKind::OutgoingCalls, cx);
+                })
+                .register_action(|workspace, _: &ShowSupertypes, cx| {
+                    HierarchyView::deploy(workspace, HierarchyKind::Supertypes, cx);
+                })
+                .register_action(|workspace, _: &ShowSubtypes, cx| {
+                    HierarchyView::deploy(workspace, HierarchyKind::Subtypes, cx);
+                });
+        },
+    )
+    .detach();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyKind {
+    IncomingCalls,
+    OutgoingCalls,
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyKind {
+    fn title(&self) -> &'static str {
+        match self {
+            HierarchyKind::IncomingCalls => "Incoming Calls",
+            HierarchyKind::OutgoingCalls => "Outgoing Calls",
+            HierarchyKind::Supertypes => "Supertypes",
+            HierarchyKind::Subtypes => "Subtypes",
+        }
+    }
+
+    fn is_call_hierarchy(&self) -> bool {
+        matches!(
+            self,
+            HierarchyKind::IncomingCalls | HierarchyKind::OutgoingCalls
+        )
+    }
+
+    fn prepare(
+        &self,
+        project: &Model<Project>,
+        buffer: &Model<language::Buffer>,
+        position: language::Anchor,
+        cx: &mut AppContext,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        project.update(cx, |project, cx| {
+            if self.is_call_hierarchy() {
+                project.prepare_call_hierarchy(buffer, position, cx)
+            } else {
+                project.prepare_type_hierarchy(buffer, position, cx)
+            }
+        })
+    }
+
+    /// Fetches the children of an item, along with the number of call sites for each of them.
+    fn children(
+        &self,
+        project: &Model<Project>,
+        item: &HierarchyItem,
+        cx: &mut AppContext,
+    ) -> Task<Result<Vec<(HierarchyItem, usize)>>> {
+        enum Request {
+            Calls(Task<Result<Vec<HierarchyCall>>>),
+            Types(Task<Result<Vec<HierarchyItem>>>),
+        }
+
+        let request = project.update(cx, |project, cx| match self {
+            HierarchyKind::IncomingCalls => Request::Calls(project.incoming_calls(item, cx)),
+            HierarchyKind::OutgoingCalls => Request::Calls(project.outgoing_calls(item, cx)),
+            HierarchyKind::Supertypes => Request::Types(project.supertypes(item, cx)),
+            HierarchyKind::Subtypes => Request::Types(project.subtypes(item, cx)),
+        });
+        cx.spawn(|_| async move {
+            Ok(match request {
+                Request::Calls(calls) => calls
+                    .await?
+                    .into_iter()
+                    .map(|call| (call.item, call.call_sites.len()))
+                    .collect(),
+                Request::Types(items) => items.await?.into_iter().map(|item| (item, 0)).collect(),
+            })
+        })
+    }
+}
+
+enum Children {
+    NotLoaded,
+    Loading,
+    Loaded(Vec<usize>),
+}
+
+struct Entry {
+    item: HierarchyItem,
+    call_sites: usize,
+    depth: usize,
+    expanded: bool,
+    children: Children,
+}
+
+/// A tree of callers, callees, supertypes or subtypes of a symbol, expanded lazily.
+pub struct HierarchyView {
+    kind: HierarchyKind,
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    /// The pane the hierarchy was requested from, where locations are opened.
+    origin_pane: WeakView<Pane>,
+    entries: Vec<Entry>,
+    roots: Vec<usize>,
+    visible_entries: Vec<usize>,
+    selected_entry: Option<usize>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl HierarchyView {
+    fn deploy(workspace: &mut Workspace, kind: HierarchyKind, cx: &mut ViewContext<Workspace>) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+            let head = editor.selections.newest_anchor().head();
+            editor.buffer().read(cx).text_anchor_for_position(head, cx)
+        }) else {
+            return;
+        };
+
+        let project = workspace.project().clone();
+        let origin_pane = workspace.active_pane().downgrade();
+        let items = kind.prepare(&project, &buffer, position, cx);
+        cx.spawn(|workspace, mut cx| async move {
+            let items = items.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let workspace_handle = cx.view().downgrade();
+                let view = cx.new_view(|cx| {
+                    Self::new(kind, project, workspace_handle, origin_pane, items, cx)
+                });
+                workspace.split_item(SplitDirection::Right, Box::new(view), cx);
+            })
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    fn new(
+        kind: HierarchyKind,
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        origin_pane: WeakView<Pane>,
+        items: Vec<HierarchyItem>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut this = Self {
+            kind,
+            project,
+            workspace,
+            origin_pane,
+            entries: Vec::new(),
+            roots: Vec::new(),
+            visible_entries: Vec::new(),
+            selected_entry: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+        };
+        let roots = items
+            .into_iter()
+            .map(|item| this.push_entry(item, 0, 0))
+            .collect();
+        this.roots = roots;
+        if let [root] = this.roots[..] {
+            this.expand(root, cx);
+        }
+        this.selected_entry = this.roots.first().copied();
+        this.update_visible_entries(cx);
+        this
+    }
+
+    fn push_entry(&mut self, item: HierarchyItem, call_sites: usize, depth: usize) -> usize {
+        self.entries.push(Entry {
+            item,
+            call_sites,
+            depth,
+            expanded: false,
+            children: Children::NotLoaded,
+        });
+        self.entries.len() - 1
+    }
+
+    fn update_visible_entries(&mut self, cx: &mut ViewContext<Self>) {
+        fn visit(entries: &[Entry], ix: usize, visible_entries: &mut Vec<usize>) {
+            visible_entries.push(ix);
+            let entry = &entries[ix];
+            if let (true, Children::Loaded(children)) = (entry.expanded, &entry.children) {
+                for child in children {
+                    visit(entries, *child, visible_entries);
+                }
+            }
+        }
+
+        self.visible_entries.clear();
+        for root in &self.roots {
+            visit(&self.entries, *root, &mut self.visible_entries);
+        }
+        cx.notify();
+    }
+
+    fn expand(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let entry = &mut self.entries[ix];
+        entry.expanded = true;
+        if !matches!(entry.children, Children::NotLoaded) {
+            return;
+        }
+
+        entry.children = Children::Loading;
+        let children = self.kind.children(&self.project, &entry.item, cx);
+        let depth = entry.depth + 1;
+        cx.spawn(|this, mut cx| async move {
+            let children = children.await;
+            this.update(&mut cx, |this, cx| {
+                let children = match children {
+                    Ok(children) => children,
+                    Err(error) => {
+                        this.entries[ix].children = Children::NotLoaded;
+                        this.entries[ix].expanded = false;
+                        this.update_visible_entries(cx);
+                        return Err(error);
+                    }
+                };
+                let children = children
+                    .into_iter()
+                    .map(|(item, call_sites)| this.push_entry(item, call_sites, depth))
+                    .collect();
+                this.entries[ix].children = Children::Loaded(children);
+                this.update_visible_entries(cx);
+                Ok(())
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn toggle_expanded(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if self.entries[ix].expanded {
+            self.entries[ix].expanded = false;
+        } else {
+            self.expand(ix, cx);
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn selected_visible_index(&self) -> Option<usize> {
+        let selected_entry = self.selected_entry?;
+        self.visible_entries
+            .iter()
+            .position(|ix| *ix == selected_entry)
+    }
+
+    fn select_visible_index(&mut self, visible_ix: usize, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.visible_entries.get(visible_ix) {
+            self.selected_entry = Some(*ix);
+            self.scroll_handle
+                .scroll_to_item(visible_ix, ScrollStrategy::Top);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        let visible_ix = self
+            .selected_visible_index()
+            .map_or(0, |visible_ix| visible_ix + 1);
+        self.select_visible_index(visible_ix, cx);
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        let visible_ix = self
+            .selected_visible_index()
+            .map_or(0, |visible_ix| visible_ix.saturating_sub(1));
+        self.select_visible_index(visible_ix, cx);
+    }
+
+    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.selected_entry {
+            if !self.entries[ix].expanded {
+                self.expand(ix, cx);
+                self.update_visible_entries(cx);
+            }
+        }
+    }
+
+    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.selected_entry else {
+            return;
+        };
+        if self.entries[ix].expanded {
+            self.entries[ix].expanded = false;
+            self.update_visible_entries(cx);
+        } else if let Some(visible_ix) = self.selected_visible_index() {
+            // Move the selection to the parent, which is the closest preceding shallower entry.
+            let depth = self.entries[ix].depth;
+            if let Some(parent_visible_ix) = self.visible_entries[..visible_ix]
+                .iter()
+                .rposition(|ix| self.entries[*ix].depth < depth)
+            {
+                self.select_visible_index(parent_visible_ix, cx);
+            }
+        }
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.selected_entry {
+            self.open_entry(ix, cx);
+        }
+    }
+
+    fn open_entry(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let item = &self.entries[ix].item;
+        let buffer = item.location.buffer.clone();
+        let range = item.selection_range.to_offset(buffer.read(cx));
+        let origin_pane = self.origin_pane.upgrade();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let pane = origin_pane.unwrap_or_else(|| workspace.active_pane().clone());
+                let editor = workspace.open_project_item::<Editor>(pane, buffer, true, true, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([range]);
+                    });
+                });
+            })
+            .log_err();
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let entry = &self.entries[ix];
+        let toggle = match &entry.children {
+            Children::Loaded(children) if children.is_empty() => None,
+            _ => Some(entry.expanded),
+        };
+        let end_label = if matches!(entry.children, Children::Loading) {
+            Some(Label::new("Loading…"))
+        } else {
+            (entry.call_sites > 1).then(|| Label::new(format!("{} calls", entry.call_sites)))
+        };
+
+        ListItem::new(ix)
+            .spacing(ListItemSpacing::Sparse)
+            .indent_level(entry.depth)
+            .indent_step_size(px(12.))
+            .toggle(toggle)
+            .toggle_state(self.selected_entry == Some(ix))
+            .on_toggle(cx.listener(move |this, _, cx| this.toggle_expanded(ix, cx)))
+            .on_click(cx.listener(move |this, _, cx| {
+                this.selected_entry = Some(ix);
+                this.open_entry(ix, cx);
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(entry.item.name.clone()))
+                    .when_some(entry.item.detail.clone(), |this, detail| {
+                        this.child(
+                            Label::new(detail)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    }),
+            )
+            .end_slot(end_label.map(|label| label.size(LabelSize::Small).color(Color::Muted)))
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("HierarchyView");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+}
+
+impl Render for HierarchyView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if self.entries.is_empty() {
+            h_flex()
+                .size_full()
+                .justify_center()
+                .child(Label::new("No results").color(Color::Muted))
+                .into_any_element()
+        } else {
+            uniform_list(
+                cx.view().clone(),
+                "hierarchy-entries",
+                self.visible_entries.len(),
+                |this, range, cx| {
+                    range
+                        .map(|visible_ix| this.render_entry(this.visible_entries[visible_ix], cx))
+                        .collect()
+                },
+            )
+            .track_scroll(self.scroll_handle.clone())
+            .size_full()
+            .into_any_element()
+        };
+
+        v_flex()
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .p_1()
+            .bg(cx.theme().colors().editor_background)
+            .child(content)
+    }
+}
+
+impl EventEmitter<()> for HierarchyView {}
+
+impl FocusableView for HierarchyView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for HierarchyView {
+    type Event = ();
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        let title = self.kind.title();
+        Some(match self.roots.first() {
+            Some(root) => format!("{title}: {}", self.entries[*root].item.name).into(),
+            None => title.into(),
+        })
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::ListTree))
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        let items = self
+            .roots
+            .iter()
+            .map(|root| self.entries[*root].item.clone())
+            .collect();
+        Some(cx.new_view(|cx| {
+            Self::new(
+                self.kind,
+                self.project.clone(),
+                self.workspace.clone(),
+                self.origin_pane.clone(),
+                items,
+                cx,
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+    use project::FakeFs;
+    use serde_json::json;
+    use std::sync::Arc;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_incoming_calls_view(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "fn a() {}\nfn b() { a() }\nfn c() { a(); b(); a() }",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(rust_lang());
+        let mut fake_servers = language_registry.register_fake_lsp(
+            "Rust",
+            FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let (_buffer, _handle) = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+            })
+            .await
+            .unwrap();
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.rs"), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| s.select_ranges([3..3]));
+        });
+
+        fn item(name: &str, row: u32) -> lsp::CallHierarchyItem {
+            lsp::CallHierarchyItem {
+                name: name.to_string(),
+                kind: lsp::SymbolKind::FUNCTION,
+                tags: None,
+                detail: None,
+                uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+                range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 9)),
+                selection_range: lsp::Range::new(
+                    lsp::Position::new(row, 3),
+                    lsp::Position::new(row, 4),
+                ),
+                data: None,
+            }
+        }
+        fn call(from: lsp::CallHierarchyItem, columns: &[u32]) -> lsp::CallHierarchyIncomingCall {
+            let row = from.range.start.line;
+            lsp::CallHierarchyIncomingCall {
+                from,
+                from_ranges: columns
+                    .iter()
+                    .map(|column| {
+                        lsp::Range::new(
+                            lsp::Position::new(row, *column),
+                            lsp::Position::new(row, column + 1),
+                        )
+                    })
+                    .collect(),
+            }
+        }
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(
+            |params, _| async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 3)
+                );
+                Ok(Some(vec![item("a", 0)]))
+            },
+        );
+        fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+            |params, _| async move {
+                Ok(Some(match params.item.name.as_str() {
+                    "a" => vec![call(item("b", 1), &[9]), call(item("c", 2), &[9, 19])],
+                    "b" => vec![call(item("c", 2), &[14])],
+                    _ => Vec::new(),
+                }))
+            },
+        );
+
+        // The hierarchy opens next to the editor, with its single root expanded.
+        cx.dispatch_action(ShowIncomingCalls);
+        cx.run_until_parked();
+        let view = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<HierarchyView>(cx).unwrap()
+        });
+        assert_eq!(visible_entries(&view, cx), ["a", "  b", "  c (2 calls)"]);
+        assert_eq!(
+            view.update(cx, |view, cx| view.tab_content_text(cx)),
+            Some("Incoming Calls: a".into())
+        );
+
+        view.update(cx, |view, cx| {
+            view.select_next(&SelectNext, cx);
+            view.expand_selected_entry(&ExpandSelectedEntry, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries(&view, cx),
+            ["a", "  b", "    c", "  c (2 calls)"]
+        );
+
+        // Collapsing an entry hides its children, and collapsing it again selects its parent.
+        view.update(cx, |view, cx| {
+            view.collapse_selected_entry(&CollapseSelectedEntry, cx);
+        });
+        assert_eq!(visible_entries(&view, cx), ["a", "  b", "  c (2 calls)"]);
+        view.update(cx, |view, cx| {
+            view.collapse_selected_entry(&CollapseSelectedEntry, cx);
+            assert_eq!(view.selected_visible_index(), Some(0));
+        });
+
+        // Confirming an entry selects its name in the editor the hierarchy was opened from.
+        view.update(cx, |view, cx| {
+            view.select_next(&SelectNext, cx);
+            view.select_next(&SelectNext, cx);
+            view.confirm(&Confirm, cx);
+        });
+        cx.run_until_parked();
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.selections.newest::<usize>(cx).range(), 28..29);
+        });
+    }
+
+    fn visible_entries(view: &View<HierarchyView>, cx: &mut VisualTestContext) -> Vec<String> {
+        view.update(cx, |view, _| {
+            view.visible_entries
+                .iter()
+                .map(|ix| {
+                    let entry = &view.entries[*ix];
+                    let indent = "  ".repeat(entry.depth);
+                    if entry.call_sites > 1 {
+                        format!("{indent}{} ({} calls)", entry.item.name, entry.call_sites)
+                    } else {
+                        format!("{indent}{}", entry.item.name)
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            crate::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        ))
+    }
+}
@@ -351,6 +351,49 @@ fn test_edit_events(cx: &mut gpui::AppContext) {
     );
 }
 
+#[gpui::test]
+fn test_conflicts(cx: &mut AppContext) {
+    let text = indoc! {"
+        one
+        <<<<<<< HEAD
+        two
+        =======
+        TWO
+        >>>>>>> feature
+        three
+        <<<<<<< ours
+        four
+        ||||||| base
+        =======
+        FOUR
+        >>>>>>> theirs
+        <<<<<<< unterminated
+        five
+    "};
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+    let snapshot = buffer.read(cx).snapshot();
+    let text_for_range =
+        |range: &Range<Anchor>| snapshot.text_for_range(range.clone()).collect::<String>();
+
+    let conflicts = snapshot.conflicts();
+    assert_eq!(conflicts.len(), 2);
+
+    assert_eq!(
+        text_for_range(&conflicts[0].range),
+        "<<<<<<< HEAD\ntwo\n=======\nTWO\n>>>>>>> feature\n"
+    );
+    assert_eq!(text_for_range(&conflicts[0].ours), "two\n");
+    assert_eq!(conflicts[0].base, None);
+    assert_eq!(text_for_range(&conflicts[0].theirs), "TWO\n");
+
+    assert_eq!(text_for_range(&conflicts[1].ours), "four\n");
+    assert_eq!(
+        conflicts[1].base.as_ref().map(text_for_range).as_deref(),
+        Some("")
+    );
+    assert_eq!(text_for_range(&conflicts[1].theirs), "FOUR\n");
+}
+
 #[gpui::test]
 async fn test_apply_diff(cx: &mut TestAppContext) {
     let text = "a\nbb\nccc\ndddd\neeeee\nffffff\n";
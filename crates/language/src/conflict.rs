@@ -0,0 +1,134 @@
+use crate::BufferSnapshot;
+use std::ops::Range;
+use text::Anchor;
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// A region of a buffer delimited by the markers git writes when a merge conflicts.
+///
+/// ```text
+/// <<<<<<< ours
+/// our lines
+/// ||||||| base
+/// lines of the common ancestor, present in diff3-style conflicts
+/// =======
+/// their lines
+/// >>>>>>> theirs
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConflictRegion {
+    /// The whole region, from the start of the first marker line through the end of the last.
+    pub range: Range<Anchor>,
+    /// The lines of our side of the conflict, without markers.
+    pub ours: Range<Anchor>,
+    /// The lines of the common ancestor, without markers, if the conflict includes them.
+    pub base: Option<Range<Anchor>>,
+    /// The lines of their side of the conflict, without markers.
+    pub theirs: Range<Anchor>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Marker {
+    Ours,
+    Base,
+    Separator,
+    Theirs,
+}
+
+impl Marker {
+    fn parse(line: &str) -> Option<Self> {
+        let is_marker = |marker: &str| {
+            line.strip_prefix(marker).map_or(false, |rest| {
+                rest.is_empty() || rest.starts_with([' ', '\r'])
+            })
+        };
+        if is_marker(OURS_MARKER) {
+            Some(Self::Ours)
+        } else if is_marker(BASE_MARKER) {
+            Some(Self::Base)
+        } else if is_marker(SEPARATOR_MARKER) {
+            Some(Self::Separator)
+        } else if is_marker(THEIRS_MARKER) {
+            Some(Self::Theirs)
+        } else {
+            None
+        }
+    }
+}
+
+/// Offsets of the parts of a conflict whose end marker hasn't been found yet.
+#[derive(Default)]
+struct PendingConflict {
+    start: usize,
+    ours: Range<usize>,
+    base: Option<Range<usize>>,
+    theirs_start: Option<usize>,
+}
+
+impl BufferSnapshot {
+    /// Returns the merge conflicts in the buffer, in order.
+    ///
+    /// Markers that don't form a complete conflict are ignored.
+    pub fn conflicts(&self) -> Vec<ConflictRegion> {
+        let mut conflicts = Vec::new();
+        let mut pending: Option<PendingConflict> = None;
+        let mut lines = self.as_rope().chunks().lines();
+        let mut line_start = 0;
+        while let Some(line) = lines.next() {
+            let next_line_start = (line_start + line.len() + 1).min(self.len());
+            match Marker::parse(line) {
+                Some(Marker::Ours) => {
+                    pending = Some(PendingConflict {
+                        start: line_start,
+                        ours: next_line_start..next_line_start,
+                        ..Default::default()
+                    });
+                }
+                Some(Marker::Base) => {
+                    if let Some(pending) = pending
+                        .as_mut()
+                        .filter(|pending| pending.base.is_none() && pending.theirs_start.is_none())
+                    {
+                        pending.ours.end = line_start;
+                        pending.base = Some(next_line_start..next_line_start);
+                    }
+                }
+                Some(Marker::Separator) => {
+                    if let Some(pending) = pending
+                        .as_mut()
+                        .filter(|pending| pending.theirs_start.is_none())
+                    {
+                        match pending.base.as_mut() {
+                            Some(base) => base.end = line_start,
+                            None => pending.ours.end = line_start,
+                        }
+                        pending.theirs_start = Some(next_line_start);
+                    }
+                }
+                Some(Marker::Theirs) => {
+                    if let Some(pending) = pending.take() {
+                        if let Some(theirs_start) = pending.theirs_start {
+                            conflicts.push(ConflictRegion {
+                                range: self.anchor_before(pending.start)
+                                    ..self.anchor_after(next_line_start),
+                                ours: self.anchor_before(pending.ours.start)
+                                    ..self.anchor_after(pending.ours.end),
+                                base: pending.base.map(|base| {
+                                    self.anchor_before(base.start)..self.anchor_after(base.end)
+                                }),
+                                theirs: self.anchor_before(theirs_start)
+                                    ..self.anchor_after(line_start),
+                            });
+                        }
+                    }
+                }
+                None => {}
+            }
+            line_start = next_line_start;
+        }
+        conflicts
+    }
+}
@@ -7,6 +7,7 @@
 //!
 //! Notably we do *not* assign a single language to a single file; in real world a single file can consist of multiple programming languages - HTML is a good example of that - and `language` crate tends to reflect that status quo in its API.
 mod buffer;
+mod conflict;
 mod diagnostic_set;
 mod highlight_map;
 mod language_registry;
@@ -71,6 +72,7 @@ use util::serde::default_true;
 
 pub use buffer::Operation;
 pub use buffer::*;
+pub use conflict::ConflictRegion;
 pub use diagnostic_set::DiagnosticEntry;
 pub use language_registry::{
     AvailableLanguage, LanguageNotFound, LanguageQueries, LanguageRegistry,
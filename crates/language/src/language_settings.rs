@@ -121,6 +121,8 @@ pub struct LanguageSettings {
     pub extend_comment_on_newline: bool,
     /// Inlay hint related settings.
     pub inlay_hints: InlayHintSettings,
+    /// Semantic token highlighting related settings.
+    pub semantic_tokens: SemanticTokensSettings,
     /// Whether to automatically close brackets.
     pub use_autoclose: bool,
     /// Whether to automatically surround text with brackets.
@@ -136,6 +138,12 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to show code lenses from language servers above the lines they belong to.
+    pub show_code_lens: bool,
+    /// Where folding ranges come from.
+    pub folding_ranges: RangeSource,
+    /// Where the ranges used to expand and shrink selections come from.
+    pub selection_ranges: RangeSource,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
     /// Whether to pop the completions menu while typing in an editor without
@@ -345,6 +353,9 @@ pub struct LanguageSettingsContent {
     /// Inlay hint related settings.
     #[serde(default)]
     pub inlay_hints: Option<InlayHintSettings>,
+    /// Semantic token highlighting related settings.
+    #[serde(default)]
+    pub semantic_tokens: Option<SemanticTokensSettingsContent>,
     /// Whether to automatically type closing characters for you. For example,
     /// when you type (, Zed will automatically add a closing ) at the correct position.
     ///
@@ -378,6 +389,21 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to show code lenses, such as reference counts or "Run test" actions,
+    /// above the lines they belong to, if the language server supports it.
+    ///
+    /// Default: false
+    pub show_code_lens: Option<bool>,
+    /// Where folding ranges come from: the language server, when it supports
+    /// `textDocument/foldingRange`, or the indentation and syntax of the code.
+    ///
+    /// Default: auto
+    pub folding_ranges: Option<RangeSource>,
+    /// Where the ranges used to expand and shrink selections come from: the language
+    /// server, when it supports `textDocument/selectionRange`, or the syntax tree.
+    ///
+    /// Default: auto
+    pub selection_ranges: Option<RangeSource>,
     /// Whether indentation of pasted content should be adjusted based on the context.
     ///
     /// Default: true
@@ -433,6 +459,19 @@ pub enum SoftWrap {
     Bounded,
 }
 
+/// Where structural ranges, such as folding and selection ranges, come from.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeSource {
+    /// Use the language server's ranges if it provides them, and the syntax otherwise.
+    #[default]
+    Auto,
+    /// Only use the ranges provided by the language server.
+    LanguageServer,
+    /// Only use the ranges derived from the indentation and syntax of the code.
+    Syntax,
+}
+
 /// Controls the behavior of formatting files when they are saved.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FormatOnSave {
@@ -817,6 +856,49 @@ pub struct InlayHintSettings {
     pub scroll_debounce_ms: u64,
 }
 
+/// The settings for semantic token highlighting.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct SemanticTokensSettings {
+    /// Whether to highlight code using semantic tokens from language servers.
+    pub enabled: bool,
+    /// Maps semantic token types to syntax theme highlight names.
+    pub styles: HashMap<String, String>,
+}
+
+/// The settings for semantic token highlighting, as they appear in the settings file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct SemanticTokensSettingsContent {
+    /// Whether to highlight code using semantic tokens from language servers,
+    /// on top of the tree-sitter highlights.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Maps semantic token types to syntax theme highlight names.
+    ///
+    /// A token type can be qualified with one of its modifiers, as in
+    /// `"variable.readonly"`, to style only tokens with that modifier.
+    /// Tokens whose type is not listed keep their tree-sitter highlight,
+    /// and entries are merged with the ones from the default settings.
+    #[serde(default)]
+    pub styles: Option<HashMap<String, String>>,
+}
+
+impl SemanticTokensSettings {
+    /// Returns the highlight name for a token with the given type and modifiers,
+    /// preferring entries qualified with a modifier over the bare token type.
+    pub fn highlight_name<'a>(
+        &self,
+        token_type: &str,
+        mut modifiers: impl Iterator<Item = &'a str>,
+    ) -> Option<&str> {
+        modifiers
+            .find_map(|modifier| self.styles.get(&format!("{token_type}.{modifier}")))
+            .or_else(|| self.styles.get(token_type))
+            .map(String::as_str)
+    }
+}
+
 fn edit_debounce_ms() -> u64 {
     700
 }
@@ -1164,6 +1246,9 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.show_code_lens, src.show_code_lens);
+    merge(&mut settings.folding_ranges, src.folding_ranges);
+    merge(&mut settings.selection_ranges, src.selection_ranges);
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
@@ -1200,6 +1285,19 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.extend_comment_on_newline,
     );
     merge(&mut settings.inlay_hints, src.inlay_hints);
+    if let Some(semantic_tokens) = &src.semantic_tokens {
+        merge(
+            &mut settings.semantic_tokens.enabled,
+            semantic_tokens.enabled,
+        );
+        if let Some(styles) = &semantic_tokens.styles {
+            settings.semantic_tokens.styles.extend(
+                styles
+                    .iter()
+                    .map(|(token_type, style)| (token_type.clone(), style.clone())),
+            );
+        }
+    }
     merge(
         &mut settings.show_completions_on_input,
         src.show_completions_on_input,
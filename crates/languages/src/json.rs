@@ -122,6 +122,10 @@ impl JsonLspAdapter {
                             paths::local_tasks_file_relative_path()
                         ],
                         "schema": tasks_schema,
+                    },
+                    {
+                        "fileMatch": [paths::local_debug_file_relative_path()],
+                        "schema": task::DebugTemplates::generate_json_schema(),
                     }
 
                 ]
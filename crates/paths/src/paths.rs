@@ -151,6 +151,12 @@ pub fn keymap_file() -> &'static PathBuf {
     KEYMAP_FILE.get_or_init(|| config_dir().join("keymap.json"))
 }
 
+/// Returns the path to the `vimrc` file, read when vim mode is enabled.
+pub fn vimrc_file() -> &'static PathBuf {
+    static VIMRC_FILE: OnceLock<PathBuf> = OnceLock::new();
+    VIMRC_FILE.get_or_init(|| config_dir().join("vimrc"))
+}
+
 /// Returns the path to the `tasks.json` file.
 pub fn tasks_file() -> &'static PathBuf {
     static TASKS_FILE: OnceLock<PathBuf> = OnceLock::new();
@@ -203,6 +209,14 @@ pub fn contexts_dir() -> &'static PathBuf {
     })
 }
 
+/// Returns the path to the assistant tool logs directory.
+///
+/// This is where the record of every tool the Assistant ran in a thread is stored.
+pub fn assistant_tool_logs_dir() -> &'static PathBuf {
+    static ASSISTANT_TOOL_LOGS_DIR: OnceLock<PathBuf> = OnceLock::new();
+    ASSISTANT_TOOL_LOGS_DIR.get_or_init(|| support_dir().join("assistant_tool_logs"))
+}
+
 /// Returns the path to the contexts directory.
 ///
 /// This is where the prompts for use with the Assistant are stored.
@@ -299,6 +313,11 @@ pub fn local_tasks_file_relative_path() -> &'static Path {
     Path::new(".zed/tasks.json")
 }
 
+/// Returns the relative path to a `debug.json` file within a project.
+pub fn local_debug_file_relative_path() -> &'static Path {
+    Path::new(".zed/debug.json")
+}
+
 /// Returns the relative path to a `.vscode/tasks.json` file within a project.
 pub fn local_vscode_tasks_file_relative_path() -> &'static Path {
     Path::new(".vscode/tasks.json")
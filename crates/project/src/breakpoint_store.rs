@@ -0,0 +1,221 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collections::HashMap;
+use gpui::{AppContext, EventEmitter, Model, ModelContext, Subscription};
+use language::{Anchor, Buffer, BufferEvent, BufferSnapshot, File, Point, ToPoint as _};
+use text::BufferId;
+
+use crate::buffer_store::{BufferStore, BufferStoreEvent};
+
+pub enum BreakpointStoreEvent {
+    /// The breakpoints of the file at the given absolute path changed.
+    BreakpointsChanged(Arc<Path>),
+}
+
+impl EventEmitter<BreakpointStoreEvent> for BreakpointStore {}
+
+/// Keeps track of the breakpoints set in local files of the project.
+///
+/// Breakpoints are stored as zero-based rows per absolute path. While a file is open, its
+/// breakpoints are anchored in its buffer, so that they move along with edits.
+pub struct BreakpointStore {
+    buffer_store: Model<BufferStore>,
+    breakpoints: BTreeMap<Arc<Path>, Vec<u32>>,
+    open_buffers: HashMap<BufferId, OpenBuffer>,
+    _subscription: Subscription,
+}
+
+struct OpenBuffer {
+    abs_path: Arc<Path>,
+    anchors: Vec<Anchor>,
+    _subscription: Subscription,
+}
+
+impl BreakpointStore {
+    pub fn new(buffer_store: &Model<BufferStore>, cx: &mut ModelContext<Self>) -> Self {
+        Self {
+            buffer_store: buffer_store.clone(),
+            breakpoints: BTreeMap::default(),
+            open_buffers: HashMap::default(),
+            _subscription: cx.subscribe(buffer_store, Self::on_buffer_store_event),
+        }
+    }
+
+    fn on_buffer_store_event(
+        &mut self,
+        _: Model<BufferStore>,
+        event: &BufferStoreEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            BufferStoreEvent::BufferAdded(buffer) => {
+                if let Some(abs_path) = buffer_abs_path(buffer.read(cx), cx) {
+                    if self.breakpoints.contains_key(abs_path.as_path()) {
+                        self.track_buffer(buffer, abs_path.into(), cx);
+                    }
+                }
+            }
+            BufferStoreEvent::BufferDropped(buffer_id) => {
+                self.open_buffers.remove(buffer_id);
+            }
+            BufferStoreEvent::BufferChangedFilePath { buffer, .. } => {
+                self.open_buffers.remove(&buffer.read(cx).remote_id());
+                if let Some(abs_path) = buffer_abs_path(buffer.read(cx), cx) {
+                    if self.breakpoints.contains_key(abs_path.as_path()) {
+                        self.track_buffer(buffer, abs_path.into(), cx);
+                    }
+                }
+            }
+        }
+    }
+
+    fn track_buffer(
+        &mut self,
+        buffer: &Model<Buffer>,
+        abs_path: Arc<Path>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let anchors = self.anchors_for_rows(buffer.read(cx), &abs_path);
+        let subscription = cx.subscribe(buffer, |this, buffer, event, cx| {
+            if let BufferEvent::Edited = event {
+                this.buffer_edited(buffer, cx);
+            }
+        });
+        self.open_buffers.insert(
+            buffer_id,
+            OpenBuffer {
+                abs_path,
+                anchors,
+                _subscription: subscription,
+            },
+        );
+    }
+
+    fn anchors_for_rows(&self, buffer: &Buffer, abs_path: &Path) -> Vec<Anchor> {
+        let max_row = buffer.max_point().row;
+        self.breakpoints_for_path(abs_path)
+            .iter()
+            .map(|row| buffer.anchor_before(Point::new((*row).min(max_row), 0)))
+            .collect()
+    }
+
+    fn buffer_edited(&mut self, buffer: Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let buffer = buffer.read(cx);
+        let Some(open_buffer) = self.open_buffers.get(&buffer.remote_id()) else {
+            return;
+        };
+        let mut rows = open_buffer
+            .anchors
+            .iter()
+            .map(|anchor| anchor.to_point(buffer).row)
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows.dedup();
+
+        let abs_path = open_buffer.abs_path.clone();
+        if self.breakpoints_for_path(&abs_path) != rows {
+            self.breakpoints.insert(abs_path.clone(), rows);
+            cx.emit(BreakpointStoreEvent::BreakpointsChanged(abs_path));
+            cx.notify();
+        }
+    }
+
+    /// All breakpoints of the project, as zero-based rows per absolute path.
+    pub fn breakpoints(&self) -> impl '_ + Iterator<Item = (&Arc<Path>, &[u32])> {
+        self.breakpoints
+            .iter()
+            .map(|(abs_path, rows)| (abs_path, rows.as_slice()))
+    }
+
+    /// The zero-based rows of the breakpoints in the file at the given absolute path, in order.
+    pub fn breakpoints_for_path(&self, abs_path: &Path) -> &[u32] {
+        self.breakpoints
+            .get(abs_path)
+            .map_or(&[], |rows| rows.as_slice())
+    }
+
+    /// The zero-based rows of the breakpoints in the given buffer, in order.
+    pub fn breakpoints_for_buffer(&self, buffer: &BufferSnapshot, cx: &AppContext) -> &[u32] {
+        match file_abs_path(buffer.file(), cx) {
+            Some(abs_path) => self.breakpoints_for_path(&abs_path),
+            None => &[],
+        }
+    }
+
+    /// Adds a breakpoint at the given row of a buffer, or removes the one already there.
+    pub fn toggle_breakpoint(
+        &mut self,
+        buffer: &Model<Buffer>,
+        row: u32,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(abs_path) = buffer_abs_path(buffer.read(cx), cx) else {
+            return;
+        };
+        let mut rows = self.breakpoints_for_path(&abs_path).to_vec();
+        match rows.binary_search(&row) {
+            Ok(ix) => {
+                rows.remove(ix);
+            }
+            Err(ix) => rows.insert(ix, row),
+        }
+        self.set_breakpoints(abs_path.into(), rows, cx);
+    }
+
+    /// Replaces the breakpoints of the file at the given absolute path.
+    pub fn set_breakpoints(
+        &mut self,
+        abs_path: Arc<Path>,
+        mut rows: Vec<u32>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        rows.sort_unstable();
+        rows.dedup();
+        if self.breakpoints_for_path(&abs_path) == rows {
+            return;
+        }
+        if rows.is_empty() {
+            self.breakpoints.remove(&abs_path);
+        } else {
+            self.breakpoints.insert(abs_path.clone(), rows);
+        }
+
+        let buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter(|buffer| buffer_abs_path(buffer.read(cx), cx).as_deref() == Some(&*abs_path))
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            if self.breakpoints.contains_key(&abs_path) {
+                self.track_buffer(&buffer, abs_path.clone(), cx);
+            } else {
+                self.open_buffers.remove(&buffer.read(cx).remote_id());
+            }
+        }
+
+        cx.emit(BreakpointStoreEvent::BreakpointsChanged(abs_path));
+        cx.notify();
+    }
+
+    /// Removes every breakpoint of the project.
+    pub fn clear_breakpoints(&mut self, cx: &mut ModelContext<Self>) {
+        let abs_paths = self.breakpoints.keys().cloned().collect::<Vec<_>>();
+        for abs_path in abs_paths {
+            self.set_breakpoints(abs_path, Vec::new(), cx);
+        }
+    }
+}
+
+fn buffer_abs_path(buffer: &Buffer, cx: &AppContext) -> Option<PathBuf> {
+    file_abs_path(buffer.file(), cx)
+}
+
+fn file_abs_path(file: Option<&Arc<dyn File>>, cx: &AppContext) -> Option<PathBuf> {
+    Some(file?.as_local()?.abs_path(cx))
+}
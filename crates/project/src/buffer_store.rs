@@ -10,7 +10,7 @@ use client::Client;
 use collections::{hash_map, HashMap, HashSet};
 use fs::Fs;
 use futures::{channel::oneshot, future::Shared, Future, FutureExt as _, StreamExt};
-use git::{blame::Blame, diff::BufferDiff};
+use git::{blame::Blame, diff::BufferDiff, repository::DiffBase};
 use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Subscription,
     Task, WeakModel,
@@ -42,6 +42,8 @@ pub struct BufferStore {
     opened_buffers: HashMap<BufferId, OpenBuffer>,
     downstream_client: Option<(AnyProtoClient, u64)>,
     shared_buffers: HashMap<proto::PeerId, HashMap<BufferId, SharedBuffer>>,
+    default_diff_base: DiffBase,
+    diff_bases: HashMap<BufferId, DiffBase>,
 }
 
 #[derive(Hash, Eq, PartialEq, Clone)]
@@ -55,7 +57,13 @@ struct SharedBuffer {
 pub struct BufferChangeSet {
     pub buffer_id: BufferId,
     pub base_text: Option<Model<Buffer>>,
+    /// What [`Self::base_text`] was loaded from.
+    pub diff_base: DiffBase,
     pub diff_to_buffer: git::diff::BufferDiff,
+    /// The buffer's contents as of the HEAD commit, when known.
+    pub head_text: Option<Rope>,
+    /// The changes staged in the index relative to [`Self::head_text`], positioned in the buffer.
+    pub staged_diff: git::diff::BufferDiff,
     pub recalculate_diff_task: Option<Task<Result<()>>>,
     pub diff_updated_futures: Vec<oneshot::Sender<()>>,
     pub base_text_version: usize,
@@ -411,6 +419,97 @@ impl LocalBufferStore {
         worktree.read(cx).load_staged_file(path.as_ref(), cx)
     }
 
+    fn load_committed_text(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        let Some(file) = buffer.read(cx).file() else {
+            return Task::ready(Err(anyhow!("buffer has no file")));
+        };
+        let worktree_id = file.worktree_id(cx);
+        let path = file.path().clone();
+        let Some(worktree) = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+        else {
+            return Task::ready(Err(anyhow!("no such worktree")));
+        };
+
+        worktree.read(cx).load_committed_file(path.as_ref(), cx)
+    }
+
+    fn load_diff_base_text(
+        &self,
+        buffer: &Model<Buffer>,
+        diff_base: &DiffBase,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        let DiffBase::Revision(revision) = diff_base else {
+            return self.load_staged_text(buffer, cx);
+        };
+        let Some(file) = buffer.read(cx).file() else {
+            return Task::ready(Err(anyhow!("buffer has no file")));
+        };
+        let worktree_id = file.worktree_id(cx);
+        let path = file.path().clone();
+        let Some(worktree) = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+        else {
+            return Task::ready(Err(anyhow!("no such worktree")));
+        };
+
+        worktree
+            .read(cx)
+            .load_revision_file(path.as_ref(), revision.clone(), cx)
+    }
+
+    fn write_staged_text(
+        &self,
+        buffer: &Model<Buffer>,
+        text: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(file) = buffer.read(cx).file() else {
+            return Task::ready(Err(anyhow!("buffer has no file")));
+        };
+        let worktree_id = file.worktree_id(cx);
+        let path = file.path().clone();
+        let Some(worktree) = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+        else {
+            return Task::ready(Err(anyhow!("no such worktree")));
+        };
+
+        // The text is built from normalized buffer and base texts. Its line endings are the ones
+        // of the blob it replaces, since git may store blobs with other line endings than the
+        // file's (e.g. with `core.autocrlf`), and new blobs are stored with LF line endings.
+        let load_staged_text = worktree.read(cx).load_staged_file(path.as_ref(), cx);
+        cx.spawn(|mut cx| async move {
+            let line_ending = load_staged_text
+                .await
+                .log_err()
+                .flatten()
+                .map_or(LineEnding::Unix, |staged_text| {
+                    LineEnding::detect(&staged_text)
+                });
+            let text = text.map(|text| match line_ending {
+                LineEnding::Unix => text,
+                line_ending => text.replace('\n', line_ending.as_str()),
+            });
+            worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.write_staged_file(path.as_ref(), text, cx)
+                })?
+                .await
+        })
+    }
+
     fn save_local_buffer(
         &self,
         buffer_handle: Model<Buffer>,
@@ -541,7 +640,8 @@ impl LocalBufferStore {
                         .find(|(work_dir, _)| file.path.starts_with(work_dir))?;
                     let unstaged_changes = unstaged_changes.as_ref()?.upgrade()?;
                     let snapshot = buffer.text_snapshot();
-                    Some((unstaged_changes, snapshot, file.path.clone()))
+                    let diff_base = this.diff_base_for_buffer(buffer.remote_id()).clone();
+                    Some((unstaged_changes, snapshot, file.path.clone(), diff_base))
                 } else {
                     None
                 }
@@ -560,36 +660,42 @@ impl LocalBufferStore {
                 .spawn(async move {
                     buffer_change_sets
                         .into_iter()
-                        .filter_map(|(change_set, buffer_snapshot, path)| {
+                        .filter_map(|(change_set, buffer_snapshot, path, diff_base)| {
                             let (repo_entry, local_repo_entry) = snapshot.repo_for_path(&path)?;
                             let relative_path = repo_entry.relativize(&snapshot, &path).ok()?;
-                            let base_text = local_repo_entry.repo().load_index_text(&relative_path);
-                            Some((change_set, buffer_snapshot, base_text))
+                            let repo = local_repo_entry.repo();
+                            let (base_text, head_text) = match &diff_base {
+                                DiffBase::Index => (
+                                    repo.load_index_text(&relative_path),
+                                    repo.load_committed_text(&relative_path),
+                                ),
+                                DiffBase::Revision(revision) => (
+                                    Some(
+                                        repo.load_revision_text(revision, &relative_path)
+                                            .log_err()?
+                                            .unwrap_or_default(),
+                                    ),
+                                    None,
+                                ),
+                            };
+                            Some((change_set, buffer_snapshot, diff_base, base_text, head_text))
                         })
                         .collect::<Vec<_>>()
                 })
                 .await;
 
             this.update(&mut cx, |this, cx| {
-                for (change_set, buffer_snapshot, staged_text) in diff_bases_by_buffer {
-                    change_set.update(cx, |change_set, cx| {
-                        if let Some(staged_text) = staged_text.clone() {
-                            let _ =
-                                change_set.set_base_text(staged_text, buffer_snapshot.clone(), cx);
-                        } else {
-                            change_set.unset_base_text(buffer_snapshot.clone(), cx);
-                        }
-                    });
-
-                    if let Some((client, project_id)) = &this.downstream_client.clone() {
-                        client
-                            .send(proto::UpdateDiffBase {
-                                project_id: *project_id,
-                                buffer_id: buffer_snapshot.remote_id().to_proto(),
-                                staged_text,
-                            })
-                            .log_err();
-                    }
+                for (change_set, buffer_snapshot, diff_base, base_text, head_text) in
+                    diff_bases_by_buffer
+                {
+                    this.update_change_set_base(
+                        &change_set,
+                        buffer_snapshot,
+                        diff_base,
+                        base_text,
+                        head_text,
+                        cx,
+                    );
                 }
             })
         })
@@ -918,6 +1024,8 @@ impl BufferStore {
             shared_buffers: Default::default(),
             loading_buffers: Default::default(),
             loading_change_sets: Default::default(),
+            default_diff_base: DiffBase::default(),
+            diff_bases: Default::default(),
             worktree_store,
         }
     }
@@ -942,6 +1050,8 @@ impl BufferStore {
             loading_buffers: Default::default(),
             loading_change_sets: Default::default(),
             shared_buffers: Default::default(),
+            default_diff_base: DiffBase::default(),
+            diff_bases: Default::default(),
             worktree_store,
         }
     }
@@ -960,6 +1070,13 @@ impl BufferStore {
         }
     }
 
+    fn as_local(&self) -> Option<&LocalBufferStore> {
+        match &self.state {
+            BufferStoreState::Local(state) => Some(state),
+            _ => None,
+        }
+    }
+
     fn as_remote(&self) -> Option<&RemoteBufferStore> {
         match &self.state {
             BufferStoreState::Remote(state) => Some(state),
@@ -1023,20 +1140,37 @@ impl BufferStore {
             return Task::ready(Ok(change_set));
         }
 
+        let diff_base = self.diff_base_for_buffer(buffer_id).clone();
         let task = match self.loading_change_sets.entry(buffer_id) {
             hash_map::Entry::Occupied(e) => e.get().clone(),
             hash_map::Entry::Vacant(entry) => {
                 let load = match &self.state {
-                    BufferStoreState::Local(this) => this.load_staged_text(&buffer, cx),
+                    BufferStoreState::Local(this) => {
+                        this.load_diff_base_text(&buffer, &diff_base, cx)
+                    }
                     BufferStoreState::Remote(this) => this.load_staged_text(buffer_id, cx),
                 };
+                let load_head = match &self.state {
+                    BufferStoreState::Local(this) if diff_base.is_index() => {
+                        this.load_committed_text(&buffer, cx)
+                    }
+                    _ => Task::ready(Ok(None)),
+                };
 
                 entry
                     .insert(
                         cx.spawn(move |this, cx| async move {
-                            Self::open_unstaged_changes_internal(this, load.await, buffer, cx)
-                                .await
-                                .map_err(Arc::new)
+                            let head_text = load_head.await.log_err().flatten();
+                            Self::open_unstaged_changes_internal(
+                                this,
+                                load.await,
+                                head_text,
+                                diff_base,
+                                buffer,
+                                cx,
+                            )
+                            .await
+                            .map_err(Arc::new)
                         })
                         .shared(),
                     )
@@ -1057,6 +1191,8 @@ impl BufferStore {
     pub async fn open_unstaged_changes_internal(
         this: WeakModel<Self>,
         text: Result<Option<String>>,
+        head_text: Option<String>,
+        diff_base: DiffBase,
         buffer: Model<Buffer>,
         mut cx: AsyncAppContext,
     ) -> Result<Model<BufferChangeSet>> {
@@ -1072,13 +1208,17 @@ impl BufferStore {
         };
 
         let change_set = buffer.update(&mut cx, |buffer, cx| {
-            cx.new_model(|_| BufferChangeSet::new(buffer))
+            cx.new_model(|_| BufferChangeSet {
+                diff_base,
+                ..BufferChangeSet::new(buffer)
+            })
         })?;
 
         if let Some(text) = text {
             change_set
                 .update(&mut cx, |change_set, cx| {
                     let snapshot = buffer.read(cx).text_snapshot();
+                    change_set.set_head_text(head_text);
                     change_set.set_base_text(text, snapshot, cx)
                 })?
                 .await
@@ -1099,6 +1239,196 @@ impl BufferStore {
         Ok(change_set)
     }
 
+    /// Replaces the buffer's file contents in the git index, or removes the file from the
+    /// index when `text` is `None`.
+    pub fn write_staged_text(
+        &self,
+        buffer: &Model<Buffer>,
+        text: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        match &self.state {
+            BufferStoreState::Local(this) => this.write_staged_text(buffer, text, cx),
+            BufferStoreState::Remote(_) => Task::ready(Err(anyhow!(
+                "staging changes in remote projects is not supported"
+            ))),
+        }
+    }
+
+    /// The diff base of buffers that haven't been given one of their own.
+    pub fn default_diff_base(&self) -> &DiffBase {
+        &self.default_diff_base
+    }
+
+    pub fn diff_base_for_buffer(&self, buffer_id: BufferId) -> &DiffBase {
+        self.diff_bases
+            .get(&buffer_id)
+            .unwrap_or(&self.default_diff_base)
+    }
+
+    /// Changes what the buffers without a diff base of their own are compared against,
+    /// reloading the diffs of those that are open.
+    pub fn set_default_diff_base(
+        &mut self,
+        diff_base: DiffBase,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        Self::ensure_diff_base_supported(&self.state, &diff_base)?;
+        if self.default_diff_base == diff_base {
+            return Ok(());
+        }
+        self.default_diff_base = diff_base;
+        let buffer_ids = self
+            .opened_buffers
+            .keys()
+            .filter(|buffer_id| !self.diff_bases.contains_key(buffer_id))
+            .copied()
+            .collect();
+        self.reload_diff_bases(buffer_ids, cx);
+        Ok(())
+    }
+
+    /// Changes what a single buffer is compared against, or makes it follow the default
+    /// diff base again if `diff_base` is `None`.
+    pub fn set_buffer_diff_base(
+        &mut self,
+        buffer_id: BufferId,
+        diff_base: Option<DiffBase>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        match diff_base {
+            Some(diff_base) => {
+                Self::ensure_diff_base_supported(&self.state, &diff_base)?;
+                self.diff_bases.insert(buffer_id, diff_base);
+            }
+            None => {
+                self.diff_bases.remove(&buffer_id);
+            }
+        }
+        self.reload_diff_bases(vec![buffer_id], cx);
+        Ok(())
+    }
+
+    fn ensure_diff_base_supported(state: &BufferStoreState, diff_base: &DiffBase) -> Result<()> {
+        match state {
+            BufferStoreState::Remote(_) if !diff_base.is_index() => Err(anyhow!(
+                "diffing against other revisions in remote projects is not supported"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn reload_diff_bases(&mut self, buffer_ids: Vec<BufferId>, cx: &mut ModelContext<Self>) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let reloads = buffer_ids
+            .into_iter()
+            .filter_map(|buffer_id| {
+                let OpenBuffer::Complete {
+                    buffer,
+                    unstaged_changes,
+                } = self.opened_buffers.get(&buffer_id)?
+                else {
+                    return None;
+                };
+                let buffer = buffer.upgrade()?;
+                let change_set = unstaged_changes.as_ref()?.upgrade()?;
+                let diff_base = self.diff_base_for_buffer(buffer_id).clone();
+                let load_base = local.load_diff_base_text(&buffer, &diff_base, cx);
+                let load_head = if diff_base.is_index() {
+                    local.load_committed_text(&buffer, cx)
+                } else {
+                    Task::ready(Ok(None))
+                };
+                Some((buffer, change_set, diff_base, load_base, load_head))
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            for (buffer, change_set, diff_base, load_base, load_head) in reloads {
+                let Some(base_text) = load_base.await.log_err() else {
+                    continue;
+                };
+                let head_text = load_head.await.log_err().flatten();
+                this.update(&mut cx, |this, cx| {
+                    let buffer_snapshot = buffer.read(cx).text_snapshot();
+                    this.update_change_set_base(
+                        &change_set,
+                        buffer_snapshot,
+                        diff_base,
+                        base_text,
+                        head_text,
+                        cx,
+                    );
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn update_change_set_base(
+        &self,
+        change_set: &Model<BufferChangeSet>,
+        buffer_snapshot: text::BufferSnapshot,
+        diff_base: DiffBase,
+        base_text: Option<String>,
+        head_text: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let is_index = diff_base.is_index();
+        change_set.update(cx, |change_set, cx| {
+            change_set.diff_base = diff_base;
+            change_set.set_head_text(head_text);
+            if let Some(base_text) = base_text.clone() {
+                let _ = change_set.set_base_text(base_text, buffer_snapshot.clone(), cx);
+            } else {
+                change_set.unset_base_text(buffer_snapshot.clone(), cx);
+            }
+        });
+
+        let Some((client, project_id)) = self.downstream_client.clone() else {
+            return;
+        };
+        let buffer_id = buffer_snapshot.remote_id();
+        if is_index {
+            client
+                .send(proto::UpdateDiffBase {
+                    project_id,
+                    buffer_id: buffer_id.to_proto(),
+                    staged_text: base_text,
+                })
+                .log_err();
+        } else if let Some(buffer) = self.get(buffer_id) {
+            let staged_text = self.load_staged_text_for_guests(&buffer, cx);
+            cx.background_executor()
+                .spawn(async move {
+                    client.send(proto::UpdateDiffBase {
+                        project_id,
+                        buffer_id: buffer_id.to_proto(),
+                        staged_text: staged_text.await?,
+                    })
+                })
+                .detach_and_log_err(cx);
+        }
+    }
+
+    /// Loads the index text of a buffer. Guests always diff against the index, so this is the
+    /// staged text they are sent while the host diffs the buffer against another revision.
+    fn load_staged_text_for_guests(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        match &self.state {
+            BufferStoreState::Local(this) => this.load_staged_text(buffer, cx),
+            BufferStoreState::Remote(this) => {
+                this.load_staged_text(buffer.read(cx).remote_id(), cx)
+            }
+        }
+    }
+
     pub fn create_buffer(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<Model<Buffer>>> {
         match &self.state {
             BufferStoreState::Local(this) => this.create_buffer(cx),
@@ -1284,7 +1614,8 @@ impl BufferStore {
         buffer.update(cx, move |_, cx| {
             cx.on_release(move |buffer, cx| {
                 handle
-                    .update(cx, |_, cx| {
+                    .update(cx, |this, cx| {
+                        this.diff_bases.remove(&buffer.remote_id());
                         cx.emit(BufferStoreEvent::BufferDropped(buffer.remote_id()))
                     })
                     .ok();
@@ -1924,12 +2255,13 @@ impl BufferStore {
         mut cx: AsyncAppContext,
     ) -> Result<proto::GetStagedTextResponse> {
         let buffer_id = BufferId::new(request.payload.buffer_id)?;
+        let buffer = this
+            .update(&mut cx, |this, _| this.get(buffer_id))?
+            .ok_or_else(|| anyhow!("no such buffer"))?;
         let change_set = this
             .update(&mut cx, |this, cx| {
-                let buffer = this.get(buffer_id)?;
-                Some(this.open_unstaged_changes(buffer, cx))
+                this.open_unstaged_changes(buffer.clone(), cx)
             })?
-            .ok_or_else(|| anyhow!("no such buffer"))?
             .await?;
         this.update(&mut cx, |this, _| {
             let shared_buffers = this
@@ -1941,12 +2273,18 @@ impl BufferStore {
                 shared.unstaged_changes = Some(change_set.clone());
             }
         })?;
-        let staged_text = change_set.read_with(&cx, |change_set, cx| {
-            change_set
-                .base_text
-                .as_ref()
-                .map(|buffer| buffer.read(cx).text())
-        })?;
+        let staged_text = this
+            .update(&mut cx, |this, cx| {
+                let change_set = change_set.read(cx);
+                if change_set.diff_base.is_index() {
+                    return Task::ready(Ok(change_set
+                        .base_text
+                        .as_ref()
+                        .map(|buffer| buffer.read(cx).text())));
+                }
+                this.load_staged_text_for_guests(&buffer, cx)
+            })?
+            .await?;
         Ok(proto::GetStagedTextResponse { staged_text })
     }
 
@@ -2194,7 +2532,10 @@ impl BufferChangeSet {
         Self {
             buffer_id: buffer.remote_id(),
             base_text: None,
+            diff_base: DiffBase::Index,
             diff_to_buffer: git::diff::BufferDiff::new(buffer),
+            head_text: None,
+            staged_diff: git::diff::BufferDiff::new(buffer),
             recalculate_diff_task: None,
             diff_updated_futures: Vec::new(),
             base_text_version: 0,
@@ -2245,6 +2586,15 @@ impl BufferChangeSet {
         self.recalculate_diff_internal(base_text, buffer_snapshot, true, cx)
     }
 
+    /// Sets the text of the buffer as of the HEAD commit, used to compute [`Self::staged_diff`]
+    /// the next time the diff is recalculated.
+    pub fn set_head_text(&mut self, head_text: Option<String>) {
+        self.head_text = head_text.map(|mut head_text| {
+            LineEnding::normalize(&mut head_text);
+            Rope::from(head_text)
+        });
+    }
+
     pub fn unset_base_text(
         &mut self,
         buffer_snapshot: text::BufferSnapshot,
@@ -2253,6 +2603,7 @@ impl BufferChangeSet {
         if self.base_text.is_some() {
             self.base_text = None;
             self.diff_to_buffer = BufferDiff::new(&buffer_snapshot);
+            self.staged_diff = BufferDiff::new(&buffer_snapshot);
             self.recalculate_diff_task.take();
             self.base_text_version += 1;
             cx.notify();
@@ -2280,12 +2631,25 @@ impl BufferChangeSet {
     ) -> oneshot::Receiver<()> {
         let (tx, rx) = oneshot::channel();
         self.diff_updated_futures.push(tx);
+        let head_text = self.head_text.clone();
         self.recalculate_diff_task = Some(cx.spawn(|this, mut cx| async move {
-            let (base_text, diff) = cx
+            let (base_text, diff, staged_diff) = cx
                 .background_executor()
                 .spawn(async move {
                     let diff = BufferDiff::build(&base_text, &buffer_snapshot).await;
-                    (base_text, diff)
+                    let staged_diff = match head_text {
+                        Some(head_text) => {
+                            BufferDiff::build_staged(
+                                &head_text.to_string(),
+                                &Rope::from(base_text.as_str()),
+                                &diff,
+                                &buffer_snapshot,
+                            )
+                            .await
+                        }
+                        None => BufferDiff::new(&buffer_snapshot),
+                    };
+                    (base_text, diff, staged_diff)
                 })
                 .await;
             this.update(&mut cx, |this, cx| {
@@ -2296,6 +2660,7 @@ impl BufferChangeSet {
                     }));
                 }
                 this.diff_to_buffer = diff;
+                this.staged_diff = staged_diff;
                 this.recalculate_diff_task.take();
                 for tx in this.diff_updated_futures.drain(..) {
                     tx.send(()).ok();
@@ -10,8 +10,9 @@ use crate::{
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _, ProjectPath,
-    ProjectTransaction, ResolveState, Symbol, ToolchainStore,
+    CodeAction, CodeLens, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _,
+    ProjectPath, ProjectTransaction, ResolveState, SemanticToken, SemanticTokens, Symbol,
+    ToolchainStore,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
@@ -66,7 +67,7 @@ use snippet::Snippet;
 use std::{
     any::Any,
     cell::RefCell,
-    cmp::Ordering,
+    cmp::{Ordering, Reverse},
     convert::TryInto,
     ffi::OsStr,
     iter, mem,
@@ -93,6 +94,8 @@ pub use worktree::{
 };
 
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const PULL_DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(125);
+pub(crate) const WORKSPACE_DIAGNOSTICS_REPULL_DELAY: Duration = Duration::from_secs(2);
 pub const SERVER_PROGRESS_THROTTLE_TIMEOUT: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -171,6 +174,12 @@ pub struct LocalLspStore {
         >,
     >,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    semantic_tokens: HashMap<BufferId, CachedSemanticTokens>,
+    /// The result ids of the last diagnostics each language server reported for a
+    /// document in response to a pull, sent back with the next pull.
+    diagnostic_result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>,
+    pull_diagnostics_tasks: HashMap<BufferId, Task<()>>,
+    pull_workspace_diagnostics_tasks: HashMap<LanguageServerId, Task<()>>,
     _subscription: gpui::Subscription,
     registered_buffers: HashMap<BufferId, usize>,
 }
@@ -819,6 +828,68 @@ impl LocalLspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshCodeLens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshCodeLens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            if let Some(local) = this.as_local_mut() {
+                                local
+                                    .semantic_tokens
+                                    .retain(|_, cached| cached.server_id != server_id);
+                            }
+                            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshSemanticTokens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.refresh_pulled_diagnostics(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -1957,6 +2028,8 @@ impl LocalLspStore {
     ) {
         buffer.update(cx, |buffer, cx| {
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.semantic_tokens.remove(&buffer.remote_id());
+            self.pull_diagnostics_tasks.remove(&buffer.remote_id());
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
                     .notify::<lsp::notification::DidCloseTextDocument>(
@@ -2778,6 +2851,8 @@ pub enum LspStoreEvent {
     },
     Notification(String),
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     DiagnosticsUpdated {
         language_server_id: LanguageServerId,
         path: ProjectPath,
@@ -2831,10 +2906,18 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_model_request_handler(Self::handle_get_semantic_tokens);
+        client.add_model_request_handler(Self::handle_refresh_semantic_tokens);
+        client.add_model_request_handler(Self::handle_resolve_code_lens);
+        client.add_model_request_handler(Self::handle_apply_code_lens);
+        client.add_model_request_handler(Self::handle_refresh_code_lens);
         client.add_model_request_handler(Self::handle_on_type_formatting);
         client.add_model_request_handler(Self::handle_apply_additional_edits_for_completion);
         client.add_model_request_handler(Self::handle_register_buffer_with_language_servers);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCodeActions>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSelectionRanges>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCompletions>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetHover>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDefinition>);
@@ -2846,6 +2929,12 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
         client.add_model_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
     }
 
     pub fn as_remote(&self) -> Option<&RemoteLspStore> {
@@ -2945,6 +3034,10 @@ impl LspStore {
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
                 buffers_being_formatted: Default::default(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                diagnostic_result_ids: Default::default(),
+                pull_diagnostics_tasks: Default::default(),
+                pull_workspace_diagnostics_tasks: Default::default(),
                 prettier_store,
                 environment,
                 http_client,
@@ -3155,7 +3248,8 @@ impl LspStore {
     ) {
         match event {
             language::BufferEvent::Edited { .. } => {
-                self.on_buffer_edited(buffer, cx);
+                self.on_buffer_edited(buffer.clone(), cx);
+                self.pull_diagnostics(buffer, cx);
             }
 
             language::BufferEvent::Saved => {
@@ -3208,6 +3302,7 @@ impl LspStore {
             *refcount += 1;
             if *refcount == 1 {
                 local.register_buffer_with_language_servers(buffer, cx);
+                self.pull_diagnostics(buffer.clone(), cx);
             }
 
             cx.observe_release(&handle, move |this, buffer, cx| {
@@ -3741,6 +3836,139 @@ impl LspStore {
         }
     }
 
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        if lens.is_resolved() {
+            return Task::ready(Ok(lens));
+        }
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&lens)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("code lens proto request")?;
+                match response.lens {
+                    Some(lens) => Self::deserialize_code_lens(lens),
+                    None => Ok(lens),
+                }
+            })
+        } else {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, server)) =
+                self.language_server_for_local_buffer(buffer, lens.server_id, cx)
+            else {
+                return Task::ready(Ok(lens));
+            };
+            let can_resolve = server
+                .capabilities()
+                .code_lens_provider
+                .as_ref()
+                .and_then(|options| options.resolve_provider)
+                .unwrap_or(false);
+            if !can_resolve {
+                return Task::ready(Ok(lens));
+            }
+
+            let server = server.clone();
+            cx.spawn(move |_, _| async move {
+                let lsp_lens = server
+                    .request::<lsp::request::CodeLensResolve>(lens.lsp_lens)
+                    .await
+                    .context("code lens resolve LSP request")?;
+                Ok(CodeLens { lsp_lens, ..lens })
+            })
+        }
+    }
+
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        push_to_history: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ApplyCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&lens)),
+            };
+            let buffer_store = self.buffer_store();
+            cx.spawn(move |_, mut cx| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await?
+                    .transaction
+                    .ok_or_else(|| anyhow!("missing transaction"))?;
+
+                buffer_store
+                    .update(&mut cx, |buffer_store, cx| {
+                        buffer_store.deserialize_project_transaction(response, push_to_history, cx)
+                    })?
+                    .await
+            })
+        } else if self.mode.is_local() {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_local_buffer(buffer, lens.server_id, cx)
+            else {
+                return Task::ready(Ok(Default::default()));
+            };
+            let lang_server = lang_server.clone();
+            let Some(command) = lens.lsp_lens.command else {
+                return Task::ready(Err(anyhow!("code lens has no command")));
+            };
+            let executes_command = lang_server
+                .capabilities()
+                .execute_command_provider
+                .is_some_and(|options| options.commands.contains(&command.command));
+            if !executes_command {
+                return Task::ready(Err(anyhow!(
+                    "code lens command {:?} is not provided by language server {}, \
+                    and client-side commands are not supported",
+                    command.command,
+                    lang_server.name(),
+                )));
+            }
+
+            cx.spawn(move |this, mut cx| async move {
+                this.update(&mut cx, |this, _| {
+                    this.as_local_mut()
+                        .unwrap()
+                        .last_workspace_edits_by_language_server
+                        .remove(&lang_server.server_id());
+                })?;
+
+                lang_server
+                    .request::<lsp::request::ExecuteCommand>(lsp::ExecuteCommandParams {
+                        command: command.command,
+                        arguments: command.arguments.unwrap_or_default(),
+                        ..Default::default()
+                    })
+                    .await?;
+
+                this.update(&mut cx, |this, _| {
+                    this.as_local_mut()
+                        .unwrap()
+                        .last_workspace_edits_by_language_server
+                        .remove(&lang_server.server_id())
+                        .unwrap_or_default()
+                })
+            })
+        } else {
+            Task::ready(Err(anyhow!("no upstream client and not local")))
+        }
+    }
+
     pub fn resolve_inlay_hint(
         &self,
         hint: InlayHint,
@@ -3801,6 +4029,204 @@ impl LspStore {
         }
     }
 
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<SemanticTokens>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+                version: serialize_version(&buffer.read(cx).version()),
+            };
+            let buffer = buffer.clone();
+            return cx.spawn(move |_, mut cx| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("semantic tokens proto request")?;
+                let tokens = response
+                    .tokens
+                    .into_iter()
+                    .map(|token| {
+                        let start = token.start.and_then(deserialize_anchor);
+                        let end = token.end.and_then(deserialize_anchor);
+                        Ok(SemanticToken {
+                            range: start.zip(end).map(|(start, end)| start..end).ok_or_else(
+                                || anyhow!("missing semantic token range in proto response"),
+                            )?,
+                            token_type: token.token_type,
+                            token_modifiers: token.token_modifiers,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                buffer
+                    .update(&mut cx, |buffer, _| {
+                        buffer.wait_for_anchors(
+                            tokens
+                                .iter()
+                                .flat_map(|token| [token.range.start, token.range.end]),
+                        )
+                    })?
+                    .await?;
+                Ok(SemanticTokens {
+                    token_types: response.token_types.into(),
+                    token_modifiers: response.token_modifiers.into(),
+                    tokens,
+                })
+            });
+        }
+
+        let Some(local) = self.as_local() else {
+            return Task::ready(Ok(SemanticTokens::default()));
+        };
+        let buffer_ref = buffer.read(cx);
+        let buffer_id = buffer_ref.remote_id();
+        let Some(abs_path) = File::from_dyn(buffer_ref.file())
+            .and_then(File::as_local)
+            .map(|file| file.abs_path(cx))
+        else {
+            return Task::ready(Ok(SemanticTokens::default()));
+        };
+        let Some((server, options)) =
+            local
+                .language_servers_for_buffer(buffer_ref, cx)
+                .find_map(|(_, server)| {
+                    let options = semantic_tokens_options(&server.capabilities())?;
+                    match options.full {
+                        Some(lsp::SemanticTokensFullOptions::Bool(true))
+                        | Some(lsp::SemanticTokensFullOptions::Delta { .. }) => {
+                            Some((server.clone(), options))
+                        }
+                        _ => None,
+                    }
+                })
+        else {
+            return Task::ready(Ok(SemanticTokens::default()));
+        };
+        let uri = match lsp::Url::from_file_path(&abs_path) {
+            Ok(uri) => uri,
+            Err(()) => return Task::ready(Err(anyhow!("invalid file path {abs_path:?}"))),
+        };
+
+        let server_id = server.server_id();
+        let supports_delta = matches!(
+            options.full,
+            Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) })
+        );
+        let previous_result = local
+            .semantic_tokens
+            .get(&buffer_id)
+            .filter(|cached| supports_delta && cached.server_id == server_id)
+            .map(|cached| cached.result_id.clone());
+        let token_types: Arc<[String]> = options
+            .legend
+            .token_types
+            .iter()
+            .map(|token_type| token_type.as_str().to_string())
+            .collect();
+        let token_modifiers: Arc<[String]> = options
+            .legend
+            .token_modifiers
+            .iter()
+            .map(|modifier| modifier.as_str().to_string())
+            .collect();
+        let snapshot = buffer_ref.text_snapshot();
+        let text_document = lsp::TextDocumentIdentifier::new(uri);
+
+        cx.spawn(move |this, mut cx| async move {
+            let delta = if let Some(previous_result_id) = previous_result {
+                let response = server
+                    .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                        lsp::SemanticTokensDeltaParams {
+                            text_document: text_document.clone(),
+                            previous_result_id: previous_result_id.clone(),
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await?;
+                let mut apply_edits = |edits: Vec<lsp::SemanticTokensEdit>| {
+                    this.update(&mut cx, |this, _| {
+                        let cached = this
+                            .as_local_mut()
+                            .and_then(|local| local.semantic_tokens.remove(&buffer_id))
+                            .filter(|cached| cached.result_id == previous_result_id)
+                            .context("semantic tokens changed while requesting a delta")?;
+                        let data = apply_semantic_token_edits(cached.data, edits);
+                        if data.is_none() {
+                            log::warn!("misaligned semantic token edits from server {server_id}");
+                        }
+                        anyhow::Ok(data)
+                    })?
+                };
+                match response {
+                    Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                        Some((tokens.result_id, tokens.data))
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                        apply_edits(delta.edits)?.map(|data| (delta.result_id, data))
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                        apply_edits(edits)?.map(|data| (None, data))
+                    }
+                    None => Some((None, Vec::new())),
+                }
+            } else {
+                None
+            };
+            let (result_id, data) = match delta {
+                Some(delta) => delta,
+                None => {
+                    let response = server
+                        .request::<lsp::request::SemanticTokensFullRequest>(
+                            lsp::SemanticTokensParams {
+                                text_document,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?;
+                    match response {
+                        Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                            (tokens.result_id, tokens.data)
+                        }
+                        Some(lsp::SemanticTokensResult::Partial(partial)) => (None, partial.data),
+                        None => (None, Vec::new()),
+                    }
+                }
+            };
+
+            let tokens = decode_semantic_tokens(&data, &snapshot);
+            this.update(&mut cx, |this, _| {
+                if let Some(local) = this.as_local_mut() {
+                    match result_id {
+                        Some(result_id) => {
+                            local.semantic_tokens.insert(
+                                buffer_id,
+                                CachedSemanticTokens {
+                                    server_id,
+                                    result_id,
+                                    data,
+                                },
+                            );
+                        }
+                        None => {
+                            local.semantic_tokens.remove(&buffer_id);
+                        }
+                    }
+                }
+            })?;
+
+            Ok(SemanticTokens {
+                token_types,
+                token_modifiers,
+                tokens,
+            })
+        })
+    }
+
     pub(crate) fn linked_edit(
         &mut self,
         buffer: &Model<Buffer>,
@@ -5045,8 +5471,18 @@ impl LspStore {
             }
         }
 
-        for language_server_id in local.language_server_ids_for_buffer(buffer.read(cx), cx) {
+        let language_server_ids = local.language_server_ids_for_buffer(buffer.read(cx), cx);
+        for language_server_id in language_server_ids {
             self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
+
+            // Saving may change the diagnostics of other documents that depend on this one.
+            let inter_file_dependencies = self
+                .language_server_for_id(language_server_id)
+                .and_then(|server| diagnostic_options(&server.capabilities()))
+                .map_or(false, |options| options.inter_file_dependencies);
+            if inter_file_dependencies {
+                self.refresh_pulled_diagnostics(language_server_id, cx);
+            }
         }
 
         None
@@ -5816,6 +6252,61 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let resolve_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.resolve_code_lens(buffer, lens, cx))
+        })??;
+        let lens = resolve_code_lens.await?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(Self::serialize_code_lens(&lens)),
+        })
+    }
+
+    async fn handle_apply_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ApplyCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ApplyCodeLensResponse> {
+        let sender_id = envelope.original_sender_id().unwrap_or_default();
+        let lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let apply_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.apply_code_lens(buffer, lens, false, cx))
+        })??;
+
+        let project_transaction = apply_code_lens.await?;
+        let project_transaction = this.update(&mut cx, |this, cx| {
+            this.buffer_store.update(cx, |buffer_store, cx| {
+                buffer_store.serialize_project_transaction_for_peer(
+                    project_transaction,
+                    sender_id,
+                    cx,
+                )
+            })
+        })?;
+        Ok(proto::ApplyCodeLensResponse {
+            transaction: Some(project_transaction),
+        })
+    }
+
     async fn handle_register_buffer_with_language_servers(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::RegisterBufferWithLanguageServers>,
@@ -6570,6 +7061,64 @@ impl LspStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_refresh_code_lens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshCodeLens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_get_semantic_tokens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+
+        let semantic_tokens = this
+            .update(&mut cx, |this, cx| this.semantic_tokens(&buffer, cx))?
+            .await
+            .context("semantic tokens fetch")?;
+        Ok(proto::GetSemanticTokensResponse {
+            token_types: semantic_tokens.token_types.to_vec(),
+            token_modifiers: semantic_tokens.token_modifiers.to_vec(),
+            tokens: semantic_tokens
+                .tokens
+                .into_iter()
+                .map(|token| proto::SemanticToken {
+                    start: Some(serialize_anchor(&token.range.start)),
+                    end: Some(serialize_anchor(&token.range.end)),
+                    token_type: token.token_type,
+                    token_modifiers: token.token_modifiers,
+                })
+                .collect(),
+        })
+    }
+
     async fn handle_inlay_hints(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::InlayHints>,
@@ -7096,6 +7645,8 @@ impl LspStore {
             });
         }
         local.language_server_watched_paths.remove(&server_id);
+        local.diagnostic_result_ids.remove(&server_id);
+        local.pull_workspace_diagnostics_tasks.remove(&server_id);
         let server_state = local.language_servers.remove(&server_id);
         cx.notify();
         cx.emit(LspStoreEvent::LanguageServerRemoved(server_id));
@@ -7324,6 +7875,287 @@ impl LspStore {
         Ok(())
     }
 
+    /// Pulls the diagnostics of a buffer from every language server that supports
+    /// `textDocument/diagnostic`, after a short debounce.
+    pub fn pull_diagnostics(&mut self, buffer: Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        if !local.registered_buffers.contains_key(&buffer_id) {
+            return;
+        }
+
+        let task = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(PULL_DIAGNOSTICS_DEBOUNCE)
+                .await;
+
+            let Some((uri, requests)) = this
+                .update(&mut cx, |this, cx| {
+                    let local = this.as_local()?;
+                    let buffer = buffer.read(cx);
+                    let file = File::from_dyn(buffer.file())?;
+                    let uri = lsp::Url::from_file_path(file.as_local()?.abs_path(cx)).ok()?;
+                    let requests = local
+                        .language_servers_for_buffer(buffer, cx)
+                        .filter_map(|(adapter, server)| {
+                            let options = diagnostic_options(&server.capabilities())?;
+                            let server_id = server.server_id();
+                            let version = local
+                                .buffer_snapshots
+                                .get(&buffer_id)
+                                .and_then(|snapshots| snapshots.get(&server_id))
+                                .and_then(|snapshots| snapshots.last())
+                                .map(|snapshot| snapshot.version);
+                            let previous_result_id = local
+                                .diagnostic_result_ids
+                                .get(&server_id)
+                                .and_then(|result_ids| result_ids.get(&uri))
+                                .cloned();
+                            let request = server
+                                .request::<lsp::request::DocumentDiagnosticRequest>(
+                                    lsp::DocumentDiagnosticParams {
+                                        text_document: lsp::TextDocumentIdentifier::new(
+                                            uri.clone(),
+                                        ),
+                                        identifier: options.identifier,
+                                        previous_result_id,
+                                        work_done_progress_params: Default::default(),
+                                        partial_result_params: Default::default(),
+                                    },
+                                );
+                            let adapter = adapter.clone();
+                            Some(async move { (adapter, server_id, version, request.await) })
+                        })
+                        .collect::<Vec<_>>();
+                    Some((uri, requests))
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+
+            let reports = join_all(requests).await;
+            this.update(&mut cx, |this, cx| {
+                for (adapter, server_id, version, report) in reports {
+                    let Some(report) = report.log_err() else {
+                        continue;
+                    };
+                    this.apply_document_diagnostic_report(
+                        server_id,
+                        &adapter,
+                        uri.clone(),
+                        version,
+                        report,
+                        cx,
+                    );
+                }
+            })
+            .ok();
+        });
+        local.pull_diagnostics_tasks.insert(buffer_id, task);
+    }
+
+    /// Pulls the diagnostics of the whole workspace from a language server, if it
+    /// supports `workspace/diagnostic`, and pulls again after each response or error.
+    fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let Some(LanguageServerState::Running {
+            adapter, server, ..
+        }) = local.language_servers.get(&server_id)
+        else {
+            return;
+        };
+        let Some(options) = diagnostic_options(&server.capabilities()) else {
+            return;
+        };
+        if !options.workspace_diagnostics {
+            return;
+        }
+
+        let previous_result_ids = local
+            .diagnostic_result_ids
+            .get(&server_id)
+            .into_iter()
+            .flatten()
+            .map(|(uri, value)| lsp::PreviousResultId {
+                uri: uri.clone(),
+                value: value.clone(),
+            })
+            .collect();
+        let request = server.request::<lsp::request::WorkspaceDiagnosticRequest>(
+            lsp::WorkspaceDiagnosticParams {
+                identifier: options.identifier,
+                previous_result_ids,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        );
+        let adapter = adapter.clone();
+        let task = cx.spawn(move |this, mut cx| async move {
+            // A failed pull, e.g. one the server cancelled, is retried like any other.
+            if let Some(report) = request.await.log_err() {
+                let items = match report {
+                    lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+                    lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+                };
+                this.update(&mut cx, |this, cx| {
+                    for item in items {
+                        let (uri, version, report) = match item {
+                            lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                                report.uri,
+                                report.version,
+                                lsp::DocumentDiagnosticReportKind::Full(
+                                    report.full_document_diagnostic_report,
+                                ),
+                            ),
+                            lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                                report.uri,
+                                report.version,
+                                lsp::DocumentDiagnosticReportKind::Unchanged(
+                                    report.unchanged_document_diagnostic_report,
+                                ),
+                            ),
+                        };
+                        let version = version.and_then(|version| i32::try_from(version).ok());
+                        this.apply_diagnostic_report(server_id, &adapter, uri, version, report, cx);
+                    }
+                })
+                .ok();
+            }
+
+            // Servers may hold workspace pulls open until their diagnostics change, so
+            // keep one in flight, without flooding servers that answer right away.
+            cx.background_executor()
+                .timer(WORKSPACE_DIAGNOSTICS_REPULL_DELAY)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.pull_workspace_diagnostics(server_id, cx);
+            })
+            .ok();
+        });
+        local
+            .pull_workspace_diagnostics_tasks
+            .insert(server_id, task);
+    }
+
+    /// Pulls diagnostics again for every open buffer of a language server and for its
+    /// workspace, e.g. after the server asked for a refresh.
+    fn refresh_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter(|buffer| {
+                let buffer = buffer.read(cx);
+                local.registered_buffers.contains_key(&buffer.remote_id())
+                    && local
+                        .language_server_ids_for_buffer(buffer, cx)
+                        .contains(&server_id)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_diagnostics(buffer, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+    }
+
+    fn apply_document_diagnostic_report(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportResult,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let (report, related_documents) = match report {
+            lsp::DocumentDiagnosticReportResult::Report(lsp::DocumentDiagnosticReport::Full(
+                report,
+            )) => (
+                Some(lsp::DocumentDiagnosticReportKind::Full(
+                    report.full_document_diagnostic_report,
+                )),
+                report.related_documents,
+            ),
+            lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Unchanged(report),
+            ) => (
+                Some(lsp::DocumentDiagnosticReportKind::Unchanged(
+                    report.unchanged_document_diagnostic_report,
+                )),
+                report.related_documents,
+            ),
+            lsp::DocumentDiagnosticReportResult::Partial(report) => {
+                (None, report.related_documents)
+            }
+        };
+
+        if let Some(report) = report {
+            self.apply_diagnostic_report(server_id, adapter, uri, version, report, cx);
+        }
+        for (uri, report) in related_documents.into_iter().flatten() {
+            self.apply_diagnostic_report(server_id, adapter, uri, None, report, cx);
+        }
+    }
+
+    /// Feeds a pulled diagnostic report into the same path as published diagnostics,
+    /// and remembers its result id for the next pull.
+    fn apply_diagnostic_report(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let result_id = match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri: uri.clone(),
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+                .log_err();
+                report.result_id
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => Some(report.result_id),
+        };
+
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let result_ids = local.diagnostic_result_ids.entry(server_id).or_default();
+        if let Some(result_id) = result_id {
+            result_ids.insert(uri, result_id);
+        } else {
+            result_ids.remove(&uri);
+        }
+    }
+
     fn insert_newly_running_language_server(
         &mut self,
         language: LanguageName,
@@ -7392,6 +8224,9 @@ impl LspStore {
             Some(key.0),
         ));
         cx.emit(LspStoreEvent::RefreshInlayHints);
+        cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        cx.emit(LspStoreEvent::RefreshCodeLens);
+        self.refresh_pulled_diagnostics(server_id, cx);
 
         if let Some((downstream_client, project_id)) = self.downstream_client.as_ref() {
             downstream_client
@@ -7828,6 +8663,32 @@ impl LspStore {
         })
     }
 
+    pub(crate) fn serialize_code_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+        }
+    }
+
+    pub(crate) fn deserialize_code_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_lens = serde_json::from_slice(&lens.lsp_lens)?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens,
+        })
+    }
+
     fn update_last_formatting_failure<T>(&mut self, formatting_result: &anyhow::Result<T>) {
         match &formatting_result {
             Ok(_) => self.last_formatting_failure = None,
@@ -8600,6 +9461,89 @@ async fn populate_labels_for_symbols(
     }
 }
 
+/// The last semantic tokens a language server reported for a buffer, kept so that
+/// the next request can ask the server for a delta against them.
+struct CachedSemanticTokens {
+    server_id: LanguageServerId,
+    result_id: String,
+    data: Vec<lsp::SemanticToken>,
+}
+
+fn diagnostic_options(capabilities: &lsp::ServerCapabilities) -> Option<lsp::DiagnosticOptions> {
+    match capabilities.diagnostic_provider.as_ref()? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options.clone()),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(options.diagnostic_options.clone())
+        }
+    }
+}
+
+fn semantic_tokens_options(
+    capabilities: &lsp::ServerCapabilities,
+) -> Option<lsp::SemanticTokensOptions> {
+    match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => {
+            Some(options.clone())
+        }
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            Some(options.semantic_tokens_options.clone())
+        }
+    }
+}
+
+/// Applies semantic token edits to previously reported tokens. Edit offsets count
+/// the integers of the flattened token array, five per token, so edits that split a
+/// token or reach past the reported tokens can't be applied, and yield `None`.
+fn apply_semantic_token_edits(
+    mut data: Vec<lsp::SemanticToken>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) -> Option<Vec<lsp::SemanticToken>> {
+    edits.sort_by_key(|edit| Reverse(edit.start));
+    for edit in edits {
+        if edit.start % 5 != 0 || edit.delete_count % 5 != 0 {
+            return None;
+        }
+        let start = edit.start as usize / 5;
+        let end = start + edit.delete_count as usize / 5;
+        if end > data.len() {
+            return None;
+        }
+        data.splice(start..end, edit.data.unwrap_or_default());
+    }
+    Some(data)
+}
+
+fn decode_semantic_tokens(
+    data: &[lsp::SemanticToken],
+    snapshot: &TextBufferSnapshot,
+) -> Vec<SemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len());
+    let mut line = 0;
+    let mut start_column = 0;
+    for token in data {
+        if token.delta_line > 0 {
+            line += token.delta_line;
+            start_column = token.delta_start;
+        } else {
+            start_column += token.delta_start;
+        }
+        let start =
+            snapshot.clip_point_utf16(Unclipped(PointUtf16::new(line, start_column)), Bias::Left);
+        let end = snapshot.clip_point_utf16(
+            Unclipped(PointUtf16::new(line, start_column + token.length)),
+            Bias::Right,
+        );
+        if start < end {
+            tokens.push(SemanticToken {
+                range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+                token_type: token.token_type,
+                token_modifiers: token.token_modifiers_bitset,
+            });
+        }
+    }
+    tokens
+}
+
 fn include_text(server: &lsp::LanguageServer) -> Option<bool> {
     match server.capabilities().text_document_sync.as_ref()? {
         lsp::TextDocumentSyncCapability::Kind(kind) => match *kind {
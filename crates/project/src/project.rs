@@ -1,3 +1,4 @@
+pub mod breakpoint_store;
 pub mod buffer_store;
 mod color_extractor;
 pub mod connection_manager;
@@ -25,6 +26,7 @@ pub mod search_history;
 mod yarn;
 
 use anyhow::{anyhow, Context as _, Result};
+pub use breakpoint_store::{BreakpointStore, BreakpointStoreEvent};
 use buffer_store::{BufferChangeSet, BufferStore, BufferStoreEvent};
 use client::{proto, Client, Collaborator, PendingEntitySubscription, TypedEnvelope, UserStore};
 use clock::ReplicaId;
@@ -39,7 +41,12 @@ use futures::{
 pub use image_store::{ImageItem, ImageStore};
 use image_store::{ImageItemEvent, ImageStoreEvent};
 
-use git::{blame::Blame, repository::GitRepository};
+use git::{
+    blame::Blame,
+    commit::{CommitFileChange, CommitLog},
+    repository::{DiffBase, GitRepository, PullStrategy, StashEntry},
+    Oid,
+};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BorrowAppContext, Context as _, EventEmitter, Hsla,
     Model, ModelContext, SharedString, Task, WeakModel, WindowContext,
@@ -154,6 +161,7 @@ pub struct Project {
     worktree_store: Model<WorktreeStore>,
     buffer_store: Model<BufferStore>,
     image_store: Model<ImageStore>,
+    breakpoint_store: Model<BreakpointStore>,
     lsp_store: Model<LspStore>,
     _subscriptions: Vec<gpui::Subscription>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
@@ -247,6 +255,7 @@ pub enum Event {
     WorktreeRemoved(WorktreeId),
     WorktreeUpdatedEntries(WorktreeId, UpdatedEntriesSet),
     WorktreeUpdatedGitRepositories(WorktreeId),
+    DiffBaseChanged,
     DiskBasedDiagnosticsStarted {
         language_server_id: LanguageServerId,
     },
@@ -272,6 +281,8 @@ pub enum Event {
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
 }
@@ -393,6 +404,40 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A range of a buffer that can be folded, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct FoldingRange {
+    /// The folded range, from the end of its first line to the end of its last line.
+    pub range: Range<Anchor>,
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
+/// A code lens provided by a language server.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer this code lens is attached to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server. Its command is only
+    /// known once the code lens is resolved.
+    pub lsp_lens: lsp::CodeLens,
+}
+
+impl CodeLens {
+    pub fn is_resolved(&self) -> bool {
+        self.lsp_lens.command.is_some()
+    }
+
+    /// The text to display for this code lens, if it is resolved.
+    pub fn title(&self) -> Option<&str> {
+        self.lsp_lens
+            .command
+            .as_ref()
+            .map(|command| command.title.as_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -446,6 +491,67 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// An entry in a call or type hierarchy.
+#[derive(Debug, Clone)]
+pub struct HierarchyItem {
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub detail: Option<String>,
+    /// The full range of the symbol, including its body.
+    pub location: Location,
+    /// The range that should be selected when navigating to the symbol, e.g. its name.
+    pub selection_range: Range<language::Anchor>,
+    /// The item as returned by the language server, sent back verbatim when expanding it.
+    pub(crate) lsp_item: serde_json::Value,
+}
+
+/// A caller or callee of a call hierarchy item, together with the ranges of the calls.
+#[derive(Debug, Clone)]
+pub struct HierarchyCall {
+    pub item: HierarchyItem,
+    pub call_sites: Vec<Location>,
+}
+
+/// The semantic tokens a language server reported for a buffer.
+#[derive(Debug, Clone, Default)]
+pub struct SemanticTokens {
+    /// The token types of the server's legend, indexed by [`SemanticToken::token_type`].
+    pub token_types: Arc<[String]>,
+    /// The token modifiers of the server's legend, indexed by the bits of
+    /// [`SemanticToken::token_modifiers`].
+    pub token_modifiers: Arc<[String]>,
+    /// The tokens, sorted by range and non-overlapping.
+    pub tokens: Vec<SemanticToken>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SemanticToken {
+    pub range: Range<language::Anchor>,
+    pub token_type: u32,
+    pub token_modifiers: u32,
+}
+
+impl SemanticTokens {
+    pub fn token_type(&self, token: &SemanticToken) -> Option<&str> {
+        self.token_types
+            .get(token.token_type as usize)
+            .map(String::as_str)
+    }
+
+    pub fn token_modifiers<'a>(
+        &'a self,
+        token: &SemanticToken,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        let bits = token.token_modifiers;
+        self.token_modifiers
+            .iter()
+            .take(u32::BITS as usize)
+            .enumerate()
+            .filter(move |(ix, _)| bits & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+    }
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -617,6 +723,7 @@ impl Project {
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
 
+            let breakpoint_store = cx.new_model(|cx| BreakpointStore::new(&buffer_store, cx));
             let image_store = cx.new_model(|cx| ImageStore::local(worktree_store.clone(), cx));
             cx.subscribe(&image_store, Self::on_image_store_event)
                 .detach();
@@ -683,6 +790,7 @@ impl Project {
                 worktree_store,
                 buffer_store,
                 image_store,
+                breakpoint_store,
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -701,6 +809,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -746,6 +855,7 @@ impl Project {
                     cx,
                 )
             });
+            let breakpoint_store = cx.new_model(|cx| BreakpointStore::new(&buffer_store, cx));
             let image_store = cx.new_model(|cx| {
                 ImageStore::remote(
                     worktree_store.clone(),
@@ -801,6 +911,7 @@ impl Project {
                 worktree_store,
                 buffer_store,
                 image_store,
+                breakpoint_store,
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -834,6 +945,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -947,6 +1059,7 @@ impl Project {
         let buffer_store = cx.new_model(|cx| {
             BufferStore::remote(worktree_store.clone(), client.clone().into(), remote_id, cx)
         })?;
+        let breakpoint_store = cx.new_model(|cx| BreakpointStore::new(&buffer_store, cx))?;
         let image_store = cx.new_model(|cx| {
             ImageStore::remote(worktree_store.clone(), client.clone().into(), remote_id, cx)
         })?;
@@ -1014,6 +1127,7 @@ impl Project {
                 buffer_ordered_messages_tx: tx,
                 buffer_store: buffer_store.clone(),
                 image_store,
+                breakpoint_store,
                 worktree_store: worktree_store.clone(),
                 lsp_store: lsp_store.clone(),
                 active_entry: None,
@@ -1039,6 +1153,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -1907,6 +2022,110 @@ impl Project {
         })
     }
 
+    /// Replaces the buffer's file contents in the git index, or removes the file from the
+    /// index when `text` is `None`.
+    pub fn write_staged_text(
+        &self,
+        buffer: &Model<Buffer>,
+        text: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.buffer_store
+            .read(cx)
+            .write_staged_text(buffer, text, cx)
+    }
+
+    /// The revision that buffers without a diff base of their own are compared against.
+    pub fn default_diff_base<'a>(&self, cx: &'a AppContext) -> &'a DiffBase {
+        self.buffer_store.read(cx).default_diff_base()
+    }
+
+    pub fn diff_base_for_buffer<'a>(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &'a AppContext,
+    ) -> &'a DiffBase {
+        let buffer_id = buffer.read(cx).remote_id();
+        self.buffer_store.read(cx).diff_base_for_buffer(buffer_id)
+    }
+
+    /// Changes what the project's buffers are compared against in their diffs, such as the
+    /// index, `HEAD` or a branch.
+    pub fn set_default_diff_base(
+        &mut self,
+        diff_base: DiffBase,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.set_default_diff_base(diff_base, cx)
+        })?;
+        cx.emit(Event::DiffBaseChanged);
+        Ok(())
+    }
+
+    /// Changes what a single buffer is compared against, or makes it use the project's
+    /// default diff base again if `diff_base` is `None`.
+    pub fn set_buffer_diff_base(
+        &mut self,
+        buffer: &Model<Buffer>,
+        diff_base: Option<DiffBase>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let buffer_id = buffer.read(cx).remote_id();
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.set_buffer_diff_base(buffer_id, diff_base, cx)
+        })?;
+        cx.emit(Event::DiffBaseChanged);
+        Ok(())
+    }
+
+    /// Returns the paths in the worktree whose contents differ from the given revision.
+    pub fn paths_changed_since(
+        &self,
+        worktree_id: WorktreeId,
+        revision: SharedString,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<Arc<Path>>>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no such worktree")));
+        };
+        worktree.read(cx).paths_changed_since(revision, cx)
+    }
+
+    /// Loads a page of the history of the git repository containing the given path.
+    ///
+    /// If `only_path` is set, only the commits that changed the path are included.
+    pub fn load_commit_log(
+        &self,
+        project_path: &ProjectPath,
+        only_path: bool,
+        skip: usize,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<CommitLog>> {
+        let Some(worktree) = self.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no such worktree")));
+        };
+        worktree
+            .read(cx)
+            .load_commit_log(&project_path.path, only_path, skip, limit, cx)
+    }
+
+    /// Loads the files changed by a commit in the git repository containing the given path.
+    pub fn load_commit_changes(
+        &self,
+        project_path: &ProjectPath,
+        sha: Oid,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CommitFileChange>>> {
+        let Some(worktree) = self.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no such worktree")));
+        };
+        worktree
+            .read(cx)
+            .load_commit_changes(&project_path.path, sha, cx)
+    }
+
     pub fn open_buffer_by_id(
         &mut self,
         id: BufferId,
@@ -2191,6 +2410,8 @@ impl Project {
                 };
             }
             LspStoreEvent::RefreshInlayHints => cx.emit(Event::RefreshInlayHints),
+            LspStoreEvent::RefreshSemanticTokens => cx.emit(Event::RefreshSemanticTokens),
+            LspStoreEvent::RefreshCodeLens => cx.emit(Event::RefreshCodeLens),
             LspStoreEvent::LanguageServerPrompt(prompt) => {
                 cx.emit(Event::LanguageServerPrompt(prompt.clone()))
             }
@@ -2739,6 +2960,113 @@ impl Project {
         )
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &mut self,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyCall>>> {
+        let lsp_item = match serde_json::from_value(item.lsp_item.clone()) {
+            Ok(lsp_item) => lsp_item,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetIncomingCalls { item: lsp_item },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &mut self,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyCall>>> {
+        let lsp_item = match serde_json::from_value(item.lsp_item.clone()) {
+            Ok(lsp_item) => lsp_item,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetOutgoingCalls { item: lsp_item },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &mut self,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let lsp_item = match serde_json::from_value(item.lsp_item.clone()) {
+            Ok(lsp_item) => lsp_item,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSupertypes { item: lsp_item },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &mut self,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let lsp_item = match serde_json::from_value(item.lsp_item.clone()) {
+            Ok(lsp_item) => lsp_item,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSubtypes { item: lsp_item },
+            cx,
+        )
+    }
+
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<SemanticTokens>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(buffer, cx))
+    }
+
     fn document_highlights_impl(
         &mut self,
         buffer: &Model<Buffer>,
@@ -2925,6 +3253,71 @@ impl Project {
         })
     }
 
+    pub fn folding_ranges(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<FoldingRange>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    /// Returns, for each of the given positions, the ranges that contain it, from
+    /// the innermost to the outermost one.
+    pub fn selection_ranges(
+        &mut self,
+        buffer: &Model<Buffer>,
+        positions: Vec<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Vec<Range<Anchor>>>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSelectionRanges { positions },
+            cx,
+        )
+    }
+
+    pub fn code_lens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetCodeLens,
+            cx,
+        )
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_code_lens(buffer, lens, cx)
+        })
+    }
+
+    pub fn apply_code_lens(
+        &self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        push_to_history: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.apply_code_lens(buffer, lens, push_to_history, cx)
+        })
+    }
+
     fn prepare_rename_impl(
         &mut self,
         buffer: Model<Buffer>,
@@ -3587,6 +3980,55 @@ impl Project {
             .update_or_create_branch(repository, new_branch, cx)
     }
 
+    pub fn stash_entries(
+        &self,
+        repository: ProjectPath,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<StashEntry>>> {
+        self.worktree_store().read(cx).stash_entries(repository, cx)
+    }
+
+    pub fn stash_push(
+        &self,
+        repository: ProjectPath,
+        message: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .stash_push(repository, message, cx)
+    }
+
+    pub fn stash_pop(
+        &self,
+        repository: ProjectPath,
+        index: usize,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .stash_pop(repository, index, cx)
+    }
+
+    pub fn fetch(&self, repository: ProjectPath, cx: &AppContext) -> Task<Result<()>> {
+        self.worktree_store().read(cx).fetch(repository, cx)
+    }
+
+    pub fn pull(
+        &self,
+        repository: ProjectPath,
+        strategy: PullStrategy,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .pull(repository, strategy, cx)
+    }
+
+    pub fn push(&self, repository: ProjectPath, cx: &AppContext) -> Task<Result<()>> {
+        self.worktree_store().read(cx).push(repository, cx)
+    }
+
     pub fn blame_buffer(
         &self,
         buffer: &Model<Buffer>,
@@ -4196,6 +4638,10 @@ impl Project {
             .language_servers_for_local_buffer(buffer, cx)
     }
 
+    pub fn breakpoint_store(&self) -> &Model<BreakpointStore> {
+        &self.breakpoint_store
+    }
+
     pub fn buffer_store(&self) -> &Model<BufferStore> {
         &self.buffer_store
     }
@@ -19,7 +19,13 @@ use pretty_assertions::{assert_eq, assert_matches};
 use serde_json::json;
 #[cfg(not(windows))]
 use std::os;
-use std::{str::FromStr, sync::OnceLock};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        OnceLock,
+    },
+};
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
 use task::{ResolvedTask, TaskContext};
@@ -2150,6 +2156,73 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_task_problems_as_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({ "a.rs": "fn main() {\n    let x = 5;\n}", "b.rs": "" }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let output = "warning: unused variable: `x`\n --> a.rs:2:9\nerror[E0601]: `main` function not found\n --> /dir/b.rs:1:1";
+    let problems = task::match_problems(
+        &[task::ProblemMatcher::Builtin(
+            task::BuiltinProblemMatcher::Rustc,
+        )],
+        output.lines(),
+    );
+
+    project.update(cx, |project, cx| {
+        project.publish_task_diagnostics("cargo build", Some(Path::new("/dir")), problems, cx);
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 1,
+            }
+        );
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let diagnostics = snapshot
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| {
+                (
+                    entry.range,
+                    entry.diagnostic.message,
+                    entry.diagnostic.source,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![(
+                Point::new(1, 8)..Point::new(1, 9),
+                "unused variable: `x`".to_string(),
+                Some("rustc".to_string())
+            )]
+        );
+    });
+
+    project.update(cx, |project, cx| {
+        project.publish_task_diagnostics("cargo build", Some(Path::new("/dir")), Vec::new(), cx);
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary::default(),
+            "rerunning the task should clear its previous diagnostics"
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2501,135 +2574,1212 @@ async fn test_invalid_edits_from_lsp2(cx: &mut gpui::TestAppContext) {
             ]
         );
 
-        for (range, new_text) in edits {
-            buffer.edit([(range, new_text)], None, cx);
-        }
-        assert_eq!(
-            buffer.text(),
-            "
-                use a::{b, c};
+        for (range, new_text) in edits {
+            buffer.edit([(range, new_text)], None, cx);
+        }
+        assert_eq!(
+            buffer.text(),
+            "
+                use a::{b, c};
+
+                fn f() {
+                    b();
+                    c();
+                }
+            "
+            .unindent()
+        );
+    });
+}
+
+fn chunks_with_diagnostics<T: ToOffset + ToPoint>(
+    buffer: &Buffer,
+    range: Range<T>,
+) -> Vec<(String, Option<DiagnosticSeverity>)> {
+    let mut chunks: Vec<(String, Option<DiagnosticSeverity>)> = Vec::new();
+    for chunk in buffer.snapshot().chunks(range, true) {
+        if chunks.last().map_or(false, |prev_chunk| {
+            prev_chunk.1 == chunk.diagnostic_severity
+        }) {
+            chunks.last_mut().unwrap().0.push_str(chunk.text);
+        } else {
+            chunks.push((chunk.text.to_string(), chunk.diagnostic_severity));
+        }
+    }
+    chunks
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_definition(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "const fn a() { A }",
+            "b.rs": "const y: i32 = crate::a()",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir/b.rs".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/b.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
+        let params = params.text_document_position_params;
+        assert_eq!(
+            params.text_document.uri.to_file_path().unwrap(),
+            Path::new("/dir/b.rs"),
+        );
+        assert_eq!(params.position, lsp::Position::new(0, 22));
+
+        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
+            lsp::Location::new(
+                lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+                lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+            ),
+        )))
+    });
+
+    let mut definitions = project
+        .update(cx, |project, cx| project.definition(&buffer, 22, cx))
+        .await
+        .unwrap();
+
+    // Assert no new language server started
+    cx.executor().run_until_parked();
+    assert!(fake_servers.try_next().is_err());
+
+    assert_eq!(definitions.len(), 1);
+    let definition = definitions.pop().unwrap();
+    cx.update(|cx| {
+        let target_buffer = definition.target.buffer.read(cx);
+        assert_eq!(
+            target_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new("/dir/a.rs"),
+        );
+        assert_eq!(definition.target.range.to_offset(target_buffer), 9..10);
+        assert_eq!(
+            list_worktrees(&project, cx),
+            [("/dir/a.rs".as_ref(), false), ("/dir/b.rs".as_ref(), true)],
+        );
+
+        drop(definition);
+    });
+    cx.update(|cx| {
+        assert_eq!(list_worktrees(&project, cx), [("/dir/b.rs".as_ref(), true)]);
+    });
+
+    fn list_worktrees<'a>(
+        project: &'a Model<Project>,
+        cx: &'a AppContext,
+    ) -> Vec<(&'a Path, bool)> {
+        project
+            .read(cx)
+            .worktrees(cx)
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (
+                    worktree.as_local().unwrap().abs_path().as_ref(),
+                    worktree.is_visible(),
+                )
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() {}",
+            "b.rs": "fn b() { a() }",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fn item(name: &str, path: &str, range: lsp::Range) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path(path).unwrap(),
+            range,
+            selection_range: range,
+            data: None,
+        }
+    }
+    let a_range = lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4));
+    let b_range = lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4));
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(
+        move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 3)
+            );
+            Ok(Some(vec![item("a", "/dir/a.rs", a_range)]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "a");
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: item("b", "/dir/b.rs", b_range),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 9),
+                    lsp::Position::new(0, 10),
+                )],
+            }]))
+        },
+    );
+
+    let mut items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, 3, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items.pop().unwrap();
+    assert_eq!(item.name, "a");
+
+    let mut calls = project
+        .update(cx, |project, cx| project.incoming_calls(&item, cx))
+        .await
+        .unwrap();
+    assert_eq!(calls.len(), 1);
+    let call = calls.pop().unwrap();
+    cx.update(|cx| {
+        let caller_buffer = call.item.location.buffer.read(cx);
+        assert_eq!(call.item.name, "b");
+        assert_eq!(caller_buffer.text(), "fn b() { a() }");
+        assert_eq!(call.item.selection_range.to_offset(caller_buffer), 3..4);
+        assert_eq!(call.call_sites.len(), 1);
+        assert_eq!(call.call_sites[0].range.to_offset(caller_buffer), 9..10);
+    });
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "trait A {}",
+            "b.rs": "struct B;\nimpl A for B {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", Default::default());
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fn item(
+        name: &str,
+        kind: lsp::SymbolKind,
+        path: &str,
+        range: lsp::Range,
+    ) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path(path).unwrap(),
+            range,
+            selection_range: range,
+            data: Some(json!({ "name": name })),
+        }
+    }
+    let a_range = lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 7));
+    let b_range = lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 8));
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(
+        move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 6)
+            );
+            Ok(Some(vec![item(
+                "A",
+                lsp::SymbolKind::INTERFACE,
+                "/dir/a.rs",
+                a_range,
+            )]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "A");
+            assert_eq!(params.item.data, Some(json!({ "name": "A" })));
+            Ok(Some(vec![item(
+                "B",
+                lsp::SymbolKind::STRUCT,
+                "/dir/b.rs",
+                b_range,
+            )]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "B");
+            assert_eq!(params.item.data, Some(json!({ "name": "B" })));
+            Ok(Some(vec![item(
+                "A",
+                lsp::SymbolKind::INTERFACE,
+                "/dir/a.rs",
+                a_range,
+            )]))
+        },
+    );
+
+    let mut items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, 6, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items.pop().unwrap();
+    assert_eq!(item.name, "A");
+
+    let mut subtypes = project
+        .update(cx, |project, cx| project.subtypes(&item, cx))
+        .await
+        .unwrap();
+    assert_eq!(subtypes.len(), 1);
+    let subtype = subtypes.pop().unwrap();
+    cx.update(|cx| {
+        let subtype_buffer = subtype.location.buffer.read(cx);
+        assert_eq!(subtype.name, "B");
+        assert_eq!(subtype.kind, lsp::SymbolKind::STRUCT);
+        assert_eq!(subtype_buffer.text(), "struct B;\nimpl A for B {}");
+        assert_eq!(subtype.selection_range.to_offset(subtype_buffer), 7..8);
+    });
+
+    // Expanding an item sends it back to the server as it was received.
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&subtype, cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].name, "A");
+    assert_eq!(supertypes[0].kind, lsp::SymbolKind::INTERFACE);
+    assert_eq!(supertypes[0].location.buffer, buffer);
+}
+
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() {}\nfn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![
+                                    lsp::SemanticTokenType::KEYWORD,
+                                    lsp::SemanticTokenType::FUNCTION,
+                                ],
+                                token_modifiers: vec![
+                                    lsp::SemanticTokenModifier::DECLARATION,
+                                    lsp::SemanticTokenModifier::DEPRECATED,
+                                ],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fn token(delta_line: u32, delta_start: u32, token_modifiers_bitset: u32) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length: 1,
+            token_type: 1,
+            token_modifiers_bitset,
+        }
+    }
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(
+        |_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".into()),
+                    data: vec![token(0, 3, 1), token(1, 3, 1)],
+                },
+            )))
+        },
+    );
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        |params, _| async move {
+            // The second delta doesn't start at a token boundary, so it can't be applied.
+            let (result_id, start) = match params.previous_result_id.as_str() {
+                "1" => ("2", 5),
+                "2" => ("3", 3),
+                id => panic!("unexpected previous result id {id}"),
+            };
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp::SemanticTokensDelta {
+                    result_id: Some(result_id.into()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start,
+                        delete_count: 5,
+                        data: Some(vec![token(1, 3, 2)]),
+                    }],
+                },
+            )))
+        },
+    );
+
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(
+            tokens
+                .tokens
+                .iter()
+                .map(|token| (
+                    token.range.to_offset(buffer),
+                    tokens.token_type(token),
+                    tokens.token_modifiers(token).collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (3..4, Some("function"), vec!["declaration"]),
+                (13..14, Some("function"), vec!["declaration"]),
+            ]
+        );
+    });
+
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(
+            tokens
+                .tokens
+                .iter()
+                .map(|token| (
+                    token.range.to_offset(buffer),
+                    tokens.token_modifiers(token).collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            [(3..4, vec!["declaration"]), (13..14, vec!["deprecated"])]
+        );
+    });
+
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(
+            tokens
+                .tokens
+                .iter()
+                .map(|token| (
+                    token.range.to_offset(buffer),
+                    tokens.token_modifiers(token).collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            [(3..4, vec!["declaration"]), (13..14, vec!["declaration"])]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() {}\n\nfn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["run".into()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(2, 3), lsp::Position::new(2, 4)),
+                command: None,
+                data: Some(json!("b")),
+            },
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+                command: Some(lsp::Command {
+                    title: "Run a".into(),
+                    command: "run".into(),
+                    arguments: Some(vec![json!("a")]),
+                }),
+                data: None,
+            },
+        ]))
+    });
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>(|lens, _| async move {
+        assert_eq!(lens.data, Some(json!("b")));
+        Ok(lsp::CodeLens {
+            command: Some(lsp::Command {
+                title: "Run b".into(),
+                command: "run".into(),
+                arguments: Some(vec![json!("b")]),
+            }),
+            ..lens
+        })
+    });
+    let mut executed_commands =
+        fake_server.handle_request::<lsp::request::ExecuteCommand, _, _>(|params, _| async move {
+            assert_eq!(params.command, "run");
+            assert_eq!(params.arguments, vec![json!("b")]);
+            Ok(None)
+        });
+
+    let lenses = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(
+            lenses
+                .iter()
+                .map(|lens| (lens.range.to_offset(buffer), lens.title()))
+                .collect::<Vec<_>>(),
+            [(3..4, Some("Run a")), (14..15, None)]
+        );
+    });
+
+    let lens = project
+        .update(cx, |project, cx| {
+            project.resolve_code_lens(buffer.clone(), lenses[1].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(lens.title(), Some("Run b"));
+
+    project
+        .update(cx, |project, cx| {
+            project.apply_code_lens(buffer.clone(), lens, true, cx)
+        })
+        .await
+        .unwrap();
+    executed_commands.next().await.unwrap();
+}
+
+#[gpui::test]
+async fn test_folding_and_selection_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() {\n    b();\n}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::FoldingRange {
+                start_line: 1,
+                end_line: 1,
+                ..Default::default()
+            },
+            lsp::FoldingRange {
+                start_line: 0,
+                end_line: 2,
+                kind: Some(lsp::FoldingRangeKind::Region),
+                ..Default::default()
+            },
+            lsp::FoldingRange {
+                start_line: 1,
+                end_line: 5,
+                ..Default::default()
+            },
+        ]))
+    });
+    fake_server.handle_request::<lsp::request::SelectionRangeRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.positions, [lsp::Position::new(1, 4)]);
+            Ok(Some(vec![lsp::SelectionRange {
+                range: lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 7)),
+                parent: Some(Box::new(lsp::SelectionRange {
+                    range: lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 8)),
+                    parent: Some(Box::new(lsp::SelectionRange {
+                        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(2, 1)),
+                        parent: None,
+                    })),
+                })),
+            }]))
+        },
+    );
+
+    let folding_ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(
+            folding_ranges
+                .iter()
+                .map(|range| (range.range.to_offset(buffer), range.kind.clone()))
+                .collect::<Vec<_>>(),
+            [(8..19, Some(lsp::FoldingRangeKind::Region))]
+        );
+    });
+
+    let position = buffer.update(cx, |buffer, _| buffer.anchor_before(Point::new(1, 4)));
+    let selection_ranges = project
+        .update(cx, |project, cx| {
+            project.selection_ranges(&buffer, vec![position], cx)
+        })
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(
+            selection_ranges
+                .iter()
+                .map(|ranges| {
+                    ranges
+                        .iter()
+                        .map(|range| range.to_offset(buffer))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>(),
+            [vec![13..16, 13..17, 0..19]]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_pulled_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = b;",
+            "b.rs": "fn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        identifier: Some("fake".into()),
+                        inter_file_dependencies: false,
+                        workspace_diagnostics: false,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let mut requests = fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document.uri,
+                Url::from_file_path("/dir/a.rs").unwrap()
+            );
+            assert_eq!(params.identifier.as_deref(), Some("fake"));
+            if let Some(previous_result_id) = params.previous_result_id {
+                assert_eq!(previous_result_id, "1");
+                return Ok(lsp::DocumentDiagnosticReportResult::Report(
+                    lsp::DocumentDiagnosticReport::Unchanged(
+                        lsp::RelatedUnchangedDocumentDiagnosticReport {
+                            related_documents: None,
+                            unchanged_document_diagnostic_report:
+                                lsp::UnchangedDocumentDiagnosticReport {
+                                    result_id: "1".into(),
+                                },
+                        },
+                    ),
+                ));
+            }
+            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                    related_documents: Some(
+                        [(
+                            Url::from_file_path("/dir/b.rs").unwrap(),
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                lsp::FullDocumentDiagnosticReport {
+                                    result_id: Some("2".into()),
+                                    items: vec![lsp::Diagnostic {
+                                        range: lsp::Range::new(
+                                            lsp::Position::new(0, 3),
+                                            lsp::Position::new(0, 4),
+                                        ),
+                                        severity: Some(lsp::DiagnosticSeverity::WARNING),
+                                        message: "unused function".into(),
+                                        ..Default::default()
+                                    }],
+                                },
+                            ),
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                        result_id: Some("1".into()),
+                        items: vec![lsp::Diagnostic {
+                            range: lsp::Range::new(
+                                lsp::Position::new(0, 8),
+                                lsp::Position::new(0, 9),
+                            ),
+                            severity: Some(lsp::DiagnosticSeverity::ERROR),
+                            message: "undefined variable 'b'".into(),
+                            ..Default::default()
+                        }],
+                    },
+                }),
+            ))
+        },
+    );
+
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(200));
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    buffer.update(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| (entry.range, entry.diagnostic.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [(
+                Point::new(0, 8)..Point::new(0, 9),
+                "undefined variable 'b'".to_string()
+            )]
+        );
+    });
+    project.update(cx, |project, cx| {
+        let summary = project.diagnostic_summary(false, cx);
+        assert_eq!(summary.error_count, 1);
+        assert_eq!(summary.warning_count, 1);
+    });
+
+    // Editing the buffer pulls again, and an unchanged report keeps the diagnostics.
+    buffer.update(cx, |buffer, cx| buffer.edit([(10..10, "\n")], None, cx));
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(200));
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    project.update(cx, |project, cx| {
+        let summary = project.diagnostic_summary(false, cx);
+        assert_eq!(summary.error_count, 1);
+        assert_eq!(summary.warning_count, 1);
+    });
+}
+
+#[gpui::test]
+async fn test_pulled_workspace_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = b;",
+            "b.rs": "fn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let workspace_requests = Arc::new(Mutex::new(Vec::new()));
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        identifier: Some("fake".into()),
+                        inter_file_dependencies: false,
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            // The first workspace pull is made as soon as the server starts.
+            initializer: Some(Box::new({
+                let workspace_requests = workspace_requests.clone();
+                move |fake_server| {
+                    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+                        |_, _| async move {
+                            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                                lsp::DocumentDiagnosticReport::Full(Default::default()),
+                            ))
+                        },
+                    );
+                    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>({
+                        let workspace_requests = workspace_requests.clone();
+                        move |params, _| {
+                            assert_eq!(params.identifier.as_deref(), Some("fake"));
+                            let unchanged = !params.previous_result_ids.is_empty();
+                            workspace_requests.lock().push(params.previous_result_ids);
+                            let uri = Url::from_file_path("/dir/b.rs").unwrap();
+                            let item = if unchanged {
+                                lsp::WorkspaceDocumentDiagnosticReport::Unchanged(
+                                    lsp::WorkspaceUnchangedDocumentDiagnosticReport {
+                                        uri,
+                                        version: None,
+                                        unchanged_document_diagnostic_report:
+                                            lsp::UnchangedDocumentDiagnosticReport {
+                                                result_id: "1".into(),
+                                            },
+                                    },
+                                )
+                            } else {
+                                lsp::WorkspaceDocumentDiagnosticReport::Full(
+                                    lsp::WorkspaceFullDocumentDiagnosticReport {
+                                        uri,
+                                        version: None,
+                                        full_document_diagnostic_report:
+                                            lsp::FullDocumentDiagnosticReport {
+                                                result_id: Some("1".into()),
+                                                items: vec![lsp::Diagnostic {
+                                                    range: lsp::Range::new(
+                                                        lsp::Position::new(0, 3),
+                                                        lsp::Position::new(0, 4),
+                                                    ),
+                                                    severity: Some(
+                                                        lsp::DiagnosticSeverity::WARNING,
+                                                    ),
+                                                    message: "unused function".into(),
+                                                    ..Default::default()
+                                                }],
+                                            },
+                                    },
+                                )
+                            };
+                            async move {
+                                Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                                    lsp::WorkspaceDiagnosticReport { items: vec![item] },
+                                ))
+                            }
+                        }
+                    });
+                }
+            })),
+            ..Default::default()
+        },
+    );
+
+    let (_buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let _fake_server = fake_servers.next().await.unwrap();
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(200));
+    cx.executor().run_until_parked();
+
+    assert_eq!(workspace_requests.lock().clone(), [Vec::new()]);
+    project.update(cx, |project, cx| {
+        let summary = project.diagnostic_summary(false, cx);
+        assert_eq!(summary.error_count, 0);
+        assert_eq!(summary.warning_count, 1);
+    });
+
+    // The workspace is pulled again after each response, passing the previous result ids,
+    // and an unchanged report keeps the diagnostics.
+    cx.executor()
+        .advance_clock(crate::lsp_store::WORKSPACE_DIAGNOSTICS_REPULL_DELAY);
+    cx.executor().run_until_parked();
+
+    assert_eq!(
+        workspace_requests.lock().clone(),
+        [
+            Vec::new(),
+            vec![lsp::PreviousResultId {
+                uri: Url::from_file_path("/dir/b.rs").unwrap(),
+                value: "1".into(),
+            }]
+        ]
+    );
+    project.update(cx, |project, cx| {
+        let summary = project.diagnostic_summary(false, cx);
+        assert_eq!(summary.error_count, 0);
+        assert_eq!(summary.warning_count, 1);
+    });
+}
+
+#[gpui::test]
+async fn test_pulled_workspace_diagnostics_after_error(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = b;",
+            "b.rs": "fn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let workspace_request_count = Arc::new(AtomicUsize::new(0));
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            initializer: Some(Box::new({
+                let workspace_request_count = workspace_request_count.clone();
+                move |fake_server| {
+                    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+                        |_, _| async move {
+                            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                                lsp::DocumentDiagnosticReport::Full(Default::default()),
+                            ))
+                        },
+                    );
+                    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>({
+                        let workspace_request_count = workspace_request_count.clone();
+                        move |_, _| {
+                            let first_request = workspace_request_count.fetch_add(1, SeqCst) == 0;
+                            async move {
+                                if first_request {
+                                    return Err(anyhow::anyhow!("request cancelled"));
+                                }
+                                Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                                    lsp::WorkspaceDiagnosticReport {
+                                        items: vec![lsp::WorkspaceDocumentDiagnosticReport::Full(
+                                            lsp::WorkspaceFullDocumentDiagnosticReport {
+                                                uri: Url::from_file_path("/dir/b.rs").unwrap(),
+                                                version: None,
+                                                full_document_diagnostic_report:
+                                                    lsp::FullDocumentDiagnosticReport {
+                                                        result_id: None,
+                                                        items: vec![lsp::Diagnostic {
+                                                            range: lsp::Range::new(
+                                                                lsp::Position::new(0, 3),
+                                                                lsp::Position::new(0, 4),
+                                                            ),
+                                                            severity: Some(
+                                                                lsp::DiagnosticSeverity::WARNING,
+                                                            ),
+                                                            message: "unused function".into(),
+                                                            ..Default::default()
+                                                        }],
+                                                    },
+                                            },
+                                        )],
+                                    },
+                                ))
+                            }
+                        }
+                    });
+                }
+            })),
+            ..Default::default()
+        },
+    );
+
+    let (_buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let _fake_server = fake_servers.next().await.unwrap();
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(200));
+    cx.executor().run_until_parked();
 
-                fn f() {
-                    b();
-                    c();
-                }
-            "
-            .unindent()
-        );
+    assert_eq!(workspace_request_count.load(SeqCst), 1);
+    project.update(cx, |project, cx| {
+        assert_eq!(project.diagnostic_summary(false, cx).warning_count, 0);
     });
-}
 
-fn chunks_with_diagnostics<T: ToOffset + ToPoint>(
-    buffer: &Buffer,
-    range: Range<T>,
-) -> Vec<(String, Option<DiagnosticSeverity>)> {
-    let mut chunks: Vec<(String, Option<DiagnosticSeverity>)> = Vec::new();
-    for chunk in buffer.snapshot().chunks(range, true) {
-        if chunks.last().map_or(false, |prev_chunk| {
-            prev_chunk.1 == chunk.diagnostic_severity
-        }) {
-            chunks.last_mut().unwrap().0.push_str(chunk.text);
-        } else {
-            chunks.push((chunk.text.to_string(), chunk.diagnostic_severity));
-        }
-    }
-    chunks
+    // A failed workspace pull is retried after the same delay as a successful one.
+    cx.executor()
+        .advance_clock(crate::lsp_store::WORKSPACE_DIAGNOSTICS_REPULL_DELAY);
+    cx.executor().run_until_parked();
+
+    assert_eq!(workspace_request_count.load(SeqCst), 2);
+    project.update(cx, |project, cx| {
+        assert_eq!(project.diagnostic_summary(false, cx).warning_count, 1);
+    });
 }
 
-#[gpui::test(iterations = 10)]
-async fn test_definition(cx: &mut gpui::TestAppContext) {
+#[gpui::test]
+async fn test_pulled_diagnostics_refresh(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.rs": "const fn a() { A }",
-            "b.rs": "const y: i32 = crate::a()",
+            "a.rs": "let a = b;",
         }),
     )
     .await;
 
-    let project = Project::test(fs, ["/dir/b.rs".as_ref()], cx).await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
+    let pulls = Arc::new(Mutex::new(Vec::new()));
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
     language_registry.add(rust_lang());
-    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            initializer: Some(Box::new({
+                let pulls = pulls.clone();
+                move |fake_server| {
+                    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+                        let pulls = pulls.clone();
+                        move |_, _| {
+                            pulls.lock().push("document");
+                            async move {
+                                Ok(lsp::DocumentDiagnosticReportResult::Report(
+                                    lsp::DocumentDiagnosticReport::Full(Default::default()),
+                                ))
+                            }
+                        }
+                    });
+                    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>({
+                        let pulls = pulls.clone();
+                        move |_, _| {
+                            pulls.lock().push("workspace");
+                            async move {
+                                Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                                    lsp::WorkspaceDiagnosticReport { items: Vec::new() },
+                                ))
+                            }
+                        }
+                    });
+                }
+            })),
+            ..Default::default()
+        },
+    );
 
-    let (buffer, _handle) = project
+    let (_buffer, _handle) = project
         .update(cx, |project, cx| {
-            project.open_local_buffer_with_lsp("/dir/b.rs", cx)
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
         })
         .await
         .unwrap();
-
     let fake_server = fake_servers.next().await.unwrap();
-    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
-        let params = params.text_document_position_params;
-        assert_eq!(
-            params.text_document.uri.to_file_path().unwrap(),
-            Path::new("/dir/b.rs"),
-        );
-        assert_eq!(params.position, lsp::Position::new(0, 22));
-
-        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
-            lsp::Location::new(
-                lsp::Url::from_file_path("/dir/a.rs").unwrap(),
-                lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
-            ),
-        )))
-    });
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(200));
+    cx.executor().run_until_parked();
+    pulls.lock().clear();
 
-    let mut definitions = project
-        .update(cx, |project, cx| project.definition(&buffer, 22, cx))
+    // A refresh request from the server pulls the open buffers and the workspace again.
+    fake_server
+        .request::<lsp::request::WorkspaceDiagnosticRefresh>(())
         .await
         .unwrap();
-
-    // Assert no new language server started
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(200));
     cx.executor().run_until_parked();
-    assert!(fake_servers.try_next().is_err());
-
-    assert_eq!(definitions.len(), 1);
-    let definition = definitions.pop().unwrap();
-    cx.update(|cx| {
-        let target_buffer = definition.target.buffer.read(cx);
-        assert_eq!(
-            target_buffer
-                .file()
-                .unwrap()
-                .as_local()
-                .unwrap()
-                .abs_path(cx),
-            Path::new("/dir/a.rs"),
-        );
-        assert_eq!(definition.target.range.to_offset(target_buffer), 9..10);
-        assert_eq!(
-            list_worktrees(&project, cx),
-            [("/dir/a.rs".as_ref(), false), ("/dir/b.rs".as_ref(), true)],
-        );
-
-        drop(definition);
-    });
-    cx.update(|cx| {
-        assert_eq!(list_worktrees(&project, cx), [("/dir/b.rs".as_ref(), true)]);
-    });
 
-    fn list_worktrees<'a>(
-        project: &'a Model<Project>,
-        cx: &'a AppContext,
-    ) -> Vec<(&'a Path, bool)> {
-        project
-            .read(cx)
-            .worktrees(cx)
-            .map(|worktree| {
-                let worktree = worktree.read(cx);
-                (
-                    worktree.as_local().unwrap().abs_path().as_ref(),
-                    worktree.is_visible(),
-                )
-            })
-            .collect::<Vec<_>>()
-    }
+    let mut pulls = pulls.lock().clone();
+    pulls.sort();
+    assert_eq!(pulls, ["document", "workspace"]);
 }
 
 #[gpui::test]
@@ -3366,6 +4516,57 @@ async fn test_buffer_identity_across_renames(cx: &mut gpui::TestAppContext) {
     buffer.update(cx, |buffer, _| assert!(!buffer.is_dirty()));
 }
 
+#[gpui::test]
+async fn test_breakpoints_follow_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "one\ntwo\nthree\nfour\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let breakpoint_store = project.read_with(cx, |project, _| project.breakpoint_store().clone());
+    let path = Path::new("/dir/a.rs");
+
+    // Breakpoints can be set before the file is opened.
+    breakpoint_store.update(cx, |store, cx| {
+        store.set_breakpoints(path.into(), vec![3, 1], cx)
+    });
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer(path, cx))
+        .await
+        .unwrap();
+    breakpoint_store.update(cx, |store, cx| {
+        assert_eq!(store.breakpoints_for_path(path), [1, 3]);
+        store.toggle_breakpoint(&buffer, 2, cx);
+        assert_eq!(store.breakpoints_for_path(path), [1, 2, 3]);
+        store.toggle_breakpoint(&buffer, 1, cx);
+        assert_eq!(store.breakpoints_for_path(path), [2, 3]);
+    });
+
+    // Inserting lines above a breakpoint moves it down.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    breakpoint_store.read_with(cx, |store, _| {
+        assert_eq!(store.breakpoints_for_path(path), [3, 4]);
+    });
+
+    // Deleting the line of a breakpoint merges it with the next one.
+    buffer.update(cx, |buffer, cx| {
+        let start = buffer.text().find("three").unwrap();
+        buffer.edit([(start..start + "three\n".len(), "")], None, cx)
+    });
+    breakpoint_store.update(cx, |store, cx| {
+        assert_eq!(store.breakpoints_for_path(path), [3]);
+        store.clear_breakpoints(cx);
+        assert_eq!(store.breakpoints().count(), 0);
+    });
+}
+
 #[gpui::test]
 async fn test_buffer_deduping(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -5652,6 +6853,123 @@ async fn test_unstaged_changes_for_buffer(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_diff_against_revision(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let main_contents = r#"
+        fn main() {
+            println!("hello world");
+        }
+    "#
+    .unindent();
+    let file_contents = r#"
+        fn main() {
+            println!("goodbye world");
+        }
+    "#
+    .unindent();
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+           "src": {
+               "main.rs": file_contents.clone(),
+           }
+        }),
+    )
+    .await;
+
+    fs.set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("src/main.rs"), file_contents.clone())],
+    );
+    fs.with_git_state(Path::new("/dir/.git"), true, |state| {
+        state.revision_contents.insert(
+            "main".into(),
+            [(PathBuf::from("src/main.rs"), main_contents)]
+                .into_iter()
+                .collect(),
+        );
+    });
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/src/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let changes = project
+        .update(cx, |project, cx| {
+            project.open_unstaged_changes(buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+
+    cx.run_until_parked();
+    changes.update(cx, |changes, cx| {
+        let snapshot = buffer.read(cx).snapshot();
+        assert_hunks(
+            changes.diff_hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot),
+            &snapshot,
+            &changes.base_text.as_ref().unwrap().read(cx).text(),
+            &[],
+        );
+    });
+
+    project
+        .update(cx, |project, cx| {
+            project.set_default_diff_base(DiffBase::Revision("main".into()), cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    changes.update(cx, |changes, cx| {
+        assert_eq!(changes.diff_base, DiffBase::Revision("main".into()));
+        let snapshot = buffer.read(cx).snapshot();
+        assert_hunks(
+            changes.diff_hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot),
+            &snapshot,
+            &changes.base_text.as_ref().unwrap().read(cx).text(),
+            &[(
+                1..2,
+                "    println!(\"hello world\");\n",
+                "    println!(\"goodbye world\");\n",
+            )],
+        );
+    });
+
+    project
+        .update(cx, |project, cx| {
+            project.set_buffer_diff_base(&buffer, Some(DiffBase::Index), cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    changes.update(cx, |changes, cx| {
+        assert_eq!(changes.diff_base, DiffBase::Index);
+        let snapshot = buffer.read(cx).snapshot();
+        assert_hunks(
+            changes.diff_hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot),
+            &snapshot,
+            &changes.base_text.as_ref().unwrap().read(cx).text(),
+            &[],
+        );
+    });
+
+    project
+        .update(cx, |project, cx| {
+            project.set_buffer_diff_base(&buffer, None, cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    changes.update(cx, |changes, _| {
+        assert_eq!(changes.diff_base, DiffBase::Revision("main".into()));
+    });
+}
+
 async fn search(
     project: &Model<Project>,
     query: SearchQuery,
@@ -8,14 +8,15 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use collections::{HashMap, HashSet, VecDeque};
 use gpui::{AppContext, Context as _, Model, Task};
 use itertools::Itertools;
 use language::{ContextProvider, File, Language, LanguageToolchainStore, Location};
 use settings::{parse_json_with_comments, SettingsLocation};
 use task::{
-    ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables, VariableName,
+    DependsOrder, ResolvedTask, SpawnInTerminal, TaskContext, TaskId, TaskInput, TaskTemplate,
+    TaskTemplates, TaskVariables, VariableName,
 };
 use text::{Point, ToPoint};
 use util::{post_inc, NumericPrefixWithSuffix, ResultExt as _};
@@ -242,6 +243,146 @@ impl Inventory {
         self.last_scheduled_tasks.retain(|(_, task)| &task.id != id);
     }
 
+    /// Resolves the `depends_on` graph of the task given into the steps to spawn before it,
+    /// looking the dependencies up by label among the worktree tasks first and the global tasks second.
+    /// Dependencies are resolved with the same [`TaskContext`] as the task itself.
+    ///
+    /// Every task appears in the steps at most once, at its earliest position.
+    /// Fails if a dependency is unknown or forms a cycle.
+    pub fn resolve_task_dependencies(
+        &self,
+        worktree: Option<WorktreeId>,
+        task: &ResolvedTask,
+    ) -> Result<Vec<Vec<SpawnInTerminal>>> {
+        let mut stack = vec![task.original_task().label.clone()];
+        let steps = self.dependency_steps(
+            worktree,
+            task.original_task(),
+            task.task_context(),
+            &mut stack,
+        )?;
+
+        let mut spawned_ids = HashSet::default();
+        Ok(steps
+            .into_iter()
+            .map(|step| {
+                step.into_iter()
+                    .filter(|spawn_in_terminal| spawned_ids.insert(spawn_in_terminal.id.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|step| !step.is_empty())
+            .collect())
+    }
+
+    fn dependency_steps(
+        &self,
+        worktree: Option<WorktreeId>,
+        template: &TaskTemplate,
+        task_context: &TaskContext,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<Vec<SpawnInTerminal>>> {
+        let mut steps = Vec::<Vec<SpawnInTerminal>>::new();
+        for dependency_label in &template.depends_on {
+            if stack.contains(dependency_label) {
+                let cycle = stack
+                    .iter()
+                    .skip_while(|label| *label != dependency_label)
+                    .chain(Some(dependency_label))
+                    .join(" -> ");
+                bail!("task dependency cycle: {cycle}");
+            }
+            let Some((kind, dependency)) = self.dependency_template(worktree, dependency_label)
+            else {
+                bail!(
+                    "task `{}` depends on unknown task `{dependency_label}`",
+                    template.label
+                );
+            };
+            let Some(resolved_task) = dependency.resolve_task(&kind.to_id_base(), task_context)
+            else {
+                bail!("failed to resolve task `{dependency_label}`");
+            };
+            if let Some(input) = resolved_task.missing_inputs().next() {
+                bail!(
+                    "task `{dependency_label}` has no value for its input `{}`",
+                    input.id
+                );
+            }
+            let Some(resolved) = resolved_task.resolved else {
+                bail!("failed to resolve task `{dependency_label}`");
+            };
+
+            stack.push(dependency_label.clone());
+            let mut dependency_steps =
+                self.dependency_steps(worktree, &dependency, task_context, stack)?;
+            stack.pop();
+            if !resolved.command.trim().is_empty() {
+                dependency_steps.push(vec![resolved]);
+            }
+
+            match template.depends_order {
+                DependsOrder::Sequence => steps.extend(dependency_steps),
+                DependsOrder::Parallel => {
+                    for (index, step) in dependency_steps.into_iter().enumerate() {
+                        match steps.get_mut(index) {
+                            Some(existing_step) => existing_step.extend(step),
+                            None => steps.push(step),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(steps)
+    }
+
+    /// Inputs of the task dependencies, at any depth, that the task context given has no values for.
+    /// Dependencies are resolved with the context of the task depending on them, so these have to be
+    /// asked for along with the task's own inputs.
+    pub fn missing_dependency_inputs(
+        &self,
+        worktree: Option<WorktreeId>,
+        task: &ResolvedTask,
+    ) -> Vec<TaskInput> {
+        let task_variables = &task.task_context().task_variables;
+        let mut visited = HashSet::default();
+        let mut labels = task
+            .original_task()
+            .depends_on
+            .iter()
+            .cloned()
+            .collect::<VecDeque<_>>();
+        let mut inputs = Vec::<TaskInput>::new();
+        while let Some(label) = labels.pop_front() {
+            if !visited.insert(label.clone()) {
+                continue;
+            }
+            let Some((_, dependency)) = self.dependency_template(worktree, &label) else {
+                continue;
+            };
+            for input in &dependency.inputs {
+                let variable = input.variable_name();
+                if task_variables.get(&variable).is_none()
+                    && !inputs.iter().any(|known| known.variable_name() == variable)
+                {
+                    inputs.push(input.clone());
+                }
+            }
+            labels.extend(dependency.depends_on.iter().cloned());
+        }
+        inputs
+    }
+
+    /// Looks a task dependency up by label among the worktree tasks first and the global tasks second.
+    fn dependency_template(
+        &self,
+        worktree: Option<WorktreeId>,
+        label: &str,
+    ) -> Option<(TaskSourceKind, TaskTemplate)> {
+        self.worktree_templates_from_settings(worktree)
+            .chain(self.global_templates_from_settings())
+            .find(|(_, template)| template.label == label)
+    }
+
     fn global_templates_from_settings(
         &self,
     ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
@@ -858,6 +999,144 @@ mod tests {
         TaskStore::init(None);
     }
 
+    #[gpui::test]
+    async fn test_resolve_task_dependencies(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(Inventory::new);
+        let worktree = WorktreeId::from_usize(1);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    None,
+                    Some(
+                        &json!([
+                            { "label": "db", "command": "start-db" },
+                            { "label": "lint", "command": "cargo clippy" },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+            inventory
+                .update_file_based_tasks(
+                    Some(SettingsLocation {
+                        worktree_id: worktree,
+                        path: Path::new(".zed"),
+                    }),
+                    Some(
+                        &json!([
+                            { "label": "build", "command": "cargo build" },
+                            { "label": "test", "command": "cargo test", "depends_on": ["build"] },
+                            { "label": "server", "command": "run-server", "depends_on": ["db", "build"] },
+                            {
+                                "label": "check",
+                                "depends_on": ["test", "lint"],
+                                "depends_order": "parallel",
+                            },
+                            { "label": "ping", "command": "ping", "depends_on": ["pong"] },
+                            { "label": "pong", "command": "pong", "depends_on": ["ping"] },
+                            { "label": "broken", "command": "echo", "depends_on": ["missing"] },
+                            {
+                                "label": "deploy",
+                                "command": "deploy --env $ZED_INPUT_env",
+                                "inputs": [{ "id": "env", "type": "prompt_string" }],
+                            },
+                            { "label": "release", "command": "release", "depends_on": ["deploy"] },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let steps = |label: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let (kind, template) = inventory
+                    .list_tasks(None, None, Some(worktree), cx)
+                    .into_iter()
+                    .find(|(_, template)| template.label == label)
+                    .unwrap();
+                let task = template
+                    .resolve_task(&kind.to_id_base(), &TaskContext::default())
+                    .unwrap();
+                inventory
+                    .resolve_task_dependencies(Some(worktree), &task)
+                    .map(|steps| {
+                        steps
+                            .into_iter()
+                            .map(|step| step.into_iter().map(|task| task.label).collect::<Vec<_>>())
+                            .collect::<Vec<_>>()
+                    })
+                    .map_err(|e| e.to_string())
+            })
+        };
+
+        assert_eq!(steps("build", cx), Ok(Vec::<Vec<String>>::new()));
+        assert_eq!(steps("test", cx), Ok(vec![vec!["build".to_string()]]));
+        assert_eq!(
+            steps("server", cx),
+            Ok(vec![vec!["db".to_string()], vec!["build".to_string()]]),
+            "sequential dependencies should be spawned one after another"
+        );
+        assert_eq!(
+            steps("check", cx),
+            Ok(vec![
+                vec!["build".to_string(), "lint".to_string()],
+                vec!["test".to_string()]
+            ]),
+            "parallel dependencies should be spawned together, after their own dependencies"
+        );
+        assert_eq!(
+            steps("ping", cx),
+            Err("task dependency cycle: ping -> pong -> ping".to_string())
+        );
+        assert_eq!(
+            steps("broken", cx),
+            Err("task `broken` depends on unknown task `missing`".to_string())
+        );
+        assert_eq!(
+            steps("release", cx),
+            Err("task `deploy` has no value for its input `env`".to_string()),
+            "dependencies should not be spawned with their inputs unsubstituted"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            let (kind, template) = inventory
+                .list_tasks(None, None, Some(worktree), cx)
+                .into_iter()
+                .find(|(_, template)| template.label == "release")
+                .unwrap();
+            let task = template
+                .resolve_task(&kind.to_id_base(), &TaskContext::default())
+                .unwrap();
+            assert_eq!(
+                inventory
+                    .missing_dependency_inputs(Some(worktree), &task)
+                    .into_iter()
+                    .map(|input| input.id)
+                    .collect::<Vec<_>>(),
+                vec!["env".to_string()]
+            );
+
+            let mut task_context = TaskContext::default();
+            task_context.task_variables.insert(
+                VariableName::Input(Cow::Borrowed("env")),
+                "staging".to_string(),
+            );
+            let task = template
+                .resolve_task(&kind.to_id_base(), &task_context)
+                .unwrap();
+            assert!(inventory
+                .missing_dependency_inputs(Some(worktree), &task)
+                .is_empty());
+            let steps = inventory
+                .resolve_task_dependencies(Some(worktree), &task)
+                .unwrap();
+            assert_eq!(steps.len(), 1);
+            assert_eq!(steps[0][0].command, "deploy --env staging");
+        });
+    }
+
     async fn resolved_task_names(
         inventory: &Model<Inventory>,
         worktree: Option<WorktreeId>,
@@ -1,9 +1,12 @@
 use crate::Project;
 use anyhow::{Context as _, Result};
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, Task, WeakModel};
 use itertools::Itertools;
-use language::LanguageName;
+use language::{
+    Diagnostic, DiagnosticEntry, LanguageName, LanguageServerId, PointUtf16, Unclipped,
+};
+use lsp::DiagnosticSeverity;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
@@ -13,7 +16,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use task::{Shell, SpawnInTerminal};
+use task::{match_problems, Problem, ProblemMatcher, ProblemSeverity, Shell, SpawnInTerminal};
 use terminal::{
     terminal_settings::{self, TerminalSettings, VenvSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
@@ -25,6 +28,15 @@ use util::ResultExt;
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    /// Diagnostics reported by the problem matchers of the tasks, by task label.
+    pub(crate) task_diagnostics: HashMap<String, TaskDiagnostics>,
+}
+
+/// Diagnostics found in the output of a task, replaced on every run of the task.
+pub(crate) struct TaskDiagnostics {
+    /// A pseudo language server id to report the diagnostics as, so they are grouped and cleared together.
+    server_id: LanguageServerId,
+    paths: HashSet<PathBuf>,
 }
 
 /// Terminals are opened either for the users shell, or to run a task.
@@ -83,6 +95,17 @@ impl Project {
         kind: TerminalKind,
         window: AnyWindowHandle,
         cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Terminal>>> {
+        self.create_terminal_with_scrollback(kind, None, window, cx)
+    }
+
+    /// Creates a terminal showing the output saved from a previous session above its shell's output.
+    pub fn create_terminal_with_scrollback(
+        &mut self,
+        kind: TerminalKind,
+        restored_scrollback: Option<String>,
+        window: AnyWindowHandle,
+        cx: &mut ModelContext<Self>,
     ) -> Task<Result<Model<Terminal>>> {
         let path: Option<Arc<Path>> = match &kind {
             TerminalKind::Shell(path) => path.as_ref().map(|path| Arc::from(path.as_ref())),
@@ -125,6 +148,19 @@ impl Project {
             None
         };
 
+        let problem_matchers = match &kind {
+            TerminalKind::Task(spawn_task)
+                if !spawn_task.problem_matchers.is_empty() && self.is_local() =>
+            {
+                self.clear_task_diagnostics(&spawn_task.label, cx);
+                Some((
+                    spawn_task.label.clone(),
+                    spawn_task.problem_matchers.clone(),
+                ))
+            }
+            _ => None,
+        };
+
         cx.spawn(move |this, mut cx| async move {
             let python_venv_directory = if let Some(path) = path.clone() {
                 this.update(&mut cx, |this, cx| {
@@ -247,6 +283,7 @@ impl Project {
                     settings.alternate_scroll,
                     settings.max_scroll_history_lines,
                     ssh_details.is_some(),
+                    restored_scrollback,
                     window,
                     completion_tx,
                     cx,
@@ -279,6 +316,15 @@ impl Project {
                             cx,
                         );
                     }
+                    if let Some((task_label, problem_matchers)) = problem_matchers {
+                        this.report_task_problems(
+                            task_label,
+                            problem_matchers,
+                            path,
+                            &terminal_handle,
+                            cx,
+                        );
+                    }
                     terminal_handle
                 })
             })?;
@@ -287,6 +333,100 @@ impl Project {
         })
     }
 
+    /// Once the task in the terminal finishes, reports the problems its matchers find in the output as diagnostics.
+    fn report_task_problems(
+        &mut self,
+        task_label: String,
+        problem_matchers: Vec<ProblemMatcher>,
+        cwd: Option<Arc<Path>>,
+        terminal: &Model<Terminal>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let task_completed = terminal.read(cx).wait_for_completed_task(cx);
+        let terminal = terminal.downgrade();
+        cx.spawn(|project, mut cx| async move {
+            task_completed.await;
+            let output_lines = terminal.update(&mut cx, |terminal, _| terminal.output_lines())?;
+            let problems = cx
+                .background_executor()
+                .spawn(async move {
+                    match_problems(&problem_matchers, output_lines.iter().map(String::as_str))
+                })
+                .await;
+            project.update(&mut cx, |project, cx| {
+                project.publish_task_diagnostics(&task_label, cwd.as_deref(), problems, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub(crate) fn publish_task_diagnostics(
+        &mut self,
+        task_label: &str,
+        cwd: Option<&Path>,
+        problems: Vec<Problem>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.clear_task_diagnostics(task_label, cx);
+        if problems.is_empty() {
+            return;
+        }
+
+        let mut diagnostics_by_path = HashMap::<PathBuf, Vec<_>>::default();
+        for (group_id, problem) in problems.into_iter().enumerate() {
+            let path = Path::new(&problem.path);
+            let abs_path = match cwd {
+                Some(cwd) if path.is_relative() => cwd.join(path),
+                _ => path.to_path_buf(),
+            };
+            diagnostics_by_path
+                .entry(abs_path)
+                .or_default()
+                .push(problem_diagnostic(task_label, group_id, problem));
+        }
+
+        let server_id = match self.terminals.task_diagnostics.get(task_label) {
+            Some(task_diagnostics) => task_diagnostics.server_id,
+            None => self.languages.next_language_server_id(),
+        };
+        let lsp_store = self.lsp_store.clone();
+        lsp_store.update(cx, |lsp_store, cx| {
+            for (abs_path, diagnostics) in &diagnostics_by_path {
+                lsp_store
+                    .update_diagnostic_entries(
+                        server_id,
+                        abs_path.clone(),
+                        None,
+                        diagnostics.clone(),
+                        cx,
+                    )
+                    .log_err();
+            }
+        });
+        self.terminals.task_diagnostics.insert(
+            task_label.to_string(),
+            TaskDiagnostics {
+                server_id,
+                paths: diagnostics_by_path.into_keys().collect(),
+            },
+        );
+    }
+
+    fn clear_task_diagnostics(&mut self, task_label: &str, cx: &mut ModelContext<Self>) {
+        let Some(task_diagnostics) = self.terminals.task_diagnostics.get_mut(task_label) else {
+            return;
+        };
+        let server_id = task_diagnostics.server_id;
+        let paths = std::mem::take(&mut task_diagnostics.paths);
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            for abs_path in paths {
+                lsp_store
+                    .update_diagnostic_entries(server_id, abs_path, None, Vec::new(), cx)
+                    .log_err();
+            }
+        });
+    }
+
     fn python_venv_directory(
         &self,
         abs_path: Arc<Path>,
@@ -428,6 +568,42 @@ impl Project {
     }
 }
 
+fn problem_diagnostic(
+    task_label: &str,
+    group_id: usize,
+    problem: Problem,
+) -> DiagnosticEntry<Unclipped<PointUtf16>> {
+    let start = PointUtf16::new(
+        problem.line.saturating_sub(1),
+        problem.column.unwrap_or(1).saturating_sub(1),
+    );
+    let end = match problem.end_line {
+        Some(end_line) => PointUtf16::new(
+            end_line.saturating_sub(1),
+            problem.end_column.unwrap_or(1).saturating_sub(1),
+        ),
+        None => start,
+    };
+    DiagnosticEntry {
+        range: Unclipped(start)..Unclipped(end),
+        diagnostic: Diagnostic {
+            source: Some(problem.source.unwrap_or_else(|| task_label.to_string())),
+            code: problem.code,
+            severity: match problem.severity {
+                ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+            },
+            message: problem.message,
+            group_id,
+            is_primary: true,
+            is_disk_based: true,
+            ..Diagnostic::default()
+        },
+    }
+}
+
 pub fn wrap_for_ssh(
     ssh_command: &SshCommand,
     command: Option<(&String, &Vec<String>)>,
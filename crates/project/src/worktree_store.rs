@@ -1,4 +1,5 @@
 use std::{
+    future::Future,
     path::{Path, PathBuf},
     sync::{atomic::AtomicUsize, Arc},
 };
@@ -10,6 +11,7 @@ use futures::{
     future::{BoxFuture, Shared},
     FutureExt, SinkExt,
 };
+use git::repository::{GitRepository, PullStrategy, StashEntry};
 use gpui::{
     AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
@@ -74,6 +76,12 @@ impl WorktreeStore {
         client.add_model_request_handler(Self::handle_expand_project_entry);
         client.add_model_request_handler(Self::handle_git_branches);
         client.add_model_request_handler(Self::handle_update_branch);
+        client.add_model_request_handler(Self::handle_git_stash_list);
+        client.add_model_request_handler(Self::handle_git_stash_push);
+        client.add_model_request_handler(Self::handle_git_stash_pop);
+        client.add_model_request_handler(Self::handle_git_fetch);
+        client.add_model_request_handler(Self::handle_git_pull);
+        client.add_model_request_handler(Self::handle_git_push);
     }
 
     pub fn local(retain_worktrees: bool, fs: Arc<dyn Fs>) -> Self {
@@ -984,6 +992,201 @@ impl WorktreeStore {
         }
     }
 
+    pub fn stash_entries(
+        &self,
+        repository: ProjectPath,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<StashEntry>>> {
+        self.git_operation(
+            repository,
+            cx,
+            |repo| repo.stash_entries(),
+            |client, project_id, repository| {
+                let request = client.request(proto::GitStashList {
+                    project_id,
+                    repository: Some(repository),
+                });
+                async move {
+                    let response = request.await?;
+                    Ok(response
+                        .entries
+                        .into_iter()
+                        .map(|entry| StashEntry {
+                            index: entry.index as usize,
+                            message: entry.message,
+                        })
+                        .collect())
+                }
+            },
+        )
+    }
+
+    pub fn stash_push(
+        &self,
+        repository: ProjectPath,
+        message: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let local_message = message.clone();
+        self.git_operation(
+            repository,
+            cx,
+            move |repo| repo.stash_push(local_message.as_deref()),
+            |client, project_id, repository| {
+                let request = client.request(proto::GitStashPush {
+                    project_id,
+                    repository: Some(repository),
+                    message,
+                });
+                async move {
+                    request.await?;
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    pub fn stash_pop(
+        &self,
+        repository: ProjectPath,
+        index: usize,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.git_operation(
+            repository,
+            cx,
+            move |repo| repo.stash_pop(index),
+            |client, project_id, repository| {
+                let request = client.request(proto::GitStashPop {
+                    project_id,
+                    repository: Some(repository),
+                    index: index as u64,
+                });
+                async move {
+                    request.await?;
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    pub fn fetch(&self, repository: ProjectPath, cx: &AppContext) -> Task<Result<()>> {
+        self.git_operation(
+            repository,
+            cx,
+            |repo| repo.fetch(),
+            |client, project_id, repository| {
+                let request = client.request(proto::GitFetch {
+                    project_id,
+                    repository: Some(repository),
+                });
+                async move {
+                    request.await?;
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    pub fn pull(
+        &self,
+        repository: ProjectPath,
+        strategy: PullStrategy,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.git_operation(
+            repository,
+            cx,
+            move |repo| repo.pull(strategy),
+            |client, project_id, repository| {
+                let request = client.request(proto::GitPull {
+                    project_id,
+                    repository: Some(repository),
+                    rebase: strategy == PullStrategy::Rebase,
+                });
+                async move {
+                    request.await?;
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    pub fn push(&self, repository: ProjectPath, cx: &AppContext) -> Task<Result<()>> {
+        self.git_operation(
+            repository,
+            cx,
+            |repo| repo.push(),
+            |client, project_id, repository| {
+                let request = client.request(proto::GitPush {
+                    project_id,
+                    repository: Some(repository),
+                });
+                async move {
+                    request.await?;
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    /// Runs a git operation on the repository containing the given path, on a background
+    /// thread for local worktrees and on the host for remote ones.
+    fn git_operation<T, F>(
+        &self,
+        repository: ProjectPath,
+        cx: &AppContext,
+        local: impl FnOnce(Arc<dyn GitRepository>) -> Result<T> + Send + 'static,
+        remote: impl FnOnce(AnyProtoClient, u64, proto::ProjectPath) -> F,
+    ) -> Task<Result<T>>
+    where
+        T: Send + 'static,
+        F: Future<Output = Result<T>> + Send + 'static,
+    {
+        let Some(worktree) = self.worktree_for_id(repository.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let repo = util::maybe!({
+                    let worktree_error = |error| {
+                        format!(
+                            "{} for worktree {}",
+                            error,
+                            local_worktree.abs_path().to_string_lossy()
+                        )
+                    };
+
+                    let entry = local_worktree
+                        .git_entry(repository.path)
+                        .with_context(|| worktree_error("No git entry found"))?;
+
+                    anyhow::Ok(
+                        local_worktree
+                            .get_local_repo(&entry)
+                            .with_context(|| worktree_error("No repository found"))?
+                            .repo()
+                            .clone(),
+                    )
+                });
+
+                match repo {
+                    Ok(repo) => cx.background_executor().spawn(async move { local(repo) }),
+                    Err(error) => Task::ready(Err(error)),
+                }
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote(
+                    remote_worktree.client(),
+                    remote_worktree.project_id(),
+                    repository.to_proto(),
+                );
+                cx.background_executor().spawn(request)
+            }
+        }
+    }
+
     async fn filter_paths(
         fs: &Arc<dyn Fs>,
         mut input: Receiver<MatchingEntry>,
@@ -1160,6 +1363,93 @@ impl WorktreeStore {
 
         Ok(proto::Ack {})
     }
+
+    pub async fn handle_git_stash_list(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashList>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitStashListResponse> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let entries = this
+            .read_with(&cx, |this, cx| this.stash_entries(repository, cx))?
+            .await?;
+        Ok(proto::GitStashListResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::GitStashEntry {
+                    index: entry.index as u64,
+                    message: entry.message,
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle_git_stash_push(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashPush>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let message = envelope.payload.message;
+        this.read_with(&cx, |this, cx| this.stash_push(repository, message, cx))?
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_stash_pop(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashPop>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let index = envelope.payload.index as usize;
+        this.read_with(&cx, |this, cx| this.stash_pop(repository, index, cx))?
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_fetch(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitFetch>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        this.read_with(&cx, |this, cx| this.fetch(repository, cx))?
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_pull(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitPull>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let strategy = if envelope.payload.rebase {
+            PullStrategy::Rebase
+        } else {
+            PullStrategy::FastForwardOnly
+        };
+        this.read_with(&cx, |this, cx| this.pull(repository, strategy, cx))?
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_push(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitPush>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        this.read_with(&cx, |this, cx| this.push(repository, cx))?
+            .await?;
+        Ok(proto::Ack {})
+    }
+}
+
+fn repository_from_proto(repository: Option<proto::ProjectPath>) -> Result<ProjectPath> {
+    let repository = repository.context("missing repository path")?;
+    Ok(ProjectPath::from_proto(repository))
 }
 
 #[derive(Clone, Debug)]
@@ -329,6 +329,32 @@ messages!(
     (RestartLanguageServers, Foreground),
     (LinkedEditingRange, Background),
     (LinkedEditingRangeResponse, Background),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetIncomingCallsResponse, Background),
+    (GetOutgoingCalls, Background),
+    (GetOutgoingCallsResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
+    (GetSupertypes, Background),
+    (GetSupertypesResponse, Background),
+    (GetSubtypes, Background),
+    (GetSubtypesResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (RefreshSemanticTokens, Foreground),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (ApplyCodeLens, Background),
+    (ApplyCodeLensResponse, Background),
+    (RefreshCodeLens, Foreground),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetSelectionRanges, Background),
+    (GetSelectionRangesResponse, Background),
     (AdvertiseContexts, Foreground),
     (OpenContext, Foreground),
     (OpenContextResponse, Foreground),
@@ -358,6 +384,13 @@ messages!(
     (GitBranches, Background),
     (GitBranchesResponse, Background),
     (UpdateGitBranch, Background),
+    (GitStashList, Background),
+    (GitStashListResponse, Background),
+    (GitStashPush, Background),
+    (GitStashPop, Background),
+    (GitFetch, Background),
+    (GitPull, Background),
+    (GitPush, Background),
     (ListToolchains, Foreground),
     (ListToolchainsResponse, Foreground),
     (ActivateToolchain, Foreground),
@@ -415,6 +448,20 @@ request_messages!(
     (GetStagedText, GetStagedTextResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (LinkedEditingRange, LinkedEditingRangeResponse),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (GetIncomingCalls, GetIncomingCallsResponse),
+    (GetOutgoingCalls, GetOutgoingCallsResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
+    (GetSupertypes, GetSupertypesResponse),
+    (GetSubtypes, GetSubtypesResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (RefreshSemanticTokens, Ack),
+    (GetCodeLens, GetCodeLensResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (ApplyCodeLens, ApplyCodeLensResponse),
+    (RefreshCodeLens, Ack),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetSelectionRanges, GetSelectionRangesResponse),
     (ListRemoteDirectory, ListRemoteDirectoryResponse),
     (GetUsers, UsersResponse),
     (IncomingCall, Ack),
@@ -489,6 +536,12 @@ request_messages!(
     (LanguageServerPromptRequest, LanguageServerPromptResponse),
     (GitBranches, GitBranchesResponse),
     (UpdateGitBranch, Ack),
+    (GitStashList, GitStashListResponse),
+    (GitStashPush, Ack),
+    (GitStashPop, Ack),
+    (GitFetch, Ack),
+    (GitPull, Ack),
+    (GitPush, Ack),
     (ListToolchains, ListToolchainsResponse),
     (ActivateToolchain, Ack),
     (ActiveToolchain, ActiveToolchainResponse),
@@ -533,6 +586,18 @@ entity_messages!(
     JoinProject,
     LeaveProject,
     LinkedEditingRange,
+    PrepareCallHierarchy,
+    GetIncomingCalls,
+    GetOutgoingCalls,
+    PrepareTypeHierarchy,
+    GetSupertypes,
+    GetSubtypes,
+    GetSemanticTokens,
+    GetCodeLens,
+    ResolveCodeLens,
+    ApplyCodeLens,
+    GetFoldingRanges,
+    GetSelectionRanges,
     MultiLspQuery,
     RestartLanguageServers,
     OnTypeFormatting,
@@ -543,6 +608,8 @@ entity_messages!(
     PerformRename,
     PrepareRename,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
@@ -578,6 +645,12 @@ entity_messages!(
     LanguageServerPromptRequest,
     GitBranches,
     UpdateGitBranch,
+    GitStashList,
+    GitStashPush,
+    GitStashPop,
+    GitFetch,
+    GitPull,
+    GitPush,
     ListToolchains,
     ActivateToolchain,
     ActiveToolchain,
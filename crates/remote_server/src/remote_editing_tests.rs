@@ -3,6 +3,7 @@ use client::{Client, UserStore};
 use clock::FakeSystemClock;
 use extension::ExtensionHostProxy;
 use fs::{FakeFs, Fs};
+use git::repository::{GitFileStatus, PullStrategy, StashEntry};
 use gpui::{Context, Model, SemanticVersion, TestAppContext};
 use http_client::{BlockedHttpClient, FakeHttpClient};
 use language::{
@@ -1226,6 +1227,94 @@ async fn test_remote_git_branches(cx: &mut TestAppContext, server_cx: &mut TestA
     assert_eq!(server_branch.as_ref(), "totally-new-branch");
 }
 
+#[gpui::test]
+async fn test_remote_git_stash_and_sync(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                ".git": {},
+                "README.md": "# project 1",
+            },
+        }),
+    )
+    .await;
+
+    let dot_git = Path::new("/code/project1/.git");
+    let (project, _headless_project) = init_test(&fs, cx, server_cx).await;
+    fs.insert_branches(dot_git, &["main"]);
+    fs.set_status_for_repo_via_git_operation(
+        dot_git,
+        &[(Path::new("README.md"), GitFileStatus::Modified)],
+    );
+
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = cx.update(|cx| worktree.read(cx).id());
+    let root_path = ProjectPath::root_path(worktree_id);
+    cx.run_until_parked();
+
+    project
+        .update(cx, |project, cx| {
+            project.stash_push(root_path.clone(), Some("wip".to_string()), cx)
+        })
+        .await
+        .unwrap();
+    let stash_entries = project
+        .update(cx, |project, cx| {
+            project.stash_entries(root_path.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        stash_entries,
+        [StashEntry {
+            index: 0,
+            message: "On main: wip".to_string(),
+        }]
+    );
+    fs.with_git_state(dot_git, false, |state| {
+        assert!(state.worktree_statuses.is_empty());
+    });
+
+    project
+        .update(cx, |project, cx| {
+            project.stash_pop(root_path.clone(), 0, cx)
+        })
+        .await
+        .unwrap();
+    fs.with_git_state(dot_git, false, |state| {
+        assert!(state.stashes.is_empty());
+        assert_eq!(state.worktree_statuses.len(), 1);
+    });
+
+    project
+        .update(cx, |project, cx| project.fetch(root_path.clone(), cx))
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.pull(root_path.clone(), PullStrategy::Rebase, cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| project.push(root_path.clone(), cx))
+        .await
+        .unwrap();
+    fs.with_git_state(dot_git, false, |state| {
+        assert_eq!(
+            state.remote_operations,
+            ["fetch", "pull --rebase", "push origin main"]
+        );
+    });
+}
+
 pub async fn init_test(
     server_fs: &Arc<FakeFs>,
     cx: &mut TestAppContext,
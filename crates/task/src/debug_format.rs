@@ -0,0 +1,258 @@
+use std::path::PathBuf;
+
+use collections::{HashMap, HashSet};
+use schemars::{gen::SchemaSettings, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    task_template::{
+        substitute_all_template_variables_in_str, substitute_all_template_variables_in_vec,
+    },
+    TaskContext, TaskTemplate, VariableName,
+};
+
+/// Whether a debug session starts the program to debug, or attaches to a running one.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugRequestKind {
+    /// Start the program with the debug adapter's `launch` request.
+    #[default]
+    Launch,
+    /// Attach to a running program with the debug adapter's `attach` request.
+    Attach,
+}
+
+/// A template definition of a debug session, started with a debug adapter.
+/// Its string fields may use the same [`VariableName`] substitutions as a [`TaskTemplate`].
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DebugTemplate {
+    /// Human readable name of the debug configuration to display in the UI.
+    pub label: String,
+    /// The identifier of the debug adapter, e.g. `lldb` or `debugpy`, sent to the adapter when the session starts.
+    pub adapter: String,
+    /// Executable command that starts the debug adapter.
+    pub command: String,
+    /// Arguments to the debug adapter command.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Env overrides for the debug adapter command.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Current working directory to spawn the debug adapter into, defaults to current project root.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Whether to launch the program or attach to a running one:
+    /// * `launch` — start the program (default)
+    /// * `attach` — attach to a running program
+    #[serde(default)]
+    pub request: DebugRequestKind,
+    /// The adapter-specific arguments of the `launch` or `attach` request, such as the program to debug.
+    /// Task variables in its strings are substituted.
+    #[serde(default)]
+    pub configuration: serde_json::Value,
+}
+
+/// A group of debug configurations defined in a JSON file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DebugTemplates(pub Vec<DebugTemplate>);
+
+impl DebugTemplates {
+    /// Generates JSON schema of the debug configurations JSON format.
+    pub fn generate_json_schema() -> serde_json_lenient::Value {
+        let schema = SchemaSettings::draft07()
+            .with(|settings| settings.option_add_null_type = false)
+            .into_generator()
+            .into_root_schema_for::<Self>();
+
+        serde_json_lenient::to_value(schema).unwrap()
+    }
+}
+
+/// A final form of the [`DebugTemplate`], resolved with a particular [`TaskContext`] and ready to start a debug session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedDebugTemplate {
+    /// The label of the template, with its variables substituted.
+    pub label: String,
+    /// The identifier of the debug adapter.
+    pub adapter: String,
+    /// Executable command that starts the debug adapter.
+    pub command: String,
+    /// Arguments to the debug adapter command.
+    pub args: Vec<String>,
+    /// Environment of the debug adapter process.
+    pub env: HashMap<String, String>,
+    /// Current working directory to spawn the debug adapter into.
+    pub cwd: Option<PathBuf>,
+    /// Whether to launch the program or attach to a running one.
+    pub request: DebugRequestKind,
+    /// The arguments of the `launch` or `attach` request.
+    pub configuration: serde_json::Value,
+}
+
+impl DebugTemplate {
+    /// Replaces all [`VariableName`] task variables in the template, the way [`TaskTemplate::resolve_task`] does.
+    /// Returns `None` if any replacement fails.
+    pub fn resolve(&self, cx: &TaskContext) -> Option<ResolvedDebugTemplate> {
+        if self.adapter.trim().is_empty() {
+            return None;
+        }
+        let task = TaskTemplate {
+            label: self.label.clone(),
+            command: self.command.clone(),
+            env: self.env.clone(),
+            cwd: self.cwd.clone(),
+            ..TaskTemplate::default()
+        }
+        .resolve_task("debug", cx)?
+        .resolved?;
+
+        let mut variable_names = HashMap::default();
+        let task_variables = cx
+            .task_variables
+            .0
+            .iter()
+            .map(|(key, value)| {
+                let key_string = key.to_string();
+                variable_names.insert(key_string.clone(), key.clone());
+                (key_string, value.as_str())
+            })
+            .collect::<HashMap<_, _>>();
+        let mut substituted_variables = HashSet::default();
+        let args = substitute_all_template_variables_in_vec(
+            &self.args,
+            &task_variables,
+            &variable_names,
+            &mut substituted_variables,
+        )?;
+        let configuration = substitute_variables_in_json(
+            &self.configuration,
+            &task_variables,
+            &variable_names,
+            &mut substituted_variables,
+        )?;
+
+        Some(ResolvedDebugTemplate {
+            label: task.full_label,
+            adapter: self.adapter.clone(),
+            command: task.command,
+            args,
+            env: task.env,
+            cwd: task.cwd,
+            request: self.request,
+            configuration,
+        })
+    }
+}
+
+fn substitute_variables_in_json(
+    value: &serde_json::Value,
+    task_variables: &HashMap<String, &str>,
+    variable_names: &HashMap<String, VariableName>,
+    substituted_variables: &mut HashSet<VariableName>,
+) -> Option<serde_json::Value> {
+    Some(match value {
+        serde_json::Value::String(string) => {
+            serde_json::Value::String(substitute_all_template_variables_in_str(
+                string,
+                task_variables,
+                variable_names,
+                substituted_variables,
+            )?)
+        }
+        serde_json::Value::Array(values) => serde_json::Value::Array(
+            values
+                .iter()
+                .map(|value| {
+                    substitute_variables_in_json(
+                        value,
+                        task_variables,
+                        variable_names,
+                        substituted_variables,
+                    )
+                })
+                .collect::<Option<_>>()?,
+        ),
+        serde_json::Value::Object(object) => serde_json::Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| {
+                    Some((
+                        key.clone(),
+                        substitute_variables_in_json(
+                            value,
+                            task_variables,
+                            variable_names,
+                            substituted_variables,
+                        )?,
+                    ))
+                })
+                .collect::<Option<_>>()?,
+        ),
+        value => value.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use crate::TaskVariables;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_debug_template() {
+        let template = DebugTemplate {
+            label: "Debug $ZED_STEM".into(),
+            adapter: "lldb".into(),
+            command: "lldb-dap".into(),
+            args: vec!["--port".into(), "0".into()],
+            cwd: Some("$ZED_WORKTREE_ROOT".into()),
+            configuration: json!({
+                "program": "$ZED_WORKTREE_ROOT/target/debug/$ZED_STEM",
+                "args": ["$ZED_FILE"],
+                "stopOnEntry": true,
+            }),
+            ..DebugTemplate::default()
+        };
+        let cx = TaskContext {
+            cwd: None,
+            task_variables: TaskVariables::from_iter([
+                (VariableName::Stem, "main".to_string()),
+                (VariableName::WorktreeRoot, "/project".to_string()),
+                (VariableName::File, "/project/src/main.rs".to_string()),
+            ]),
+            project_env: HashMap::default(),
+        };
+
+        let resolved = template.resolve(&cx).unwrap();
+        assert_eq!(resolved.label, "Debug main");
+        assert_eq!(resolved.command, "lldb-dap");
+        assert_eq!(resolved.args, vec!["--port", "0"]);
+        assert_eq!(resolved.cwd.as_deref(), Some(Path::new("/project")));
+        assert_eq!(resolved.request, DebugRequestKind::Launch);
+        assert_eq!(
+            resolved.configuration,
+            json!({
+                "program": "/project/target/debug/main",
+                "args": ["/project/src/main.rs"],
+                "stopOnEntry": true,
+            })
+        );
+
+        let unknown_variable = DebugTemplate {
+            configuration: json!({ "program": "$ZED_UNKNOWN" }),
+            ..template.clone()
+        };
+        assert_eq!(unknown_variable.resolve(&cx), None);
+
+        let no_adapter = DebugTemplate {
+            adapter: String::new(),
+            ..template
+        };
+        assert_eq!(no_adapter.resolve(&cx), None);
+    }
+}
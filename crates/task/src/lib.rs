@@ -1,6 +1,8 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod debug_format;
+mod problem_matcher;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
@@ -13,7 +15,15 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use debug_format::{DebugRequestKind, DebugTemplate, DebugTemplates, ResolvedDebugTemplate};
+pub use problem_matcher::{
+    match_problems, BuiltinProblemMatcher, CustomProblemMatcher, Problem, ProblemMatcher,
+    ProblemPattern, ProblemSeverity,
+};
+pub use task_template::{
+    DependsOrder, HideStrategy, RevealStrategy, TaskInput, TaskInputKind, TaskTemplate,
+    TaskTemplates,
+};
 pub use vscode_format::VsCodeTaskFile;
 pub use zed_actions::RevealTarget;
 
@@ -58,6 +68,12 @@ pub struct SpawnInTerminal {
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     pub show_command: bool,
+    /// Tasks to spawn before this one, in steps: all tasks of a step are spawned together,
+    /// and a step starts only after every task of the previous one succeeded.
+    /// A task with dependencies may have an empty `command`, then nothing is spawned after them.
+    pub depends_on: Vec<Vec<SpawnInTerminal>>,
+    /// Matchers to find problems in the task output with, once the task finishes.
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particualar [`TaskContext`] and now is ready to spawn the actual task.
@@ -71,6 +87,8 @@ pub struct ResolvedTask {
     pub id: TaskId,
     /// A template the task got resolved from.
     original_task: TaskTemplate,
+    /// The context the task got resolved with, used to resolve its dependencies.
+    task_context: TaskContext,
     /// Full, unshortened label of the task after all resolutions are made.
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
@@ -86,6 +104,21 @@ impl ResolvedTask {
         &self.original_task
     }
 
+    /// The context the task got resolved with.
+    pub fn task_context(&self) -> &TaskContext {
+        &self.task_context
+    }
+
+    /// Inputs of the task that got no values in its context: the task has to be resolved again with their values before spawning.
+    pub fn missing_inputs(&self) -> impl Iterator<Item = &TaskInput> {
+        self.original_task.inputs.iter().filter(|input| {
+            self.task_context
+                .task_variables
+                .get(&input.variable_name())
+                .is_none()
+        })
+    }
+
     /// Variables that were substituted during the task template resolution.
     pub fn substituted_variables(&self) -> &HashSet<VariableName> {
         &self.substituted_variables
@@ -131,6 +164,9 @@ pub enum VariableName {
     /// Custom variable, provided by the plugin or other external source.
     /// Will be printed with `CUSTOM_` prefix to avoid potential conflicts with other variables.
     Custom(Cow<'static, str>),
+    /// A value of the task's input, provided by the user when the task is spawned.
+    /// Will be printed with `INPUT_` prefix.
+    Input(Cow<'static, str>),
 }
 
 impl VariableName {
@@ -166,6 +202,10 @@ impl FromStr for VariableName {
                     without_prefix.strip_prefix(ZED_CUSTOM_VARIABLE_NAME_PREFIX)
                 {
                     Self::Custom(Cow::Owned(custom_name.to_owned()))
+                } else if let Some(input_id) =
+                    without_prefix.strip_prefix(ZED_INPUT_VARIABLE_NAME_PREFIX)
+                {
+                    Self::Input(Cow::Owned(input_id.to_owned()))
                 } else {
                     return Err(());
                 }
@@ -178,6 +218,7 @@ impl FromStr for VariableName {
 /// A prefix that all [`VariableName`] variants are prefixed with when used in environment variables and similar template contexts.
 pub const ZED_VARIABLE_NAME_PREFIX: &str = "ZED_";
 const ZED_CUSTOM_VARIABLE_NAME_PREFIX: &str = "CUSTOM_";
+const ZED_INPUT_VARIABLE_NAME_PREFIX: &str = "INPUT_";
 
 impl std::fmt::Display for VariableName {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -197,6 +238,10 @@ impl std::fmt::Display for VariableName {
                 f,
                 "{ZED_VARIABLE_NAME_PREFIX}{ZED_CUSTOM_VARIABLE_NAME_PREFIX}{s}"
             ),
+            Self::Input(s) => write!(
+                f,
+                "{ZED_VARIABLE_NAME_PREFIX}{ZED_INPUT_VARIABLE_NAME_PREFIX}{s}"
+            ),
         }
     }
 }
@@ -0,0 +1,591 @@
+//! Problem matchers turn the output of a task into a list of problems (errors, warnings, etc.) found in the files it processed.
+
+use std::sync::LazyLock;
+
+use anyhow::Context as _;
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+
+/// A way to find problems in the task output, either one of the built-in matchers or a custom one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// One of the matchers for well-known tools.
+    Builtin(BuiltinProblemMatcher),
+    /// A matcher with user-defined patterns.
+    Custom(CustomProblemMatcher),
+}
+
+/// Matchers for the output of well-known tools.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinProblemMatcher {
+    /// `rustc` and `cargo` diagnostics in the default, human-readable format.
+    Rustc,
+    /// `gcc` and `clang` diagnostics.
+    Gcc,
+    /// TypeScript compiler diagnostics.
+    Tsc,
+    /// ESLint diagnostics in the default, `stylish` format.
+    Eslint,
+}
+
+/// A problem matcher defined by regular expressions, each matching a line of the task output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CustomProblemMatcher {
+    /// Name of the tool reporting the problems, shown along with them.
+    /// Defaults to the task label.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Severity of the problems whose patterns do not capture any.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+    /// Patterns matching consecutive lines of a single problem report, in order.
+    /// The problem is reported when the last pattern matches.
+    pub pattern: Vec<ProblemPattern>,
+}
+
+/// A regular expression matching a task output line, and the indices of its capture groups for every part of a problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ProblemPattern {
+    /// The regular expression to match the line with.
+    pub regexp: String,
+    /// Capture group with the path to the file, absolute or relative to the task's working directory.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// Capture group with the 1-based line of the problem.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Capture group with the 1-based column of the problem.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// Capture group with the 1-based line the problem ends at.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// Capture group with the 1-based column the problem ends at.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// Capture group with the severity of the problem: `error`, `warning`, `info` or `hint`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// Capture group with the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// Capture group with the message of the problem.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the pattern, if it is the last one, can match multiple consecutive lines,
+    /// each reporting a problem with the parts captured by the previous patterns.
+    #[serde(default, rename = "loop")]
+    pub loop_: bool,
+}
+
+/// How severe a problem is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error (default).
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message, e.g. a `note`.
+    Info,
+    /// A hint.
+    Hint,
+}
+
+impl ProblemSeverity {
+    fn parse(severity: &str) -> Option<Self> {
+        match severity.to_lowercase().as_str() {
+            "error" | "fatal" => Some(Self::Error),
+            "warning" | "warn" => Some(Self::Warning),
+            "info" | "information" | "note" => Some(Self::Info),
+            "hint" | "help" => Some(Self::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in the task output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// Path to the file, as printed by the task.
+    pub path: String,
+    /// 1-based line of the problem.
+    pub line: u32,
+    /// 1-based column of the problem, if reported.
+    pub column: Option<u32>,
+    /// 1-based line the problem ends at, if reported.
+    pub end_line: Option<u32>,
+    /// 1-based column the problem ends at, if reported.
+    pub end_column: Option<u32>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// Code of the problem, e.g. `E0308`, if reported.
+    pub code: Option<String>,
+    /// Message describing the problem.
+    pub message: String,
+    /// Name of the tool that reported the problem, if known.
+    pub source: Option<String>,
+}
+
+static RUSTC: LazyLock<CustomProblemMatcher> = LazyLock::new(|| CustomProblemMatcher {
+    source: Some("rustc".to_string()),
+    severity: ProblemSeverity::Error,
+    pattern: vec![
+        ProblemPattern {
+            regexp: r"^(warning|error)(?:\[(\w+)\])?: (.*)$".to_string(),
+            severity: Some(1),
+            code: Some(2),
+            message: Some(3),
+            ..ProblemPattern::default()
+        },
+        ProblemPattern {
+            regexp: r"^\s*--> (.+?):(\d+):(\d+)$".to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            ..ProblemPattern::default()
+        },
+    ],
+});
+
+static GCC: LazyLock<CustomProblemMatcher> = LazyLock::new(|| CustomProblemMatcher {
+    source: Some("gcc".to_string()),
+    severity: ProblemSeverity::Error,
+    pattern: vec![ProblemPattern {
+        regexp: r"^(.+?):(\d+):(\d+):\s+(?:fatal\s+)?(error|warning|note):\s+(.*)$".to_string(),
+        file: Some(1),
+        line: Some(2),
+        column: Some(3),
+        severity: Some(4),
+        message: Some(5),
+        ..ProblemPattern::default()
+    }],
+});
+
+static TSC: LazyLock<CustomProblemMatcher> = LazyLock::new(|| CustomProblemMatcher {
+    source: Some("tsc".to_string()),
+    severity: ProblemSeverity::Error,
+    pattern: vec![ProblemPattern {
+        regexp: concat!(
+            r"^([^\s].*?)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)",
+            r"(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$",
+        )
+        .to_string(),
+        file: Some(1),
+        line: Some(2),
+        column: Some(3),
+        severity: Some(4),
+        code: Some(5),
+        message: Some(6),
+        ..ProblemPattern::default()
+    }],
+});
+
+static ESLINT: LazyLock<CustomProblemMatcher> = LazyLock::new(|| CustomProblemMatcher {
+    source: Some("eslint".to_string()),
+    severity: ProblemSeverity::Error,
+    pattern: vec![
+        ProblemPattern {
+            regexp: r"^([^\s].*)$".to_string(),
+            file: Some(1),
+            ..ProblemPattern::default()
+        },
+        ProblemPattern {
+            regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$".to_string(),
+            line: Some(1),
+            column: Some(2),
+            severity: Some(3),
+            message: Some(4),
+            code: Some(5),
+            loop_: true,
+            ..ProblemPattern::default()
+        },
+    ],
+});
+
+impl BuiltinProblemMatcher {
+    fn matcher(&self) -> &'static CustomProblemMatcher {
+        match self {
+            Self::Rustc => &RUSTC,
+            Self::Gcc => &GCC,
+            Self::Tsc => &TSC,
+            Self::Eslint => &ESLINT,
+        }
+    }
+}
+
+impl ProblemMatcher {
+    fn custom(&self) -> &CustomProblemMatcher {
+        match self {
+            Self::Builtin(builtin) => builtin.matcher(),
+            Self::Custom(custom) => custom,
+        }
+    }
+}
+
+/// Finds all problems the matchers given report in the task output lines.
+/// Matchers with invalid regular expressions are skipped.
+pub fn match_problems<'a>(
+    matchers: &[ProblemMatcher],
+    lines: impl IntoIterator<Item = &'a str>,
+) -> Vec<Problem> {
+    let lines = lines.into_iter().collect::<Vec<_>>();
+    matchers
+        .iter()
+        .filter_map(|matcher| CompiledMatcher::new(matcher.custom()).log_err())
+        .flat_map(|matcher| matcher.match_lines(&lines))
+        .collect()
+}
+
+struct CompiledMatcher<'a> {
+    matcher: &'a CustomProblemMatcher,
+    regexes: Vec<Regex>,
+}
+
+/// Parts of a problem, captured by the patterns matched so far.
+#[derive(Clone, Default)]
+struct CapturedProblem {
+    path: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl<'a> CompiledMatcher<'a> {
+    fn new(matcher: &'a CustomProblemMatcher) -> anyhow::Result<Self> {
+        let regexes = matcher
+            .pattern
+            .iter()
+            .map(|pattern| {
+                Regex::new(&pattern.regexp).with_context(|| {
+                    format!("compiling problem matcher pattern {:?}", pattern.regexp)
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self { matcher, regexes })
+    }
+
+    fn match_lines(&self, lines: &[&str]) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        if self.regexes.is_empty() {
+            return problems;
+        }
+
+        let mut next_pattern = 0;
+        let mut captured = CapturedProblem::default();
+        for line in lines {
+            if next_pattern > 0 {
+                if let Some(pattern_ix) =
+                    self.match_line(next_pattern, line, &mut captured, &mut problems)
+                {
+                    next_pattern = pattern_ix;
+                    continue;
+                }
+                next_pattern = 0;
+                captured = CapturedProblem::default();
+            }
+            if let Some(pattern_ix) = self.match_line(0, line, &mut captured, &mut problems) {
+                next_pattern = pattern_ix;
+            }
+        }
+        problems
+    }
+
+    /// Matches the line with the pattern given, returning the index of the pattern to match the next line with.
+    fn match_line(
+        &self,
+        pattern_ix: usize,
+        line: &str,
+        captured: &mut CapturedProblem,
+        problems: &mut Vec<Problem>,
+    ) -> Option<usize> {
+        let captures = self.regexes[pattern_ix].captures(line)?;
+        let pattern = &self.matcher.pattern[pattern_ix];
+        if pattern_ix + 1 < self.regexes.len() {
+            captured.capture(pattern, &captures);
+            return Some(pattern_ix + 1);
+        }
+
+        let mut problem = captured.clone();
+        problem.capture(pattern, &captures);
+        problems.extend(problem.into_problem(self.matcher));
+        if pattern.loop_ {
+            Some(pattern_ix)
+        } else {
+            *captured = CapturedProblem::default();
+            Some(0)
+        }
+    }
+}
+
+impl CapturedProblem {
+    fn capture(&mut self, pattern: &ProblemPattern, captures: &Captures) {
+        let group = |ix: Option<usize>| {
+            ix.and_then(|ix| captures.get(ix))
+                .map(|group| group.as_str().trim())
+                .filter(|group| !group.is_empty())
+        };
+        let number = |ix: Option<usize>| group(ix).and_then(|group| group.parse().ok());
+
+        if let Some(path) = group(pattern.file) {
+            self.path = Some(path.to_string());
+        }
+        if let Some(line) = number(pattern.line) {
+            self.line = Some(line);
+        }
+        if let Some(column) = number(pattern.column) {
+            self.column = Some(column);
+        }
+        if let Some(end_line) = number(pattern.end_line) {
+            self.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(pattern.end_column) {
+            self.end_column = Some(end_column);
+        }
+        if let Some(severity) = group(pattern.severity).and_then(ProblemSeverity::parse) {
+            self.severity = Some(severity);
+        }
+        if let Some(code) = group(pattern.code) {
+            self.code = Some(code.to_string());
+        }
+        if let Some(message) = group(pattern.message) {
+            self.message = Some(message.to_string());
+        }
+    }
+
+    fn into_problem(self, matcher: &CustomProblemMatcher) -> Option<Problem> {
+        Some(Problem {
+            path: self.path?,
+            line: self.line?,
+            column: self.column,
+            end_line: self.end_line,
+            end_column: self.end_column,
+            severity: self.severity.unwrap_or(matcher.severity),
+            code: self.code,
+            message: self.message?,
+            source: matcher.source.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(
+        path: &str,
+        line: u32,
+        column: u32,
+        severity: ProblemSeverity,
+        code: Option<&str>,
+        message: &str,
+        source: &str,
+    ) -> Problem {
+        Problem {
+            path: path.to_string(),
+            line,
+            column: Some(column),
+            end_line: None,
+            end_column: None,
+            severity,
+            code: code.map(|code| code.to_string()),
+            message: message.to_string(),
+            source: Some(source.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_rustc_matcher() {
+        let output = r#"   Compiling demo v0.1.0 (/projects/demo)
+warning: unused variable: `x`
+ --> src/main.rs:2:9
+  |
+2 |     let x = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+error[E0308]: mismatched types
+  --> src/lib.rs:10:18
+   |
+error: could not compile `demo` (bin "demo") due to 1 previous error"#;
+        assert_eq!(
+            match_problems(
+                &[ProblemMatcher::Builtin(BuiltinProblemMatcher::Rustc)],
+                output.lines()
+            ),
+            vec![
+                problem(
+                    "src/main.rs",
+                    2,
+                    9,
+                    ProblemSeverity::Warning,
+                    None,
+                    "unused variable: `x`",
+                    "rustc"
+                ),
+                problem(
+                    "src/lib.rs",
+                    10,
+                    18,
+                    ProblemSeverity::Error,
+                    Some("E0308"),
+                    "mismatched types",
+                    "rustc"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gcc_and_tsc_matchers() {
+        let output = r#"main.c:3:5: error: implicit declaration of function 'foo'
+main.c:7:1: warning: control reaches end of non-void function
+src/index.ts(4,7): error TS2322: Type 'string' is not assignable to type 'number'.
+src/util.ts:12:3 - error TS2304: Cannot find name 'bar'."#;
+        assert_eq!(
+            match_problems(
+                &[
+                    ProblemMatcher::Builtin(BuiltinProblemMatcher::Gcc),
+                    ProblemMatcher::Builtin(BuiltinProblemMatcher::Tsc),
+                ],
+                output.lines()
+            ),
+            vec![
+                problem(
+                    "main.c",
+                    3,
+                    5,
+                    ProblemSeverity::Error,
+                    None,
+                    "implicit declaration of function 'foo'",
+                    "gcc"
+                ),
+                problem(
+                    "main.c",
+                    7,
+                    1,
+                    ProblemSeverity::Warning,
+                    None,
+                    "control reaches end of non-void function",
+                    "gcc"
+                ),
+                problem(
+                    "src/index.ts",
+                    4,
+                    7,
+                    ProblemSeverity::Error,
+                    Some("TS2322"),
+                    "Type 'string' is not assignable to type 'number'.",
+                    "tsc"
+                ),
+                problem(
+                    "src/util.ts",
+                    12,
+                    3,
+                    ProblemSeverity::Error,
+                    Some("TS2304"),
+                    "Cannot find name 'bar'.",
+                    "tsc"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_eslint_matcher() {
+        let output = r#"
+/projects/demo/src/a.js
+   1:10  error    'foo' is defined but never used  no-unused-vars
+  14:3   warning  Unexpected console statement     no-console
+
+/projects/demo/src/b.js
+  2:1  error  Parsing error: Unexpected token
+
+✖ 3 problems (2 errors, 1 warning)"#;
+        assert_eq!(
+            match_problems(
+                &[ProblemMatcher::Builtin(BuiltinProblemMatcher::Eslint)],
+                output.lines()
+            ),
+            vec![
+                problem(
+                    "/projects/demo/src/a.js",
+                    1,
+                    10,
+                    ProblemSeverity::Error,
+                    Some("no-unused-vars"),
+                    "'foo' is defined but never used",
+                    "eslint"
+                ),
+                problem(
+                    "/projects/demo/src/a.js",
+                    14,
+                    3,
+                    ProblemSeverity::Warning,
+                    Some("no-console"),
+                    "Unexpected console statement",
+                    "eslint"
+                ),
+                problem(
+                    "/projects/demo/src/b.js",
+                    2,
+                    1,
+                    ProblemSeverity::Error,
+                    None,
+                    "Parsing error: Unexpected token",
+                    "eslint"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_matcher() {
+        let matcher: ProblemMatcher = serde_json::from_value(serde_json::json!({
+            "severity": "warning",
+            "pattern": [{
+                "regexp": "^(.*)@(\\d+): (.*)$",
+                "file": 1,
+                "line": 2,
+                "message": 3,
+            }],
+        }))
+        .unwrap();
+        let invalid_matcher = ProblemMatcher::Custom(CustomProblemMatcher {
+            source: None,
+            severity: ProblemSeverity::Error,
+            pattern: vec![ProblemPattern {
+                regexp: "(".to_string(),
+                ..ProblemPattern::default()
+            }],
+        });
+        assert_eq!(
+            match_problems(
+                &[matcher, invalid_matcher],
+                ["docs/readme.md@3: line too long", "all good"]
+            ),
+            vec![Problem {
+                path: "docs/readme.md".to_string(),
+                line: 3,
+                column: None,
+                end_line: None,
+                end_column: None,
+                severity: ProblemSeverity::Warning,
+                code: None,
+                message: "line too long".to_string(),
+                source: None,
+            }]
+        );
+        assert_eq!(
+            serde_json::from_value::<ProblemMatcher>(serde_json::json!("rustc")).unwrap(),
+            ProblemMatcher::Builtin(BuiltinProblemMatcher::Rustc)
+        );
+    }
+}
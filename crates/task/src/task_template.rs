@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{borrow::Cow, path::PathBuf};
 use util::serde::default_true;
 
 use anyhow::{bail, Context};
@@ -9,8 +9,8 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    ProblemMatcher, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId,
+    VariableName, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -70,6 +70,83 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks to run before this one, e.g. a build before the tests.
+    /// The task only starts once all of them succeed; a task with dependencies may omit its `command`.
+    /// Dependencies that never exit, e.g. watchers, keep the task from starting until they are stopped.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks in `depends_on`:
+    /// * `sequence` — one after another, in the order they are listed, each after the previous one succeeds (default)
+    /// * `parallel` — all at once
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Matchers that find problems in the task output and report them as project diagnostics, once the task finishes.
+    /// Either names of the built-in matchers: `rustc`, `gcc`, `tsc`, `eslint`,
+    /// or custom matchers, with regular expressions and the capture groups of the problem parts in them.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Values to ask the user for when the task is spawned, available in the task as `$ZED_INPUT_<id>` variables.
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+}
+
+/// A value to ask the user for when the task is spawned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TaskInput {
+    /// Identifier of the input, the value is available in the task as `$ZED_INPUT_<id>`.
+    pub id: String,
+    /// Text describing the input, shown when asking for the value.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// How to get the value.
+    #[serde(flatten)]
+    pub kind: TaskInputKind,
+}
+
+/// How to get the value of a [`TaskInput`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskInputKind {
+    /// Free text, typed by the user.
+    PromptString {
+        /// The value to use when nothing is typed.
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// One of the options given, picked by the user.
+    PickString {
+        /// The options to pick from.
+        options: Vec<String>,
+        /// The option to select initially.
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// One of the lines of the shell command output, picked by the user if there are several.
+    Command {
+        /// The command to run, in the task's working directory.
+        command: String,
+        /// Arguments of the command.
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl TaskInput {
+    /// The variable the value of the input is available as.
+    pub fn variable_name(&self) -> VariableName {
+        VariableName::Input(Cow::Owned(self.id.clone()))
+    }
+}
+
+/// How to run the dependencies of a task.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Run the dependencies one after another, in the order they are listed.
+    #[default]
+    Sequence,
+    /// Run all dependencies at once.
+    Parallel,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -122,12 +199,22 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
         let mut variable_names = HashMap::default();
         let mut substituted_variables = HashSet::default();
+        // Inputs without values yet are kept as variables in the resolved task, to be substituted once the user provides them.
+        let missing_inputs = self
+            .inputs
+            .iter()
+            .map(|input| input.variable_name())
+            .filter(|variable| cx.task_variables.get(variable).is_none())
+            .map(|variable| (variable.to_string(), format!("${{{variable}}}")))
+            .collect::<HashMap<_, _>>();
         let task_variables = cx
             .task_variables
             .0
@@ -139,8 +226,14 @@ impl TaskTemplate {
                 }
                 (key_string, value.as_str())
             })
+            .chain(
+                missing_inputs
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.as_str())),
+            )
             .collect::<HashMap<_, _>>();
-        let truncated_variables = truncate_variables(&task_variables);
+        let mut truncated_variables = truncate_variables(&task_variables);
+        truncated_variables.extend(missing_inputs.clone());
         let cwd = match self.cwd.as_deref() {
             Some(cwd) => {
                 let substitured_cwd = substitute_all_template_variables_in_str(
@@ -213,7 +306,12 @@ impl TaskTemplate {
             )?;
 
             // Last step: set the task variables as environment variables too
-            env.extend(task_variables.into_iter().map(|(k, v)| (k, v.to_owned())));
+            env.extend(
+                task_variables
+                    .into_iter()
+                    .filter(|(k, _)| !missing_inputs.contains_key(k))
+                    .map(|(k, v)| (k, v.to_owned())),
+            );
             env
         };
 
@@ -221,6 +319,7 @@ impl TaskTemplate {
             id: id.clone(),
             substituted_variables,
             original_task: self.clone(),
+            task_context: cx.clone(),
             resolved_label: full_label.clone(),
             resolved: Some(SpawnInTerminal {
                 id,
@@ -246,6 +345,8 @@ impl TaskTemplate {
                 shell: self.shell.clone(),
                 show_summary: self.show_summary,
                 show_command: self.show_command,
+                depends_on: Vec::new(),
+                problem_matchers: self.problem_matchers.clone(),
             }),
         })
     }
@@ -272,7 +373,7 @@ fn to_hex_hash(object: impl Serialize) -> anyhow::Result<String> {
     Ok(hex::encode(hasher.finalize()))
 }
 
-fn substitute_all_template_variables_in_str<A: AsRef<str>>(
+pub(crate) fn substitute_all_template_variables_in_str<A: AsRef<str>>(
     template_str: &str,
     task_variables: &HashMap<String, A>,
     variable_names: &HashMap<String, VariableName>,
@@ -309,7 +410,7 @@ fn substitute_all_template_variables_in_str<A: AsRef<str>>(
     Some(substituted_string.into_owned())
 }
 
-fn substitute_all_template_variables_in_vec(
+pub(crate) fn substitute_all_template_variables_in_vec(
     template_strs: &[String],
     task_variables: &HashMap<String, &str>,
     variable_names: &HashMap<String, VariableName>,
@@ -396,6 +497,116 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolving_templates_with_dependencies_only() {
+        let compound_task = TaskTemplate {
+            label: "build and test".to_string(),
+            depends_on: vec!["build".to_string(), "test".to_string()],
+            ..TaskTemplate::default()
+        };
+        let resolved_task = compound_task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("task with dependencies and no command should resolve");
+        let spawn_in_terminal = resolved_task.resolved.as_ref().unwrap();
+        assert_eq!(spawn_in_terminal.label, "build and test");
+        assert!(spawn_in_terminal.command.is_empty());
+        assert!(
+            spawn_in_terminal.depends_on.is_empty(),
+            "dependencies are resolved by the task inventory, not the template"
+        );
+        assert_eq!(
+            resolved_task.original_task().depends_on,
+            compound_task.depends_on
+        );
+
+        assert_eq!(
+            TaskTemplate {
+                label: "".to_string(),
+                ..compound_task
+            }
+            .resolve_task(TEST_ID_BASE, &TaskContext::default()),
+            None,
+            "should not resolve task with blank label"
+        );
+    }
+
+    #[test]
+    fn test_resolving_templates_with_inputs() {
+        let template: TaskTemplate = serde_json::from_value(serde_json::json!({
+            "label": "deploy to $ZED_INPUT_environment",
+            "command": "deploy --env ${ZED_INPUT_environment} --tag $ZED_INPUT_tag",
+            "inputs": [
+                {
+                    "id": "environment",
+                    "type": "pick_string",
+                    "description": "Where to deploy",
+                    "options": ["staging", "production"],
+                },
+                { "id": "tag", "type": "prompt_string", "default": "latest" },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(
+            template.inputs,
+            vec![
+                TaskInput {
+                    id: "environment".to_string(),
+                    description: Some("Where to deploy".to_string()),
+                    kind: TaskInputKind::PickString {
+                        options: vec!["staging".to_string(), "production".to_string()],
+                        default: None,
+                    },
+                },
+                TaskInput {
+                    id: "tag".to_string(),
+                    description: None,
+                    kind: TaskInputKind::PromptString {
+                        default: Some("latest".to_string()),
+                    },
+                },
+            ]
+        );
+
+        let pending_task = template
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve task with missing inputs");
+        assert_eq!(
+            pending_task
+                .missing_inputs()
+                .map(|input| input.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["environment", "tag"]
+        );
+        let spawn_in_terminal = pending_task.resolved.as_ref().unwrap();
+        assert_eq!(
+            spawn_in_terminal.label,
+            "deploy to ${ZED_INPUT_environment}"
+        );
+        assert!(
+            !spawn_in_terminal.env.contains_key("ZED_INPUT_environment"),
+            "missing inputs should not get into the environment"
+        );
+
+        let mut context = TaskContext::default();
+        for (input, value) in [("environment", "staging"), ("tag", "v1")] {
+            context
+                .task_variables
+                .insert(VariableName::Input(Cow::Borrowed(input)), value.to_string());
+        }
+        let resolved_task = template.resolve_task(TEST_ID_BASE, &context).unwrap();
+        assert_eq!(resolved_task.missing_inputs().count(), 0);
+        let spawn_in_terminal = resolved_task.resolved.as_ref().unwrap();
+        assert_eq!(spawn_in_terminal.label, "deploy to staging");
+        assert_eq!(spawn_in_terminal.command, "deploy --env staging --tag v1");
+        assert_eq!(
+            spawn_in_terminal
+                .env
+                .get("ZED_INPUT_tag")
+                .map(String::as_str),
+            Some("v1")
+        );
+    }
+
     #[test]
     fn test_template_cwd_resolution() {
         let task_without_cwd = TaskTemplate {
@@ -3,7 +3,10 @@ use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    BuiltinProblemMatcher, DependsOrder, ProblemMatcher, TaskInput, TaskInputKind, TaskTemplate,
+    TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -41,6 +44,54 @@ enum Command {
     },
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeInput {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    description: Option<String>,
+    default: Option<String>,
+    #[serde(default)]
+    options: Vec<VsCodePickOption>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodePickOption {
+    Value(String),
+    Labeled { value: String },
+}
+
+impl VsCodeInput {
+    /// Maps the prompted and picked inputs; `command` ones run VSCode commands and have no Zed equivalent.
+    fn into_zed_format(self) -> Option<TaskInput> {
+        let kind = match self.kind.as_str() {
+            "promptString" => TaskInputKind::PromptString {
+                default: self.default,
+            },
+            "pickString" => TaskInputKind::PickString {
+                options: self
+                    .options
+                    .into_iter()
+                    .map(|option| match option {
+                        VsCodePickOption::Value(value) | VsCodePickOption::Labeled { value } => {
+                            value
+                        }
+                    })
+                    .collect(),
+                default: self.default,
+            },
+            _ => return None,
+        };
+        Some(TaskInput {
+            id: self.id,
+            description: self.description,
+            kind,
+        })
+    }
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -55,6 +106,11 @@ impl EnvVariableReplacer {
     // Replaces occurrences of VsCode-specific environment variables with Zed equivalents.
     fn replace(&self, input: &str) -> String {
         shellexpand::env_with_context_no_errors(&input, |var: &str| {
+            // Inputs are asked for when the task is spawned, as `ZED_INPUT_` variables.
+            if let Some(input_id) = var.strip_prefix("input:") {
+                let variable = VariableName::Input(input_id.to_owned().into());
+                return Some(format!("${{{variable}}}"));
+            }
             // Colons denote a default value in case the variable is not set. We want to preserve that default, as otherwise shellexpand will substitute it for us.
             let colon_position = var.find(':').unwrap_or(var.len());
             let (variable_name, default) = var.split_at(colon_position);
@@ -85,21 +141,17 @@ impl EnvVariableReplacer {
 
 impl VsCodeTaskDefinition {
     fn into_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
-        };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        let depends_on = self.depends_on()?;
+        let depends_order = self.depends_order()?;
+        let problem_matchers = self.problem_matchers();
+        // `type` might not be set in tasks that only use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
+        // as such compound tasks are mapped to templates without a command of their own.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
@@ -108,6 +160,9 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order,
+            problem_matchers,
             ..Default::default()
         };
         if let Some(options) = self.options {
@@ -116,12 +171,71 @@ impl VsCodeTaskDefinition {
         }
         Ok(ret)
     }
+
+    /// `dependsOn` is either a single task label or a list of them.
+    fn depends_on(&self) -> anyhow::Result<Vec<String>> {
+        match self.other_attributes.get("dependsOn") {
+            None => Ok(Vec::new()),
+            Some(serde_json_lenient::Value::String(label)) => Ok(vec![label.clone()]),
+            Some(serde_json_lenient::Value::Array(labels)) => labels
+                .iter()
+                .map(|label| match label {
+                    serde_json_lenient::Value::String(label) => Ok(label.clone()),
+                    other => bail!("Unsupported `dependsOn` entry: {other}"),
+                })
+                .collect(),
+            Some(other) => bail!("Unsupported `dependsOn` value: {other}"),
+        }
+    }
+
+    /// Maps the VSCode's built-in problem matchers to Zed ones, skipping the rest.
+    /// Matchers extending a built-in one with a `base` are mapped to it, ignoring their other properties.
+    fn problem_matchers(&self) -> Vec<ProblemMatcher> {
+        fn builtin(matcher: &serde_json_lenient::Value) -> Option<ProblemMatcher> {
+            let name = match matcher {
+                serde_json_lenient::Value::String(name) => name.as_str(),
+                serde_json_lenient::Value::Object(matcher) => matcher.get("base")?.as_str()?,
+                _ => return None,
+            };
+            let builtin = match name {
+                "$rustc" => BuiltinProblemMatcher::Rustc,
+                "$gcc" => BuiltinProblemMatcher::Gcc,
+                "$tsc" | "$tsc-watch" => BuiltinProblemMatcher::Tsc,
+                "$eslint-stylish" => BuiltinProblemMatcher::Eslint,
+                _ => return None,
+            };
+            Some(ProblemMatcher::Builtin(builtin))
+        }
+
+        match self.other_attributes.get("problemMatcher") {
+            Some(serde_json_lenient::Value::Array(matchers)) => {
+                matchers.iter().filter_map(builtin).collect()
+            }
+            Some(matcher) => builtin(matcher).into_iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Unlike in Zed, VSCode runs the dependencies in parallel by default.
+    fn depends_order(&self) -> anyhow::Result<DependsOrder> {
+        match self.other_attributes.get("dependsOrder") {
+            None => Ok(DependsOrder::Parallel),
+            Some(serde_json_lenient::Value::String(order)) => match order.as_str() {
+                "parallel" => Ok(DependsOrder::Parallel),
+                "sequence" => Ok(DependsOrder::Sequence),
+                other => bail!("Unsupported `dependsOrder` value: {other}"),
+            },
+            Some(other) => bail!("Unsupported `dependsOrder` value: {other}"),
+        }
+    }
 }
 
 /// [`VsCodeTaskFile`] is a superset of Code's task definition format.
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
     tasks: Vec<VsCodeTaskDefinition>,
+    #[serde(default)]
+    inputs: Vec<VsCodeInput>,
 }
 
 impl TryFrom<VsCodeTaskFile> for TaskTemplates {
@@ -140,21 +254,44 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                 VariableName::SelectedText.to_string(),
             ),
         ]));
+        let inputs = value
+            .inputs
+            .into_iter()
+            .filter_map(VsCodeInput::into_zed_format)
+            .collect::<Vec<_>>();
         let templates = value
             .tasks
             .into_iter()
             .filter_map(|vscode_definition| vscode_definition.into_zed_format(&replacer).log_err())
+            .map(|mut template| {
+                template.inputs = inputs
+                    .iter()
+                    .filter(|input| uses_variable(&template, &input.variable_name()))
+                    .cloned()
+                    .collect();
+                template
+            })
             .collect();
         Ok(Self(templates))
     }
 }
 
+/// Whether the converted template substitutes the variable: only `command`, `args` and `options` are substituted by VSCode.
+fn uses_variable(template: &TaskTemplate, variable: &VariableName) -> bool {
+    let variable = format!("${{{variable}}}");
+    std::iter::once(&template.command)
+        .chain(&template.args)
+        .chain(&template.cwd)
+        .any(|value| value.contains(&variable))
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
     use crate::{
         vscode_format::{Command, VsCodeTaskDefinition},
+        BuiltinProblemMatcher, DependsOrder, ProblemMatcher, TaskInput, TaskInputKind,
         TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
@@ -257,6 +394,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
@@ -268,6 +406,7 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
@@ -360,28 +499,172 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Rustc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Rustc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Tsc)],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                depends_order: DependsOrder::Parallel,
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Rustc)],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                depends_order: DependsOrder::Parallel,
+                problem_matchers: vec![ProblemMatcher::Builtin(BuiltinProblemMatcher::Rustc)],
+                ..Default::default()
+            },
+        ];
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(tasks.0, expected);
+    }
+
+    #[test]
+    fn can_deserialize_depends_on() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "test",
+                        "type": "shell",
+                        "command": "cargo test",
+                        "dependsOn": "build"
+                    },
+                    {
+                        "label": "db and server",
+                        "dependsOn": ["db", "server"],
+                        "dependsOrder": "sequence"
+                    },
+                    {
+                        "label": "no command",
+                        "problemMatcher": "$rustc"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expected = vec![
+            TaskTemplate {
+                label: "test".to_string(),
+                command: "cargo test".to_string(),
+                depends_on: vec!["build".to_string()],
+                depends_order: DependsOrder::Parallel,
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "db and server".to_string(),
+                depends_on: vec!["db".to_string(), "server".to_string()],
+                depends_order: DependsOrder::Sequence,
+                ..Default::default()
+            },
+        ];
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(tasks.0, expected);
+    }
+
+    #[test]
+    fn can_deserialize_inputs() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "deploy",
+                        "type": "shell",
+                        "command": "deploy",
+                        "args": ["--env", "${input:environment}", "--tag", "${input:tag}"]
+                    },
+                    {
+                        "label": "build",
+                        "type": "shell",
+                        "command": "cargo build"
+                    }
+                ],
+                "inputs": [
+                    {
+                        "id": "environment",
+                        "type": "pickString",
+                        "description": "Where to deploy",
+                        "options": ["staging", { "label": "Production", "value": "production" }],
+                        "default": "staging"
+                    },
+                    {
+                        "id": "tag",
+                        "type": "promptString"
+                    },
+                    {
+                        "id": "env",
+                        "type": "promptString"
+                    },
+                    {
+                        "id": "pid",
+                        "type": "command",
+                        "command": "workbench.action.pickProcess"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expected = vec![
+            TaskTemplate {
+                label: "deploy".to_string(),
+                command: "deploy".to_string(),
+                args: vec![
+                    "--env".to_string(),
+                    "${ZED_INPUT_environment}".to_string(),
+                    "--tag".to_string(),
+                    "${ZED_INPUT_tag}".to_string(),
+                ],
+                inputs: vec![
+                    TaskInput {
+                        id: "environment".to_string(),
+                        description: Some("Where to deploy".to_string()),
+                        kind: TaskInputKind::PickString {
+                            options: vec!["staging".to_string(), "production".to_string()],
+                            default: Some("staging".to_string()),
+                        },
+                    },
+                    TaskInput {
+                        id: "tag".to_string(),
+                        description: None,
+                        kind: TaskInputKind::PromptString { default: None },
+                    },
+                ],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "build".to_string(),
+                command: "cargo build".to_string(),
                 ..Default::default()
             },
         ];
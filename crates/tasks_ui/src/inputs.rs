@@ -0,0 +1,417 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    rems, DismissEvent, EventEmitter, FocusableView, InteractiveElement, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView,
+};
+use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
+use project::TaskSourceKind;
+use task::{ResolvedTask, TaskContext, TaskInput, TaskInputKind};
+use ui::{
+    v_flex, Color, IntoElement, ListItem, ListItemSpacing, RenderOnce, Toggleable, WindowContext,
+};
+use util::{command::new_smol_command, ResultExt};
+use workspace::{
+    notifications::NotifyTaskExt, tasks::schedule_resolved_task, ModalView, Workspace,
+};
+
+/// A task waiting for the user to provide the values of its inputs.
+pub(crate) struct PendingTaskInputs {
+    task_source_kind: TaskSourceKind,
+    pending_task: ResolvedTask,
+    /// Inputs of the task dependencies, which get resolved with the context of the task.
+    dependency_inputs: Vec<TaskInput>,
+    task_context: TaskContext,
+    omit_history: bool,
+}
+
+impl PendingTaskInputs {
+    fn next_input(&self) -> Option<TaskInput> {
+        self.pending_task
+            .original_task()
+            .inputs
+            .iter()
+            .chain(&self.dependency_inputs)
+            .find(|input| {
+                self.task_context
+                    .task_variables
+                    .get(&input.variable_name())
+                    .is_none()
+            })
+            .cloned()
+    }
+
+    fn provide(&mut self, input: &TaskInput, value: String) {
+        self.task_context
+            .task_variables
+            .insert(input.variable_name(), value);
+    }
+}
+
+/// Asks the user for the values of the task inputs, then of its dependencies' inputs, one by one,
+/// then schedules the task resolved with them.
+pub(crate) fn prompt_task_inputs(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    pending_task: ResolvedTask,
+    dependency_inputs: Vec<TaskInput>,
+    omit_history: bool,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let task_context = pending_task.task_context().clone();
+    prompt_next_input(
+        workspace,
+        PendingTaskInputs {
+            task_source_kind,
+            pending_task,
+            dependency_inputs,
+            task_context,
+            omit_history,
+        },
+        cx,
+    );
+}
+
+fn prompt_next_input(
+    workspace: &mut Workspace,
+    mut pending: PendingTaskInputs,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(input) = pending.next_input() else {
+        schedule_with_inputs(workspace, pending, cx);
+        return;
+    };
+
+    match &input.kind {
+        TaskInputKind::PromptString { .. } => {
+            toggle_input_modal(workspace, input, Vec::new(), pending, cx)
+        }
+        TaskInputKind::PickString { options, .. } => {
+            let options = options.clone();
+            toggle_input_modal(workspace, input, options, pending, cx)
+        }
+        TaskInputKind::Command { command, args } => {
+            if !workspace.project().read(cx).is_local() {
+                workspace.show_error(
+                    &anyhow::anyhow!(
+                        "Task input `{}` runs a command, which is only supported in local projects",
+                        input.id
+                    ),
+                    cx,
+                );
+                return;
+            }
+            let mut command = new_smol_command(command);
+            command.args(args);
+            if let Some(cwd) = pending
+                .pending_task
+                .resolved
+                .as_ref()
+                .and_then(|resolved| resolved.cwd.as_ref())
+            {
+                command.current_dir(cwd);
+            }
+            cx.spawn(|workspace, mut cx| async move {
+                let output = command
+                    .output()
+                    .await
+                    .with_context(|| format!("running the command of task input `{}`", input.id))?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Command of task input `{}` failed: {}",
+                    input.id,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                let options = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<_>>();
+                workspace.update(&mut cx, |workspace, cx| match options.len() {
+                    0 => anyhow::bail!("Command of task input `{}` printed nothing", input.id),
+                    1 => {
+                        let value = options.into_iter().next().unwrap_or_default();
+                        pending.provide(&input, value);
+                        prompt_next_input(workspace, pending, cx);
+                        Ok(())
+                    }
+                    _ => {
+                        toggle_input_modal(workspace, input, options, pending, cx);
+                        Ok(())
+                    }
+                })?
+            })
+            .detach_and_notify_err(cx);
+        }
+    }
+}
+
+fn schedule_with_inputs(
+    workspace: &mut Workspace,
+    pending: PendingTaskInputs,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let id_base = pending.task_source_kind.to_id_base();
+    let Some(mut resolved_task) = pending
+        .pending_task
+        .original_task()
+        .resolve_task(&id_base, &pending.task_context)
+    else {
+        return;
+    };
+    // Keep the amendments done to the task before it got to ask for the inputs.
+    if let (Some(resolved), Some(pending_resolved)) = (
+        resolved_task.resolved.as_mut(),
+        pending.pending_task.resolved.as_ref(),
+    ) {
+        resolved.reveal_target = pending_resolved.reveal_target;
+        resolved.allow_concurrent_runs = pending_resolved.allow_concurrent_runs;
+        resolved.use_new_terminal = pending_resolved.use_new_terminal;
+    }
+    schedule_resolved_task(
+        workspace,
+        pending.task_source_kind,
+        resolved_task,
+        pending.omit_history,
+        cx,
+    );
+}
+
+fn toggle_input_modal(
+    workspace: &mut Workspace,
+    input: TaskInput,
+    options: Vec<String>,
+    pending: PendingTaskInputs,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let workspace_handle = workspace.weak_handle();
+    workspace.toggle_modal(cx, |cx| {
+        TaskInputModal::new(input, options, pending, workspace_handle, cx)
+    });
+}
+
+/// A modal used to ask for the value of a single task input.
+pub(crate) struct TaskInputModal {
+    picker: View<Picker<TaskInputModalDelegate>>,
+    _subscription: Subscription,
+}
+
+impl TaskInputModal {
+    fn new(
+        input: TaskInput,
+        options: Vec<String>,
+        pending: PendingTaskInputs,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let picker = cx.new_view(|cx| {
+            Picker::uniform_list(
+                TaskInputModalDelegate::new(input, options, pending, workspace),
+                cx,
+            )
+        });
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for TaskInputModal {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("TaskInputModal")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl EventEmitter<DismissEvent> for TaskInputModal {}
+
+impl FocusableView for TaskInputModal {
+    fn focus_handle(&self, cx: &gpui::AppContext) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for TaskInputModal {}
+
+struct TaskInputModalDelegate {
+    input: TaskInput,
+    /// Values to pick from, empty when the value is typed in.
+    options: Vec<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+    pending: Option<PendingTaskInputs>,
+    workspace: WeakView<Workspace>,
+    placeholder_text: Arc<str>,
+}
+
+impl TaskInputModalDelegate {
+    fn new(
+        input: TaskInput,
+        options: Vec<String>,
+        pending: PendingTaskInputs,
+        workspace: WeakView<Workspace>,
+    ) -> Self {
+        let placeholder_text = Arc::from(
+            input
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("Value of `{}`", input.id)),
+        );
+        Self {
+            input,
+            options,
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+            pending: Some(pending),
+            workspace,
+            placeholder_text,
+        }
+    }
+
+    fn default_value(&self) -> Option<&str> {
+        match &self.input.kind {
+            TaskInputKind::PromptString { default } | TaskInputKind::PickString { default, .. } => {
+                default.as_deref()
+            }
+            TaskInputKind::Command { .. } => None,
+        }
+    }
+
+    fn value(&self) -> Option<String> {
+        if self.options.is_empty() {
+            if self.query.is_empty() {
+                Some(self.default_value().unwrap_or_default().to_owned())
+            } else {
+                Some(self.query.clone())
+            }
+        } else {
+            self.matches
+                .get(self.selected_index)
+                .map(|hit| self.options[hit.candidate_id].clone())
+        }
+    }
+}
+
+impl PickerDelegate for TaskInputModalDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        self.placeholder_text.clone()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        if !self.options.is_empty() {
+            "No matching options".into()
+        } else if let Some(default) = self.default_value().filter(|_| self.query.is_empty()) {
+            format!("Press enter to use `{default}`").into()
+        } else {
+            "Type the value and press enter".into()
+        }
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .options
+            .iter()
+            .enumerate()
+            .map(|(id, option)| StringMatchCandidate::new(id, option))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                true,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.selected_index = if query.is_empty() {
+                        delegate
+                            .default_value()
+                            .and_then(|default| {
+                                matches.iter().position(|hit| hit.string == default)
+                            })
+                            .unwrap_or(0)
+                    } else {
+                        0
+                    };
+                    delegate.matches = matches;
+                    delegate.query = query;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(value) = self.value() else {
+            return;
+        };
+        let Some(mut pending) = self.pending.take() else {
+            return;
+        };
+        pending.provide(&self.input, value);
+        cx.emit(DismissEvent);
+        // Ask for the next input once this modal is gone, so it does not get toggled off instead.
+        let workspace = self.workspace.clone();
+        WindowContext::defer(cx, move |cx| {
+            workspace
+                .update(cx, |workspace, cx| {
+                    prompt_next_input(workspace, pending, cx);
+                })
+                .ok();
+        });
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let highlighted_option = HighlightedText {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            char_count: hit.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("task-input-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(highlighted_option.render(cx)),
+        )
+    }
+}
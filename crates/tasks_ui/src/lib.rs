@@ -7,6 +7,7 @@ use task::{RevealTarget, TaskId};
 use workspace::tasks::schedule_task;
 use workspace::{tasks::schedule_resolved_task, Workspace};
 
+mod inputs;
 mod modal;
 mod settings;
 
@@ -15,7 +16,26 @@ pub use modal::{Rerun, Spawn};
 pub fn init(cx: &mut AppContext) {
     settings::TaskSettings::register(cx);
     cx.observe_new_views(
-        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            cx.subscribe(&cx.view().clone(), |workspace, _, event, cx| {
+                if let workspace::Event::PromptTaskInputs {
+                    task_source_kind,
+                    pending_task,
+                    dependency_inputs,
+                    omit_history,
+                } = event
+                {
+                    inputs::prompt_task_inputs(
+                        workspace,
+                        task_source_kind.clone(),
+                        pending_task.as_ref().clone(),
+                        dependency_inputs.clone(),
+                        *omit_history,
+                        cx,
+                    );
+                }
+            })
+            .detach();
             workspace
                 .register_action(spawn_task_or_modal)
                 .register_action(move |workspace, action: &modal::Rerun, cx| {
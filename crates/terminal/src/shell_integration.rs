@@ -0,0 +1,685 @@
+//! Support for the `OSC 133` (FinalTerm) and `OSC 633` (VS Code) shell integration sequences.
+//!
+//! Alacritty does not understand these sequences and drops them, so the PTY output is filtered
+//! before it reaches the parser: every prompt mark is removed from the stream and replaced with
+//! zero-width Unicode tag characters, which Alacritty attaches to the cell printed right before them.
+//! This way the marks stay with the text they annotate while the grid scrolls, reflows or gets
+//! trimmed, and can be read back from the grid at any time.
+
+use std::{io, sync::Arc};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    grid::Dimensions,
+    index::{Boundary, Column, Line, Point as AlacPoint},
+    term::cell::Cell,
+    tty::{self, ChildEvent, EventedPty, EventedReadWrite},
+    Term,
+};
+use polling::{Event as PollingEvent, PollMode, Poller};
+
+use crate::text_between;
+
+/// Tag characters (U+E0020..U+E007E) mirror printable ASCII and are never rendered.
+const TAG_OFFSET: u32 = 0xE0000;
+/// `CANCEL TAG`, terminates an encoded mark.
+const MARK_END: char = '\u{E007F}';
+const MAX_MARK_LEN: usize = 64;
+
+/// A point in the shell's command lifecycle, as reported by the shell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellMark {
+    /// `A`: the shell started printing the prompt.
+    PromptStart,
+    /// `B`: the prompt was printed and the user can type the command.
+    CommandStart,
+    /// `C`: the command was submitted and its output follows.
+    OutputStart,
+    /// `D[;<exit code>]`: the command finished.
+    CommandFinished { exit_code: Option<i32> },
+}
+
+impl ShellMark {
+    fn parse(params: &[u8]) -> Option<Self> {
+        let mut params = params.split(|byte| *byte == b';');
+        match params.next()? {
+            b"A" => Some(Self::PromptStart),
+            b"B" => Some(Self::CommandStart),
+            b"C" => Some(Self::OutputStart),
+            b"D" => Some(Self::CommandFinished {
+                exit_code: params
+                    .next()
+                    .and_then(|code| std::str::from_utf8(code).ok())
+                    .and_then(|code| code.parse().ok()),
+            }),
+            _ => None,
+        }
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        let payload = match self {
+            Self::PromptStart => "A".to_string(),
+            Self::CommandStart => "B".to_string(),
+            Self::OutputStart => "C".to_string(),
+            Self::CommandFinished { exit_code: None } => "D".to_string(),
+            Self::CommandFinished {
+                exit_code: Some(exit_code),
+            } => format!("D{exit_code}"),
+        };
+        let mut buffer = [0; 4];
+        for c in payload.chars().chain(Some(MARK_END)) {
+            let tag = if c == MARK_END {
+                c
+            } else {
+                char::from_u32(TAG_OFFSET + c as u32).unwrap()
+            };
+            output.extend_from_slice(tag.encode_utf8(&mut buffer).as_bytes());
+        }
+    }
+
+    fn decode(mut payload: impl Iterator<Item = char>) -> Option<Self> {
+        match payload.next()? {
+            'A' => Some(Self::PromptStart),
+            'B' => Some(Self::CommandStart),
+            'C' => Some(Self::OutputStart),
+            'D' => Some(Self::CommandFinished {
+                exit_code: payload.collect::<String>().parse().ok(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the character is a part of an encoded [`ShellMark`] and should not be shown or copied.
+pub fn is_mark_char(c: char) -> bool {
+    (TAG_OFFSET..=MARK_END as u32).contains(&(c as u32))
+}
+
+/// Removes encoded shell marks from text taken from the terminal grid.
+pub fn strip_marks(text: String) -> String {
+    if text.chars().any(is_mark_char) {
+        text.chars().filter(|c| !is_mark_char(*c)).collect()
+    } else {
+        text
+    }
+}
+
+/// Returns the shell marks attached to the cell, in the order the shell reported them.
+pub fn cell_marks(cell: &Cell) -> impl Iterator<Item = ShellMark> + '_ {
+    let mut payloads = cell
+        .zerowidth()
+        .unwrap_or_default()
+        .split(|c| *c == MARK_END);
+    // The characters after the last `MARK_END` don't form a complete mark.
+    payloads.next_back();
+    payloads.filter_map(|payload| {
+        ShellMark::decode(
+            payload
+                .iter()
+                .filter(|c| is_mark_char(**c))
+                .filter_map(|c| char::from_u32(*c as u32 - TAG_OFFSET)),
+        )
+    })
+}
+
+fn line_has_prompt<T>(term: &Term<T>, line: Line) -> bool {
+    let grid = term.grid();
+    (0..grid.columns()).any(|column| {
+        cell_marks(&grid[AlacPoint::new(line, Column(column))])
+            .any(|mark| mark == ShellMark::PromptStart)
+    })
+}
+
+/// A command run in the terminal, reconstructed from the shell marks in the grid.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShellCommand {
+    /// The first cell of the prompt.
+    pub prompt: AlacPoint,
+    /// The last cell of the prompt, the command text follows it.
+    pub input: Option<AlacPoint>,
+    /// The first cell of the command output.
+    pub output: Option<AlacPoint>,
+    /// The first cell printed after the command finished.
+    pub finished: Option<AlacPoint>,
+    /// The exit code of the command, if it has finished and the shell reported one.
+    pub exit_code: Option<i32>,
+}
+
+impl ShellCommand {
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+}
+
+/// Collects the commands whose marks are located at or below `start_line`, top to bottom.
+pub fn shell_commands<T>(term: &Term<T>, start_line: Line) -> Vec<ShellCommand> {
+    let grid = term.grid();
+    let mut commands = Vec::new();
+    let mut current: Option<ShellCommand> = None;
+
+    let start_line = start_line.max(term.topmost_line());
+    for line in start_line.0..=term.bottommost_line().0 {
+        let line = Line(line);
+        for column in 0..grid.columns() {
+            let point = AlacPoint::new(line, Column(column));
+            for mark in cell_marks(&grid[point]) {
+                match mark {
+                    ShellMark::PromptStart => {
+                        commands.extend(current.take());
+                        current = Some(ShellCommand {
+                            prompt: point,
+                            ..ShellCommand::default()
+                        });
+                    }
+                    ShellMark::CommandStart => {
+                        current
+                            .get_or_insert_with(|| ShellCommand {
+                                prompt: point,
+                                ..ShellCommand::default()
+                            })
+                            .input = Some(point);
+                    }
+                    ShellMark::OutputStart => {
+                        if let Some(current) = current.as_mut() {
+                            current.output = Some(point);
+                        }
+                    }
+                    ShellMark::CommandFinished { exit_code } => {
+                        if let Some(mut command) = current.take() {
+                            command.finished = Some(point);
+                            command.exit_code = exit_code;
+                            commands.push(command);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    commands.extend(current);
+    commands
+}
+
+/// Returns the result of `f` for the last command it returns one for.
+///
+/// The grid is scanned upwards one prompt at a time, so that only the commands from the
+/// returned one to the bottom are read, rather than the whole scrollback.
+pub fn find_last_shell_command<T, R>(
+    term: &Term<T>,
+    mut f: impl FnMut(&ShellCommand) -> Option<R>,
+) -> Option<R> {
+    let topmost_line = term.topmost_line();
+    let mut line = term.bottommost_line();
+    loop {
+        // Commands without a prompt start mark are only found when reaching the top.
+        if line == topmost_line || line_has_prompt(term, line) {
+            let result = shell_commands(term, line).iter().rev().find_map(&mut f);
+            if result.is_some() || line == topmost_line {
+                return result;
+            }
+        }
+        line = Line(line.0 - 1);
+    }
+}
+
+/// Returns the closest line above `line` with the start of a shell prompt.
+pub fn previous_prompt_line<T>(term: &Term<T>, line: Line) -> Option<Line> {
+    (term.topmost_line().0..line.0)
+        .rev()
+        .map(Line)
+        .find(|line| line_has_prompt(term, *line))
+}
+
+/// Returns the text entered at the prompt of the command, once it was submitted.
+fn command_text<T>(term: &Term<T>, command: &ShellCommand) -> Option<String> {
+    let start = command.input?.add(term, Boundary::Grid, 1);
+    let end = command.output.or(command.finished)?;
+    let text = text_between(term, start, end);
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Returns the text of the last command entered at a shell prompt.
+pub fn last_command<T>(term: &Term<T>) -> Option<String> {
+    find_last_shell_command(term, |command| command_text(term, command))
+}
+
+/// Returns the commands entered at shell prompts in the whole scrollback, the most recent first
+/// and without repeating any command.
+pub fn command_history<T>(term: &Term<T>) -> Vec<String> {
+    let mut history = Vec::new();
+    for command in shell_commands(term, term.topmost_line()).iter().rev() {
+        if let Some(text) = command_text(term, command) {
+            if !history.contains(&text) {
+                history.push(text);
+            }
+        }
+    }
+    history
+}
+
+/// Returns the output of the last finished command.
+pub fn last_command_output<T>(term: &Term<T>) -> Option<String> {
+    let command = find_last_shell_command(term, |command| {
+        command.is_finished().then(|| command.clone())
+    })?;
+    let output = text_between(term, command.output?, command.finished?);
+    Some(output.trim_end_matches('\n').to_string())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    /// `ESC` was read but not forwarded yet, as it might start a shell integration sequence.
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    Osc,
+    OscEscape,
+    ControlString,
+    ControlStringEscape,
+}
+
+/// Removes the shell integration sequences from the PTY output, replacing them with encoded marks.
+#[derive(Debug, Default)]
+pub(crate) struct MarkScanner {
+    state: ScanState,
+    /// The `OSC` payload read so far, while it may still turn out to be a shell integration mark.
+    osc: Vec<u8>,
+    /// Whether the current `OSC` is not a shell integration mark and is forwarded as is.
+    osc_passthrough: bool,
+    /// Marks waiting for the next printed character to be attached to.
+    deferred: Vec<ShellMark>,
+    utf8_remaining: usize,
+}
+
+impl MarkScanner {
+    pub(crate) fn advance(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        for &byte in input {
+            self.advance_byte(byte, output);
+        }
+    }
+
+    fn advance_byte(&mut self, byte: u8, output: &mut Vec<u8>) {
+        match self.state {
+            ScanState::Ground => match byte {
+                0x1b => {
+                    self.utf8_remaining = 0;
+                    self.state = ScanState::Escape;
+                }
+                0x80..=0xbf if self.utf8_remaining > 0 => {
+                    output.push(byte);
+                    self.utf8_remaining -= 1;
+                    if self.utf8_remaining == 0 {
+                        self.flush_deferred(output);
+                    }
+                }
+                0x20..=0x7e | 0x80..=0xff => {
+                    output.push(byte);
+                    self.utf8_remaining = match byte {
+                        0xc0..=0xdf => 1,
+                        0xe0..=0xef => 2,
+                        0xf0..=0xf7 => 3,
+                        _ => 0,
+                    };
+                    if self.utf8_remaining == 0 {
+                        self.flush_deferred(output);
+                    }
+                }
+                _ => {
+                    self.utf8_remaining = 0;
+                    output.push(byte);
+                }
+            },
+            ScanState::Escape => match byte {
+                b']' => {
+                    self.osc.clear();
+                    self.osc_passthrough = false;
+                    self.state = ScanState::Osc;
+                }
+                // Only the latest `ESC` may start a shell integration sequence.
+                0x1b => output.push(byte),
+                _ => {
+                    output.extend_from_slice(&[0x1b, byte]);
+                    self.state = match byte {
+                        b'[' => ScanState::Csi,
+                        b'P' | b'X' | b'^' | b'_' => ScanState::ControlString,
+                        0x20..=0x2f => ScanState::EscapeIntermediate,
+                        _ => ScanState::Ground,
+                    };
+                }
+            },
+            ScanState::EscapeIntermediate => {
+                output.push(byte);
+                if !(0x20..=0x2f).contains(&byte) {
+                    self.state = ScanState::Ground;
+                }
+            }
+            ScanState::Csi => {
+                output.push(byte);
+                if (0x40..=0x7e).contains(&byte) {
+                    self.state = ScanState::Ground;
+                }
+            }
+            ScanState::Osc => match byte {
+                0x07 => {
+                    self.finish_osc(&[byte], output);
+                    self.state = ScanState::Ground;
+                }
+                0x1b => self.state = ScanState::OscEscape,
+                _ => {
+                    if self.osc_passthrough {
+                        output.push(byte);
+                    } else {
+                        self.osc.push(byte);
+                        if !self.may_be_mark() {
+                            output.extend_from_slice(b"\x1b]");
+                            output.append(&mut self.osc);
+                            self.osc_passthrough = true;
+                        }
+                    }
+                }
+            },
+            ScanState::OscEscape => {
+                if byte == b'\\' {
+                    self.finish_osc(b"\x1b\\", output);
+                    self.state = ScanState::Ground;
+                } else {
+                    // `ESC` cancels the sequence and starts a new one.
+                    self.finish_osc(&[], output);
+                    self.state = ScanState::Escape;
+                    self.advance_byte(byte, output);
+                }
+            }
+            ScanState::ControlString => {
+                output.push(byte);
+                if byte == 0x1b {
+                    self.state = ScanState::ControlStringEscape;
+                }
+            }
+            ScanState::ControlStringEscape => {
+                output.push(byte);
+                self.state = if byte == b'\\' {
+                    ScanState::Ground
+                } else {
+                    ScanState::ControlString
+                };
+            }
+        }
+    }
+
+    fn may_be_mark(&self) -> bool {
+        self.osc.len() <= MAX_MARK_LEN
+            && [b"133;", b"633;"].iter().any(|prefix| {
+                let len = self.osc.len().min(prefix.len());
+                prefix[..len] == self.osc[..len]
+            })
+    }
+
+    fn finish_osc(&mut self, terminator: &[u8], output: &mut Vec<u8>) {
+        if self.osc_passthrough {
+            output.extend_from_slice(terminator);
+            return;
+        }
+
+        match self.osc.get(4..).and_then(ShellMark::parse) {
+            // The end of the prompt is attached to the last prompt character right away, the rest
+            // of the marks annotate the text that is printed after them.
+            Some(ShellMark::CommandStart) if self.deferred.is_empty() => {
+                ShellMark::CommandStart.encode(output)
+            }
+            Some(mark) => self.deferred.push(mark),
+            // Other shell integration sequences, e.g. `633;E` or `633;P`, are not used.
+            None if self.osc.len() >= 4 => {}
+            None => {
+                output.extend_from_slice(b"\x1b]");
+                output.append(&mut self.osc);
+                output.extend_from_slice(terminator);
+            }
+        }
+        self.osc.clear();
+    }
+
+    fn flush_deferred(&mut self, output: &mut Vec<u8>) {
+        for mark in self.deferred.drain(..) {
+            mark.encode(output);
+        }
+    }
+}
+
+/// Wraps the PTY, filtering its output with a [`MarkScanner`] before Alacritty parses it.
+pub(crate) struct ShellIntegrationPty {
+    pty: tty::Pty,
+    scanner: MarkScanner,
+    read_buffer: Box<[u8]>,
+    output: Vec<u8>,
+}
+
+impl ShellIntegrationPty {
+    pub(crate) fn new(pty: tty::Pty) -> Self {
+        Self {
+            pty,
+            scanner: MarkScanner::default(),
+            read_buffer: vec![0; 0x1_0000].into_boxed_slice(),
+            output: Vec::new(),
+        }
+    }
+}
+
+impl io::Read for ShellIntegrationPty {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.output.is_empty() {
+            let read = self.pty.reader().read(&mut self.read_buffer)?;
+            if read == 0 {
+                return Ok(0);
+            }
+            self.scanner
+                .advance(&self.read_buffer[..read], &mut self.output);
+        }
+
+        let len = buf.len().min(self.output.len());
+        buf[..len].copy_from_slice(&self.output[..len]);
+        self.output.drain(..len);
+        Ok(len)
+    }
+}
+
+impl EventedReadWrite for ShellIntegrationPty {
+    type Reader = Self;
+    type Writer = <tty::Pty as EventedReadWrite>::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poll, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl EventedPty for ShellIntegrationPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl OnResize for ShellIntegrationPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{event::VoidListener, term::Config, vte::ansi::Processor};
+
+    use crate::TerminalSize;
+
+    use super::*;
+
+    fn scan(chunks: &[&[u8]]) -> Vec<u8> {
+        let mut scanner = MarkScanner::default();
+        let mut output = Vec::new();
+        for chunk in chunks {
+            scanner.advance(chunk, &mut output);
+        }
+        output
+    }
+
+    fn term_with_output(output: &[u8]) -> Term<VoidListener> {
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        let mut parser: Processor = Processor::new();
+        for byte in scan(&[output]) {
+            parser.advance(&mut term, byte);
+        }
+        term
+    }
+
+    #[test]
+    fn test_other_sequences_are_kept() {
+        let input: &[u8] = b"\x1b[1;32mgreen\x1b[0m \x1b]0;title\x07 \x1b]8;;https://zed.dev\x1b\\link\x1b]8;;\x1b\\";
+        assert_eq!(scan(&[input]), input);
+        let (start, end) = input.split_at(17);
+        assert_eq!(scan(&[start, end]), input);
+    }
+
+    #[test]
+    fn test_marks_are_replaced() {
+        let output = scan(&[
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07",
+            b"file\r\n\x1b]133;D;1\x1b\\\x1b]633;A\x07$ ",
+        ]);
+        let text = String::from_utf8(output).unwrap();
+        assert!(!text.contains('\x1b'));
+        assert_eq!(strip_marks(text), "$ ls\r\nfile\r\n$ ");
+    }
+
+    #[test]
+    fn test_commands_from_grid() {
+        let term = term_with_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07file\r\n\x1b]133;D;2\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07true\r\n\x1b]133;C\x07\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07",
+        );
+
+        let commands = shell_commands(&term, term.topmost_line());
+        let point = |line, column| AlacPoint::new(Line(line), Column(column));
+        assert_eq!(
+            commands,
+            vec![
+                ShellCommand {
+                    prompt: point(0, 0),
+                    input: Some(point(0, 1)),
+                    output: Some(point(1, 0)),
+                    finished: Some(point(2, 0)),
+                    exit_code: Some(2),
+                },
+                ShellCommand {
+                    prompt: point(2, 0),
+                    input: Some(point(2, 1)),
+                    output: Some(point(3, 0)),
+                    finished: Some(point(3, 0)),
+                    exit_code: Some(0),
+                },
+                ShellCommand {
+                    prompt: point(3, 0),
+                    input: Some(point(3, 1)),
+                    output: None,
+                    finished: None,
+                    exit_code: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_previous_prompt_line() {
+        let term = term_with_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07file\r\n\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07",
+        );
+        assert_eq!(previous_prompt_line(&term, Line(5)), Some(Line(2)));
+        assert_eq!(previous_prompt_line(&term, Line(2)), Some(Line(0)));
+        assert_eq!(previous_prompt_line(&term, Line(0)), None);
+    }
+
+    #[test]
+    fn test_last_command() {
+        let term = term_with_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a.txt\r\nb.txt\r\n\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07",
+        );
+        assert_eq!(last_command(&term).as_deref(), Some("ls"));
+        assert_eq!(last_command_output(&term).as_deref(), Some("a.txt\nb.txt"));
+
+        // The command being typed isn't the last command until it's submitted.
+        let term = term_with_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a.txt\r\n\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07cat a.txt\r\n\x1b]133;C\x07a.txt",
+        );
+        assert_eq!(last_command(&term).as_deref(), Some("cat a.txt"));
+        assert_eq!(last_command_output(&term).as_deref(), Some("a.txt"));
+
+        let term = term_with_output(b"$ ls\r\na.txt\r\n$ ");
+        assert_eq!(last_command(&term), None);
+        assert_eq!(last_command_output(&term), None);
+    }
+
+    #[test]
+    fn test_command_history() {
+        let term = term_with_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a.txt\r\n\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07cat a.txt\r\n\x1b]133;C\x07a\r\n\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a.txt\r\n\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07\r\n\x1b]133;C\x07\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07echo",
+        );
+        // Empty and unsubmitted commands are left out, and repeated ones are listed once.
+        assert_eq!(command_history(&term), vec!["ls", "cat a.txt"]);
+
+        let term = term_with_output(b"$ ls\r\na.txt\r\n$ ");
+        assert_eq!(command_history(&term), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_last_command_in_scrollback() {
+        let mut output = b"\x1b]133;A\x07$ \x1b]133;B\x07seq 100\r\n\x1b]133;C\x07".to_vec();
+        for i in 1..=100 {
+            output.extend(format!("{i}\r\n").bytes());
+        }
+        output.extend(b"\x1b]133;D;0\x07\x1b]133;A\x07$ \x1b]133;B\x07");
+        let term = term_with_output(&output);
+
+        assert!(term.topmost_line() < Line(0));
+        assert_eq!(last_command(&term).as_deref(), Some("seq 100"));
+        let expected_output = (1..=100).map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(last_command_output(&term), Some(expected_output.join("\n")));
+        let last_prompt_line = term.bottommost_line();
+        assert_eq!(
+            previous_prompt_line(&term, last_prompt_line),
+            Some(term.topmost_line())
+        );
+    }
+}
@@ -3,6 +3,7 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -13,7 +14,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Flags},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -21,6 +22,7 @@ use alacritty_terminal::{
     vi_mode::{ViModeCursor, ViMotion},
     vte::ansi::{
         ClearMode, CursorStyle as AlacCursorStyle, Handler, NamedPrivateMode, PrivateMode,
+        Processor,
     },
     Term,
 };
@@ -41,6 +43,7 @@ use futures::StreamExt;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{shell_commands, strip_marks, ShellCommand, ShellIntegrationPty};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
@@ -80,6 +83,12 @@ actions!(
         ScrollToTop,
         ScrollToBottom,
         ToggleViMode,
+        ExportSession,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
+        RerunLastCommand,
+        RerunCommand,
     ]
 );
 
@@ -331,6 +340,7 @@ impl TerminalBuilder {
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
         is_ssh_terminal: bool,
+        restored_scrollback: Option<String>,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
         cx: &AppContext,
@@ -414,6 +424,12 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Write the output saved from a previous session before the shell starts, so that the
+        // shell's own output comes after it.
+        if let Some(scrollback) = restored_scrollback {
+            write_to_term(&mut term, &scrollback);
+        }
+
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
@@ -438,7 +454,7 @@ impl TerminalBuilder {
         let event_loop = EventLoop::new(
             term.clone(),
             ZedListener(events_tx.clone()),
-            pty,
+            ShellIntegrationPty::new(pty),
             pty_options.hold,
             false,
         )?;
@@ -569,6 +585,8 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    /// Commands reported by the shell integration, whose prompts are on the screen.
+    pub shell_commands: Vec<ShellCommand>,
 }
 
 #[derive(Clone)]
@@ -593,6 +611,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            shell_commands: Vec::new(),
         }
     }
 }
@@ -821,7 +840,7 @@ impl Terminal {
                         term.selection = Some(selection);
 
                         #[cfg(any(target_os = "linux", target_os = "freebsd"))]
-                        if let Some(selection_text) = term.selection_to_string() {
+                        if let Some(selection_text) = selection_text(term) {
                             cx.write_to_primary(ClipboardItem::new_string(selection_text));
                         }
 
@@ -834,7 +853,7 @@ impl Terminal {
                 term.selection = selection.as_ref().map(|(sel, _)| sel.clone());
 
                 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
-                if let Some(selection_text) = term.selection_to_string() {
+                if let Some(selection_text) = selection_text(term) {
                     cx.write_to_primary(ClipboardItem::new_string(selection_text));
                 }
 
@@ -855,7 +874,7 @@ impl Terminal {
                     term.selection = Some(selection);
 
                     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
-                    if let Some(selection_text) = term.selection_to_string() {
+                    if let Some(selection_text) = selection_text(term) {
                         cx.write_to_primary(ClipboardItem::new_string(selection_text));
                     }
 
@@ -865,7 +884,7 @@ impl Terminal {
             }
 
             InternalEvent::Copy => {
-                if let Some(txt) = term.selection_to_string() {
+                if let Some(txt) = selection_text(term) {
                     cx.write_to_clipboard(ClipboardItem::new_string(txt))
                 }
             }
@@ -921,10 +940,12 @@ impl Terminal {
 
                     Some((url, true, url_match))
                 } else if let Some(url_match) = regex_match_at(term, point, &mut self.url_regex) {
-                    let url = term.bounds_to_string(*url_match.start(), *url_match.end());
+                    let url =
+                        strip_marks(term.bounds_to_string(*url_match.start(), *url_match.end()));
                     Some((url, true, url_match))
                 } else if let Some(word_match) = regex_match_at(term, point, &mut self.word_regex) {
-                    let file_path = term.bounds_to_string(*word_match.start(), *word_match.end());
+                    let file_path =
+                        strip_marks(term.bounds_to_string(*word_match.start(), *word_match.end()));
 
                     let (sanitized_match, sanitized_word) = if file_path.starts_with('[')
                         && file_path.ends_with(']')
@@ -1327,12 +1348,13 @@ impl Terminal {
                 .collect::<Vec<IndexedCell>>(),
             mode: content.mode,
             display_offset: content.display_offset,
-            selection_text: term.selection_to_string(),
+            selection_text: selection_text(term),
             selection: content.selection,
             cursor: content.cursor,
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            shell_commands: visible_shell_commands(term, content.display_offset),
         }
     }
 
@@ -1361,6 +1383,64 @@ impl Terminal {
         lines
     }
 
+    /// Returns all lines of the terminal, including the scrollback history, with the wrapped lines joined back.
+    pub fn output_lines(&self) -> Vec<String> {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        term_output_lines(&terminal)
+    }
+
+    /// Scrolls the closest shell prompt above the viewport to its top.
+    pub fn scroll_to_previous_prompt(&mut self) {
+        let term = self.term.lock();
+        let viewport_start = Line(-(term.grid().display_offset() as i32));
+        let prompt_line = shell_integration::previous_prompt_line(&term, viewport_start);
+        drop(term);
+
+        if let Some(prompt_line) = prompt_line {
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                    viewport_start.0 - prompt_line.0,
+                )));
+        }
+    }
+
+    /// Scrolls the closest shell prompt below the top of the viewport to its top,
+    /// or to the bottom if there is none.
+    pub fn scroll_to_next_prompt(&mut self) {
+        let term = self.term.lock();
+        let viewport_start = Line(-(term.grid().display_offset() as i32));
+        let prompt_line = shell_commands(&term, viewport_start)
+            .into_iter()
+            .map(|command| command.prompt.line)
+            .find(|line| *line > viewport_start);
+        drop(term);
+
+        match prompt_line {
+            Some(prompt_line) => self
+                .events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                    viewport_start.0 - prompt_line.0,
+                ))),
+            None => self.scroll_to_bottom(),
+        }
+    }
+
+    /// Returns the output of the last finished command, if the shell reports its commands.
+    pub fn last_command_output(&self) -> Option<String> {
+        shell_integration::last_command_output(&self.term.lock())
+    }
+
+    /// Returns the text of the last command entered at a shell prompt, if the shell reports its commands.
+    pub fn last_command(&self) -> Option<String> {
+        shell_integration::last_command(&self.term.lock())
+    }
+
+    /// Returns the commands entered at shell prompts, the most recent first, if the shell reports its commands.
+    pub fn command_history(&self) -> Vec<String> {
+        shell_integration::command_history(&self.term.lock())
+    }
+
     pub fn focus_in(&self) {
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
             self.write_to_pty("\x1b[I".to_string());
@@ -1740,6 +1820,8 @@ impl Terminal {
         cx: &mut ModelContext<'_, Terminal>,
     ) {
         self.completion_tx.try_send(()).ok();
+        // Close the channel, so every `wait_for_completed_task` waiter wakes up, not only the one receiving the message.
+        self.completion_tx.close();
         let task = match &mut self.task {
             Some(task) => task,
             None => {
@@ -1880,6 +1962,59 @@ pub fn visible_regex_match_iter<'a, T>(
         .take_while(move |rm| rm.start().line <= viewport_end)
 }
 
+fn term_output_lines<T>(terminal: &Term<T>) -> Vec<String> {
+    let last_column = Column(terminal.columns().saturating_sub(1));
+    let mut lines = Vec::new();
+    let mut line_buffer = String::new();
+    for line in terminal.topmost_line().0..=terminal.bottommost_line().0 {
+        let row = &terminal.grid()[Line(line)];
+        for cell in row {
+            if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                line_buffer.push(cell.c);
+            }
+        }
+        if !row[last_column].flags.contains(Flags::WRAPLINE) {
+            lines.push(line_buffer.trim_end().to_string());
+            line_buffer.clear();
+        }
+    }
+    if !line_buffer.is_empty() {
+        lines.push(line_buffer.trim_end().to_string());
+    }
+    lines
+}
+
+fn write_to_term<T: EventListener>(terminal: &mut Term<T>, text: &str) {
+    let mut parser: Processor = Processor::new();
+    for line in text.lines() {
+        for byte in line.bytes().chain(b"\r\n".iter().copied()) {
+            parser.advance(terminal, byte);
+        }
+    }
+}
+
+fn selection_text<T>(term: &Term<T>) -> Option<String> {
+    term.selection_to_string().map(strip_marks)
+}
+
+/// Returns the grid text from `start` up to, but not including, `end`.
+fn text_between<T>(term: &Term<T>, start: AlacPoint, end: AlacPoint) -> String {
+    if end <= start {
+        return String::new();
+    }
+    let end = end.sub(term, Boundary::Grid, 1);
+    strip_marks(term.bounds_to_string(start, end))
+}
+
+fn visible_shell_commands<T>(term: &Term<T>, display_offset: usize) -> Vec<ShellCommand> {
+    let viewport_start = Line(-(display_offset as i32));
+    let viewport_end = Line(viewport_start.0 + term.screen_lines() as i32 - 1);
+    shell_commands(term, viewport_start)
+        .into_iter()
+        .take_while(|command| command.prompt.line <= viewport_end)
+        .collect()
+}
+
 fn make_selection(range: &RangeInclusive<AlacPoint>) -> Selection {
     let mut selection = Selection::new(SelectionType::Simple, *range.start(), AlacDirection::Left);
     selection.update(*range.end(), AlacDirection::Right);
@@ -1990,14 +2125,17 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
+        event::VoidListener,
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{cell::Cell, Config},
+        Term,
     };
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, rgb_for_index, term_output_lines, write_to_term, IndexedCell,
+        TerminalContent, TerminalSize,
     };
 
     #[test]
@@ -2130,6 +2268,16 @@ mod tests {
             .collect();
         assert_eq!(results, expected);
     }
+
+    #[test]
+    fn test_restore_scrollback() {
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        write_to_term(&mut term, "$ echo hello\nhello\n$ ");
+        let lines = term_output_lines(&term);
+        assert_eq!(lines[..3], ["$ echo hello", "hello", "$"]);
+        assert!(lines[3..].iter().all(|line| line.is_empty()));
+    }
+
     #[test]
     fn test_url_regex() {
         re_test(
@@ -47,6 +47,8 @@ pub struct TerminalSettings {
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
     pub toolbar: Toolbar,
+    pub persist_scrollback: bool,
+    pub persisted_scrollback_lines: usize,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -187,6 +189,16 @@ pub struct TerminalSettingsContent {
     pub max_scroll_history_lines: Option<usize>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
+    /// Whether to save the scrollback of the terminals with the workspace,
+    /// and restore it when the workspace is reopened.
+    ///
+    /// Default: false
+    pub persist_scrollback: Option<bool>,
+    /// The number of the most recent scrollback lines saved for each terminal
+    /// when `persist_scrollback` is enabled.
+    ///
+    /// Default: 5000
+    pub persisted_scrollback_lines: Option<usize>,
 }
 
 impl settings::Settings for TerminalSettings {
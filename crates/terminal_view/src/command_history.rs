@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{DismissEvent, EventEmitter, FocusableView, Model, Subscription, Task, View, WeakModel};
+use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
+use terminal::Terminal;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::ModalView;
+
+/// A modal listing the commands run in a terminal, to run one of them again.
+pub struct CommandHistory {
+    picker: View<Picker<CommandHistoryDelegate>>,
+    _subscription: Subscription,
+}
+
+impl CommandHistory {
+    pub(crate) fn new(terminal: &Model<Terminal>, cx: &mut ViewContext<Self>) -> Self {
+        let delegate = CommandHistoryDelegate {
+            commands: terminal.read(cx).command_history(),
+            terminal: terminal.downgrade(),
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for CommandHistory {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("TerminalCommandHistory")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl EventEmitter<DismissEvent> for CommandHistory {}
+
+impl FocusableView for CommandHistory {
+    fn focus_handle(&self, cx: &gpui::AppContext) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for CommandHistory {}
+
+pub struct CommandHistoryDelegate {
+    commands: Vec<String>,
+    terminal: WeakModel<Terminal>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for CommandHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Search commands".into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        "No commands".into()
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .commands
+            .iter()
+            .enumerate()
+            .map(|(id, command)| StringMatchCandidate::new(id, command))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let mut matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            // Without a query, the commands stay ordered from the most recent one.
+            if query.is_empty() {
+                matches.sort_by_key(|hit| hit.candidate_id);
+            }
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = 0;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(hit) = self.matches.get(self.selected_index) {
+            let command = self.commands[hit.candidate_id].clone();
+            self.terminal
+                .update(cx, |terminal, _| terminal.input(format!("{command}\r")))
+                .ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let highlighted_text = HighlightedText {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            char_count: hit.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("terminal-command-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(highlighted_text.render(cx)),
+        )
+    }
+}
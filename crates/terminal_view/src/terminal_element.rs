@@ -45,6 +45,8 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    /// Display lines of the shell prompts on the screen, with the color of their command's status.
+    prompt_markers: Vec<(i32, Hsla)>,
     last_hovered_word: Option<HoveredWord>,
     block_below_cursor_element: Option<AnyElement>,
 }
@@ -735,11 +737,25 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    shell_commands,
                     ..
                 } = &self.terminal.read(cx).last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                let prompt_markers = shell_commands
+                    .iter()
+                    .map(|command| {
+                        let color = match (command.is_finished(), command.exit_code) {
+                            (false, _) => theme.status().info,
+                            (true, Some(0)) => theme.status().success,
+                            (true, Some(_)) => theme.status().error,
+                            (true, None) => theme.status().hint,
+                        };
+                        (command.prompt.line.0 + display_offset as i32, color)
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -855,6 +871,7 @@ impl Element for TerminalElement {
                     display_offset,
                     hyperlink_tooltip,
                     gutter,
+                    prompt_markers,
                     last_hovered_word,
                     block_below_cursor_element,
                 }
@@ -919,6 +936,19 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, cx);
                     }
 
+                    let line_height = layout.dimensions.line_height;
+                    for (line, color) in &layout.prompt_markers {
+                        let marker_origin = point(
+                            origin.x - layout.gutter * 0.75,
+                            origin.y + *line as f32 * line_height + line_height * 0.15,
+                        );
+                        let marker_size = size(layout.gutter * 0.4, line_height * 0.7);
+                        cx.paint_quad(
+                            fill(Bounds::new(marker_origin, marker_size), *color)
+                                .corner_radii(layout.gutter * 0.2),
+                        );
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
@@ -10,7 +10,10 @@ use crate::{
 use breadcrumbs::Breadcrumbs;
 use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
-use futures::future::join_all;
+use futures::{
+    channel::oneshot,
+    future::{join_all, try_join_all},
+};
 use gpui::{
     actions, Action, AnchorCorner, AnyView, AppContext, AsyncWindowContext, Entity, EventEmitter,
     ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render,
@@ -23,7 +26,7 @@ use settings::Settings;
 use task::{RevealStrategy, RevealTarget, Shell, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{TerminalDockPosition, TerminalSettings},
-    Terminal,
+    TaskStatus, Terminal,
 };
 use ui::{
     prelude::*, ButtonCommon, Clickable, ContextMenu, FluentBuilder, PopoverMenu, Toggleable,
@@ -33,14 +36,16 @@ use util::{ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     item::SerializableItem,
-    move_item, pane,
+    move_item,
+    notifications::{NotificationId, NotifyTaskExt},
+    pane,
     ui::IconName,
     ActivateNextPane, ActivatePane, ActivatePaneInDirection, ActivatePreviousPane, DraggedTab,
     ItemId, NewTerminal, Pane, PaneGroup, SplitDirection, SplitDown, SplitLeft, SplitRight,
-    SplitUp, SwapPaneInDirection, ToggleZoom, Workspace,
+    SplitUp, SwapPaneInDirection, Toast, ToggleZoom, Workspace,
 };
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use zed_actions::InlineAssist;
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
@@ -421,17 +426,98 @@ impl TerminalPanel {
     }
 
     fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        if spawn_in_terminal.depends_on.is_empty() {
+            self.spawn_single_task(spawn_in_terminal, cx)
+                .detach_and_log_err(cx);
+            return;
+        }
+
+        let spawn_in_terminal = spawn_in_terminal.clone();
+        cx.spawn(|terminal_panel, mut cx| async move {
+            struct WaitingForTaskDependency;
+
+            for step in &spawn_in_terminal.depends_on {
+                let step_terminals = terminal_panel.update(&mut cx, |terminal_panel, cx| {
+                    step.iter()
+                        .map(|dependency| terminal_panel.spawn_single_task(dependency, cx))
+                        .collect::<Vec<_>>()
+                })?;
+                let step_terminals = try_join_all(step_terminals).await?;
+                for (dependency, terminal) in step.iter().zip(step_terminals) {
+                    // Dependencies that never exit, e.g. watchers, keep the task waiting
+                    // until they are stopped, so tell the user what the task waits for.
+                    let completed = terminal
+                        .update(&mut cx, |terminal, cx| terminal.wait_for_completed_task(cx))?;
+                    let toast_id = NotificationId::composite::<WaitingForTaskDependency>(
+                        SharedString::from(spawn_in_terminal.id.0.clone()),
+                    );
+                    terminal_panel.update(&mut cx, |terminal_panel, cx| {
+                        terminal_panel.workspace.update(cx, |workspace, cx| {
+                            workspace.show_toast(
+                                Toast::new(
+                                    toast_id.clone(),
+                                    format!(
+                                        "Task `{}` is waiting for `{}` to finish",
+                                        spawn_in_terminal.label, dependency.label
+                                    ),
+                                ),
+                                cx,
+                            )
+                        })
+                    })??;
+                    completed.await;
+                    terminal_panel.update(&mut cx, |terminal_panel, cx| {
+                        terminal_panel
+                            .workspace
+                            .update(cx, |workspace, cx| workspace.dismiss_toast(&toast_id, cx))
+                    })??;
+                    let succeeded = terminal.read_with(&cx, |terminal, _| {
+                        terminal.task().map(|task| task.status)
+                            == Some(TaskStatus::Completed { success: true })
+                    })?;
+                    if !succeeded {
+                        bail!(
+                            "Task `{}` did not run: its dependency `{}` failed",
+                            spawn_in_terminal.label,
+                            dependency.label
+                        );
+                    }
+                }
+            }
+
+            if !spawn_in_terminal.command.trim().is_empty() {
+                terminal_panel
+                    .update(&mut cx, |terminal_panel, cx| {
+                        terminal_panel.spawn_single_task(&spawn_in_terminal, cx)
+                    })?
+                    .await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    /// Spawns the task given, ignoring its dependencies, and returns the terminal it runs in.
+    fn spawn_single_task(
+        &mut self,
+        spawn_in_terminal: &SpawnInTerminal,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Model<Terminal>>> {
         let mut spawn_task = spawn_in_terminal.clone();
-        let Ok(is_local) = self
+        let is_local = match self
             .workspace
             .update(cx, |workspace, cx| workspace.project().read(cx).is_local())
-        else {
-            return;
+        {
+            Ok(is_local) => is_local,
+            Err(e) => return Task::ready(Err(e)),
         };
         if let ControlFlow::Break(_) =
             Self::fill_command(is_local, spawn_in_terminal, &mut spawn_task)
         {
-            return;
+            return Task::ready(Err(anyhow!(
+                "failed to determine the shell to run task `{}` in",
+                spawn_in_terminal.label
+            )));
         }
         let spawn_task = spawn_task;
 
@@ -439,22 +525,19 @@ impl TerminalPanel {
         let use_new_terminal = spawn_in_terminal.use_new_terminal;
 
         if allow_concurrent_runs && use_new_terminal {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, cx);
         }
 
         let terminals_for_task = self.terminals_for_task(&spawn_in_terminal.full_label, cx);
         if terminals_for_task.is_empty() {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, cx);
         }
         let (existing_item_index, task_pane, existing_terminal) = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
         let id = spawn_in_terminal.id.clone();
+        let label = spawn_in_terminal.label.clone();
         cx.spawn(move |this, mut cx| async move {
             if allow_concurrent_runs {
                 debug_assert!(
@@ -470,42 +553,46 @@ impl TerminalPanel {
                         cx,
                     )
                 })?
-                .await;
+                .await
+                .with_context(|| format!("replacing the terminal of task `{label}`"))
             } else {
+                let (new_terminal_tx, new_terminal_rx) = oneshot::channel();
                 this.update(&mut cx, |this, cx| {
                     this.deferred_tasks.insert(
                         id,
                         cx.spawn(|terminal_panel, mut cx| async move {
                             wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                            let Ok(Some(new_terminal_task)) =
+                            let Ok(new_terminal_task) =
                                 terminal_panel.update(&mut cx, |terminal_panel, cx| {
                                     if use_new_terminal {
-                                        terminal_panel
-                                            .spawn_in_new_terminal(spawn_task, cx)
-                                            .detach_and_log_err(cx);
-                                        None
+                                        terminal_panel.spawn_in_new_terminal(spawn_task, cx)
                                     } else {
-                                        Some(terminal_panel.replace_terminal(
+                                        let replace_task = terminal_panel.replace_terminal(
                                             spawn_task,
                                             task_pane,
                                             existing_item_index,
                                             existing_terminal,
                                             cx,
-                                        ))
+                                        );
+                                        cx.spawn(|_, _| async move {
+                                            replace_task.await.with_context(|| {
+                                                format!("replacing the terminal of task `{label}`")
+                                            })
+                                        })
                                     }
                                 })
                             else {
                                 return;
                             };
-                            new_terminal_task.await;
+                            new_terminal_tx.send(new_terminal_task.await).ok();
                         }),
                     );
-                })
-                .ok();
+                })?;
+                new_terminal_rx
+                    .await
+                    .context("task got cancelled before it started")?
             }
-            anyhow::Result::<_, anyhow::Error>::Ok(())
         })
-        .detach()
     }
 
     pub fn fill_command(
@@ -813,7 +900,7 @@ impl TerminalPanel {
         terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
-    ) -> Task<Option<()>> {
+    ) -> Task<Option<Model<Terminal>>> {
         let reveal = spawn_task.reveal;
         let reveal_target = spawn_task.reveal_target;
         let window = cx.window_handle();
@@ -836,7 +923,7 @@ impl TerminalPanel {
                 .log_err()?;
             terminal_to_replace
                 .update(&mut cx, |terminal_to_replace, cx| {
-                    terminal_to_replace.set_terminal(new_terminal, cx);
+                    terminal_to_replace.set_terminal(new_terminal.clone(), cx);
                 })
                 .ok()?;
 
@@ -906,7 +993,7 @@ impl TerminalPanel {
                 RevealStrategy::Never => {}
             }
 
-            Some(())
+            Some(new_terminal)
         })
     }
 
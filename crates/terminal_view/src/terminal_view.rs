@@ -1,15 +1,17 @@
+mod command_history;
 mod persistence;
 pub mod terminal_element;
 pub mod terminal_panel;
 
 use collections::HashSet;
+use command_history::CommandHistory;
 use editor::{actions::SelectAll, scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    anchored, deferred, div, impl_actions, AnyElement, AppContext, DismissEvent, EventEmitter,
-    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
-    MouseDownEvent, Pixels, Render, ScrollWheelEvent, Styled, Subscription, Task, View,
-    VisualContext, WeakView,
+    anchored, deferred, div, impl_actions, AnyElement, AppContext, ClipboardItem, DismissEvent,
+    EventEmitter, FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model,
+    MouseButton, MouseDownEvent, Pixels, Render, ScrollWheelEvent, Styled, Subscription, Task,
+    View, VisualContext, WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
@@ -20,9 +22,10 @@ use terminal::{
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{CursorShape, TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown,
-    ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal,
-    TerminalSize, ToggleViMode,
+    Clear, Copy, CopyLastCommandOutput, Event, ExportSession, MaybeNavigationTarget, Paste,
+    RerunCommand, RerunLastCommand, ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp,
+    ScrollToBottom, ScrollToNextPrompt, ScrollToPreviousPrompt, ScrollToTop, ShowCharacterPalette,
+    TaskStatus, Terminal, TerminalSize, ToggleViMode,
 };
 use terminal_element::{is_blank, TerminalElement};
 use terminal_panel::TerminalPanel;
@@ -30,6 +33,7 @@ use ui::{h_flex, prelude::*, ContextMenu, Icon, IconName, Label, Tooltip};
 use util::{paths::PathWithPosition, ResultExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, SerializableItem, TabContentParams},
+    notifications::NotifyTaskExt,
     register_serializable_item,
     searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
     CloseActiveItem, NewCenterTerminal, NewTerminal, OpenVisible, ToolbarItemLocation, Workspace,
@@ -57,6 +61,9 @@ const REGEX_SPECIAL_CHARS: &[char] = &[
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 
+/// How long the terminal output has to stay unchanged before it gets persisted.
+const SCROLLBACK_SERIALIZATION_DEBOUNCE: Duration = Duration::from_secs(2);
+
 const GIT_DIFF_PATH_PREFIXES: &[char] = &['a', 'b'];
 
 ///Event to transmit the scroll from the element to the view
@@ -111,6 +118,7 @@ pub struct TerminalView {
     show_breadcrumbs: bool,
     block_below_cursor: Option<Rc<BlockProperties>>,
     scroll_top: Pixels,
+    pending_scrollback_serialization: Task<()>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -156,6 +164,17 @@ impl TerminalView {
         let cursor_shape = TerminalSettings::get_global(cx)
             .cursor_shape
             .unwrap_or_default();
+        let view = cx.view().downgrade();
+        let save_scrollback_on_quit = cx.on_app_quit(move |cx| {
+            let task = view
+                .upgrade()
+                .and_then(|view| view.read(cx).save_scrollback(view.entity_id().as_u64(), cx));
+            async move {
+                if let Some(task) = task {
+                    task.await.log_err();
+                }
+            }
+        });
 
         Self {
             terminal,
@@ -173,9 +192,11 @@ impl TerminalView {
             show_breadcrumbs: TerminalSettings::get_global(cx).toolbar.breadcrumbs,
             block_below_cursor: None,
             scroll_top: Pixels::ZERO,
+            pending_scrollback_serialization: Task::ready(()),
             _subscriptions: vec![
                 focus_in,
                 focus_out,
+                save_scrollback_on_quit,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
             ],
             _terminal_subscriptions: terminal_subscriptions,
@@ -186,6 +207,57 @@ impl TerminalView {
         &self.terminal
     }
 
+    /// Saves the terminal output, to be restored with the terminal in the next session.
+    fn save_scrollback(
+        &self,
+        item_id: workspace::ItemId,
+        cx: &AppContext,
+    ) -> Option<Task<anyhow::Result<()>>> {
+        let workspace_id = self.workspace_id?;
+        let terminal = self.terminal.read(cx);
+        let settings = TerminalSettings::get_global(cx);
+        if !settings.persist_scrollback || terminal.task().is_some() {
+            return None;
+        }
+
+        let mut lines = terminal.output_lines();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let skipped = lines
+            .len()
+            .saturating_sub(settings.persisted_scrollback_lines);
+        lines.drain(..skipped);
+        let scrollback = lines.join("\n");
+        Some(cx.background_executor().spawn(async move {
+            TERMINAL_DB
+                .save_scrollback(item_id, workspace_id, scrollback)
+                .await
+        }))
+    }
+
+    /// Saves the terminal output once it stops changing, rather than on every change:
+    /// collecting the output walks the whole scrollback.
+    fn schedule_scrollback_serialization(&mut self, cx: &mut ViewContext<Self>) {
+        if self.workspace_id.is_none() || !TerminalSettings::get_global(cx).persist_scrollback {
+            return;
+        }
+        self.pending_scrollback_serialization = cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(SCROLLBACK_SERIALIZATION_DEBOUNCE)
+                .await;
+            let task = this
+                .update(&mut cx, |this, cx| {
+                    this.save_scrollback(cx.entity_id().as_u64(), cx)
+                })
+                .ok()
+                .flatten();
+            if let Some(task) = task {
+                task.await.log_err();
+            }
+        });
+    }
+
     pub fn has_bell(&self) -> bool {
         self.has_bell
     }
@@ -207,6 +279,7 @@ impl TerminalView {
             .map_or(false, |terminal_panel| {
                 terminal_panel.read(cx).assistant_enabled()
             });
+        let has_shell_commands = self.terminal.read(cx).last_command().is_some();
         let context_menu = ContextMenu::build(cx, |menu, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal))
@@ -215,6 +288,13 @@ impl TerminalView {
                 .action("Paste", Box::new(Paste))
                 .action("Select All", Box::new(SelectAll))
                 .action("Clear", Box::new(Clear))
+                .action("Export Session…", Box::new(ExportSession))
+                .when(has_shell_commands, |menu| {
+                    menu.separator()
+                        .action("Copy Last Command Output", Box::new(CopyLastCommandOutput))
+                        .action("Rerun Last Command", Box::new(RerunLastCommand))
+                        .action("Rerun Command…", Box::new(RerunCommand))
+                })
                 .when(assistant_enabled, |menu| {
                     menu.separator()
                         .action("Inline Assist", Box::new(InlineAssist::default()))
@@ -417,6 +497,56 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn export_session(&mut self, _: &ExportSession, cx: &mut ViewContext<Self>) {
+        let terminal = self.terminal.read(cx);
+        let lines = terminal.output_lines();
+        let start_path = terminal
+            .working_directory()
+            .unwrap_or_else(|| util::paths::home_dir().clone());
+        export_session_lines(lines, &start_path, cx).detach_and_notify_err(cx);
+    }
+
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        if let Some(output) = self.terminal.read(cx).last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
+    fn rerun_last_command(&mut self, _: &RerunLastCommand, cx: &mut ViewContext<Self>) {
+        let Some(command) = self.terminal.read(cx).last_command() else {
+            return;
+        };
+        self.clear_bell(cx);
+        self.terminal
+            .update(cx, |term, _| term.input(format!("{command}\r")));
+    }
+
+    fn rerun_command(&mut self, _: &RerunCommand, cx: &mut ViewContext<Self>) {
+        let terminal = self.terminal.clone();
+        self.clear_bell(cx);
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| CommandHistory::new(&terminal, cx));
+            })
+            .ok();
+    }
+
     pub fn should_show_cursor(&self, focused: bool, cx: &mut gpui::ViewContext<Self>) -> bool {
         //Don't blink the cursor when not focused, blinking is disabled, or paused
         if !focused
@@ -623,6 +753,7 @@ fn subscribe_for_terminal_events(
                 cx.emit(Event::Wakeup);
                 cx.emit(ItemEvent::UpdateTab);
                 cx.emit(SearchEvent::MatchesInvalidated);
+                this.schedule_scrollback_serialization(cx);
             }
 
             Event::Bell => {
@@ -887,12 +1018,42 @@ fn regex_to_literal(regex: &str) -> String {
         .collect()
 }
 
+/// Asks for a file and writes the lines of a terminal session to it, without the trailing empty lines.
+fn export_session_lines(
+    mut lines: Vec<String>,
+    start_path: &Path,
+    cx: &mut WindowContext,
+) -> Task<anyhow::Result<()>> {
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let abs_path = cx.prompt_for_new_path(start_path);
+    cx.spawn(|_| async move {
+        let Some(abs_path) = abs_path.await?? else {
+            return Ok(());
+        };
+        let mut contents = lines.join("\n");
+        contents.push('\n');
+        smol::fs::write(&abs_path, contents)
+            .await
+            .with_context(|| format!("exporting the terminal session to {abs_path:?}"))
+    })
+}
+
 pub fn regex_search_for_query(query: &project::search::SearchQuery) -> Option<RegexSearch> {
-    let query = query.as_str();
-    if query == "." {
+    let mut pattern = query.as_str().to_string();
+    if pattern == "." {
         return None;
     }
-    let searcher = RegexSearch::new(query);
+    // The searches run on DFAs, which only support ASCII word boundaries.
+    if query.whole_word() {
+        pattern = format!(r"(?-u:\b)(?:{pattern})(?-u:\b)");
+    }
+    // Without the option, the search is case-insensitive unless the query has uppercase letters.
+    if query.case_sensitive() {
+        pattern = format!("(?-i){pattern}");
+    }
+    let searcher = RegexSearch::new(&pattern);
     searcher.ok()
 }
 
@@ -955,6 +1116,12 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
+            .on_action(cx.listener(TerminalView::export_session))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
+            .on_action(cx.listener(TerminalView::rerun_last_command))
+            .on_action(cx.listener(TerminalView::rerun_command))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
             .on_key_down(cx.listener(Self::key_down))
@@ -1160,7 +1327,7 @@ impl SerializableItem for TerminalView {
         &mut self,
         _workspace: &mut Workspace,
         item_id: workspace::ItemId,
-        _closing: bool,
+        closing: bool,
         cx: &mut ViewContext<Self>,
     ) -> Option<Task<gpui::Result<()>>> {
         let terminal = self.terminal().read(cx);
@@ -1168,15 +1335,29 @@ impl SerializableItem for TerminalView {
             return None;
         }
 
-        if let Some((cwd, workspace_id)) = terminal.working_directory().zip(self.workspace_id) {
-            Some(cx.background_executor().spawn(async move {
-                TERMINAL_DB
-                    .save_working_directory(item_id, workspace_id, cwd)
-                    .await
-            }))
+        let workspace_id = self.workspace_id?;
+        let cwd = terminal.working_directory();
+        // The output is saved once it settles, see `schedule_scrollback_serialization`.
+        let save_scrollback = if closing {
+            self.save_scrollback(item_id, cx)
         } else {
             None
+        };
+        if cwd.is_none() && save_scrollback.is_none() {
+            return None;
         }
+
+        Some(cx.background_executor().spawn(async move {
+            if let Some(cwd) = cwd {
+                TERMINAL_DB
+                    .save_working_directory(item_id, workspace_id, cwd)
+                    .await?;
+            }
+            if let Some(save_scrollback) = save_scrollback {
+                save_scrollback.await?;
+            }
+            Ok(())
+        }))
     }
 
     fn should_serialize(&self, event: &Self::Event) -> bool {
@@ -1212,9 +1393,29 @@ impl SerializableItem for TerminalView {
                 .ok()
                 .flatten();
 
+            let scrollback = cx
+                .update(|cx| {
+                    if TerminalSettings::get_global(cx).persist_scrollback {
+                        TERMINAL_DB
+                            .get_scrollback(item_id, workspace_id)
+                            .log_err()
+                            .flatten()
+                    } else {
+                        None
+                    }
+                })
+                .ok()
+                .flatten()
+                .filter(|scrollback| !scrollback.is_empty());
+
             let terminal = project
                 .update(&mut cx, |project, cx| {
-                    project.create_terminal(TerminalKind::Shell(cwd), window, cx)
+                    project.create_terminal_with_scrollback(
+                        TerminalKind::Shell(cwd),
+                        scrollback,
+                        window,
+                        cx,
+                    )
                 })?
                 .await?;
             cx.update(|cx| {
@@ -1229,8 +1430,8 @@ impl SearchableItem for TerminalView {
 
     fn supported_options() -> SearchOptions {
         SearchOptions {
-            case: false,
-            word: false,
+            case: true,
+            word: true,
             regex: true,
             replacement: false,
             selection: false,
@@ -1378,6 +1579,14 @@ mod tests {
     use gpui::TestAppContext;
     use project::{Entry, Project, ProjectPath, Worktree};
     use std::path::Path;
+    use terminal::alacritty_terminal::{
+        event::VoidListener,
+        grid::Dimensions,
+        index::{Column, Direction},
+        term::{search::RegexIter, Config},
+        vte::ansi::Processor,
+        Term,
+    };
     use workspace::AppState;
 
     // Working directory calculation tests
@@ -1561,6 +1770,71 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_export_session(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.txt");
+        let lines = ["$ echo hello", "hello", "$", "", ""]
+            .map(str::to_string)
+            .to_vec();
+
+        let cx = cx.add_empty_window();
+        let export = cx.update(|cx| export_session_lines(lines, dir.path(), cx));
+        cx.simulate_new_path_selection(|start_path| {
+            assert_eq!(start_path, dir.path());
+            Some(path.clone())
+        });
+        export.await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "$ echo hello\nhello\n$\n"
+        );
+    }
+
+    fn search_matches(text: &str, query: SearchQuery) -> Vec<String> {
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        let mut parser: Processor = Processor::new();
+        for byte in text.replace('\n', "\r\n").bytes() {
+            parser.advance(&mut term, byte);
+        }
+        let mut searcher = regex_search_for_query(&query).unwrap();
+        let start = Point::new(term.topmost_line(), Column(0));
+        let end = Point::new(term.bottommost_line(), term.last_column());
+        RegexIter::new(start, end, Direction::Right, &term, &mut searcher)
+            .map(|found| term.bounds_to_string(*found.start(), *found.end()))
+            .collect()
+    }
+
+    #[test]
+    fn test_search_options() {
+        let text = "Error: error_count errors\nERROR";
+        let query = |whole_word, case_sensitive| {
+            SearchQuery::regex(
+                "error",
+                whole_word,
+                case_sensitive,
+                false,
+                Default::default(),
+                Default::default(),
+                None,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            search_matches(text, query(false, false)),
+            ["Error", "error", "error", "ERROR"]
+        );
+        assert_eq!(search_matches(text, query(false, true)), ["error", "error"]);
+        // `_` is a word character, so `error_count` doesn't contain the word.
+        assert_eq!(search_matches(text, query(true, false)), ["Error", "ERROR"]);
+        assert_eq!(
+            search_matches(text, query(true, true)),
+            Vec::<String>::new()
+        );
+    }
+
     #[test]
     fn escapes_only_special_characters() {
         assert_eq!(regex_to_literal(r"test(\w)"), r"test\(\\w\)".to_string());
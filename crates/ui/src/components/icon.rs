@@ -162,6 +162,12 @@ pub enum IconName {
     CursorIBeam,
     Dash,
     DatabaseZap,
+    Debug,
+    DebugBreakpoint,
+    DebugPause,
+    DebugStepInto,
+    DebugStepOut,
+    DebugStepOver,
     Delete,
     Diff,
     Disconnected,
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Context, Result};
 use fuzzy::{StringMatch, StringMatchCandidate};
-use git::repository::Branch;
+use git::repository::{Branch, PullStrategy, StashEntry};
 use gpui::{
     rems, AnyElement, AppContext, AsyncAppContext, DismissEvent, EventEmitter, FocusHandle,
     FocusableView, InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled,
@@ -9,7 +9,7 @@ use gpui::{
 use picker::{Picker, PickerDelegate};
 use project::ProjectPath;
 use std::{ops::Not, sync::Arc};
-use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use ui::{prelude::*, HighlightedLabel, Icon, IconName, ListItem, ListItemSpacing};
 use util::ResultExt;
 use workspace::notifications::DetachAndPromptErr;
 use workspace::{ModalView, Workspace};
@@ -80,6 +80,7 @@ impl Render for BranchList {
 enum BranchEntry {
     Branch(StringMatch),
     NewBranch { name: String },
+    Operation(GitOperation, StringMatch),
 }
 
 impl BranchEntry {
@@ -87,6 +88,50 @@ impl BranchEntry {
         match self {
             Self::Branch(branch) => &branch.string,
             Self::NewBranch { name } => &name,
+            Self::Operation(_, operation) => &operation.string,
+        }
+    }
+}
+
+/// A repository operation offered below the branches.
+#[derive(Debug, Clone)]
+enum GitOperation {
+    Fetch,
+    Pull(PullStrategy),
+    Push,
+    StashPush,
+    StashPop(StashEntry),
+}
+
+impl GitOperation {
+    fn label(&self) -> String {
+        match self {
+            Self::Fetch => "Fetch".to_string(),
+            Self::Pull(PullStrategy::FastForwardOnly) => "Pull (fast-forward only)".to_string(),
+            Self::Pull(PullStrategy::Rebase) => "Pull (rebase)".to_string(),
+            Self::Push => "Push".to_string(),
+            Self::StashPush => "Stash all changes".to_string(),
+            Self::StashPop(entry) => format!("Pop stash: {}", entry.message),
+        }
+    }
+
+    fn icon(&self) -> IconName {
+        match self {
+            Self::Fetch => IconName::Download,
+            Self::Pull(_) => IconName::ArrowDown,
+            Self::Push => IconName::ArrowUp,
+            Self::StashPush => IconName::Save,
+            Self::StashPop(_) => IconName::Undo,
+        }
+    }
+
+    fn error_prompt(&self) -> &'static str {
+        match self {
+            Self::Fetch => "Failed to fetch",
+            Self::Pull(_) => "Failed to pull",
+            Self::Push => "Failed to push",
+            Self::StashPush => "Failed to stash changes",
+            Self::StashPop(_) => "Failed to pop stash",
         }
     }
 }
@@ -94,6 +139,7 @@ impl BranchEntry {
 pub struct BranchListDelegate {
     matches: Vec<BranchEntry>,
     all_branches: Vec<Branch>,
+    operations: Vec<GitOperation>,
     workspace: WeakView<Workspace>,
     selected_index: usize,
     last_query: String,
@@ -107,22 +153,36 @@ impl BranchListDelegate {
         branch_name_trailoff_after: usize,
         cx: &AsyncAppContext,
     ) -> Result<Self> {
-        let all_branches_request = cx.update(|cx| {
+        let (all_branches_request, stash_entries_request) = cx.update(|cx| {
             let project = workspace.read(cx).project().read(cx);
             let first_worktree = project
                 .visible_worktrees(cx)
                 .next()
                 .context("No worktrees found")?;
             let project_path = ProjectPath::root_path(first_worktree.read(cx).id());
-            anyhow::Ok(project.branches(project_path, cx))
+            anyhow::Ok((
+                project.branches(project_path.clone(), cx),
+                project.stash_entries(project_path, cx),
+            ))
         })??;
 
         let all_branches = all_branches_request.await?;
+        let stash_entries = stash_entries_request.await.log_err().unwrap_or_default();
+
+        let mut operations = vec![
+            GitOperation::Fetch,
+            GitOperation::Pull(PullStrategy::FastForwardOnly),
+            GitOperation::Pull(PullStrategy::Rebase),
+            GitOperation::Push,
+            GitOperation::StashPush,
+        ];
+        operations.extend(stash_entries.into_iter().map(GitOperation::StashPop));
 
         Ok(Self {
             matches: vec![],
             workspace: workspace.downgrade(),
             all_branches,
+            operations,
             selected_index: 0,
             last_query: Default::default(),
             branch_name_trailoff_after,
@@ -130,6 +190,67 @@ impl BranchListDelegate {
     }
 }
 
+impl BranchListDelegate {
+    fn run_operation(&self, operation: GitOperation, cx: &mut ViewContext<Picker<Self>>) {
+        let error_prompt = operation.error_prompt();
+        cx.spawn(|picker, mut cx| async move {
+            let task = picker.update(&mut cx, |this, cx| {
+                let workspace = this
+                    .delegate
+                    .workspace
+                    .upgrade()
+                    .ok_or_else(|| anyhow!("workspace was dropped"))?;
+                let project = workspace.read(cx).project().read(cx);
+                let worktree = project
+                    .visible_worktrees(cx)
+                    .next()
+                    .context("worktree disappeared")?;
+                let repository = ProjectPath::root_path(worktree.read(cx).id());
+                let task = match operation {
+                    GitOperation::Fetch => project.fetch(repository, cx),
+                    GitOperation::Pull(strategy) => project.pull(repository, strategy, cx),
+                    GitOperation::Push => project.push(repository, cx),
+                    GitOperation::StashPush => project.stash_push(repository, None, cx),
+                    GitOperation::StashPop(entry) => project.stash_pop(repository, entry.index, cx),
+                };
+                cx.emit(DismissEvent);
+                anyhow::Ok(task)
+            })??;
+            task.await
+        })
+        .detach_and_prompt_err(error_prompt, cx, |_, _| None);
+    }
+}
+
+async fn match_candidates(
+    candidates: &[StringMatchCandidate],
+    query: &str,
+    cx: &AsyncAppContext,
+) -> Vec<StringMatch> {
+    if query.is_empty() {
+        candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| StringMatch {
+                candidate_id: index,
+                string: candidate.string.clone(),
+                positions: Vec::new(),
+                score: 0.0,
+            })
+            .collect()
+    } else {
+        fuzzy::match_strings(
+            candidates,
+            query,
+            true,
+            10000,
+            &Default::default(),
+            cx.background_executor().clone(),
+        )
+        .await
+    }
+}
+
 impl PickerDelegate for BranchListDelegate {
     type ListItem = ListItem;
 
@@ -150,6 +271,12 @@ impl PickerDelegate for BranchListDelegate {
     }
 
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let operations = self.operations.clone();
+        let operation_candidates = operations
+            .iter()
+            .enumerate()
+            .map(|(ix, operation)| StringMatchCandidate::new(ix, &operation.label()))
+            .collect::<Vec<_>>();
         cx.spawn(move |picker, mut cx| async move {
             let candidates = picker.update(&mut cx, |view, _| {
                 const RECENT_BRANCHES_COUNT: usize = 10;
@@ -178,39 +305,26 @@ impl PickerDelegate for BranchListDelegate {
             let Some(candidates) = candidates.log_err() else {
                 return;
             };
-            let matches = if query.is_empty() {
-                candidates
-                    .into_iter()
-                    .enumerate()
-                    .map(|(index, candidate)| StringMatch {
-                        candidate_id: index,
-                        string: candidate.string,
-                        positions: Vec::new(),
-                        score: 0.0,
-                    })
-                    .collect()
-            } else {
-                fuzzy::match_strings(
-                    &candidates,
-                    &query,
-                    true,
-                    10000,
-                    &Default::default(),
-                    cx.background_executor().clone(),
-                )
-                .await
-            };
+            let matches = match_candidates(&candidates, &query, &cx).await;
+            let operation_matches = match_candidates(&operation_candidates, &query, &cx).await;
             picker
                 .update(&mut cx, |picker, _| {
                     let delegate = &mut picker.delegate;
                     delegate.matches = matches.into_iter().map(BranchEntry::Branch).collect();
+                    if delegate.matches.is_empty() && !query.is_empty() {
+                        delegate.matches.push(BranchEntry::NewBranch {
+                            name: query.trim().replace(' ', "-"),
+                        });
+                    }
+                    delegate
+                        .matches
+                        .extend(operation_matches.into_iter().map(|operation_match| {
+                            BranchEntry::Operation(
+                                operations[operation_match.candidate_id].clone(),
+                                operation_match,
+                            )
+                        }));
                     if delegate.matches.is_empty() {
-                        if !query.is_empty() {
-                            delegate.matches.push(BranchEntry::NewBranch {
-                                name: query.trim().replace(' ', "-"),
-                            });
-                        }
-
                         delegate.selected_index = 0;
                     } else {
                         delegate.selected_index =
@@ -226,38 +340,39 @@ impl PickerDelegate for BranchListDelegate {
         let Some(branch) = self.matches.get(self.selected_index()) else {
             return;
         };
-        cx.spawn({
-            let branch = branch.clone();
-            |picker, mut cx| async move {
-                let branch_change_task = picker.update(&mut cx, |this, cx| {
-                    let workspace = this
-                        .delegate
-                        .workspace
-                        .upgrade()
-                        .ok_or_else(|| anyhow!("workspace was dropped"))?;
-
-                    let project = workspace.read(cx).project().read(cx);
-                    let branch_to_checkout = match branch {
-                        BranchEntry::Branch(branch) => branch.string,
-                        BranchEntry::NewBranch { name: branch_name } => branch_name,
-                    };
-                    let worktree = project
-                        .visible_worktrees(cx)
-                        .next()
-                        .context("worktree disappeared")?;
-                    let repository = ProjectPath::root_path(worktree.read(cx).id());
-
-                    anyhow::Ok(project.update_or_create_branch(repository, branch_to_checkout, cx))
-                })??;
-
-                branch_change_task.await?;
-
-                picker.update(&mut cx, |_, cx| {
-                    cx.emit(DismissEvent);
-
-                    Ok::<(), anyhow::Error>(())
-                })
+        let branch_to_checkout = match branch {
+            BranchEntry::Branch(branch) => branch.string.clone(),
+            BranchEntry::NewBranch { name } => name.clone(),
+            BranchEntry::Operation(operation, _) => {
+                self.run_operation(operation.clone(), cx);
+                return;
             }
+        };
+        cx.spawn(|picker, mut cx| async move {
+            let branch_change_task = picker.update(&mut cx, |this, cx| {
+                let workspace = this
+                    .delegate
+                    .workspace
+                    .upgrade()
+                    .ok_or_else(|| anyhow!("workspace was dropped"))?;
+
+                let project = workspace.read(cx).project().read(cx);
+                let worktree = project
+                    .visible_worktrees(cx)
+                    .next()
+                    .context("worktree disappeared")?;
+                let repository = ProjectPath::root_path(worktree.read(cx).id());
+
+                anyhow::Ok(project.update_or_create_branch(repository, branch_to_checkout, cx))
+            })??;
+
+            branch_change_task.await?;
+
+            picker.update(&mut cx, |_, cx| {
+                cx.emit(DismissEvent);
+
+                Ok::<(), anyhow::Error>(())
+            })
         })
         .detach_and_prompt_err("Failed to change branch", cx, |_, _| None);
     }
@@ -295,6 +410,12 @@ impl PickerDelegate for BranchListDelegate {
                     BranchEntry::NewBranch { name } => {
                         parent.child(Label::new(format!("Create branch '{name}'")))
                     }
+                    BranchEntry::Operation(operation, operation_match) => parent
+                        .start_slot(Icon::new(operation.icon()).color(Color::Muted))
+                        .child(HighlightedLabel::new(
+                            operation_match.string.clone(),
+                            operation_match.positions.clone(),
+                        )),
                 }),
         )
     }
@@ -9,23 +9,26 @@ use anyhow::{anyhow, Result};
 use command_palette_hooks::CommandInterceptResult;
 use editor::{
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
-    Editor, ToPoint,
+    Anchor, Editor, MultiBufferSnapshot, ToPoint,
 };
-use gpui::{actions, impl_actions, Action, AppContext, Global, ViewContext};
+use gpui::{actions, impl_actions, Action, AppContext, FocusableView, Global, View, ViewContext};
 use language::Point;
 use multi_buffer::MultiBufferRow;
+use regex::Regex;
 use serde::Deserialize;
 use ui::WindowContext;
 use util::ResultExt;
 use workspace::{notifications::NotifyResultExt, SaveIntent};
 
 use crate::{
+    listings::{ShowJumps, ShowMarks, ShowRegisters},
     motion::{EndOfDocument, Motion, StartOfDocument},
     normal::{
         search::{FindCommand, ReplaceCommand, Replacement},
         JoinLines,
     },
     state::Mode,
+    vimrc::Vimrc,
     visual::VisualDeleteLine,
     Vim,
 };
@@ -53,11 +56,37 @@ pub struct WithCount {
     action: WrappedAction,
 }
 
+/// Runs the action on the lines matching (or, when inverted, not matching) the search, as `:g` and `:v` do.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OnMatchingLines {
+    range: CommandRange,
+    search: String,
+    invert: bool,
+    action: WrappedAction,
+}
+
+/// Types the keystrokes in normal mode on each line of the range, or at the cursors, as `:normal` does.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NormalCommand {
+    range: Option<CommandRange>,
+    keystrokes: String,
+}
+
 #[derive(Debug)]
 struct WrappedAction(Box<dyn Action>);
 
 actions!(vim, [VisualCommand, CountCommand]);
-impl_actions!(vim, [GoToLine, YankCommand, WithRange, WithCount]);
+impl_actions!(
+    vim,
+    [
+        GoToLine,
+        YankCommand,
+        WithRange,
+        WithCount,
+        OnMatchingLines,
+        NormalCommand
+    ]
+);
 
 impl<'de> Deserialize<'de> for WrappedAction {
     fn deserialize<D>(_: D) -> Result<Self, D::Error>
@@ -158,6 +187,14 @@ pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
         }
     });
 
+    Vim::action(editor, cx, |vim, action: &OnMatchingLines, cx| {
+        action.run(vim, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &NormalCommand, cx| {
+        action.run(vim, cx)
+    });
+
     Vim::action(editor, cx, |vim, action: &WithRange, cx| {
         let result = vim.update_editor(cx, |vim, editor, cx| {
             action.range.buffer_range(vim, editor, cx)
@@ -497,6 +534,265 @@ impl CommandRange {
     }
 }
 
+impl OnMatchingLines {
+    // Parses `:g/pattern/command`, `:g!/pattern/command` and `:v/pattern/command`.
+    // The pattern gets the parentheses flipped like in `:s`, and the command is parsed as any other ex command.
+    fn parse(
+        query: &str,
+        range: &Option<CommandRange>,
+        expand_user_commands: bool,
+        cx: &AppContext,
+    ) -> Option<Self> {
+        let (mut invert, rest) = if let Some(rest) = strip_command(query, "g", "lobal") {
+            (false, rest)
+        } else if let Some(rest) = strip_command(query, "v", "global") {
+            (true, rest)
+        } else {
+            return None;
+        };
+        let mut chars = rest.chars().peekable();
+        if chars.peek() == Some(&'!') {
+            chars.next();
+            invert = !invert;
+        }
+        let delimiter = chars
+            .next()
+            .filter(|c| !c.is_alphanumeric() && !c.is_whitespace() && *c != '"' && *c != '|')?;
+
+        let mut search = String::new();
+        let mut escaped = false;
+        for c in chars.by_ref() {
+            if escaped {
+                escaped = false;
+                if c != '(' && c != ')' && c != delimiter {
+                    search.push('\\')
+                }
+                search.push(c)
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == delimiter {
+                break;
+            } else {
+                if c == '(' || c == ')' {
+                    search.push('\\')
+                }
+                search.push(c)
+            }
+        }
+        if search.is_empty() {
+            return None;
+        }
+
+        let command = chars.collect::<String>();
+        let action = intercept_command(command.trim(), expand_user_commands, cx)?.action;
+        Some(Self {
+            range: range.clone().unwrap_or(CommandRange {
+                start: Position::Line { row: 1, offset: 0 },
+                end: Some(Position::LastLine { offset: 0 }),
+            }),
+            search,
+            invert,
+            action: WrappedAction(action),
+        })
+    }
+
+    fn run(&self, vim: &mut Vim, cx: &mut ViewContext<Vim>) {
+        vim.switch_mode(Mode::Normal, false, cx);
+        let result = vim.update_editor(cx, |vim, editor, cx| {
+            let range = self.range.buffer_range(vim, editor, cx)?;
+            let regex = Regex::new(&self.search)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let lines = (range.start.0..=range.end.0)
+                .filter(|row| line_matches(&snapshot, *row, &regex) != self.invert)
+                .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                .collect::<Vec<_>>();
+            if lines.is_empty() {
+                return Err(anyhow!("Pattern not found: {}", self.search));
+            }
+            anyhow::Ok((regex, lines))
+        });
+        let (regex, lines) = match result {
+            None => return,
+            Some(Err(e)) => {
+                let Some(workspace) = vim.workspace(cx) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| workspace.show_error(&e, cx));
+                return;
+            }
+            Some(Ok(result)) => result,
+        };
+
+        // `:normal` types its keystrokes asynchronously, so rather than once per line it is
+        // run once, with a cursor at the start of every line.
+        if self
+            .action
+            .as_any()
+            .downcast_ref::<NormalCommand>()
+            .is_some()
+        {
+            vim.update_editor(cx, |_, editor, cx| {
+                editor.change_selections(None, cx, |s| {
+                    s.select_anchor_ranges(lines.into_iter().map(|line| line..line));
+                });
+            });
+            cx.dispatch_action(self.action.boxed_clone());
+            return;
+        }
+
+        // `:s` searches asynchronously too, so it is run once, within the lines it would have
+        // changed when run on each line. As in Vim, an empty pattern reuses the one of `:g`.
+        if let Some(replace) = self.action.as_any().downcast_ref::<ReplaceCommand>() {
+            let ranges = vim.update_editor(cx, |vim, editor, cx| {
+                lines
+                    .into_iter()
+                    .map(|line| {
+                        editor.change_selections(None, cx, |s| {
+                            s.select_anchor_ranges([line..line]);
+                        });
+                        replace.line_range(vim, editor, cx)
+                    })
+                    .collect::<Result<Vec<_>>>()
+            });
+            match ranges {
+                None => {}
+                Some(Err(e)) => {
+                    let Some(workspace) = vim.workspace(cx) else {
+                        return;
+                    };
+                    workspace.update(cx, |workspace, cx| workspace.show_error(&e, cx));
+                }
+                Some(Ok(ranges)) => {
+                    let mut replacement = replace.replacement.clone();
+                    if replacement.search.is_empty() {
+                        replacement.search = self.search.clone();
+                    }
+                    vim.replace_in_ranges(replacement, ranges, cx);
+                }
+            }
+            return;
+        }
+
+        let Some(editor) = vim.editor() else {
+            return;
+        };
+        let action = self.action.boxed_clone();
+        let invert = self.invert;
+        cx.window_context()
+            .defer(move |cx| run_on_lines(editor, action, regex, invert, lines.into_iter(), cx));
+    }
+}
+
+/// Runs the action at the start of each of the lines in turn, as `:g` does. Each run waits
+/// for the effects of the previous one, so that it sees the edits made on the earlier lines.
+/// Lines that no longer match, e.g. because an earlier run joined them, are skipped.
+fn run_on_lines(
+    editor: View<Editor>,
+    action: Box<dyn Action>,
+    regex: Regex,
+    invert: bool,
+    mut lines: std::vec::IntoIter<Anchor>,
+    cx: &mut WindowContext,
+) {
+    let next_row = lines.find_map(|line| {
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let row = line.to_point(&snapshot).row;
+        (line_matches(&snapshot, row, &regex) != invert).then_some(row)
+    });
+    let Some(row) = next_row else {
+        return;
+    };
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(row, 0)..Point::new(row, 0)]);
+        });
+    });
+    let focus_handle = editor.read(cx).focus_handle(cx);
+    focus_handle.dispatch_action(&*action, cx);
+    cx.defer(move |cx| run_on_lines(editor, action, regex, invert, lines, cx));
+}
+
+fn line_matches(snapshot: &MultiBufferSnapshot, row: u32, regex: &Regex) -> bool {
+    let line = snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(MultiBufferRow(row))))
+        .collect::<String>();
+    regex.is_match(&line)
+}
+
+impl NormalCommand {
+    fn parse(query: &str, range: &Option<CommandRange>) -> Option<Self> {
+        let keys = strip_command(query, "norm", "al")?;
+        let keys = keys.strip_prefix('!').unwrap_or(keys);
+        let keys = keys.strip_prefix(' ')?;
+        if keys.is_empty() {
+            return None;
+        }
+        let keystrokes = keys
+            .chars()
+            .map(|c| match c {
+                ' ' => "space".to_string(),
+                c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+                c => c.to_string(),
+            })
+            // Leave any pending operator or insert mode, then the cursors other than the first one.
+            .chain(["escape".to_string(), "escape".to_string()])
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some(Self {
+            range: range.clone(),
+            keystrokes,
+        })
+    }
+
+    fn run(&self, vim: &mut Vim, cx: &mut ViewContext<Vim>) {
+        vim.switch_mode(Mode::Normal, false, cx);
+        if let Some(range) = &self.range {
+            let result = vim.update_editor(cx, |vim, editor, cx| {
+                let range = range.buffer_range(vim, editor, cx)?;
+                editor.change_selections(None, cx, |s| {
+                    s.select_ranges(
+                        (range.start.0..=range.end.0)
+                            .map(|row| Point::new(row, 0)..Point::new(row, 0)),
+                    );
+                });
+                anyhow::Ok(())
+            });
+            match result {
+                None => return,
+                Some(e @ Err(_)) => {
+                    let Some(workspace) = vim.workspace(cx) else {
+                        return;
+                    };
+                    workspace.update(cx, |workspace, cx| {
+                        e.notify_err(workspace, cx);
+                    });
+                    return;
+                }
+                Some(Ok(())) => {}
+            }
+        }
+        // The keys are typed rather than replayed as `ReplayableAction`s: unlike a recording,
+        // they have not been resolved to actions yet, and resolving them depends on the keymap,
+        // the mode and the operators pending as each key is typed.
+        cx.dispatch_action(workspace::SendKeystrokes(self.keystrokes.clone()).boxed_clone());
+    }
+}
+
+/// Strips an ex command name, given as its shortest form and the rest of the full name, off the query.
+fn strip_command<'a>(query: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    let rest = query.strip_prefix(prefix)?;
+    let name_len = suffix
+        .chars()
+        .zip(rest.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let rest = &rest[name_len..];
+    if rest.starts_with(|c: char| c.is_alphanumeric()) {
+        return None;
+    }
+    Some(rest)
+}
+
 fn generate_commands(_: &AppContext) -> Vec<VimCommand> {
     vec![
         VimCommand::new(
@@ -646,6 +942,10 @@ fn generate_commands(_: &AppContext) -> Vec<VimCommand> {
         .bang(workspace::CloseInactiveTabsAndPanes {
             save_intent: Some(SaveIntent::Skip),
         }),
+        VimCommand::new(("reg", "isters"), ShowRegisters),
+        VimCommand::new(("di", "splay"), ShowRegisters),
+        VimCommand::new(("marks", ""), ShowMarks),
+        VimCommand::new(("ju", "mps"), ShowJumps),
         VimCommand::str(("cl", "ist"), "diagnostics::Deploy"),
         VimCommand::new(("cc", ""), editor::actions::Hover),
         VimCommand::new(("ll", ""), editor::actions::Hover),
@@ -741,7 +1041,17 @@ fn wrap_count(action: Box<dyn Action>, range: &CommandRange) -> Option<Box<dyn A
     })
 }
 
-pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
+pub fn command_interceptor(input: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
+    intercept_command(input, true, cx)
+}
+
+/// Resolves the ex command typed as input. The ex commands the user defined in their vimrc are
+/// not expanded in the replacements of those, so that they cannot expand indefinitely.
+fn intercept_command(
+    mut input: &str,
+    expand_user_commands: bool,
+    cx: &AppContext,
+) -> Option<CommandInterceptResult> {
     // NOTE: We also need to support passing arguments to commands like :w
     // (ideally with filename autocompletion).
     while input.starts_with(':') {
@@ -752,6 +1062,12 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
     let range_prefix = input[0..(input.len() - query.len())].to_string();
     let query = query.as_str().trim();
 
+    if expand_user_commands && query.starts_with(|c: char| c.is_ascii_uppercase()) {
+        if let Some(result) = intercept_user_command(&range_prefix, query, cx) {
+            return Some(result);
+        }
+    }
+
     let action = if range.is_some() && query.is_empty() {
         Some(
             GoToLine {
@@ -767,6 +1083,12 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
             }
             .boxed_clone(),
         )
+    } else if let Some(on_matching_lines) =
+        OnMatchingLines::parse(query, &range, expand_user_commands, cx)
+    {
+        Some(on_matching_lines.boxed_clone())
+    } else if let Some(normal) = NormalCommand::parse(query, &range) {
+        Some(normal.boxed_clone())
     } else if query.starts_with('s') {
         let mut substitute = "substitute".chars().peekable();
         let mut query = query.chars().peekable();
@@ -817,6 +1139,35 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
     None
 }
 
+/// Resolves a command of the user's vimrc, which runs an action or another ex command given the same range.
+fn intercept_user_command(
+    range_prefix: &str,
+    query: &str,
+    cx: &AppContext,
+) -> Option<CommandInterceptResult> {
+    let command = cx.try_global::<Vimrc>()?.command(query)?;
+    let replacement = command.replacement.trim_start_matches(':');
+    let action = match replacement.split_once(char::is_whitespace) {
+        _ if !replacement.contains("::") => None,
+        Some((name, arguments)) => {
+            let arguments = serde_json::from_str(arguments).ok()?;
+            cx.build_action(name, Some(arguments)).ok()
+        }
+        None => cx.build_action(replacement, None).ok(),
+    };
+    let action = match action {
+        Some(action) => action,
+        None => intercept_command(&format!("{range_prefix}{replacement}"), false, cx)?.action,
+    };
+    let string = format!(":{range_prefix}{}", command.name);
+    let positions = generate_positions(&string, &format!("{range_prefix}{query}"));
+    Some(CommandInterceptResult {
+        action,
+        string,
+        positions,
+    })
+}
+
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
     let mut positions = Vec::new();
     let mut chars = query.chars();
@@ -1040,6 +1391,139 @@ mod test {
         cx.shared_state().await.assert_eq("k\nk\nˇk\n4\n4\n3\n2\n1");
     }
 
+    #[gpui::test]
+    async fn test_command_global(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa TODO
+            b
+            c TODO
+            d"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": g / T O D O / d enter");
+        cx.assert_state(
+            indoc! {"
+            b
+            ˇd"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            ˇa TODO
+            b
+            c TODO
+            d TODO"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": v / T O D O / d enter");
+        cx.assert_state(
+            indoc! {"
+            a TODO
+            ˇc TODO
+            d TODO"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            ˇa(1)
+            b
+            c(2)"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": 2 , 3 g ! / ( / d enter");
+        cx.assert_state(
+            indoc! {"
+            a(1)
+            ˇc(2)"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_command_global_substitute(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa foo
+            b
+            c foo foo
+            d"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": g / f o o / s / / x / enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            a x
+            b
+            ˇc x x
+            d"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            ˇa foo
+            b foo
+            c foo"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": g / ^ [ a c ] / s / o / 0 / enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            a f00
+            b foo
+            ˇc f00"},
+            Mode::Normal,
+        );
+    }
+
+    // SendKeystrokes-based tests are flaky on our linux CI machines
+    #[cfg(target_os = "macos")]
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            c"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": % n o r m space shift-a ; enter");
+        cx.assert_state(
+            indoc! {"
+            aˇ;
+            b;
+            c;"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            ˇfoo 1
+            bar 2
+            foo 3"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": g / f o o / n o r m space d w enter");
+        cx.assert_state(
+            indoc! {"
+            ˇ1
+            bar 2
+            3"},
+            Mode::Normal,
+        );
+    }
+
     fn assert_active_item(
         workspace: &mut Workspace,
         expected_path: &str,
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use collections::HashMap;
 use editor::Editor;
 use gpui::{impl_actions, AppContext, Keystroke, KeystrokeEvent};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use settings::Settings;
 use std::sync::LazyLock;
 use ui::ViewContext;
@@ -12,8 +12,8 @@ use crate::{state::Operator, Vim, VimSettings};
 
 mod default;
 
-#[derive(PartialEq, Clone, Deserialize)]
-struct Literal(String, char);
+#[derive(PartialEq, Clone, Deserialize, Serialize)]
+pub(crate) struct Literal(String, char);
 impl_actions!(vim, [Literal]);
 
 pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
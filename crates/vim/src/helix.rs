@@ -1,15 +1,153 @@
-use editor::{movement, scroll::Autoscroll, DisplayPoint, Editor};
-use gpui::{actions, Action};
-use language::{CharClassifier, CharKind};
-use ui::ViewContext;
+use std::{ops::Range, sync::Arc};
 
-use crate::{motion::Motion, state::Mode, Vim};
+use editor::{
+    display_map::{DisplaySnapshot, ToDisplayPoint},
+    movement,
+    scroll::Autoscroll,
+    Bias, DisplayPoint, Editor,
+};
+use gpui::{
+    actions, impl_actions, rems, Action, DismissEvent, EventEmitter, FocusableView,
+    InteractiveElement, ParentElement, Render, SharedString, Styled, Subscription, Task, View,
+    ViewContext, VisualContext, WeakView,
+};
+use language::{CharClassifier, CharKind, Point, Selection, SelectionGoal};
+use picker::{Picker, PickerDelegate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use ui::{v_flex, IntoElement, ListItem, WindowContext};
+use workspace::{ModalView, Workspace};
 
-actions!(vim, [HelixNormalAfter, HelixDelete]);
+use crate::{
+    motion::Motion,
+    normal::paste::Paste,
+    object::Object,
+    state::{Mode, Operator},
+    Vim,
+};
+
+actions!(
+    vim,
+    [
+        HelixNormalAfter,
+        HelixDelete,
+        HelixChange,
+        HelixYank,
+        HelixInsert,
+        HelixAppend,
+        HelixSelectLine,
+        HelixExtendToLineBounds,
+        HelixCollapseSelection,
+        HelixFlipSelections,
+        HelixKeepPrimarySelection,
+        HelixSelectRegex,
+        HelixSplitSelection,
+        HelixKeepSelections,
+        HelixRemoveSelections,
+    ]
+);
+
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HelixPaste {
+    #[serde(default)]
+    before: bool,
+}
+
+impl_actions!(vim, [HelixPaste]);
 
 pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
     Vim::action(editor, cx, Vim::helix_normal_after);
     Vim::action(editor, cx, Vim::helix_delete);
+    Vim::action(editor, cx, Vim::helix_change);
+    Vim::action(editor, cx, Vim::helix_yank);
+    Vim::action(editor, cx, Vim::helix_paste);
+    Vim::action(editor, cx, |vim, _: &HelixInsert, cx| {
+        vim.helix_insert(false, cx);
+    });
+    Vim::action(editor, cx, |vim, _: &HelixAppend, cx| {
+        vim.helix_insert(true, cx);
+    });
+    Vim::action(editor, cx, Vim::helix_select_line);
+    Vim::action(editor, cx, Vim::helix_extend_to_line_bounds);
+    Vim::action(editor, cx, |vim, _: &HelixCollapseSelection, cx| {
+        vim.helix_collapse_selections(cx);
+    });
+    Vim::action(editor, cx, |vim, _: &HelixFlipSelections, cx| {
+        vim.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|_, selection| {
+                    if !selection.is_empty() {
+                        selection.swap_head_tail();
+                    }
+                });
+            });
+        });
+    });
+    Vim::action(editor, cx, |vim, _: &HelixKeepPrimarySelection, cx| {
+        vim.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                let primary = s.newest_anchor().clone();
+                s.select_anchors(vec![primary]);
+            });
+        });
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSelectRegex, cx| {
+        vim.prompt_regex_selection(RegexSelection::Select, cx);
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSplitSelection, cx| {
+        vim.prompt_regex_selection(RegexSelection::Split, cx);
+    });
+    Vim::action(editor, cx, |vim, _: &HelixKeepSelections, cx| {
+        vim.prompt_regex_selection(RegexSelection::Keep, cx);
+    });
+    Vim::action(editor, cx, |vim, _: &HelixRemoveSelections, cx| {
+        vim.prompt_regex_selection(RegexSelection::Remove, cx);
+    });
+}
+
+/// What `s`, `S`, `K` and `alt-K` do with the parts of the selections matching a regex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RegexSelection {
+    /// Select every match within the selections.
+    Select,
+    /// Split the selections on every match.
+    Split,
+    /// Keep the selections containing a match.
+    Keep,
+    /// Remove the selections containing a match.
+    Remove,
+}
+
+impl RegexSelection {
+    fn prompt(self) -> &'static str {
+        match self {
+            RegexSelection::Select => "select:",
+            RegexSelection::Split => "split:",
+            RegexSelection::Keep => "keep:",
+            RegexSelection::Remove => "remove:",
+        }
+    }
+}
+
+/// Makes each cursor cover the character under it, as the block cursor of helix does.
+fn expand_cursors(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.move_with(|map, selection| {
+            if selection.is_empty() && !selection.reversed {
+                selection.end = movement::right(map, selection.end);
+            }
+        });
+    });
+}
+
+/// The position of the block cursor of a selection.
+fn cursor_position(map: &DisplaySnapshot, selection: &Selection<DisplayPoint>) -> DisplayPoint {
+    if selection.is_empty() || selection.reversed {
+        selection.head()
+    } else {
+        movement::left(map, selection.head())
+    }
 }
 
 impl Vim {
@@ -152,11 +290,7 @@ impl Vim {
             editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
                 s.move_with(|map, selection| {
                     let goal = selection.goal;
-                    let cursor = if selection.is_empty() || selection.reversed {
-                        selection.head()
-                    } else {
-                        movement::left(map, selection.head())
-                    };
+                    let cursor = cursor_position(map, selection);
 
                     let (point, goal) = motion
                         .move_point(map, cursor, selection.goal, times, &text_layout_details)
@@ -224,10 +358,117 @@ impl Vim {
                     found
                 })
             }
+            Motion::FindForward { .. } | Motion::FindBackward { .. } => {
+                self.helix_select_to(motion, times, cx)
+            }
             _ => self.helix_move_and_collapse(motion, times, cx),
         }
     }
 
+    /// Selects from the cursor to where the motion lands, both included.
+    fn helix_select_to(
+        &mut self,
+        motion: Motion,
+        times: Option<usize>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = cursor_position(map, selection);
+                    let Some((point, goal)) =
+                        motion.move_point(map, cursor, selection.goal, times, &text_layout_details)
+                    else {
+                        return;
+                    };
+                    if point >= cursor {
+                        selection.collapse_to(cursor, goal);
+                        selection.set_head(movement::right(map, point), goal);
+                    } else {
+                        selection.collapse_to(movement::right(map, cursor), goal);
+                        selection.set_head(point, goal);
+                    }
+                })
+            });
+        });
+    }
+
+    pub(crate) fn helix_expand_cursors(&mut self, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| expand_cursors(editor, cx));
+    }
+
+    /// Collapses each selection to its block cursor.
+    pub(crate) fn helix_collapse_selections(&mut self, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = cursor_position(map, selection);
+                    selection.collapse_to(cursor, SelectionGoal::None)
+                })
+            });
+        });
+    }
+
+    fn helix_select_line(&mut self, _: &HelixSelectLine, cx: &mut ViewContext<Self>) {
+        let count = Vim::take_count(cx).unwrap_or(1) as u32;
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let start = selection.start.to_point(map);
+                    let end = selection.end.to_point(map);
+                    let whole_lines = start.column == 0 && end.column == 0 && end.row > start.row;
+                    let last_row = if end.column == 0 && end.row > start.row {
+                        end.row - 1
+                    } else {
+                        end.row
+                    };
+                    // Selecting whole lines again extends the selection to the lines below.
+                    let target_row = if whole_lines {
+                        last_row + count
+                    } else {
+                        last_row + count - 1
+                    };
+                    let max_point = map.buffer_snapshot.max_point();
+                    let end = if target_row >= max_point.row {
+                        max_point
+                    } else {
+                        Point::new(target_row + 1, 0)
+                    };
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.reversed = false;
+                    selection.goal = SelectionGoal::None;
+                })
+            });
+        });
+    }
+
+    fn helix_extend_to_line_bounds(
+        &mut self,
+        _: &HelixExtendToLineBounds,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let start = selection.start.to_point(map);
+                    let mut end = selection.end.to_point(map);
+                    if end.column != 0 || end.row == start.row {
+                        end = if end.row >= map.buffer_snapshot.max_point().row {
+                            map.buffer_snapshot.max_point()
+                        } else {
+                            Point::new(end.row + 1, 0)
+                        };
+                    }
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.goal = SelectionGoal::None;
+                })
+            });
+        });
+    }
+
     pub fn helix_delete(&mut self, _: &HelixDelete, cx: &mut ViewContext<Self>) {
         self.store_visual_marks(cx);
         self.update_editor(cx, |vim, editor, cx| {
@@ -235,19 +476,280 @@ impl Vim {
             // Specifically:
             //  - Make sure that each cursor acts as a 1 character wide selection
             editor.transact(cx, |editor, cx| {
-                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                    s.move_with(|map, selection| {
-                        if selection.is_empty() && !selection.reversed {
-                            selection.end = movement::right(map, selection.end);
-                        }
-                    });
-                });
+                expand_cursors(editor, cx);
             });
 
             vim.copy_selections_content(editor, false, cx);
             editor.insert("", cx);
         });
     }
+
+    pub fn helix_change(&mut self, _: &HelixChange, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.update_editor(cx, |vim, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                expand_cursors(editor, cx);
+                vim.copy_selections_content(editor, false, cx);
+                editor.insert("", cx);
+            });
+        });
+        self.switch_mode(Mode::Insert, true, cx);
+    }
+
+    pub fn helix_yank(&mut self, _: &HelixYank, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |vim, editor, cx| {
+            let (map, selections) = editor.selections.all_display(cx);
+            let ranges = selections
+                .into_iter()
+                .map(|selection| {
+                    let end = if selection.is_empty() {
+                        movement::right(&map, selection.end)
+                    } else {
+                        selection.end
+                    };
+                    selection.start.to_point(&map)..end.to_point(&map)
+                })
+                .collect();
+            vim.copy_ranges(editor, false, true, ranges, cx);
+        });
+    }
+
+    /// Pastes after (or before) the selections rather than replacing them.
+    pub fn helix_paste(&mut self, action: &HelixPaste, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                s.move_with(|map, selection| {
+                    let point = if action.before || selection.is_empty() {
+                        selection.start
+                    } else {
+                        movement::left(map, selection.end)
+                    };
+                    selection.collapse_to(point, SelectionGoal::None)
+                });
+            });
+        });
+        self.paste(
+            &Paste {
+                before: action.before,
+                preserve_clipboard: false,
+            },
+            cx,
+        );
+    }
+
+    fn helix_insert(&mut self, append: bool, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let point = if !append {
+                        selection.start
+                    } else if selection.is_empty() {
+                        movement::right(map, selection.end)
+                    } else {
+                        selection.end
+                    };
+                    selection.collapse_to(point, SelectionGoal::None)
+                });
+            });
+        });
+        self.switch_mode(Mode::Insert, false, cx);
+    }
+
+    /// Selects the object around each block cursor for `m i` and `m a`, or hands it to the surround operators.
+    pub(crate) fn helix_object(&mut self, object: Object, cx: &mut ViewContext<Self>) {
+        let Some(Operator::Object { around }) = self.active_operator() else {
+            self.normal_object(object, cx);
+            return;
+        };
+        self.pop_operator(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = cursor_position(map, selection);
+                    let mut object_selection = selection.clone();
+                    object_selection.collapse_to(cursor, SelectionGoal::None);
+                    if object.expand_selection(map, &mut object_selection, around) {
+                        *selection = object_selection;
+                    }
+                })
+            });
+        });
+    }
+
+    fn prompt_regex_selection(&mut self, kind: RegexSelection, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let vim = cx.view().downgrade();
+        workspace.update(cx, |workspace, cx| {
+            let workspace_handle = workspace.weak_handle();
+            workspace.toggle_modal(cx, |cx| {
+                HelixRegexPrompt::new(kind, vim, workspace_handle, cx)
+            });
+        });
+    }
+
+    /// Changes the selections using the matches of `query` within them.
+    pub(crate) fn helix_select_regex(
+        &mut self,
+        kind: RegexSelection,
+        query: &str,
+        cx: &mut ViewContext<Self>,
+    ) -> anyhow::Result<()> {
+        let regex = Regex::new(query)?;
+        self.update_editor(cx, |_, editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let mut new_ranges: Vec<Range<usize>> = Vec::new();
+            for selection in editor.selections.all::<usize>(cx) {
+                let mut range = selection.range();
+                if range.is_empty() {
+                    range.end = buffer.clip_offset(range.end + 1, Bias::Right);
+                }
+                let text = buffer.text_for_range(range.clone()).collect::<String>();
+                match kind {
+                    RegexSelection::Select => new_ranges.extend(
+                        regex
+                            .find_iter(&text)
+                            .filter(|found| !found.is_empty())
+                            .map(|found| range.start + found.start()..range.start + found.end()),
+                    ),
+                    RegexSelection::Split => {
+                        let mut start = range.start;
+                        for found in regex.find_iter(&text) {
+                            new_ranges.push(start..range.start + found.start());
+                            start = range.start + found.end();
+                        }
+                        new_ranges.push(start..range.end);
+                        new_ranges.retain(|range| !range.is_empty());
+                    }
+                    RegexSelection::Keep | RegexSelection::Remove => {
+                        if regex.is_match(&text) == (kind == RegexSelection::Keep) {
+                            new_ranges.push(selection.range());
+                        }
+                    }
+                }
+            }
+            // Like helix, keep the selections as they are rather than removing all of them.
+            if new_ranges.is_empty() {
+                return;
+            }
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(new_ranges));
+        });
+        Ok(())
+    }
+}
+
+/// A modal asking for the regex used by `s`, `S`, `K` and `alt-K`.
+pub(crate) struct HelixRegexPrompt {
+    picker: View<Picker<HelixRegexPromptDelegate>>,
+    _subscription: Subscription,
+}
+
+impl HelixRegexPrompt {
+    fn new(
+        kind: RegexSelection,
+        vim: WeakView<Vim>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = HelixRegexPromptDelegate {
+            kind,
+            query: String::new(),
+            vim,
+            workspace,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for HelixRegexPrompt {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("HelixRegexPrompt")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl EventEmitter<DismissEvent> for HelixRegexPrompt {}
+
+impl FocusableView for HelixRegexPrompt {
+    fn focus_handle(&self, cx: &gpui::AppContext) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for HelixRegexPrompt {}
+
+struct HelixRegexPromptDelegate {
+    kind: RegexSelection,
+    query: String,
+    vim: WeakView<Vim>,
+    workspace: WeakView<Workspace>,
+}
+
+impl PickerDelegate for HelixRegexPromptDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        0
+    }
+
+    fn selected_index(&self) -> usize {
+        0
+    }
+
+    fn set_selected_index(&mut self, _: usize, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        self.kind.prompt().into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        "Type a regex and press enter".into()
+    }
+
+    fn update_matches(&mut self, query: String, _: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.query = query;
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if !self.query.is_empty() {
+            let kind = self.kind;
+            let query = self.query.clone();
+            let result = self
+                .vim
+                .update(cx, |vim, cx| vim.helix_select_regex(kind, &query, cx));
+            if let Ok(Err(error)) = result {
+                self.workspace
+                    .update(cx, |workspace, cx| workspace.show_error(&error, cx))
+                    .ok();
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        _: usize,
+        _: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -370,4 +872,208 @@ mod test {
             Mode::HelixNormal,
         );
     }
+
+    #[gpui::test]
+    async fn test_helix_mode_setting(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+        assert_eq!(cx.mode(), Mode::HelixNormal);
+
+        cx.set_state("The qu«ick ˇ»brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("c x escape");
+        cx.assert_state("The quxˇbrown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("i y escape");
+        cx.assert_state("The quxyˇbrown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("a z escape");
+        cx.assert_state("The quxybzˇrown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            ˇ»fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            the lazy dog.ˇ»"},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("2 x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            The qu«ick brown
+            fox juˇ»mps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("shift-x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_find_selects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("The quˇick brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("f b");
+        cx.assert_state("The qu«ick bˇ»rown", Mode::HelixNormal);
+
+        cx.set_state("The quˇick brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("t b");
+        cx.assert_state("The qu«ick ˇ»brown", Mode::HelixNormal);
+
+        cx.set_state("The quick brˇown", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-f q");
+        cx.assert_state("The «ˇquick bro»wn", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_flip_collapse_and_keep_primary(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("The qu«ick ˇ»brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-;");
+        cx.assert_state("The qu«ˇick »brown", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("The quˇick brown", Mode::HelixNormal);
+
+        cx.set_state("The qu«ick ˇ»brown", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("The quickˇ brown", Mode::HelixNormal);
+
+        cx.set_state("The «quickˇ» «brownˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes(",");
+        cx.assert_state("The quick «brownˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_regex_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state(
+            indoc! {"
+            «The quick brown
+            fox jumps overˇ»
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("s o enter");
+        cx.assert_state(
+            indoc! {"
+            The quick br«oˇ»wn
+            f«oˇ»x jumps «oˇ»ver
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state("«The quick brownˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-s space enter");
+        cx.assert_state("«Theˇ» «quickˇ» «brownˇ»", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("shift-k i enter");
+        cx.assert_state("The «quickˇ» brown", Mode::HelixNormal);
+
+        cx.set_state("«Theˇ» «quickˇ» «brownˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-shift-k i enter");
+        cx.assert_state("«Theˇ» quick «brownˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_yank_and_paste(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("y");
+        cx.assert_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("p");
+        cx.assert_state("The quickquicˇk brown", Mode::HelixNormal);
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-p");
+        cx.assert_state("The quicˇkquick brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_match_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("call(one, ˇtwo)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i (");
+        cx.assert_state("call(«one, twoˇ»)", Mode::HelixNormal);
+
+        cx.set_state("call(one, ˇtwo)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m a (");
+        cx.assert_state("call«(one, two)ˇ»", Mode::HelixNormal);
+
+        cx.set_state("call(one, «twoˇ»)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s ]");
+        cx.assert_state("call(one, ˇ[two])", Mode::HelixNormal);
+
+        cx.set_state("call(one, ˇtwo)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m d (");
+        cx.assert_state("callˇone, two", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_change_case(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("~");
+        cx.assert_state("The «QUICKˇ» brown", Mode::HelixNormal);
+
+        cx.set_state("The ˇquick brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("r x");
+        cx.assert_state("The ˇxuick brown", Mode::HelixNormal);
+    }
 }
@@ -1,7 +1,8 @@
-use crate::{state::Mode, Vim};
+use crate::{state::Mode, Vim, VimSettings};
 use editor::{scroll::Autoscroll, Bias, Editor};
 use gpui::{actions, Action, ViewContext};
 use language::SelectionGoal;
+use settings::Settings;
 
 actions!(vim, [NormalBefore, TemporaryNormal]);
 
@@ -21,14 +22,18 @@ impl Vim {
         self.stop_recording_immediately(action.boxed_clone(), cx);
         if count <= 1 || Vim::globals(cx).dot_replaying {
             self.create_mark("^".into(), false, cx);
+            // The block cursor of helix stays on the character after the inserted text.
+            let move_left = !VimSettings::get_global(cx).helix_mode;
             self.update_editor(cx, |_, editor, cx| {
                 editor.dismiss_menus_and_popups(true, false, cx);
-                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                    s.move_cursors_with(|map, mut cursor, _| {
-                        *cursor.column_mut() = cursor.column().saturating_sub(1);
-                        (map.clip_point(cursor, Bias::Left), SelectionGoal::None)
+                if move_left {
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                        s.move_cursors_with(|map, mut cursor, _| {
+                            *cursor.column_mut() = cursor.column().saturating_sub(1);
+                            (map.clip_point(cursor, Bias::Left), SelectionGoal::None)
+                        });
                     });
-                });
+                }
             });
             self.switch_mode(Mode::Normal, false, cx);
             return;
@@ -0,0 +1,449 @@
+use std::sync::Arc;
+
+use editor::{scroll::Autoscroll, Anchor, Editor, NavigationData, ToPoint};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, div, rems, DismissEvent, EventEmitter, FocusableView, InteractiveElement,
+    ParentElement, Render, SharedString, Styled, Subscription, Task, View, ViewContext,
+    VisualContext, WeakView,
+};
+use language::Point;
+use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
+use project::ProjectPath;
+use ui::{
+    h_flex, v_flex, Color, IntoElement, Label, LabelCommon, ListItem, ListItemSpacing, RenderOnce,
+    Toggleable, WindowContext,
+};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{state::VimGlobals, Vim};
+
+actions!(vim, [ShowRegisters, ShowMarks, ShowJumps]);
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
+    Vim::action(editor, cx, |vim, _: &ShowRegisters, cx| {
+        let entries = vim.register_entries(cx);
+        vim.show_listing("Registers", entries, cx);
+    });
+    Vim::action(editor, cx, |vim, _: &ShowMarks, cx| {
+        let entries = vim.mark_entries(cx);
+        vim.show_listing("Marks", entries, cx);
+    });
+    Vim::action(editor, cx, |vim, _: &ShowJumps, cx| {
+        let entries = vim.jump_entries(cx);
+        vim.show_listing("Jumps", entries, cx);
+    });
+}
+
+/// A line of the listings shown by `:registers`, `:marks` and `:jumps`.
+struct ListingEntry {
+    label: String,
+    text: String,
+    target: Option<ListingTarget>,
+}
+
+/// Where to go when an entry is confirmed.
+enum ListingTarget {
+    Anchor(WeakView<Editor>, Anchor),
+    Path(ProjectPath, Point),
+}
+
+impl Vim {
+    fn register_entries(&self, cx: &mut ViewContext<Self>) -> Vec<ListingEntry> {
+        let globals = cx.global::<VimGlobals>();
+        let mut registers = globals.registers.iter().collect::<Vec<_>>();
+        registers.sort_by_key(|(name, _)| **name);
+        let mut recordings = globals.recordings.iter().collect::<Vec<_>>();
+        recordings.sort_by_key(|(name, _)| **name);
+        registers
+            .into_iter()
+            .map(|(name, register)| ListingEntry {
+                label: format!("\"{name}"),
+                text: single_line(&register.text),
+                target: None,
+            })
+            .chain(recordings.into_iter().map(|(name, actions)| ListingEntry {
+                label: format!("@{name}"),
+                text: format!("macro of {} actions", actions.len()),
+                target: None,
+            }))
+            .collect()
+    }
+
+    fn mark_entries(&self, cx: &mut ViewContext<Self>) -> Vec<ListingEntry> {
+        let Some(editor) = self.editor() else {
+            return Vec::new();
+        };
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let mut marks = self.marks.iter().collect::<Vec<_>>();
+        marks.sort_by(|(a, _), (b, _)| a.cmp(b));
+        marks
+            .into_iter()
+            .filter_map(|(name, anchors)| {
+                let anchor = *anchors.first()?;
+                let point = anchor.to_point(&snapshot);
+                let line = snapshot
+                    .text_for_range(Point::new(point.row, 0)..Point::new(point.row, u32::MAX))
+                    .collect::<String>();
+                Some(ListingEntry {
+                    label: name.clone(),
+                    text: format!("{}:{} {}", point.row + 1, point.column + 1, line.trim()),
+                    target: Some(ListingTarget::Anchor(editor.downgrade(), anchor)),
+                })
+            })
+            .collect()
+    }
+
+    fn jump_entries(&self, cx: &mut ViewContext<Self>) -> Vec<ListingEntry> {
+        let Some(pane) = self.pane(cx) else {
+            return Vec::new();
+        };
+        let mut jumps = Vec::new();
+        pane.read(cx)
+            .nav_history()
+            .for_each_entry(cx, |entry, (project_path, _)| {
+                let point = entry
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.downcast_ref::<NavigationData>())
+                    .map(|data| data.cursor_position())
+                    .unwrap_or_default();
+                jumps.push((entry.timestamp, project_path, point));
+            });
+        jumps.sort_by_key(|(timestamp, _, _)| std::cmp::Reverse(*timestamp));
+        jumps
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (_, project_path, point))| ListingEntry {
+                label: ix.to_string(),
+                text: format!(
+                    "{}:{}:{}",
+                    project_path.path.to_string_lossy(),
+                    point.row + 1,
+                    point.column + 1
+                ),
+                target: Some(ListingTarget::Path(project_path, point)),
+            })
+            .collect()
+    }
+
+    fn show_listing(
+        &self,
+        title: &'static str,
+        entries: Vec<ListingEntry>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            let workspace_handle = workspace.weak_handle();
+            workspace.toggle_modal(cx, |cx| {
+                VimListing::new(title, entries, workspace_handle, cx)
+            });
+        });
+    }
+}
+
+fn single_line(text: &str) -> String {
+    text.replace('\n', "^J").replace('\t', "^I")
+}
+
+/// A modal listing the registers, marks or jumps of vim.
+pub(crate) struct VimListing {
+    picker: View<Picker<VimListingDelegate>>,
+    _subscription: Subscription,
+}
+
+impl VimListing {
+    fn new(
+        title: &'static str,
+        entries: Vec<ListingEntry>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = VimListingDelegate {
+            title,
+            entries,
+            matches: Vec::new(),
+            selected_index: 0,
+            workspace,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for VimListing {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("VimListing")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl EventEmitter<DismissEvent> for VimListing {}
+
+impl FocusableView for VimListing {
+    fn focus_handle(&self, cx: &gpui::AppContext) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for VimListing {}
+
+struct VimListingDelegate {
+    title: &'static str,
+    entries: Vec<ListingEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    workspace: WeakView<Workspace>,
+}
+
+impl PickerDelegate for VimListingDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        format!("Search {}", self.title.to_lowercase()).into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        format!("No {}", self.title.to_lowercase()).into()
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| StringMatchCandidate::new(id, &entry.text))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = 0;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let target = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|hit| self.entries[hit.candidate_id].target.as_ref());
+        match target {
+            Some(ListingTarget::Anchor(editor, anchor)) => {
+                let anchor = *anchor;
+                editor
+                    .update(cx, |editor, cx| {
+                        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                            s.select_anchor_ranges([anchor..anchor])
+                        });
+                    })
+                    .ok();
+            }
+            Some(ListingTarget::Path(project_path, point)) => {
+                let point = *point;
+                let open_task = self
+                    .workspace
+                    .update(cx, |workspace, cx| {
+                        workspace.open_path(project_path.clone(), None, true, cx)
+                    })
+                    .ok();
+                if let Some(open_task) = open_task {
+                    cx.spawn(|_, mut cx| async move {
+                        let item = open_task.await?;
+                        if let Some(editor) = item.downcast::<Editor>() {
+                            editor.update(&mut cx, |editor, cx| {
+                                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                                    s.select_ranges([point..point])
+                                });
+                            })?;
+                        }
+                        anyhow::Ok(())
+                    })
+                    .detach_and_log_err(cx);
+                }
+            }
+            None => {}
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let entry = &self.entries[hit.candidate_id];
+        let highlighted_text = HighlightedText {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            char_count: hit.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("vim-listing-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            div()
+                                .w(rems(2.))
+                                .child(Label::new(entry.label.clone()).color(Color::Muted)),
+                        )
+                        .child(highlighted_text.render(cx)),
+                ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::{
+        state::{Mode, VimGlobals},
+        test::VimTestContext,
+    };
+
+    use super::VimListing;
+
+    /// The entries of the open listing, each as its label followed by its text.
+    fn listed_entries(cx: &mut VimTestContext) -> Vec<String> {
+        cx.workspace(|workspace, cx| {
+            let listing = workspace
+                .active_modal::<VimListing>(cx)
+                .expect("no vim listing is open");
+            let delegate = &listing.read(cx).picker.read(cx).delegate;
+            delegate
+                .matches
+                .iter()
+                .map(|hit| {
+                    let entry = &delegate.entries[hit.candidate_id];
+                    format!("{} {}", entry.label, entry.text)
+                })
+                .collect()
+        })
+    }
+
+    fn listing_is_open(cx: &mut VimTestContext) -> bool {
+        cx.workspace(|workspace, cx| workspace.active_modal::<VimListing>(cx).is_some())
+    }
+
+    #[gpui::test]
+    async fn test_registers_listing(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇhello world\nfoo", Mode::Normal);
+        cx.simulate_keystrokes("q q l q");
+        cx.simulate_keystrokes("\" a y i w");
+        cx.simulate_keystrokes("\" b y y");
+        let recorded_actions = cx.read(|cx| cx.global::<VimGlobals>().recordings[&'q'].len());
+
+        cx.simulate_keystrokes(": r e g i s t e r s enter");
+        cx.run_until_parked();
+        assert_eq!(
+            listed_entries(&mut cx),
+            [
+                "\"\" hello world^J".to_string(),
+                "\"a hello".to_string(),
+                "\"b hello world^J".to_string(),
+                format!("@q macro of {recorded_actions} actions"),
+            ]
+        );
+
+        // Registers have nowhere to go, so confirming one only closes the listing.
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert!(!listing_is_open(&mut cx));
+        cx.assert_state("ˇhello world\nfoo", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_marks_listing(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("line one\nline ˇtwo\nline three", Mode::Normal);
+        cx.simulate_keystrokes("m a j m b g g");
+
+        cx.simulate_keystrokes(": m a r k s enter");
+        cx.run_until_parked();
+        assert_eq!(
+            listed_entries(&mut cx),
+            ["a 2:6 line two", "b 3:6 line three"]
+        );
+
+        cx.simulate_keystrokes("down enter");
+        cx.run_until_parked();
+        assert!(!listing_is_open(&mut cx));
+        cx.assert_state("line one\nline two\nline ˇthree", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_jumps_listing(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        let text = (1..=12)
+            .map(|row| format!("line {row}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        cx.set_state(&format!("ˇ{text}"), Mode::Normal);
+        cx.simulate_keystrokes("G g g");
+
+        cx.simulate_keystrokes(": j u m p s enter");
+        cx.run_until_parked();
+        let path = Path::new("dir").join("file.rs");
+        let path = path.to_string_lossy();
+        assert_eq!(
+            listed_entries(&mut cx),
+            [format!("0 {path}:12:1"), format!("1 {path}:1:1")]
+        );
+
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert!(!listing_is_open(&mut cx));
+        cx.assert_state(&text.replace("line 12", "ˇline 12"), Mode::Normal);
+    }
+}
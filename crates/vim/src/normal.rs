@@ -1,9 +1,9 @@
 mod case;
 mod change;
 mod delete;
-mod increment;
+pub(crate) mod increment;
 pub(crate) mod mark;
-mod paste;
+pub(crate) mod paste;
 pub(crate) mod repeat;
 mod scroll;
 pub(crate) mod search;
@@ -146,7 +146,20 @@ impl Vim {
                         }
                     }
 
-                    Mode::HelixNormal => {}
+                    Mode::HelixNormal => {
+                        let mut range = selection.range();
+                        if range.is_empty() {
+                            range.end =
+                                snapshot.clip_point(range.end + Point::new(0, 1), Bias::Right);
+                        }
+                        ranges.push(range);
+                        // Keep the selections, including their direction.
+                        cursor_positions.push(if selection.reversed {
+                            selection.end..selection.start
+                        } else {
+                            selection.start..selection.end
+                        });
+                    }
                     Mode::Insert | Mode::Normal | Mode::Replace => {
                         let start = selection.start;
                         let mut end = start;
@@ -3,20 +3,20 @@ use std::ops::Range;
 use editor::{scroll::Autoscroll, Editor, MultiBufferSnapshot, ToOffset, ToPoint};
 use gpui::{impl_actions, ViewContext};
 use language::{Bias, Point};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{state::Mode, Vim};
 
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct Increment {
+pub(crate) struct Increment {
     #[serde(default)]
     step: bool,
 }
 
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct Decrement {
+pub(crate) struct Decrement {
     #[serde(default)]
     step: bool,
 }
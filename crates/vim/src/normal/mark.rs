@@ -1,20 +1,31 @@
-use std::{ops::Range, sync::Arc};
+use std::{ops::Range, path::PathBuf, sync::Arc};
 
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor, ToPoint,
 };
-use gpui::ViewContext;
-use language::SelectionGoal;
+use gpui::{AppContext, ViewContext};
+use language::{Point, SelectionGoal};
+use util::ResultExt;
+use workspace::WorkspaceId;
 
 use crate::{
     motion::{self, Motion},
+    persistence::DB,
     state::Mode,
     Vim,
 };
 
+/// The marks are stored per workspace and file, for the editors of a single local file.
+fn marks_path(editor: &Editor, cx: &AppContext) -> Option<(WorkspaceId, PathBuf)> {
+    let workspace_id = editor.workspace()?.read(cx).database_id()?;
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    let path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+    Some((workspace_id, path))
+}
+
 impl Vim {
     pub fn create_mark(&mut self, text: Arc<str>, tail: bool, cx: &mut ViewContext<Self>) {
         let Some(anchors) = self.update_editor(cx, |_, editor, _| {
@@ -28,9 +39,86 @@ impl Vim {
             return;
         };
         self.marks.insert(text.to_string(), anchors);
+        self.persist_marks(cx);
         self.clear_operator(cx);
     }
 
+    /// Restores the marks set in the file of the editor in the previous sessions.
+    pub(crate) fn restore_marks(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(Some((workspace_id, path))) =
+            self.update_editor(cx, |_, editor, cx| marks_path(editor, cx))
+        else {
+            return;
+        };
+        cx.spawn(|vim, mut cx| async move {
+            let marks = cx
+                .background_executor()
+                .spawn(async move {
+                    DB.get_marks(workspace_id, path)
+                        .log_err()
+                        .unwrap_or_default()
+                })
+                .await;
+            vim.update(&mut cx, |vim, cx| {
+                let Some(snapshot) =
+                    vim.update_editor(cx, |_, editor, cx| editor.buffer().read(cx).snapshot(cx))
+                else {
+                    return;
+                };
+                for (name, points) in marks {
+                    let Some(points) = serde_json::from_str::<Vec<(u32, u32)>>(&points).log_err()
+                    else {
+                        continue;
+                    };
+                    vim.marks.entry(name).or_insert_with(|| {
+                        points
+                            .into_iter()
+                            .map(|(row, column)| {
+                                snapshot.anchor_before(
+                                    snapshot.clip_point(Point::new(row, column), Bias::Left),
+                                )
+                            })
+                            .collect()
+                    });
+                }
+            })
+            .log_err();
+        })
+        .detach();
+    }
+
+    /// Stores the named marks of the editor's file, to restore them in the next sessions.
+    pub(crate) fn persist_marks(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(Some(((workspace_id, path), snapshot))) =
+            self.update_editor(cx, |_, editor, cx| {
+                Some((
+                    marks_path(editor, cx)?,
+                    editor.buffer().read(cx).snapshot(cx),
+                ))
+            })
+        else {
+            return;
+        };
+        let marks = self
+            .marks
+            .iter()
+            .filter(|(name, _)| name.chars().all(|c| c.is_ascii_alphabetic()))
+            .filter_map(|(name, anchors)| {
+                let points = anchors
+                    .iter()
+                    .map(|anchor| {
+                        let point = anchor.to_point(&snapshot);
+                        (point.row, point.column)
+                    })
+                    .collect::<Vec<_>>();
+                Some((name.clone(), serde_json::to_string(&points).log_err()?))
+            })
+            .collect::<Vec<_>>();
+        cx.background_executor()
+            .spawn(async move { DB.save_marks(workspace_id, path, marks).await.log_err() })
+            .detach();
+    }
+
     // When handling an action, you must create visual marks if you will switch to normal
     // mode without the default selection behavior.
     pub(crate) fn store_visual_marks(&mut self, cx: &mut ViewContext<Self>) {
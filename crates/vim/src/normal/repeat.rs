@@ -56,7 +56,12 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
     Vim::action(editor, cx, |vim, _: &ToggleRecord, cx| {
         let globals = Vim::globals(cx);
         if let Some(char) = globals.recording_register.take() {
-            globals.last_recorded_register = Some(char)
+            globals.last_recorded_register = Some(char);
+            if let Err(error) = cx.global::<VimGlobals>().persist_recording(char, cx) {
+                if let Some(workspace) = vim.workspace(cx) {
+                    workspace.update(cx, |workspace, cx| workspace.show_error(&error, cx));
+                }
+            }
         } else {
             vim.push_operator(Operator::RecordRegister, cx);
         }
@@ -327,10 +332,10 @@ mod test {
     use futures::StreamExt;
     use indoc::indoc;
 
-    use gpui::ViewInputHandler;
+    use gpui::{BorrowAppContext, ViewInputHandler};
 
     use crate::{
-        state::Mode,
+        state::{Mode, VimGlobals},
         test::{NeovimBackedTestContext, VimTestContext},
     };
 
@@ -665,6 +670,31 @@ mod test {
         cx.shared_state().await.assert_eq("ababˇb world");
     }
 
+    #[gpui::test]
+    async fn test_restore_recording_and_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two three four", Mode::Normal);
+        cx.simulate_keystrokes("q a d w q");
+        cx.assert_state("ˇtwo three four", Mode::Normal);
+        cx.simulate_keystrokes("\" b y e");
+
+        cx.update(|cx| {
+            cx.update_global(|globals: &mut VimGlobals, cx| {
+                let registers = globals.serialized_registers();
+                let recording = globals.serialized_recording('a', cx).unwrap();
+                globals.registers.clear();
+                globals.recordings.clear();
+                globals.restore_state(registers, vec![("a".to_string(), recording)], cx);
+            })
+        });
+
+        cx.simulate_keystrokes("@ a");
+        cx.assert_state("ˇthree four", Mode::Normal);
+        cx.simulate_keystrokes("\" b shift-p");
+        cx.assert_state("twˇothree four", Mode::Normal);
+    }
+
     #[gpui::test]
     async fn test_record_replay_of_dot(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
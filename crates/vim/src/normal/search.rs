@@ -1,12 +1,13 @@
-use std::{iter::Peekable, str::Chars, time::Duration};
+use std::{iter::Peekable, ops::Range, str::Chars, time::Duration};
 
-use editor::Editor;
+use anyhow::Result;
+use editor::{Anchor, Editor};
 use gpui::{actions, impl_actions, ViewContext};
 use language::Point;
 use search::{buffer_search, BufferSearchBar, SearchOptions};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use util::serde::default_true;
-use workspace::{notifications::NotifyResultExt, searchable::Direction};
+use workspace::searchable::Direction;
 
 use crate::{
     command::CommandRange,
@@ -15,7 +16,7 @@ use crate::{
     Vim,
 };
 
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct MoveToNext {
     #[serde(default = "default_true")]
@@ -26,7 +27,7 @@ pub(crate) struct MoveToNext {
     regex: bool,
 }
 
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct MoveToPrev {
     #[serde(default = "default_true")]
@@ -37,7 +38,7 @@ pub(crate) struct MoveToPrev {
     regex: bool,
 }
 
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 pub(crate) struct Search {
     #[serde(default)]
     backwards: bool,
@@ -59,7 +60,7 @@ pub struct ReplaceCommand {
 
 #[derive(Debug, Default, PartialEq, Deserialize, Clone)]
 pub(crate) struct Replacement {
-    search: String,
+    pub(crate) search: String,
     replacement: String,
     should_replace_all: bool,
     is_case_sensitive: bool,
@@ -371,25 +372,33 @@ impl Vim {
     }
 
     fn replace_command(&mut self, action: &ReplaceCommand, cx: &mut ViewContext<Self>) {
-        let replacement = action.replacement.clone();
-        let Some(((pane, workspace), editor)) =
-            self.pane(cx).zip(self.workspace(cx)).zip(self.editor())
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let Some(result) =
+            self.update_editor(cx, |vim, editor, cx| action.line_range(vim, editor, cx))
         else {
             return;
         };
-        if let Some(result) = self.update_editor(cx, |vim, editor, cx| {
-            let range = action.range.buffer_range(vim, editor, cx)?;
-            let snapshot = &editor.snapshot(cx).buffer_snapshot;
-            let end_point = Point::new(range.end.0, snapshot.line_len(range.end));
-            let range = snapshot.anchor_before(Point::new(range.start.0, 0))
-                ..snapshot.anchor_after(end_point);
-            editor.set_search_within_ranges(&[range], cx);
-            anyhow::Ok(())
-        }) {
-            workspace.update(cx, |workspace, cx| {
-                result.notify_err(workspace, cx);
-            })
+        match result {
+            Ok(range) => self.replace_in_ranges(action.replacement.clone(), vec![range], cx),
+            Err(error) => workspace.update(cx, |workspace, cx| workspace.show_error(&error, cx)),
         }
+    }
+
+    /// Runs the substitution within the given ranges, as `:s` does within the lines of its range.
+    pub(crate) fn replace_in_ranges(
+        &mut self,
+        replacement: Replacement,
+        ranges: Vec<Range<Anchor>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some((pane, editor)) = self.pane(cx).zip(self.editor()) else {
+            return;
+        };
+        editor.update(cx, |editor, cx| {
+            editor.set_search_within_ranges(&ranges, cx)
+        });
         let vim = cx.view().clone();
         pane.update(cx, |pane, cx| {
             let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() else {
@@ -453,6 +462,22 @@ impl Vim {
     }
 }
 
+impl ReplaceCommand {
+    /// Returns the lines the substitution runs on, from the start of the first one to the end of
+    /// the last one.
+    pub(crate) fn line_range(
+        &self,
+        vim: &Vim,
+        editor: &mut Editor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Result<Range<Anchor>> {
+        let range = self.range.buffer_range(vim, editor, cx)?;
+        let snapshot = &editor.snapshot(cx).buffer_snapshot;
+        let end_point = Point::new(range.end.0, snapshot.line_len(range.end));
+        Ok(snapshot.anchor_before(Point::new(range.start.0, 0))..snapshot.anchor_after(end_point))
+    }
+}
+
 impl Replacement {
     // convert a vim query into something more usable by zed.
     // we don't attempt to fully convert between the two regex syntaxes,
@@ -0,0 +1,267 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // vim_registers(
+    //   workspace_id: usize,
+    //   name: String,
+    //   text: String,
+    //   clipboard_selections: Option<String>, // JSON
+    // )
+    // vim_recordings(
+    //   workspace_id: usize,
+    //   register: String,
+    //   actions: String, // JSON
+    // )
+    // vim_marks(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   name: String,
+    //   points: String, // JSON
+    // )
+    pub static ref DB: VimDb<WorkspaceDb> =
+        &[sql! (
+            CREATE TABLE vim_registers(
+                workspace_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                text TEXT NOT NULL,
+                clipboard_selections TEXT,
+                PRIMARY KEY(workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE vim_recordings(
+                workspace_id INTEGER NOT NULL,
+                register TEXT NOT NULL,
+                actions TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, register),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE vim_marks(
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                name TEXT NOT NULL,
+                points TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, path, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+);
+
+impl VimDb {
+    query! {
+        pub fn get_registers(workspace_id: WorkspaceId) -> Result<Vec<(String, String, Option<String>)>> {
+            SELECT name, text, clipboard_selections
+            FROM vim_registers
+            WHERE workspace_id = ?
+        }
+    }
+
+    /// Replaces all registers stored for the workspace.
+    pub async fn save_registers(
+        &self,
+        workspace_id: WorkspaceId,
+        registers: Vec<(String, String, Option<String>)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_registers", || {
+                conn.exec_bound(sql!(DELETE FROM vim_registers WHERE workspace_id = ?))?(
+                    workspace_id,
+                )?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO vim_registers(workspace_id, name, text, clipboard_selections)
+                    VALUES (?, ?, ?, ?)
+                ))?;
+                for (name, text, clipboard_selections) in registers {
+                    insert((workspace_id, name, text, clipboard_selections))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_recordings(workspace_id: WorkspaceId) -> Result<Vec<(String, String)>> {
+            SELECT register, actions
+            FROM vim_recordings
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_recording(workspace_id: WorkspaceId, register: String, actions: String) -> Result<()> {
+            INSERT OR REPLACE INTO vim_recordings(workspace_id, register, actions)
+            VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        pub fn get_marks(workspace_id: WorkspaceId, path: PathBuf) -> Result<Vec<(String, String)>> {
+            SELECT name, points
+            FROM vim_marks
+            WHERE workspace_id = ? AND path = ?
+        }
+    }
+
+    /// Replaces all marks stored for the file in the workspace.
+    pub async fn save_marks(
+        &self,
+        workspace_id: WorkspaceId,
+        path: PathBuf,
+        marks: Vec<(String, String)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_marks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM vim_marks WHERE workspace_id = ? AND path = ?
+                ))?((workspace_id, path.clone()))?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO vim_marks(workspace_id, path, name, points) VALUES (?, ?, ?, ?)
+                ))?;
+                for (name, points) in marks {
+                    insert((workspace_id, path.clone(), name, points))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{state::Mode, test::VimTestContext};
+    use gpui::TestAppContext;
+    use std::time::Duration;
+
+    /// Each test stores its vim state under a workspace of its own, so that tests sharing the
+    /// database don't see each other's registers, macros and marks.
+    async fn vim_test_context(
+        workspace_id: WorkspaceId,
+        cx: &mut TestAppContext,
+    ) -> VimTestContext {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.workspace(|workspace, _| workspace.set_database_id(workspace_id));
+        cx
+    }
+
+    #[gpui::test]
+    async fn test_restore_vim_state_of_workspace(
+        cx_a: &mut TestAppContext,
+        cx_b: &mut TestAppContext,
+    ) {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        let mut cx = vim_test_context(workspace_id, cx_a).await;
+        cx.set_state("ˇone two three four", Mode::Normal);
+        // Activate vim again for the editor to see the workspace id.
+        cx.disable_vim();
+        cx.enable_vim();
+        cx.run_until_parked();
+        cx.simulate_keystrokes("q a d w q");
+        cx.simulate_keystrokes("w m a");
+        cx.simulate_keystrokes("\" b y e");
+        cx.assert_state("two ˇthree four", Mode::Normal);
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+
+        // Another session of the same workspace gets them back.
+        let mut cx = vim_test_context(workspace_id, cx_b).await;
+        cx.set_state("ˇtwo three four", Mode::Normal);
+        cx.disable_vim();
+        cx.enable_vim();
+        cx.run_until_parked();
+        cx.simulate_keystrokes("` a");
+        cx.assert_state("two ˇthree four", Mode::Normal);
+        cx.simulate_keystrokes("@ a");
+        cx.assert_state("two ˇfour", Mode::Normal);
+        cx.simulate_keystrokes("\" b shift-p");
+        cx.assert_state("two threˇefour", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_save_and_get_vim_state() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let other_workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        DB.save_registers(
+            workspace_id,
+            vec![
+                ("a".to_string(), "hello".to_string(), None),
+                (
+                    "\"".to_string(),
+                    "world\n".to_string(),
+                    Some("[]".to_string()),
+                ),
+            ],
+        )
+        .await
+        .unwrap();
+        let mut registers = DB.get_registers(workspace_id).unwrap();
+        registers.sort();
+        assert_eq!(
+            registers,
+            vec![
+                (
+                    "\"".to_string(),
+                    "world\n".to_string(),
+                    Some("[]".to_string())
+                ),
+                ("a".to_string(), "hello".to_string(), None),
+            ]
+        );
+        assert_eq!(DB.get_registers(other_workspace_id).unwrap(), Vec::new());
+
+        DB.save_recording(workspace_id, "q".to_string(), "[]".to_string())
+            .await
+            .unwrap();
+        DB.save_recording(workspace_id, "q".to_string(), "[1]".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_recordings(workspace_id).unwrap(),
+            vec![("q".to_string(), "[1]".to_string())]
+        );
+        assert_eq!(DB.get_recordings(other_workspace_id).unwrap(), Vec::new());
+
+        let path = PathBuf::from("/project/a.rs");
+        DB.save_marks(
+            workspace_id,
+            path.clone(),
+            vec![("a".to_string(), "[[1,2]]".to_string())],
+        )
+        .await
+        .unwrap();
+        DB.save_marks(
+            workspace_id,
+            path.clone(),
+            vec![("b".to_string(), "[[3,4]]".to_string())],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            DB.get_marks(workspace_id, path.clone()).unwrap(),
+            vec![("b".to_string(), "[[3,4]]".to_string())]
+        );
+        assert_eq!(
+            DB.get_marks(workspace_id, PathBuf::from("/project/b.rs"))
+                .unwrap(),
+            Vec::new()
+        );
+        assert_eq!(DB.get_marks(other_workspace_id, path).unwrap(), Vec::new());
+    }
+}
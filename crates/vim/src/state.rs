@@ -1,22 +1,26 @@
 use std::borrow::BorrowMut;
-use std::{fmt::Display, ops::Range, sync::Arc};
+use std::{fmt::Display, ops::Range, sync::Arc, time::Duration};
 
 use crate::command::command_interceptor;
 use crate::normal::repeat::Replayer;
+use crate::persistence::DB;
 use crate::surrounds::SurroundsType;
 use crate::{motion::Motion, object::Object};
 use crate::{UseSystemClipboard, Vim, VimSettings};
-use collections::HashMap;
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
 use command_palette_hooks::{CommandPaletteFilter, CommandPaletteInterceptor};
 use editor::{Anchor, ClipboardSelection, Editor};
 use gpui::{
-    Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, View, WeakView,
+    Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, Keystroke, Task,
+    View, WeakView,
 };
 use language::Point;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
 use ui::{SharedString, ViewContext};
-use workspace::searchable::Direction;
+use util::ResultExt;
+use workspace::{searchable::Direction, WorkspaceId};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Mode {
@@ -59,7 +63,7 @@ impl Default for Mode {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Operator {
     Change,
     Delete,
@@ -147,7 +151,9 @@ impl From<String> for Register {
     }
 }
 
-#[derive(Default, Clone)]
+const REGISTERS_PERSISTENCE_DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
 pub struct VimGlobals {
     pub last_find: Option<Motion>,
 
@@ -174,8 +180,17 @@ pub struct VimGlobals {
     pub last_yank: Option<SharedString>,
     pub registers: HashMap<char, Register>,
     pub recordings: HashMap<char, Vec<ReplayableAction>>,
+    registers_persistence: Option<Task<()>>,
+    /// The workspace of the focused vim, which registers and macros are stored for.
+    workspace_id: Option<WorkspaceId>,
+    restored_workspaces: HashSet<WorkspaceId>,
 
     pub focused_vim: Option<WeakView<Vim>>,
+
+    /// The keystrokes left to send for the user's mappings, and whether they are sent without remapping.
+    pub(crate) remapped_keystrokes: Vec<(Keystroke, bool)>,
+    pub(crate) remapping: bool,
+    pub(crate) noremap: bool,
 }
 impl Global for VimGlobals {}
 
@@ -286,6 +301,144 @@ impl VimGlobals {
                 }
             }
         }
+        self.persist_registers(cx);
+    }
+
+    /// Stores the registers and macros in the workspace from now on, and restores the ones of its
+    /// previous sessions the first time one of its editors is focused.
+    pub(crate) fn set_workspace(workspace_id: Option<WorkspaceId>, cx: &mut AppContext) {
+        let globals = Vim::globals(cx);
+        globals.workspace_id = workspace_id;
+        let Some(workspace_id) = workspace_id else {
+            return;
+        };
+        if !globals.restored_workspaces.insert(workspace_id) {
+            return;
+        }
+        cx.spawn(|mut cx| async move {
+            let (registers, recordings) = cx
+                .background_executor()
+                .spawn(async move {
+                    (
+                        DB.get_registers(workspace_id).log_err().unwrap_or_default(),
+                        DB.get_recordings(workspace_id)
+                            .log_err()
+                            .unwrap_or_default(),
+                    )
+                })
+                .await;
+            cx.update_global(|globals: &mut VimGlobals, cx| {
+                globals.restore_state(registers, recordings, cx)
+            })
+            .log_err();
+        })
+        .detach();
+    }
+
+    /// Restores registers and macro recordings stored as returned by
+    /// [`Self::serialized_registers`] and [`Self::serialized_recording`], keeping the ones
+    /// already set in this session.
+    pub(crate) fn restore_state(
+        &mut self,
+        registers: Vec<(String, String, Option<String>)>,
+        recordings: Vec<(String, String)>,
+        cx: &AppContext,
+    ) {
+        for (name, text, clipboard_selections) in registers {
+            let Some(name) = name.chars().next() else {
+                continue;
+            };
+            let clipboard_selections = clipboard_selections
+                .and_then(|selections| serde_json::from_str(&selections).log_err());
+            self.registers.entry(name).or_insert_with(|| Register {
+                text: text.into(),
+                clipboard_selections,
+            });
+        }
+        for (register, actions) in recordings {
+            let Some(register) = register.chars().next() else {
+                continue;
+            };
+            let Some(actions) =
+                serde_json::from_str::<Vec<SerializedReplayableAction>>(&actions).log_err()
+            else {
+                continue;
+            };
+            let actions = actions
+                .into_iter()
+                .map(|action| ReplayableAction::deserialize(action, cx))
+                .collect::<Option<Vec<_>>>();
+            if let Some(actions) = actions {
+                self.recordings.entry(register).or_insert(actions);
+            }
+        }
+    }
+
+    pub(crate) fn serialized_registers(&self) -> Vec<(String, String, Option<String>)> {
+        self.registers
+            .iter()
+            .map(|(name, register)| {
+                (
+                    name.to_string(),
+                    register.text.to_string(),
+                    register
+                        .clipboard_selections
+                        .as_ref()
+                        .and_then(|selections| serde_json::to_string(selections).log_err()),
+                )
+            })
+            .collect()
+    }
+
+    /// Stores the registers once no yank or delete happened for a moment, since they often
+    /// come in bursts, e.g. when replaying a macro.
+    fn persist_registers(&mut self, cx: &AppContext) {
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        self.registers_persistence = Some(cx.spawn(|cx| async move {
+            cx.background_executor()
+                .timer(REGISTERS_PERSISTENCE_DEBOUNCE)
+                .await;
+            let Some(registers) = cx
+                .read_global(|globals: &VimGlobals, _| globals.serialized_registers())
+                .log_err()
+            else {
+                return;
+            };
+            DB.save_registers(workspace_id, registers).await.log_err();
+        }));
+    }
+
+    /// Returns the recording of the register as JSON, or an error naming the first of its
+    /// actions that cannot be restored.
+    pub(crate) fn serialized_recording(&self, register: char, cx: &AppContext) -> Result<String> {
+        let actions = self
+            .recordings
+            .get(&register)
+            .with_context(|| format!("no recording in register {register}"))?
+            .iter()
+            .map(|action| action.serialize(cx))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(serde_json::to_string(&actions)?)
+    }
+
+    /// Stores the recording of the register, unless some of its actions cannot be restored.
+    pub(crate) fn persist_recording(&self, register: char, cx: &AppContext) -> Result<()> {
+        let Some(workspace_id) = self.workspace_id else {
+            return Ok(());
+        };
+        let actions = self.serialized_recording(register, cx).with_context(|| {
+            format!("the macro of register {register} can't be saved for the next sessions")
+        })?;
+        cx.background_executor()
+            .spawn(async move {
+                DB.save_recording(workspace_id, register.to_string(), actions)
+                    .await
+                    .log_err()
+            })
+            .detach();
+        Ok(())
     }
 
     pub(crate) fn read_register(
@@ -421,6 +574,63 @@ pub enum ReplayableAction {
     },
 }
 
+/// A [`ReplayableAction`] as stored in the database: actions are stored by their names and
+/// arguments, which only the actions listed in [`action_arguments`] can have.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum SerializedReplayableAction {
+    Action {
+        name: String,
+        #[serde(default)]
+        arguments: Option<serde_json::Value>,
+    },
+    Insertion {
+        text: String,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+impl ReplayableAction {
+    fn serialize(&self, cx: &AppContext) -> Result<SerializedReplayableAction> {
+        match self {
+            Self::Action(action) => {
+                let name = action.name();
+                let arguments = action_arguments(&**action);
+                cx.build_action(name, arguments.clone())
+                    .ok()
+                    .filter(|built| built.partial_eq(&**action))
+                    .with_context(|| format!("action {name} can't be restored"))?;
+                Ok(SerializedReplayableAction::Action {
+                    name: name.to_string(),
+                    arguments,
+                })
+            }
+            Self::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Ok(SerializedReplayableAction::Insertion {
+                text: text.to_string(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            }),
+        }
+    }
+
+    fn deserialize(action: SerializedReplayableAction, cx: &AppContext) -> Option<Self> {
+        match action {
+            SerializedReplayableAction::Action { name, arguments } => cx
+                .build_action(&name, arguments)
+                .log_err()
+                .map(Self::Action),
+            SerializedReplayableAction::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Some(Self::Insertion {
+                text: text.into(),
+                utf16_range_to_replace,
+            }),
+        }
+    }
+}
+
 impl Clone for ReplayableAction {
     fn clone(&self) -> Self {
         match self {
@@ -436,6 +646,54 @@ impl Clone for ReplayableAction {
     }
 }
 
+/// Returns the arguments of the vim actions that have some, so that macros using them can be
+/// stored. Actions with arguments that aren't listed here can't be restored.
+fn action_arguments(action: &dyn Action) -> Option<serde_json::Value> {
+    macro_rules! serialize_arguments {
+        ($($action:ty),* $(,)?) => {
+            $(
+                if let Some(action) = action.as_any().downcast_ref::<$action>() {
+                    return serde_json::to_value(action).log_err();
+                }
+            )*
+        };
+    }
+
+    serialize_arguments!(
+        crate::SwitchMode,
+        crate::PushOperator,
+        crate::Number,
+        crate::SelectRegister,
+        crate::digraph::Literal,
+        crate::helix::HelixPaste,
+        crate::motion::NextWordStart,
+        crate::motion::NextWordEnd,
+        crate::motion::PreviousWordStart,
+        crate::motion::PreviousWordEnd,
+        crate::motion::NextSubwordStart,
+        crate::motion::NextSubwordEnd,
+        crate::motion::PreviousSubwordStart,
+        crate::motion::PreviousSubwordEnd,
+        crate::motion::Up,
+        crate::motion::Down,
+        crate::motion::FirstNonWhitespace,
+        crate::motion::EndOfLine,
+        crate::motion::StartOfLine,
+        crate::motion::UnmatchedForward,
+        crate::motion::UnmatchedBackward,
+        crate::normal::increment::Increment,
+        crate::normal::increment::Decrement,
+        crate::normal::paste::Paste,
+        crate::normal::search::MoveToNext,
+        crate::normal::search::MoveToPrev,
+        crate::normal::search::Search,
+        crate::object::Word,
+        crate::object::IndentObj,
+        crate::vimrc::Remap,
+    );
+    None
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct SearchState {
     pub direction: Direction,
@@ -497,7 +755,9 @@ impl Operator {
 
     pub fn is_waiting(&self, mode: Mode) -> bool {
         match self {
-            Operator::AddSurrounds { target } => target.is_some() || mode.is_visual(),
+            Operator::AddSurrounds { target } => {
+                target.is_some() || mode.is_visual() || mode == Mode::HelixNormal
+            }
             Operator::FindForward { .. }
             | Operator::Mark
             | Operator::Jump { .. }
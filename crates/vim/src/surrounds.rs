@@ -6,7 +6,7 @@ use crate::{
 };
 use editor::{movement, scroll::Autoscroll, Bias};
 use language::BracketPair;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use ui::ViewContext;
 
@@ -17,7 +17,7 @@ pub enum SurroundsType {
     Selection,
 }
 
-// This exists so that we can have Deserialize on Operators, but not on Motions.
+// These exist so that we can have Deserialize and Serialize on Operators, but not on Motions.
 impl<'de> Deserialize<'de> for SurroundsType {
     fn deserialize<D>(_: D) -> Result<Self, D::Error>
     where
@@ -27,6 +27,15 @@ impl<'de> Deserialize<'de> for SurroundsType {
     }
 }
 
+impl Serialize for SurroundsType {
+    fn serialize<S>(&self, _: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Err(serde::ser::Error::custom("Cannot serialize SurroundsType"))
+    }
+}
+
 impl Vim {
     pub fn add_surrounds(
         &mut self,
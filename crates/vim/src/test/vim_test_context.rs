@@ -31,6 +31,16 @@ impl VimTestContext {
         Self::new_with_lsp(lsp, enabled)
     }
 
+    pub async fn new_helix(cx: &mut gpui::TestAppContext) -> VimTestContext {
+        Self::init(cx);
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<VimSettings>(cx, |s| s.helix_mode = Some(true));
+            });
+        });
+        Self::new(cx, true).await
+    }
+
     pub async fn new_html(cx: &mut gpui::TestAppContext) -> VimTestContext {
         Self::init(cx);
         Self::new_with_lsp(EditorLspTestContext::new_html(cx).await, true)
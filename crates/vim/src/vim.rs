@@ -9,14 +9,17 @@ mod digraph;
 mod helix;
 mod indent;
 mod insert;
+mod listings;
 mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
 mod replace;
 mod rewrap;
 mod state;
 mod surrounds;
+mod vimrc;
 mod visual;
 
 use anyhow::Result;
@@ -44,6 +47,7 @@ use surrounds::SurroundsType;
 use theme::ThemeSettings;
 use ui::{px, IntoElement, VisualContext};
 use vim_mode_setting::VimModeSetting;
+pub use vimrc::{bind_user_mappings, handle_vimrc_file_changes, Vimrc, INITIAL_VIMRC_CONTENT};
 use workspace::{self, Pane, ResizeIntent, Workspace};
 
 use crate::state::ReplayableAction;
@@ -53,21 +57,21 @@ use crate::state::ReplayableAction;
 pub struct ResizePane(pub ResizeIntent);
 
 /// An Action to Switch between modes
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 pub struct SwitchMode(pub Mode);
 
 /// PushOperator is used to put vim into a "minor" mode,
 /// where it's waiting for a specific next set of keystrokes.
 /// For example 'd' needs a motion to complete.
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 pub struct PushOperator(pub Operator);
 
 /// Number is used to manage vim's count. Pushing a digit
 /// multiplis the current value by 10 and adds the digit.
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 struct Number(usize);
 
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
 struct SelectRegister(String);
 
 actions!(
@@ -242,10 +246,15 @@ impl Vim {
 
     pub fn new(cx: &mut ViewContext<Editor>) -> View<Self> {
         let editor = cx.view().clone();
+        let initial_mode = if VimSettings::get_global(cx).helix_mode {
+            Mode::HelixNormal
+        } else {
+            Mode::Normal
+        };
 
         cx.new_view(|cx| Vim {
-            mode: Mode::Normal,
-            last_mode: Mode::Normal,
+            mode: initial_mode,
+            last_mode: initial_mode,
             temp_mode: false,
             exit_temporary_mode: false,
             operator_stack: Vec::new(),
@@ -348,8 +357,14 @@ impl Vim {
             visual::register(editor, cx);
             change_list::register(editor, cx);
             digraph::register(editor, cx);
+            listings::register(editor, cx);
+
+            Vim::action(editor, cx, |_, action: &vimrc::Remap, cx| {
+                vimrc::remap(action, cx)
+            });
 
             cx.defer(|vim, cx| {
+                vim.restore_marks(cx);
                 vim.focused(false, cx);
             })
         })
@@ -458,6 +473,7 @@ impl Vim {
                 self.transaction_undone(transaction_id, cx)
             }
             EditorEvent::Edited { .. } => self.push_to_change_list(cx),
+            EditorEvent::Saved => self.persist_marks(cx),
             EditorEvent::FocusedIn => self.sync_vim_settings(cx),
             EditorEvent::CursorShapeChanged => self.cursor_shape_changed(cx),
             _ => {}
@@ -510,6 +526,13 @@ impl Vim {
             self.temp_mode = false;
         }
 
+        // With helix mode, normal mode is the helix one.
+        let mode = if mode == Mode::Normal && VimSettings::get_global(cx).helix_mode {
+            Mode::HelixNormal
+        } else {
+            mode
+        };
+
         let last_mode = self.mode;
         let prior_mode = self.last_mode;
         let prior_tx = self.current_tx;
@@ -588,7 +611,7 @@ impl Vim {
                 }
 
                 s.move_with(|map, selection| {
-                    if last_mode.is_visual() && !mode.is_visual() {
+                    if last_mode.is_visual() && !mode.is_visual() && mode != Mode::HelixNormal {
                         let mut point = selection.head();
                         if !selection.reversed && !selection.is_empty() {
                             point = movement::left(map, selection.head());
@@ -722,6 +745,9 @@ impl Vim {
         if mode == "normal" || mode == "visual" || mode == "operator" {
             context.add("VimControl");
         }
+        if cx.global::<VimGlobals>().noremap {
+            context.add("VimNoremap");
+        }
         context.set("vim_mode", mode);
         context.set("vim_operator", operator_id);
     }
@@ -781,6 +807,10 @@ impl Vim {
             }
         }
         Vim::globals(cx).focused_vim = Some(cx.view().downgrade());
+        let workspace_id = self
+            .workspace(cx)
+            .and_then(|workspace| workspace.read(cx).database_id());
+        VimGlobals::set_workspace(workspace_id, cx);
     }
 
     fn blurred(&mut self, cx: &mut ViewContext<Self>) {
@@ -1098,6 +1128,10 @@ impl Vim {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
                     self.visual_replace(text, cx)
                 }
+                Mode::HelixNormal => {
+                    self.helix_expand_cursors(cx);
+                    self.visual_replace(text, cx)
+                }
                 _ => self.clear_operator(cx),
             },
             Some(Operator::Digraph { first_char }) => {
@@ -1125,10 +1159,15 @@ impl Vim {
                     self.add_surrounds(text, SurroundsType::Selection, cx);
                     self.clear_operator(cx);
                 }
+                Mode::HelixNormal => {
+                    self.helix_expand_cursors(cx);
+                    self.add_surrounds(text, SurroundsType::Selection, cx);
+                    self.clear_operator(cx);
+                }
                 _ => self.clear_operator(cx),
             },
             Some(Operator::ChangeSurrounds { target }) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     if let Some(target) = target {
                         self.change_surrounds(text, target, cx);
                         self.clear_operator(cx);
@@ -1137,7 +1176,7 @@ impl Vim {
                 _ => self.clear_operator(cx),
             },
             Some(Operator::DeleteSurrounds) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     self.delete_surrounds(text, cx);
                     self.clear_operator(cx);
                 }
@@ -1225,6 +1264,7 @@ struct VimSettings {
     pub use_smartcase_find: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
+    pub helix_mode: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1235,6 +1275,7 @@ struct VimSettingsContent {
     pub use_smartcase_find: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
+    pub helix_mode: Option<bool>,
 }
 
 impl Settings for VimSettings {
@@ -0,0 +1,503 @@
+//! User-defined ex commands and key mappings, declared in the `vimrc` file of the config directory.
+
+use anyhow::{anyhow, Context as _, Result};
+use futures::{channel::mpsc, StreamExt};
+use gpui::{impl_actions, AppContext, Global, KeyBinding, Keystroke, WindowContext};
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+
+use crate::Vim;
+
+/// The content of the `vimrc` file created on the first time it is opened.
+pub const INITIAL_VIMRC_CONTENT: &str = r#"" Zed reads this file when vim mode is enabled, and again whenever it changes.
+"
+" Ex commands, whose names start with an uppercase letter, run an ex command or a Zed action:
+"   command W w
+"   command Fmt editor::Format
+"
+" Mappings use vim's key notation, and are recursive unless declared with a `noremap` variant:
+"   let mapleader = " "
+"   nnoremap <Leader>w :w<CR>
+"   inoremap jk <Esc>
+"#;
+
+/// Sends the keystrokes a mapping is replaced with.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub(crate) struct Remap {
+    keystrokes: String,
+    recursive: bool,
+}
+
+impl_actions!(vim, [Remap]);
+
+/// Mappings may send each other keystrokes, so stop after that many to break infinite recursions.
+const MAX_REMAPPED_KEYSTROKES: usize = 1000;
+
+/// The modes a mapping applies in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MapMode {
+    /// `map`: normal, visual and operator pending modes.
+    NormalVisualOperator,
+    Normal,
+    Visual,
+    Operator,
+    Insert,
+}
+
+impl MapMode {
+    fn context(self) -> &'static str {
+        match self {
+            MapMode::NormalVisualOperator => "VimControl && !menu && !VimNoremap",
+            MapMode::Normal => "vim_mode == normal && !menu && !VimNoremap",
+            MapMode::Visual => "vim_mode == visual && !menu && !VimNoremap",
+            MapMode::Operator => "vim_mode == operator && !VimNoremap",
+            MapMode::Insert => "vim_mode == insert && !VimNoremap",
+        }
+    }
+}
+
+/// An ex command declared with `:command`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct UserCommand {
+    pub name: String,
+    /// An ex command, or the name of an action optionally followed by its JSON arguments.
+    pub replacement: String,
+}
+
+/// A key mapping declared with `:map` and its variants.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct UserMapping {
+    pub mode: MapMode,
+    /// The space separated keystrokes that trigger the mapping.
+    pub keystrokes: String,
+    /// The space separated keystrokes sent instead.
+    pub replacement: String,
+    pub recursive: bool,
+}
+
+/// The ex commands and key mappings read from the user's `vimrc` file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Vimrc {
+    pub(crate) commands: Vec<UserCommand>,
+    pub(crate) mappings: Vec<UserMapping>,
+}
+
+impl Global for Vimrc {}
+
+impl Vimrc {
+    /// Parses the supported subset of vimscript: `command`, `let mapleader`, and the `map` and `noremap` commands.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut vimrc = Self::default();
+        let mut leader = "\\".to_string();
+        for (ix, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('"') {
+                continue;
+            }
+            vimrc
+                .parse_line(line, &mut leader)
+                .with_context(|| format!("line {}: `{line}`", ix + 1))?;
+        }
+        Ok(vimrc)
+    }
+
+    fn parse_line(&mut self, line: &str, leader: &mut String) -> Result<()> {
+        let (command, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let args = args.trim();
+        match command {
+            "let" => {
+                let (name, value) = args
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected `let name = value`"))?;
+                anyhow::ensure!(name.trim() == "mapleader", "only `mapleader` can be set");
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .or_else(|| {
+                        value
+                            .strip_prefix('\'')
+                            .and_then(|value| value.strip_suffix('\''))
+                    })
+                    .ok_or_else(|| anyhow!("expected a quoted string"))?;
+                *leader = value.to_string();
+            }
+            "command" | "command!" => {
+                let (name, replacement) = args
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| anyhow!("expected `command Name replacement`"))?;
+                anyhow::ensure!(
+                    name.starts_with(|c: char| c.is_ascii_uppercase())
+                        && name.chars().all(|c| c.is_ascii_alphanumeric()),
+                    "command names must be alphanumeric and start with an uppercase letter"
+                );
+                self.commands.retain(|command| command.name != name);
+                self.commands.push(UserCommand {
+                    name: name.to_string(),
+                    replacement: replacement.trim().to_string(),
+                });
+            }
+            _ => {
+                let (mode, recursive) = map_command(command)
+                    .ok_or_else(|| anyhow!("unsupported command `{command}`"))?;
+                let mut args = args;
+                while let Some(rest) = ["<silent>", "<nowait>", "<unique>"]
+                    .iter()
+                    .find_map(|modifier| args.strip_prefix(modifier))
+                {
+                    args = rest.trim_start();
+                }
+                let (keys, replacement) = args
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| anyhow!("expected `{command} keys replacement`"))?;
+                let keystrokes = parse_key_notation(keys, leader)?;
+                let replacement = parse_key_notation(replacement.trim(), leader)?;
+                self.mappings
+                    .retain(|mapping| mapping.mode != mode || mapping.keystrokes != keystrokes);
+                self.mappings.push(UserMapping {
+                    mode,
+                    keystrokes,
+                    replacement,
+                    recursive,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the command named by the query, or by an unambiguous prefix of its name.
+    pub(crate) fn command(&self, query: &str) -> Option<&UserCommand> {
+        if let Some(command) = self.commands.iter().find(|command| command.name == query) {
+            return Some(command);
+        }
+        let mut candidates = self
+            .commands
+            .iter()
+            .filter(|command| command.name.starts_with(query));
+        let command = candidates.next()?;
+        candidates.next().is_none().then_some(command)
+    }
+
+    fn key_bindings(&self) -> Vec<KeyBinding> {
+        self.mappings
+            .iter()
+            .filter_map(|mapping| {
+                KeyBinding::load(
+                    &mapping.keystrokes,
+                    Box::new(Remap {
+                        keystrokes: mapping.replacement.clone(),
+                        recursive: mapping.recursive,
+                    }),
+                    Some(mapping.mode.context()),
+                    None,
+                )
+                .log_err()
+            })
+            .collect()
+    }
+}
+
+fn map_command(command: &str) -> Option<(MapMode, bool)> {
+    Some(match command {
+        "map" => (MapMode::NormalVisualOperator, true),
+        "nmap" => (MapMode::Normal, true),
+        "vmap" | "xmap" => (MapMode::Visual, true),
+        "omap" => (MapMode::Operator, true),
+        "imap" => (MapMode::Insert, true),
+        "noremap" => (MapMode::NormalVisualOperator, false),
+        "nnoremap" => (MapMode::Normal, false),
+        "vnoremap" | "xnoremap" => (MapMode::Visual, false),
+        "onoremap" => (MapMode::Operator, false),
+        "inoremap" => (MapMode::Insert, false),
+        _ => return None,
+    })
+}
+
+/// Converts keys written in vim's notation, like `<C-w>j` or `<Leader>f`, to space separated keystrokes.
+fn parse_key_notation(keys: &str, leader: &str) -> Result<String> {
+    let mut keystrokes = Vec::new();
+    let mut rest = keys;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some((name, after)) = rest[1..].split_once('>') {
+                if name.eq_ignore_ascii_case("leader") {
+                    keystrokes.extend(leader.chars().map(char_keystroke));
+                    rest = after;
+                    continue;
+                }
+                if let Some(keystroke) = special_keystroke(name) {
+                    keystrokes.push(keystroke);
+                    rest = after;
+                    continue;
+                }
+            }
+        }
+        keystrokes.push(char_keystroke(c));
+        rest = &rest[c.len_utf8()..];
+    }
+    anyhow::ensure!(!keystrokes.is_empty(), "no keys given");
+    for keystroke in &keystrokes {
+        Keystroke::parse(keystroke)?;
+    }
+    Ok(keystrokes.join(" "))
+}
+
+fn char_keystroke(c: char) -> String {
+    match c {
+        ' ' => "space".to_string(),
+        c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+        c => c.to_string(),
+    }
+}
+
+/// Converts the name of a key in angle brackets, with its modifiers, like `C-S-Tab`.
+fn special_keystroke(name: &str) -> Option<String> {
+    let mut modifiers = String::new();
+    let mut key = name;
+    while let Some((modifier, rest)) = key.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+        modifiers.push_str(match modifier.to_ascii_uppercase().as_str() {
+            "C" => "ctrl-",
+            "S" => "shift-",
+            "A" | "M" => "alt-",
+            "D" => "cmd-",
+            _ => return None,
+        });
+        key = rest;
+    }
+    let key = match key.to_ascii_lowercase().as_str() {
+        "cr" | "enter" | "return" => "enter".to_string(),
+        "esc" => "escape".to_string(),
+        "tab" => "tab".to_string(),
+        "bs" => "backspace".to_string(),
+        "del" => "delete".to_string(),
+        "space" => "space".to_string(),
+        "lt" => "<".to_string(),
+        "bar" => "|".to_string(),
+        "bslash" => "\\".to_string(),
+        key @ ("up" | "down" | "left" | "right" | "home" | "end" | "pageup" | "pagedown"
+        | "insert") => key.to_string(),
+        key if key.len() > 1
+            && key.starts_with('f')
+            && key[1..].parse::<u8>().is_ok_and(|n| (1..=12).contains(&n)) =>
+        {
+            key.to_string()
+        }
+        _ if modifiers.is_empty() => return None,
+        // Like in vim, `<C-A>` is the same as `<C-a>`.
+        _ if key.chars().count() == 1 => key.to_lowercase(),
+        _ => return None,
+    };
+    Some(modifiers + &key)
+}
+
+/// Adds the key bindings of the user's mappings; they are lost when the keymap is reloaded.
+pub fn bind_user_mappings(cx: &mut AppContext) {
+    let Some(bindings) = cx.try_global::<Vimrc>().map(Vimrc::key_bindings) else {
+        return;
+    };
+    cx.bind_keys(bindings);
+}
+
+/// Reads the user's `vimrc` each time it changes, keeping the last valid one when it cannot be parsed.
+pub fn handle_vimrc_file_changes(
+    mut vimrc_file_rx: mpsc::UnboundedReceiver<String>,
+    cx: &mut AppContext,
+    vimrc_changed: impl Fn(Option<anyhow::Error>, &mut AppContext) + 'static,
+) {
+    cx.spawn(move |cx| async move {
+        while let Some(content) = vimrc_file_rx.next().await {
+            let result = cx.update(|cx| match Vimrc::parse(&content) {
+                Ok(vimrc) => {
+                    cx.set_global(vimrc);
+                    vimrc_changed(None, cx);
+                }
+                Err(error) => vimrc_changed(Some(error), cx),
+            });
+            if result.is_err() {
+                break;
+            }
+        }
+    })
+    .detach();
+}
+
+/// Sends the keystrokes of a mapping. While they are sent for a non-recursive mapping,
+/// the other user mappings are disabled by the `VimNoremap` key context.
+pub(crate) fn remap(action: &Remap, cx: &mut WindowContext) {
+    let globals = Vim::globals(cx);
+    // The keystrokes are a stack, so that the ones of a mapping replacement are sent before the rest.
+    globals.remapped_keystrokes.extend(
+        action
+            .keystrokes
+            .split(' ')
+            .rev()
+            .filter_map(|keystroke| Keystroke::parse(keystroke).log_err())
+            .map(|keystroke| (keystroke, !action.recursive)),
+    );
+    if globals.remapping {
+        return;
+    }
+    globals.remapping = true;
+
+    cx.spawn(|mut cx| async move {
+        for _ in 0..MAX_REMAPPED_KEYSTROKES {
+            let done = cx.update(|cx| {
+                let globals = Vim::globals(cx);
+                let Some((keystroke, noremap)) = globals.remapped_keystrokes.pop() else {
+                    globals.remapping = false;
+                    if globals.noremap {
+                        globals.noremap = false;
+                        cx.refresh();
+                    }
+                    return true;
+                };
+                if globals.noremap != noremap {
+                    globals.noremap = noremap;
+                    // The key context is computed when rendering.
+                    cx.refresh();
+                    cx.draw();
+                }
+                let focused = cx.focused();
+                cx.dispatch_keystroke(keystroke);
+                if cx.focused() != focused {
+                    cx.draw();
+                }
+                false
+            })?;
+            if done {
+                return Ok(());
+            }
+        }
+
+        cx.update(|cx| {
+            let globals = Vim::globals(cx);
+            globals.remapped_keystrokes.clear();
+            globals.remapping = false;
+            globals.noremap = false;
+            cx.refresh();
+        })?;
+        Err(anyhow!(
+            "over {MAX_REMAPPED_KEYSTROKES} keystrokes sent by key mappings, they may be recursive"
+        ))
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod test {
+    use gpui::TestAppContext;
+
+    use super::*;
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[test]
+    fn test_parse_vimrc() {
+        let vimrc = Vimrc::parse(indoc::indoc! {r#"
+            " comments and blank lines are ignored
+
+            command W w
+            command! Fmt editor::Format
+            nmap Q gq
+            let mapleader = ","
+            nnoremap <Leader>w :w<CR>
+            inoremap <silent> jk <Esc>
+            xnoremap <C-A> <C-S-Tab><lt><F5>
+        "#})
+        .unwrap();
+        assert_eq!(
+            vimrc.commands,
+            vec![
+                UserCommand {
+                    name: "W".into(),
+                    replacement: "w".into(),
+                },
+                UserCommand {
+                    name: "Fmt".into(),
+                    replacement: "editor::Format".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            vimrc.mappings,
+            vec![
+                UserMapping {
+                    mode: MapMode::Normal,
+                    keystrokes: "shift-q".into(),
+                    replacement: "g q".into(),
+                    recursive: true,
+                },
+                UserMapping {
+                    mode: MapMode::Normal,
+                    keystrokes: ", w".into(),
+                    replacement: ": w enter".into(),
+                    recursive: false,
+                },
+                UserMapping {
+                    mode: MapMode::Insert,
+                    keystrokes: "j k".into(),
+                    replacement: "escape".into(),
+                    recursive: false,
+                },
+                UserMapping {
+                    mode: MapMode::Visual,
+                    keystrokes: "ctrl-a".into(),
+                    replacement: "ctrl-shift-tab < f5".into(),
+                    recursive: false,
+                },
+            ]
+        );
+
+        assert_eq!(vimrc.command("Fmt").unwrap().name, "Fmt");
+        assert_eq!(vimrc.command("F").unwrap().name, "Fmt");
+        assert!(vimrc.command("X").is_none());
+
+        let error = Vimrc::parse("nmap Q gq\nset number").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: `set number`");
+        assert!(Vimrc::parse("command w x").is_err());
+        assert!(Vimrc::parse("nnoremap x").is_err());
+    }
+
+    #[gpui::test]
+    async fn test_user_commands(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|cx| {
+            cx.set_global(Vimrc::parse("command Dl d\ncommand Up vim::Up").unwrap());
+        });
+
+        cx.set_state("ˇa\nb\nc\nd\ne", Mode::Normal);
+        cx.simulate_keystrokes(": shift-d l enter");
+        cx.assert_state("ˇb\nc\nd\ne", Mode::Normal);
+        // The range is given to the replacement, and a unique prefix is enough.
+        cx.simulate_keystrokes(": 2 , 3 shift-d enter");
+        cx.assert_state("b\nˇe", Mode::Normal);
+        cx.simulate_keystrokes(": shift-u p enter");
+        cx.assert_state("ˇb\ne", Mode::Normal);
+    }
+
+    // TODO: this test is flaky on our linux CI machines, like the ones of `SendKeystrokes`
+    #[cfg(target_os = "macos")]
+    #[gpui::test]
+    async fn test_user_mappings(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|cx| {
+            cx.set_global(
+                Vimrc::parse(indoc::indoc! {"
+                    nmap w b
+                    nnoremap E w
+                    nmap R w
+                    inoremap jk <Esc>
+                "})
+                .unwrap(),
+            );
+            bind_user_mappings(cx);
+        });
+
+        cx.set_state("one ˇtwo three", Mode::Normal);
+        cx.simulate_keystrokes("shift-e");
+        cx.assert_state("one two ˇthree", Mode::Normal);
+        cx.simulate_keystrokes("shift-r");
+        cx.assert_state("one ˇtwo three", Mode::Normal);
+        cx.simulate_keystrokes("i x j k");
+        cx.assert_state("one ˇxtwo three", Mode::Normal);
+    }
+}
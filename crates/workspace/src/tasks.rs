@@ -1,6 +1,8 @@
-use project::TaskSourceKind;
+use std::path::Path;
+
+use project::{TaskSourceKind, WorktreeId};
 use remote::ConnectionState;
-use task::{ResolvedTask, TaskContext, TaskTemplate};
+use task::{ResolvedTask, TaskContext, TaskTemplate, VariableName};
 use ui::ViewContext;
 
 use crate::Workspace;
@@ -46,7 +48,59 @@ pub fn schedule_resolved_task(
     omit_history: bool,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
-    if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
+    let dependency_inputs = if resolved_task.original_task().depends_on.is_empty() {
+        Vec::new()
+    } else {
+        let worktree = task_worktree(workspace, &task_source_kind, &resolved_task, cx);
+        workspace
+            .project()
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .task_inventory()
+            .map(|inventory| {
+                inventory
+                    .read(cx)
+                    .missing_dependency_inputs(worktree, &resolved_task)
+            })
+            .unwrap_or_default()
+    };
+    if resolved_task.missing_inputs().next().is_some() || !dependency_inputs.is_empty() {
+        cx.emit(crate::Event::PromptTaskInputs {
+            task_source_kind,
+            pending_task: Box::new(resolved_task),
+            dependency_inputs,
+            omit_history,
+        });
+        return;
+    }
+
+    if let Some(mut spawn_in_terminal) = resolved_task.resolved.take() {
+        if !resolved_task.original_task().depends_on.is_empty() {
+            let worktree = task_worktree(workspace, &task_source_kind, &resolved_task, cx);
+            let dependencies = workspace
+                .project()
+                .read(cx)
+                .task_store()
+                .read(cx)
+                .task_inventory()
+                .map(|inventory| {
+                    inventory
+                        .read(cx)
+                        .resolve_task_dependencies(worktree, &resolved_task)
+                })
+                .transpose();
+            match dependencies {
+                Ok(dependencies) => {
+                    spawn_in_terminal.depends_on = dependencies.unwrap_or_default();
+                }
+                Err(e) => {
+                    workspace.show_error(&e, cx);
+                    return;
+                }
+            }
+        }
+
         if !omit_history {
             resolved_task.resolved = Some(spawn_in_terminal.clone());
             workspace.project().update(cx, |project, cx| {
@@ -65,3 +119,24 @@ pub fn schedule_resolved_task(
         });
     }
 }
+
+/// The worktree to look the task dependencies up in: the one the task comes from, or the one it got resolved for.
+fn task_worktree(
+    workspace: &Workspace,
+    task_source_kind: &TaskSourceKind,
+    resolved_task: &ResolvedTask,
+    cx: &ViewContext<'_, Workspace>,
+) -> Option<WorktreeId> {
+    if let TaskSourceKind::Worktree { id, .. } = task_source_kind {
+        return Some(*id);
+    }
+    let worktree_root = resolved_task
+        .task_context()
+        .task_variables
+        .get(&VariableName::WorktreeRoot)?;
+    let (worktree, _) = workspace
+        .project()
+        .read(cx)
+        .find_worktree(Path::new(worktree_root), cx)?;
+    Some(worktree.read(cx).id())
+}
@@ -62,7 +62,8 @@ use persistence::{
 };
 use postage::stream::Stream;
 use project::{
-    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
+    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, TaskSourceKind, Worktree,
+    WorktreeId,
 };
 use remote::{ssh_session::ConnectionIdentifier, SshClientDelegate, SshConnectionOptions};
 use serde::Deserialize;
@@ -88,7 +89,7 @@ use std::{
     sync::{atomic::AtomicUsize, Arc, LazyLock, Weak},
     time::Duration,
 };
-use task::SpawnInTerminal;
+use task::{ResolvedTask, SpawnInTerminal, TaskInput};
 use theme::{ActiveTheme, SystemAppearance, ThemeSettings};
 pub use toolbar::{Toolbar, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
 pub use ui;
@@ -689,6 +690,12 @@ pub enum Event {
     SpawnTask {
         action: Box<SpawnInTerminal>,
     },
+    PromptTaskInputs {
+        task_source_kind: TaskSourceKind,
+        pending_task: Box<ResolvedTask>,
+        dependency_inputs: Vec<TaskInput>,
+        omit_history: bool,
+    },
     OpenBundledFile {
         text: Cow<'static, str>,
         title: &'static str,
@@ -4498,6 +4505,11 @@ impl Workspace {
         workspace
     }
 
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn set_database_id(&mut self, database_id: WorkspaceId) {
+        self.database_id = Some(database_id);
+    }
+
     pub fn register_action<A: Action>(
         &mut self,
         callback: impl Fn(&mut Self, &A, &mut ViewContext<Self>) + 'static,
@@ -20,13 +20,14 @@ use futures::{
 use fuzzy::CharBag;
 use git::GitHostingProviderRegistry;
 use git::{
+    commit::{CommitFileChange, CommitLog},
     repository::{GitFileStatus, GitRepository, RepoPath},
     status::GitStatus,
-    COOKIES, DOT_GIT, FSMONITOR_DAEMON, GITIGNORE,
+    Oid, COOKIES, DOT_GIT, FSMONITOR_DAEMON, GITIGNORE,
 };
 use gpui::{
     AppContext, AsyncAppContext, BackgroundExecutor, Context, EventEmitter, Model, ModelContext,
-    Task,
+    SharedString, Task,
 };
 use ignore::IgnoreStack;
 use language::DiskState;
@@ -733,6 +734,201 @@ impl Worktree {
         }
     }
 
+    pub fn load_committed_file(
+        &self,
+        path: &Path,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        match self {
+            Worktree::Local(this) => {
+                let path = Arc::from(path);
+                let snapshot = this.snapshot();
+                cx.background_executor().spawn(async move {
+                    if let Some(repo) = snapshot.repository_for_path(&path) {
+                        if let Some(repo_path) = repo.relativize(&snapshot, &path).log_err() {
+                            if let Some(git_repo) =
+                                snapshot.git_repositories.get(&*repo.work_directory)
+                            {
+                                return Ok(git_repo.repo_ptr.load_committed_text(&repo_path));
+                            }
+                        }
+                    }
+                    Ok(None)
+                })
+            }
+            Worktree::Remote(_) => Task::ready(Err(anyhow!(
+                "remote worktrees can't yet load committed files"
+            ))),
+        }
+    }
+
+    /// Loads the contents of a file as of the given revision of its repository.
+    ///
+    /// Files that don't exist in that revision are loaded as empty, so that they appear
+    /// entirely added when diffed against it.
+    pub fn load_revision_file(
+        &self,
+        path: &Path,
+        revision: SharedString,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        match self {
+            Worktree::Local(this) => {
+                let path = Arc::from(path);
+                let snapshot = this.snapshot();
+                cx.background_executor().spawn(async move {
+                    let Some((repo, git_repo)) = snapshot.repo_for_path(&path) else {
+                        return Ok(None);
+                    };
+                    let repo_path = repo.relativize(&snapshot, &path)?;
+                    Ok(Some(
+                        git_repo
+                            .repo_ptr
+                            .load_revision_text(&revision, &repo_path)?
+                            .unwrap_or_default(),
+                    ))
+                })
+            }
+            Worktree::Remote(_) => Task::ready(Err(anyhow!(
+                "remote worktrees can't yet load files from other revisions"
+            ))),
+        }
+    }
+
+    /// Returns the paths in the worktree whose contents differ from the given revision,
+    /// across all of the worktree's repositories.
+    ///
+    /// Repositories in which the revision doesn't exist are skipped.
+    pub fn paths_changed_since(
+        &self,
+        revision: SharedString,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<Arc<Path>>>> {
+        match self {
+            Worktree::Local(this) => {
+                let snapshot = this.snapshot();
+                cx.background_executor().spawn(async move {
+                    let mut paths = Vec::new();
+                    for (work_directory, repo) in snapshot.repositories() {
+                        let Some(git_repo) = snapshot.git_repositories.get(&*repo.work_directory)
+                        else {
+                            continue;
+                        };
+                        let Some(repo_paths) =
+                            git_repo.repo_ptr.paths_changed_since(&revision).log_err()
+                        else {
+                            continue;
+                        };
+                        for repo_path in repo_paths {
+                            let relative_path = match &repo.location_in_repo {
+                                Some(location_in_repo) => {
+                                    match repo_path.strip_prefix(location_in_repo) {
+                                        Ok(path) => path,
+                                        Err(_) => continue,
+                                    }
+                                }
+                                None => repo_path.as_ref(),
+                            };
+                            paths.push(Arc::from(work_directory.join(relative_path)));
+                        }
+                    }
+                    Ok(paths)
+                })
+            }
+            Worktree::Remote(_) => Task::ready(Err(anyhow!(
+                "remote worktrees can't yet compare files to other revisions"
+            ))),
+        }
+    }
+
+    pub fn write_staged_file(
+        &self,
+        path: &Path,
+        text: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        match self {
+            Worktree::Local(this) => {
+                let path = Arc::from(path);
+                let snapshot = this.snapshot();
+                cx.background_executor().spawn(async move {
+                    let repo = snapshot
+                        .repository_for_path(&path)
+                        .with_context(|| format!("no repository found for {path:?}"))?;
+                    let repo_path = repo.relativize(&snapshot, &path)?;
+                    let git_repo = snapshot
+                        .git_repositories
+                        .get(&*repo.work_directory)
+                        .with_context(|| format!("no repository found for {path:?}"))?;
+                    git_repo.repo_ptr.set_index_text(&repo_path, text)
+                })
+            }
+            Worktree::Remote(_) => Task::ready(Err(anyhow!(
+                "remote worktrees can't yet write staged files"
+            ))),
+        }
+    }
+
+    /// Loads a page of the history of the repository containing `path`, newest first.
+    ///
+    /// If `only_path` is set, only the commits that changed `path` are included.
+    pub fn load_commit_log(
+        &self,
+        path: &Path,
+        only_path: bool,
+        skip: usize,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<CommitLog>> {
+        match self {
+            Worktree::Local(this) => {
+                let path = Arc::from(path);
+                let snapshot = this.snapshot();
+                cx.background_executor().spawn(async move {
+                    let (repo, git_repo) = snapshot
+                        .repo_for_path(&path)
+                        .with_context(|| format!("no repository found for {path:?}"))?;
+                    let repo_path = if only_path {
+                        Some(repo.relativize(&snapshot, &path)?)
+                    } else {
+                        None
+                    };
+                    Ok(CommitLog {
+                        commits: git_repo.repo_ptr.log(repo_path.as_ref(), skip, limit)?,
+                        remote_url: git_repo.repo_ptr.remote_url("origin"),
+                    })
+                })
+            }
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktrees can't yet load git history")))
+            }
+        }
+    }
+
+    /// Loads the files changed by a commit in the repository containing `path`.
+    pub fn load_commit_changes(
+        &self,
+        path: &Path,
+        sha: Oid,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CommitFileChange>>> {
+        match self {
+            Worktree::Local(this) => {
+                let path = Arc::from(path);
+                let snapshot = this.snapshot();
+                cx.background_executor().spawn(async move {
+                    let (_, git_repo) = snapshot
+                        .repo_for_path(&path)
+                        .with_context(|| format!("no repository found for {path:?}"))?;
+                    git_repo.repo_ptr.commit_changes(sha)
+                })
+            }
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktrees can't yet load git history")))
+            }
+        }
+    }
+
     pub fn load_binary_file(
         &self,
         path: &Path,
@@ -213,6 +213,11 @@ fn main() {
         fs.clone(),
         paths::keymap_file().clone(),
     );
+    let user_vimrc_file_rx = watch_config_file(
+        &app.background_executor(),
+        fs.clone(),
+        paths::vimrc_file().clone(),
+    );
 
     if !stdout_is_a_pty() {
         app.background_executor()
@@ -253,6 +258,7 @@ fn main() {
         settings::init(cx);
         handle_settings_file_changes(user_settings_file_rx, cx, handle_settings_changed);
         handle_keymap_file_changes(user_keymap_file_rx, cx, handle_keymap_changed);
+        vim::handle_vimrc_file_changes(user_vimrc_file_rx, cx, handle_vimrc_changed);
         client::init_settings(cx);
         let user_agent = format!(
             "Zed/{} ({}; {})",
@@ -445,6 +451,7 @@ fn main() {
         tab_switcher::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
+        hierarchy_view::init(cx);
         project_panel::init(Assets, cx);
         git_ui::git_panel::init(cx);
         outline_panel::init(Assets, cx);
@@ -463,6 +470,7 @@ fn main() {
         notifications::init(app_state.client.clone(), app_state.user_store.clone(), cx);
         collab_ui::init(&app_state, cx);
         git_ui::init(cx);
+        debugger_ui::init(cx);
         vcs_menu::init(cx);
         feedback::init(cx);
         markdown_preview::init(cx);
@@ -591,6 +599,31 @@ fn handle_keymap_changed(error: Option<anyhow::Error>, cx: &mut AppContext) {
     }
 }
 
+fn handle_vimrc_changed(error: Option<anyhow::Error>, cx: &mut AppContext) {
+    struct VimrcParseErrorNotification;
+    let id = NotificationId::unique::<VimrcParseErrorNotification>();
+
+    for workspace in workspace::local_workspace_windows(cx) {
+        workspace
+            .update(cx, |workspace, cx| match &error {
+                Some(error) => {
+                    workspace.show_notification(id.clone(), cx, |cx| {
+                        cx.new_view(|_| {
+                            MessageNotification::new(format!("Invalid vimrc file\n{error:#}"))
+                                .with_click_message("Open vimrc file")
+                                .on_click(|cx| {
+                                    cx.dispatch_action(zed_actions::vim::OpenVimrc.boxed_clone());
+                                    cx.emit(DismissEvent);
+                                })
+                        })
+                    });
+                }
+                None => workspace.dismiss_notification(&id, cx),
+            })
+            .log_err();
+    }
+}
+
 fn handle_settings_changed(error: Option<anyhow::Error>, cx: &mut AppContext) {
     struct SettingsParseErrorNotification;
     let id = NotificationId::unique::<SettingsParseErrorNotification>();
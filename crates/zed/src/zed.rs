@@ -249,6 +249,8 @@ pub fn initialize_workspace(
                 workspace_handle.clone(),
                 cx.clone(),
             );
+            let debug_panel =
+                debugger_ui::debugger_panel::DebugPanel::load(workspace_handle.clone(), cx.clone());
 
             let (
                 project_panel,
@@ -257,6 +259,7 @@ pub fn initialize_workspace(
                 channels_panel,
                 chat_panel,
                 notification_panel,
+                debug_panel,
             ) = futures::try_join!(
                 project_panel,
                 outline_panel,
@@ -264,6 +267,7 @@ pub fn initialize_workspace(
                 channels_panel,
                 chat_panel,
                 notification_panel,
+                debug_panel,
             )?;
 
             workspace_handle.update(&mut cx, |workspace, cx| {
@@ -273,6 +277,7 @@ pub fn initialize_workspace(
                 workspace.add_panel(channels_panel, cx);
                 workspace.add_panel(chat_panel, cx);
                 workspace.add_panel(notification_panel, cx);
+                workspace.add_panel(debug_panel, cx);
             })?;
             let git_ui_enabled = git_ui_feature_flag.await || is_staff;
 
@@ -505,6 +510,17 @@ pub fn initialize_workspace(
                     );
                 },
             )
+            .register_action(
+                move |_: &mut Workspace,
+                      _: &zed_actions::vim::OpenVimrc,
+                      cx: &mut ViewContext<Workspace>| {
+                    open_settings_file(
+                        paths::vimrc_file(),
+                        || vim::INITIAL_VIMRC_CONTENT.into(),
+                        cx,
+                    );
+                },
+            )
             .register_action(open_project_settings_file)
             .register_action(open_project_tasks_file)
             .register_action(
@@ -845,6 +861,7 @@ pub fn handle_keymap_file_changes(
 
     let (base_keymap_tx, mut base_keymap_rx) = mpsc::unbounded();
     let (keyboard_layout_tx, mut keyboard_layout_rx) = mpsc::unbounded();
+    let (vimrc_tx, mut vimrc_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
     cx.observe_global::<SettingsStore>(move |cx| {
@@ -859,6 +876,11 @@ pub fn handle_keymap_file_changes(
     })
     .detach();
 
+    cx.observe_global::<vim::Vimrc>(move |_| {
+        vimrc_tx.unbounded_send(()).ok();
+    })
+    .detach();
+
     let mut current_mapping = settings::get_key_equivalents(cx.keyboard_layout());
     cx.on_keyboard_layout_change(move |cx| {
         let next_mapping = settings::get_key_equivalents(cx.keyboard_layout());
@@ -877,6 +899,7 @@ pub fn handle_keymap_file_changes(
             select_biased! {
                 _ = base_keymap_rx.next() => {}
                 _ = keyboard_layout_rx.next() => {}
+                _ = vimrc_rx.next() => {}
                 user_keymap_content = user_keymap_file_rx.next() => {
                     if let Some(user_keymap_content) = user_keymap_content {
                         match KeymapFile::parse(&user_keymap_content) {
@@ -919,6 +942,10 @@ pub fn load_default_keymap(cx: &mut AppContext) {
     if let Some(asset_path) = base_keymap.asset_path() {
         KeymapFile::load_asset(asset_path, cx).unwrap();
     }
+
+    if VimModeSetting::get_global(cx).0 {
+        vim::bind_user_mappings(cx);
+    }
 }
 
 pub fn open_new_ssh_project_from_project(
@@ -3473,6 +3500,8 @@ mod tests {
             editor::init(cx);
             collab_ui::init(&app_state, cx);
             git_ui::init(cx);
+            debugger_ui::init(cx);
+            hierarchy_view::init(cx);
             project_panel::init((), cx);
             outline_panel::init((), cx);
             terminal_view::init(cx);
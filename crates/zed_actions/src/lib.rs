@@ -63,6 +63,12 @@ pub mod feedback {
     actions!(feedback, [GiveFeedback]);
 }
 
+pub mod vim {
+    use gpui::actions;
+
+    actions!(vim, [OpenVimrc]);
+}
+
 pub mod theme_selector {
     use gpui::impl_actions;
     use serde::Deserialize;